@@ -0,0 +1,229 @@
+//! Events fired when the playback of an animation clip crosses given times.
+//!
+//! A [`ClipEvent`] is added to an [`AnimationClip`] with [`AnimationClip::add_event`], to play a
+//! footstep sound or spawn a hitbox at a precise time of the animation for example. Each time the
+//! playback of an [`AnimationPlayer`] crosses the time of an event, the player sends an
+//! [`AnimationEvent`].
+//!
+//! ```
+//! # use bevy_animation::{event::AnimationEvent, prelude::*};
+//! # use bevy_ecs::prelude::*;
+//! let mut clip = AnimationClip::default();
+//! clip.add_event(0.42, "footstep");
+//! clip.add_event(1.1, "spawn_hitbox");
+//!
+//! fn footsteps(mut events: EventReader<AnimationEvent>) {
+//!     for event in events.read() {
+//!         if event.name == "footstep" {
+//!             // play a sound at the position of `event.player`
+//!         }
+//!     }
+//! }
+//! # bevy_ecs::system::assert_is_system(footsteps);
+//! ```
+//!
+//! Events follow the playback of the player: they are fired on each loop of a repeating animation,
+//! and in reverse order when the speed of the player is negative. The time skipped by
+//! [`AnimationPlayer::seek_to`] doesn't fire any event, but an event at the exact time seeked to
+//! is fired on the next update.
+//!
+//! Events are only fired for the main animation of the [`AnimationPlayer`], not for the animations
+//! fading out during a transition, nor by the
+//! [`AnimationGraphPlayer`](crate::graph::AnimationGraphPlayer).
+//!
+//! [`AnimationPlayer`]: crate::AnimationPlayer
+//! [`AnimationPlayer::seek_to`]: crate::AnimationPlayer::seek_to
+
+use std::ops::Range;
+
+use bevy_asset::Handle;
+use bevy_ecs::{entity::Entity, event::Event};
+use bevy_reflect::Reflect;
+
+use crate::AnimationClip;
+
+/// An event of an [`AnimationClip`], at a given time.
+#[derive(Reflect, Clone, Debug, PartialEq)]
+pub struct ClipEvent {
+    /// Time of the event in the clip, in seconds.
+    pub time: f32,
+    /// Name of the event.
+    pub name: String,
+}
+
+/// Sent by the [`AnimationPlayer`](crate::AnimationPlayer) when its playback crosses the time of
+/// a [`ClipEvent`].
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct AnimationEvent {
+    /// The entity with the [`AnimationPlayer`](crate::AnimationPlayer).
+    pub player: Entity,
+    /// The clip being played.
+    pub clip: Handle<AnimationClip>,
+    /// Time of the event in the clip, in seconds.
+    pub time: f32,
+    /// Name of the event.
+    pub name: String,
+}
+
+/// Calls `fire` for each of the `events` crossed while playing the `played` range of an animation
+/// lasting `duration`, in playback order.
+///
+/// `events` must be sorted by time. `played` is the range of seek time before it's wrapped to the
+/// duration of the clip, so it goes past the duration or below zero when the animation loops, and
+/// it ends before it starts when the animation plays in reverse. Events at the start of the range
+/// are fired, events at its end are only fired if `finished` is set, when the animation stops at
+/// the end of the range.
+pub(crate) fn crossed_events<'a>(
+    events: &'a [ClipEvent],
+    duration: f32,
+    played: Range<f32>,
+    finished: bool,
+    mut fire: impl FnMut(&'a ClipEvent),
+) {
+    if events.is_empty() || duration <= 0.0 || played.start == played.end {
+        return;
+    }
+
+    let Range { start, end } = played;
+    let forward = end > start;
+    let first_loop = (start.min(end) / duration).floor() as i64;
+    let last_loop = (start.max(end) / duration).floor() as i64;
+    let crossed = |event: &ClipEvent, loop_index: i64| {
+        let time = loop_index as f32 * duration + event.time;
+        if forward {
+            start <= time && (time < end || (finished && time == end))
+        } else {
+            (end < time || (finished && time == end)) && time <= start
+        }
+    };
+
+    if forward {
+        for loop_index in first_loop..=last_loop {
+            for event in events {
+                if crossed(event, loop_index) {
+                    fire(event);
+                }
+            }
+        }
+    } else {
+        for loop_index in (first_loop..=last_loop).rev() {
+            for event in events.iter().rev() {
+                if crossed(event, loop_index) {
+                    fire(event);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_asset::Assets;
+    use bevy_core::Name;
+    use bevy_ecs::prelude::*;
+    use bevy_math::Vec3;
+    use bevy_time::Time;
+    use bevy_transform::prelude::Transform;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        animation_player, AnimationPlayer, EntityPath, Interpolation, Keyframes, VariableCurve,
+    };
+
+    fn setup(configure: impl FnOnce(&mut AnimationPlayer)) -> (World, Schedule) {
+        let mut clip = AnimationClip::default();
+        clip.add_curve_to_path(
+            EntityPath {
+                parts: vec![Name::new("root")],
+            },
+            VariableCurve {
+                keyframe_timestamps: vec![0.0, 1.0],
+                keyframes: Keyframes::Translation(vec![Vec3::ZERO, Vec3::X]),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        clip.add_event(0.25, "first");
+        clip.add_event(0.75, "second");
+
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<AnimationEvent>>();
+        let mut clips = Assets::<AnimationClip>::default();
+        let mut player = AnimationPlayer::default();
+        player.start(clips.add(clip));
+        configure(&mut player);
+        world.insert_resource(clips);
+        world.spawn((Name::new("root"), Transform::default(), player));
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(animation_player);
+        (world, schedule)
+    }
+
+    /// Advances the time by `seconds` and returns the names of the events fired.
+    fn step(world: &mut World, schedule: &mut Schedule, seconds: f32) -> Vec<String> {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        schedule.run(world);
+        world
+            .resource_mut::<Events<AnimationEvent>>()
+            .drain()
+            .map(|event| event.name)
+            .collect()
+    }
+
+    #[test]
+    fn events_fire_on_each_loop() {
+        let (mut world, mut schedule) = setup(|player| {
+            player.repeat();
+        });
+
+        assert_eq!(step(&mut world, &mut schedule, 0.5), ["first"]);
+        assert_eq!(step(&mut world, &mut schedule, 0.5), ["second"]);
+        assert_eq!(step(&mut world, &mut schedule, 0.5), ["first"]);
+        assert_eq!(step(&mut world, &mut schedule, 1.0), ["second", "first"]);
+        assert!(step(&mut world, &mut schedule, 0.0).is_empty());
+    }
+
+    #[test]
+    fn events_fire_in_reverse() {
+        let (mut world, mut schedule) = setup(|player| {
+            player.repeat().set_speed(-1.0);
+        });
+
+        assert_eq!(step(&mut world, &mut schedule, 0.5), ["second"]);
+        assert_eq!(step(&mut world, &mut schedule, 0.5), ["first"]);
+        assert_eq!(step(&mut world, &mut schedule, 0.5), ["second"]);
+    }
+
+    #[test]
+    fn seeking_skips_events() {
+        let (mut world, mut schedule) = setup(|player| {
+            player.seek_to(0.8);
+        });
+
+        assert!(step(&mut world, &mut schedule, 0.1).is_empty());
+        world
+            .query::<&mut AnimationPlayer>()
+            .single_mut(&mut world)
+            .seek_to(0.25);
+        assert_eq!(step(&mut world, &mut schedule, 0.1), ["first"]);
+        // the animation finishes without looping
+        assert_eq!(step(&mut world, &mut schedule, 1.0), ["second"]);
+        assert!(step(&mut world, &mut schedule, 1.0).is_empty());
+    }
+
+    #[test]
+    fn event_at_the_end_fires_when_finishing() {
+        let (mut world, mut schedule) = setup(|_| {});
+        for (_, clip) in world.resource_mut::<Assets<AnimationClip>>().iter_mut() {
+            clip.add_event(1.0, "end");
+        }
+
+        assert_eq!(step(&mut world, &mut schedule, 0.5), ["first"]);
+        assert_eq!(step(&mut world, &mut schedule, 1.0), ["second", "end"]);
+        assert!(step(&mut world, &mut schedule, 1.0).is_empty());
+    }
+}
@@ -0,0 +1,1060 @@
+//! Animation graphs, blending several [`AnimationClip`]s together.
+//!
+//! An [`AnimationGraph`] is an asset describing a tree of nodes. Its leaves play animation clips,
+//! and the other nodes combine the poses of their children:
+//!
+//! - [`AnimationNodeKind::Layers`] applies its children on top of each other, in order. This is
+//!   the kind of the root node.
+//! - [`AnimationNodeKind::Blend`] averages its children by weight.
+//! - [`AnimationNodeKind::BlendSpace1d`] blends between its children according to the value of a
+//!   parameter, like walking and running according to the speed of a character.
+//! - [`AnimationNodeKind::Additive`] adds the motion of its children, relative to their first
+//!   keyframe, to the pose of the previous layers.
+//!
+//! Any node can be restricted to a subset of the bones with an [`AnimationMask`], and its weight
+//! can be driven by a parameter of the [`AnimationGraphPlayer`] playing the graph.
+//!
+//! ```
+//! # use bevy_animation::{prelude::*, graph::*};
+//! # use bevy_asset::Handle;
+//! # use bevy_core::Name;
+//! # let (idle, walk, run, wave, breathe) = Default::default();
+//! let mut graph = AnimationGraph::new();
+//! // blend between idle, walking and running according to the speed
+//! let locomotion = graph.add_blend_space_1d("speed", 1.0, graph.root());
+//! graph.add_node(AnimationGraphNode::clip(idle).with_blend_position(0.0), locomotion);
+//! graph.add_node(AnimationGraphNode::clip(walk).with_blend_position(1.5), locomotion);
+//! graph.add_node(AnimationGraphNode::clip(run).with_blend_position(5.0), locomotion);
+//! // wave with the upper body only, with a weight set from gameplay
+//! let spine = EntityPath {
+//!     parts: vec![Name::new("Armature"), Name::new("Spine")],
+//! };
+//! graph.add_node(
+//!     AnimationGraphNode::clip(wave)
+//!         .with_weight_parameter("wave")
+//!         .with_mask(AnimationMask::new([spine])),
+//!     graph.root(),
+//! );
+//! // breathe on top of everything
+//! let additive = graph.add_additive(1.0, graph.root());
+//! graph.add_clip(breathe, 1.0, additive);
+//! # let graph: Handle<AnimationGraph> = Handle::default();
+//!
+//! let mut player = AnimationGraphPlayer::new(graph);
+//! player.set_parameter("speed", 2.0).set_parameter("wave", 1.0);
+//! ```
+
+use std::time::Duration;
+
+use bevy_asset::{Asset, Assets, Handle};
+use bevy_core::Name;
+use bevy_ecs::prelude::*;
+use bevy_hierarchy::{Children, Parent};
+use bevy_math::{Quat, Vec3};
+use bevy_reflect::Reflect;
+use bevy_render::mesh::morph::MorphWeights;
+use bevy_time::Time;
+use bevy_transform::prelude::Transform;
+use bevy_utils::{tracing::warn, HashMap, HashSet};
+
+use crate::{
+    entity_from_path, lerp_morph_weights, verify_no_ancestor_player, AnimationClip, CurveSample,
+    EntityPath, PlayerParents, PlayingAnimation, RepeatAnimation,
+};
+
+/// The index of a node in an [`AnimationGraph`].
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AnimationNodeIndex(usize);
+
+impl AnimationNodeIndex {
+    /// The position of the node in [`AnimationGraph::nodes`].
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
+/// A set of bones, made of the bones at the given [`EntityPath`]s and all their descendants.
+///
+/// For example, a mask with the path to the spine of a character restricts an animation to the
+/// upper body.
+#[derive(Reflect, Clone, Debug, Default, PartialEq)]
+pub struct AnimationMask {
+    /// The paths of the bones at the root of the masked hierarchies.
+    pub roots: Vec<EntityPath>,
+}
+
+impl AnimationMask {
+    /// Creates a mask containing the bones at `roots` and their descendants.
+    pub fn new(roots: impl IntoIterator<Item = EntityPath>) -> Self {
+        Self {
+            roots: roots.into_iter().collect(),
+        }
+    }
+
+    /// Whether the bone at `path` is in the mask.
+    pub fn contains(&self, path: &EntityPath) -> bool {
+        self.roots
+            .iter()
+            .any(|root| path.parts.starts_with(&root.parts))
+    }
+}
+
+/// How a node of an [`AnimationGraph`] computes its pose.
+#[derive(Reflect, Clone, Debug)]
+pub enum AnimationNodeKind {
+    /// Plays an animation clip, repeating it forever. This node has no children.
+    Clip(Handle<AnimationClip>),
+    /// Averages the poses of its children, according to their weights.
+    Blend,
+    /// Blends between the two children whose [`blend_position`](AnimationGraphNode::blend_position)
+    /// surrounds the value of a parameter of the [`AnimationGraphPlayer`], according to the
+    /// distance to each of them. The weights of the children are applied on top.
+    BlendSpace1d {
+        /// The name of the parameter.
+        parameter: String,
+    },
+    /// Starts from the rest pose of the bones, then applies its children in order, each one
+    /// interpolating towards its own pose according to its weight. Children of kind
+    /// [`Additive`](Self::Additive) add their motion instead.
+    Layers,
+    /// Adds the motion of its children relative to the first keyframe of their curves, scaled by
+    /// their weights. This node only has an effect as a child of a
+    /// [`Layers`](Self::Layers) node.
+    Additive,
+}
+
+/// A node of an [`AnimationGraph`].
+#[derive(Reflect, Clone, Debug)]
+pub struct AnimationGraphNode {
+    /// How this node computes its pose.
+    pub kind: AnimationNodeKind,
+    /// The weight of this node in its parent. This is ignored for the root node.
+    pub weight: f32,
+    /// The parameter of the [`AnimationGraphPlayer`] overriding the weight of this node, if any.
+    /// If the player doesn't have this parameter, [`weight`](Self::weight) is used.
+    pub weight_parameter: Option<String>,
+    /// The bones affected by this node and its children. All bones are affected if `None`.
+    pub mask: Option<AnimationMask>,
+    /// The position of this node on the axis of its parent, when it's a
+    /// [`BlendSpace1d`](AnimationNodeKind::BlendSpace1d) node.
+    pub blend_position: f32,
+    children: Vec<AnimationNodeIndex>,
+}
+
+impl AnimationGraphNode {
+    /// Creates a node of the given kind, with a weight of `1.0`.
+    pub fn new(kind: AnimationNodeKind) -> Self {
+        Self {
+            kind,
+            weight: 1.0,
+            weight_parameter: None,
+            mask: None,
+            blend_position: 0.0,
+            children: Vec::new(),
+        }
+    }
+
+    /// Creates a node playing `clip`, with a weight of `1.0`.
+    pub fn clip(clip: Handle<AnimationClip>) -> Self {
+        Self::new(AnimationNodeKind::Clip(clip))
+    }
+
+    /// Sets the weight of this node.
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Overrides the weight of this node with a parameter of the player.
+    pub fn with_weight_parameter(mut self, parameter: impl Into<String>) -> Self {
+        self.weight_parameter = Some(parameter.into());
+        self
+    }
+
+    /// Restricts this node to the bones in `mask`.
+    pub fn with_mask(mut self, mask: AnimationMask) -> Self {
+        self.mask = Some(mask);
+        self
+    }
+
+    /// Sets the position of this node in its [`BlendSpace1d`](AnimationNodeKind::BlendSpace1d)
+    /// parent.
+    pub fn with_blend_position(mut self, blend_position: f32) -> Self {
+        self.blend_position = blend_position;
+        self
+    }
+
+    /// The children of this node, in order.
+    pub fn children(&self) -> &[AnimationNodeIndex] {
+        &self.children
+    }
+
+    fn weight(&self, parameters: &HashMap<String, f32>) -> f32 {
+        self.weight_parameter
+            .as_ref()
+            .and_then(|parameter| parameters.get(parameter))
+            .copied()
+            .unwrap_or(self.weight)
+    }
+}
+
+/// A tree of nodes blending several [`AnimationClip`]s together, played by an
+/// [`AnimationGraphPlayer`].
+///
+/// The root node is a [`Layers`](AnimationNodeKind::Layers) node.
+#[derive(Asset, Reflect, Clone, Debug)]
+pub struct AnimationGraph {
+    nodes: Vec<AnimationGraphNode>,
+}
+
+impl Default for AnimationGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnimationGraph {
+    /// Creates a graph with only a root node.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![AnimationGraphNode::new(AnimationNodeKind::Layers)],
+        }
+    }
+
+    /// The index of the root node.
+    #[inline]
+    pub fn root(&self) -> AnimationNodeIndex {
+        AnimationNodeIndex(0)
+    }
+
+    /// All the nodes of the graph, indexed by [`AnimationNodeIndex::index`].
+    #[inline]
+    pub fn nodes(&self) -> &[AnimationGraphNode] {
+        &self.nodes
+    }
+
+    /// Gets a node.
+    ///
+    /// Returns `None` if the index is invalid.
+    #[inline]
+    pub fn get(&self, index: AnimationNodeIndex) -> Option<&AnimationGraphNode> {
+        self.nodes.get(index.0)
+    }
+
+    /// Gets a node mutably.
+    ///
+    /// Returns `None` if the index is invalid.
+    #[inline]
+    pub fn get_mut(&mut self, index: AnimationNodeIndex) -> Option<&mut AnimationGraphNode> {
+        self.nodes.get_mut(index.0)
+    }
+
+    /// Adds `node` as the last child of `parent`, returning its index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parent` isn't a node of this graph, or if `node` already has children.
+    pub fn add_node(
+        &mut self,
+        node: AnimationGraphNode,
+        parent: AnimationNodeIndex,
+    ) -> AnimationNodeIndex {
+        assert!(
+            node.children.is_empty(),
+            "Nodes must be added to an animation graph before their children"
+        );
+        let index = AnimationNodeIndex(self.nodes.len());
+        self.nodes[parent.0].children.push(index);
+        self.nodes.push(node);
+        index
+    }
+
+    /// Adds a [`Clip`](AnimationNodeKind::Clip) node as the last child of `parent`.
+    pub fn add_clip(
+        &mut self,
+        clip: Handle<AnimationClip>,
+        weight: f32,
+        parent: AnimationNodeIndex,
+    ) -> AnimationNodeIndex {
+        self.add_node(AnimationGraphNode::clip(clip).with_weight(weight), parent)
+    }
+
+    /// Adds a [`Blend`](AnimationNodeKind::Blend) node as the last child of `parent`.
+    pub fn add_blend(&mut self, weight: f32, parent: AnimationNodeIndex) -> AnimationNodeIndex {
+        self.add_node(
+            AnimationGraphNode::new(AnimationNodeKind::Blend).with_weight(weight),
+            parent,
+        )
+    }
+
+    /// Adds a [`BlendSpace1d`](AnimationNodeKind::BlendSpace1d) node driven by `parameter` as the
+    /// last child of `parent`.
+    pub fn add_blend_space_1d(
+        &mut self,
+        parameter: impl Into<String>,
+        weight: f32,
+        parent: AnimationNodeIndex,
+    ) -> AnimationNodeIndex {
+        let kind = AnimationNodeKind::BlendSpace1d {
+            parameter: parameter.into(),
+        };
+        self.add_node(AnimationGraphNode::new(kind).with_weight(weight), parent)
+    }
+
+    /// Adds a [`Layers`](AnimationNodeKind::Layers) node as the last child of `parent`.
+    pub fn add_layers(&mut self, weight: f32, parent: AnimationNodeIndex) -> AnimationNodeIndex {
+        self.add_node(
+            AnimationGraphNode::new(AnimationNodeKind::Layers).with_weight(weight),
+            parent,
+        )
+    }
+
+    /// Adds an [`Additive`](AnimationNodeKind::Additive) node as the last child of `parent`.
+    pub fn add_additive(&mut self, weight: f32, parent: AnimationNodeIndex) -> AnimationNodeIndex {
+        self.add_node(
+            AnimationGraphNode::new(AnimationNodeKind::Additive).with_weight(weight),
+            parent,
+        )
+    }
+
+    /// Computes the pose of the bone described by `context` for the node at `index`.
+    ///
+    /// If `additive` is set, clips return their motion relative to their first keyframe.
+    fn evaluate(
+        &self,
+        index: AnimationNodeIndex,
+        context: &BoneContext,
+        additive: bool,
+    ) -> BonePose {
+        let node = &self.nodes[index.0];
+        if let Some(mask) = &node.mask {
+            if !mask.contains(context.path) {
+                return BonePose::default();
+            }
+        }
+
+        match &node.kind {
+            AnimationNodeKind::Clip(clip) => {
+                context.sample_clip(clip, context.seek_times[index.0], additive)
+            }
+            AnimationNodeKind::Blend => {
+                let weights = node
+                    .children
+                    .iter()
+                    .map(|child| self.nodes[child.0].weight(context.parameters));
+                self.blend_children(&node.children, weights, context, additive)
+            }
+            AnimationNodeKind::BlendSpace1d { parameter } => {
+                let value = context
+                    .parameters
+                    .get(parameter)
+                    .copied()
+                    .unwrap_or_default();
+                let weights = self
+                    .blend_space_weights(&node.children, value)
+                    .into_iter()
+                    .zip(&node.children)
+                    .map(|(weight, child)| weight * self.nodes[child.0].weight(context.parameters));
+                self.blend_children(&node.children, weights, context, additive)
+            }
+            AnimationNodeKind::Layers => {
+                let mut pose = context.base.clone();
+                for child in &node.children {
+                    let child_node = &self.nodes[child.0];
+                    let weight = child_node.weight(context.parameters);
+                    if weight <= 0.0 {
+                        continue;
+                    }
+                    let child_pose = self.evaluate(*child, context, false);
+                    if matches!(child_node.kind, AnimationNodeKind::Additive) {
+                        pose.add(&child_pose, weight);
+                    } else {
+                        pose.lerp(&child_pose, weight);
+                    }
+                }
+                pose
+            }
+            AnimationNodeKind::Additive => {
+                let mut pose = BonePose::default();
+                for child in &node.children {
+                    let weight = self.nodes[child.0].weight(context.parameters);
+                    if weight > 0.0 {
+                        pose.add(&self.evaluate(*child, context, true), weight);
+                    }
+                }
+                pose
+            }
+        }
+    }
+
+    /// Averages the poses of `children`, weighted by `weights`.
+    fn blend_children(
+        &self,
+        children: &[AnimationNodeIndex],
+        weights: impl Iterator<Item = f32>,
+        context: &BoneContext,
+        additive: bool,
+    ) -> BonePose {
+        let mut pose = BonePose::default();
+        let mut total_weights = BonePoseWeights::default();
+        for (child, weight) in children.iter().zip(weights) {
+            if weight > 0.0 {
+                let child_pose = self.evaluate(*child, context, additive);
+                pose.accumulate(&child_pose, weight, &mut total_weights);
+            }
+        }
+        pose
+    }
+
+    /// The weights of `children` in a [`AnimationNodeKind::BlendSpace1d`] node, for the parameter
+    /// `value`.
+    fn blend_space_weights(&self, children: &[AnimationNodeIndex], value: f32) -> Vec<f32> {
+        let positions: Vec<_> = children
+            .iter()
+            .map(|child| self.nodes[child.0].blend_position)
+            .collect();
+        let mut weights = vec![0.0; positions.len()];
+        // the closest children on each side of the value
+        let below = (0..positions.len())
+            .filter(|i| positions[*i] <= value)
+            .max_by(|a, b| positions[*a].total_cmp(&positions[*b]));
+        let above = (0..positions.len())
+            .filter(|i| positions[*i] >= value)
+            .min_by(|a, b| positions[*a].total_cmp(&positions[*b]));
+        match (below, above) {
+            (Some(below), Some(above)) if positions[above] > positions[below] => {
+                let lerp = (value - positions[below]) / (positions[above] - positions[below]);
+                weights[below] = 1.0 - lerp;
+                weights[above] = lerp;
+            }
+            (Some(closest), _) | (None, Some(closest)) => weights[closest] = 1.0,
+            (None, None) => {}
+        }
+        weights
+    }
+}
+
+/// The value of each property of a bone, or of its motion in additive nodes.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct BonePose {
+    translation: Option<Vec3>,
+    rotation: Option<Quat>,
+    scale: Option<Vec3>,
+    weights: Option<Vec<f32>>,
+}
+
+/// The total weight accumulated for each property of a [`BonePose`].
+#[derive(Default)]
+struct BonePoseWeights {
+    translation: f32,
+    rotation: f32,
+    scale: f32,
+    weights: f32,
+}
+
+impl BonePose {
+    /// Interpolates the properties set in `other` towards their value, by `weight`.
+    fn lerp(&mut self, other: &BonePose, weight: f32) {
+        if let Some(translation) = other.translation {
+            let current = self.translation.unwrap_or(translation);
+            self.translation = Some(current.lerp(translation, weight));
+        }
+        if let Some(rotation) = other.rotation {
+            let current = self.rotation.unwrap_or(rotation);
+            self.rotation = Some(current.slerp(rotation, weight));
+        }
+        if let Some(scale) = other.scale {
+            let current = self.scale.unwrap_or(scale);
+            self.scale = Some(current.lerp(scale, weight));
+        }
+        if let Some(weights) = &other.weights {
+            let current = self.weights.get_or_insert_with(|| weights.clone());
+            lerp_morph_weights(current, weights.iter().copied(), weight);
+        }
+    }
+
+    /// Adds the motion in `delta`, scaled by `weight`.
+    fn add(&mut self, delta: &BonePose, weight: f32) {
+        if let Some(translation) = delta.translation {
+            *self.translation.get_or_insert(Vec3::ZERO) += translation * weight;
+        }
+        if let Some(rotation) = delta.rotation {
+            let current = self.rotation.unwrap_or(Quat::IDENTITY);
+            self.rotation = Some((Quat::IDENTITY.slerp(rotation, weight) * current).normalize());
+        }
+        if let Some(scale) = delta.scale {
+            *self.scale.get_or_insert(Vec3::ONE) *= Vec3::ONE.lerp(scale, weight);
+        }
+        if let Some(weights) = &delta.weights {
+            let current = self.weights.get_or_insert_with(|| vec![0.0; weights.len()]);
+            for (current, weight_delta) in current.iter_mut().zip(weights) {
+                *current += weight_delta * weight;
+            }
+        }
+    }
+
+    /// Adds `other` to a weighted average, where `total_weights` are the weights accumulated
+    /// so far.
+    fn accumulate(&mut self, other: &BonePose, weight: f32, total_weights: &mut BonePoseWeights) {
+        // the average is kept up to date by interpolating towards each new value by its share of
+        // the total weight
+        fn share(total: &mut f32, weight: f32) -> f32 {
+            *total += weight;
+            weight / *total
+        }
+        if let Some(translation) = other.translation {
+            let share = share(&mut total_weights.translation, weight);
+            self.translation = Some(
+                self.translation
+                    .map_or(translation, |current| current.lerp(translation, share)),
+            );
+        }
+        if let Some(rotation) = other.rotation {
+            let share = share(&mut total_weights.rotation, weight);
+            self.rotation = Some(
+                self.rotation
+                    .map_or(rotation, |current| current.slerp(rotation, share)),
+            );
+        }
+        if let Some(scale) = other.scale {
+            let share = share(&mut total_weights.scale, weight);
+            self.scale = Some(
+                self.scale
+                    .map_or(scale, |current| current.lerp(scale, share)),
+            );
+        }
+        if let Some(weights) = &other.weights {
+            let share = share(&mut total_weights.weights, weight);
+            match &mut self.weights {
+                Some(current) => lerp_morph_weights(current, weights.iter().copied(), share),
+                None => self.weights = Some(weights.clone()),
+            }
+        }
+    }
+}
+
+/// The bone being evaluated, and the state of the player.
+struct BoneContext<'a> {
+    path: &'a EntityPath,
+    /// The rest pose of the bone, captured when the player first animated it.
+    base: &'a BonePose,
+    /// The number of morph targets of the bone.
+    target_count: usize,
+    clips: &'a Assets<AnimationClip>,
+    /// The seek time of each node.
+    seek_times: &'a [f32],
+    parameters: &'a HashMap<String, f32>,
+}
+
+impl BoneContext<'_> {
+    fn sample_clip(
+        &self,
+        clip: &Handle<AnimationClip>,
+        seek_time: f32,
+        additive: bool,
+    ) -> BonePose {
+        let mut pose = BonePose::default();
+        let Some(curves) = self
+            .clips
+            .get(clip)
+            .and_then(|clip| clip.get_curves_by_path(self.path))
+        else {
+            return pose;
+        };
+        for curve in curves {
+            let Some(sample) = curve.sample(seek_time, self.target_count) else {
+                continue;
+            };
+            let sample = if additive {
+                match (sample, curve.first_sample(self.target_count)) {
+                    (CurveSample::Translation(value), CurveSample::Translation(reference)) => {
+                        CurveSample::Translation(value - reference)
+                    }
+                    (CurveSample::Rotation(value), CurveSample::Rotation(reference)) => {
+                        CurveSample::Rotation(value * reference.normalize().inverse())
+                    }
+                    (CurveSample::Scale(value), CurveSample::Scale(reference)) => {
+                        CurveSample::Scale(value / reference)
+                    }
+                    (CurveSample::Weights(value), CurveSample::Weights(reference)) => {
+                        CurveSample::Weights(
+                            value.iter().zip(&reference).map(|(v, r)| v - r).collect(),
+                        )
+                    }
+                    _ => unreachable!("a curve has a single keyframe type"),
+                }
+            } else {
+                sample
+            };
+            match sample {
+                CurveSample::Translation(translation) => pose.translation = Some(translation),
+                CurveSample::Rotation(rotation) => pose.rotation = Some(rotation),
+                CurveSample::Scale(scale) => pose.scale = Some(scale),
+                CurveSample::Weights(weights) => pose.weights = Some(weights),
+            }
+        }
+        pose
+    }
+}
+
+/// Plays an [`AnimationGraph`] on the entity and its descendants, as the root of the
+/// [`EntityPath`]s of the clips of the graph.
+///
+/// Clips are repeated forever, and all of them advance at the same speed whatever their weight, so
+/// that blended clips stay in sync.
+///
+/// The graph is applied on top of the rest pose of each bone: its [`Transform`] and
+/// [`MorphWeights`] when the player first animates it.
+///
+/// An entity with an [`AnimationGraphPlayer`] can't have an [`AnimationPlayer`](crate::AnimationPlayer)
+/// or an [`AnimationGraphPlayer`] as an ancestor.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct AnimationGraphPlayer {
+    graph: Handle<AnimationGraph>,
+    parameters: HashMap<String, f32>,
+    paused: bool,
+    speed: f32,
+    #[reflect(ignore)]
+    nodes: Vec<PlayingAnimation>,
+    #[reflect(ignore)]
+    path_cache: HashMap<EntityPath, Vec<Option<Entity>>>,
+    #[reflect(ignore)]
+    rest_poses: HashMap<Entity, BonePose>,
+}
+
+impl Default for AnimationGraphPlayer {
+    fn default() -> Self {
+        Self::new(Handle::default())
+    }
+}
+
+impl AnimationGraphPlayer {
+    /// Creates a player for `graph`.
+    pub fn new(graph: Handle<AnimationGraph>) -> Self {
+        Self {
+            graph,
+            parameters: HashMap::default(),
+            paused: false,
+            speed: 1.0,
+            nodes: Vec::new(),
+            path_cache: HashMap::default(),
+            rest_poses: HashMap::default(),
+        }
+    }
+
+    /// Handle to the animation graph being played.
+    pub fn graph(&self) -> &Handle<AnimationGraph> {
+        &self.graph
+    }
+
+    /// Plays another graph, restarting all its clips.
+    pub fn set_graph(&mut self, graph: Handle<AnimationGraph>) -> &mut Self {
+        self.graph = graph;
+        self.nodes.clear();
+        self
+    }
+
+    /// Sets the value of a parameter, used by
+    /// [`AnimationGraphNode::weight_parameter`] and [`AnimationNodeKind::BlendSpace1d`] nodes.
+    pub fn set_parameter(&mut self, name: impl Into<String>, value: f32) -> &mut Self {
+        self.parameters.insert(name.into(), value);
+        self
+    }
+
+    /// The value of a parameter, if it was set.
+    pub fn parameter(&self, name: &str) -> Option<f32> {
+        self.parameters.get(name).copied()
+    }
+
+    /// Pause the animation
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Unpause the animation
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Is the animation paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Speed of the animation playback
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Set the speed of the animation playback
+    pub fn set_speed(&mut self, speed: f32) -> &mut Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Seek time inside of the clip played by a [`Clip`](AnimationNodeKind::Clip) node.
+    ///
+    /// Returns `None` if the node hasn't been played yet.
+    pub fn seek_time(&self, node: AnimationNodeIndex) -> Option<f32> {
+        self.nodes.get(node.0).map(|state| state.seek_time)
+    }
+
+    /// Seek to a specific time in the clip played by a [`Clip`](AnimationNodeKind::Clip) node.
+    ///
+    /// This has no effect if the node hasn't been played yet.
+    pub fn seek_to(&mut self, node: AnimationNodeIndex, seek_time: f32) -> &mut Self {
+        if let Some(state) = self.nodes.get_mut(node.0) {
+            state.seek_time = seek_time;
+        }
+        self
+    }
+
+    /// Restart all the clips of the graph, as if no time has elapsed.
+    pub fn replay(&mut self) {
+        for state in &mut self.nodes {
+            state.replay();
+        }
+    }
+}
+
+/// System that will play all animation graphs, using any entity with an
+/// [`AnimationGraphPlayer`] as an animation root.
+#[allow(clippy::too_many_arguments)]
+pub fn animation_graph_player(
+    time: Res<Time>,
+    clips: Res<Assets<AnimationClip>>,
+    graphs: Res<Assets<AnimationGraph>>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    transforms: Query<&mut Transform>,
+    morphs: Query<&mut MorphWeights>,
+    parents: PlayerParents,
+    mut players: Query<(Entity, Option<&Parent>, &mut AnimationGraphPlayer)>,
+) {
+    players
+        .par_iter_mut()
+        .for_each(|(root, maybe_parent, mut player)| {
+            // Continue if paused unless the player was changed, to still apply seeks
+            if player.paused && !player.is_changed() {
+                return;
+            }
+            let Some(graph) = graphs.get(&player.graph) else {
+                return;
+            };
+            if !verify_no_ancestor_player(maybe_parent, &parents) {
+                warn!("Animation player on {:?} has a conflicting animation player on an ancestor. Cannot safely animate.", root);
+                return;
+            }
+
+            let player = &mut *player;
+            let delta = if player.paused {
+                Duration::ZERO
+            } else {
+                time.delta()
+            };
+            update_nodes(player, graph, &clips, delta.as_secs_f32());
+            let seek_times: Vec<_> = player.nodes.iter().map(|state| state.seek_time).collect();
+
+            let mut any_path_found = false;
+            for path in graph_paths(graph, &clips) {
+                let path_cache = player.path_cache.entry(path.clone()).or_default();
+                let Some(target) = entity_from_path(root, path, &children, &names, path_cache)
+                else {
+                    continue;
+                };
+                any_path_found = true;
+                // SAFETY: The verify_no_ancestor_player check above ensures that two animation
+                // players cannot alias any of their descendant Transforms, see `apply_animation`.
+                let Ok(mut transform) = (unsafe { transforms.get_unchecked(target) }) else {
+                    continue;
+                };
+                // SAFETY: As above, there can't be other players with this target so this fetch
+                // can't alias
+                let mut morphs = unsafe { morphs.get_unchecked(target) }.ok();
+
+                let current = BonePose {
+                    translation: Some(transform.translation),
+                    rotation: Some(transform.rotation),
+                    scale: Some(transform.scale),
+                    weights: morphs.as_ref().map(|morphs| morphs.weights().to_vec()),
+                };
+                // the graph is applied on top of the rest pose rather than the current pose, which
+                // is the output of the previous frame
+                let base = player
+                    .rest_poses
+                    .entry(target)
+                    .or_insert_with(|| current.clone());
+                let context = BoneContext {
+                    path,
+                    base,
+                    target_count: base.weights.as_ref().map_or(0, Vec::len),
+                    clips: &clips,
+                    seek_times: &seek_times,
+                    parameters: &player.parameters,
+                };
+                let pose = graph.evaluate(graph.root(), &context, false);
+                if pose == current {
+                    continue;
+                }
+
+                if let Some(translation) = pose.translation {
+                    transform.translation = translation;
+                }
+                if let Some(rotation) = pose.rotation {
+                    transform.rotation = rotation.normalize();
+                }
+                if let Some(scale) = pose.scale {
+                    transform.scale = scale;
+                }
+                if let (Some(morphs), Some(weights)) = (&mut morphs, pose.weights) {
+                    morphs.weights_mut().copy_from_slice(&weights);
+                }
+            }
+
+            if !any_path_found {
+                warn!("Animation graph player on {root:?} did not match any entity paths.");
+            }
+        });
+}
+
+/// Advances the clips of all the [`Clip`](AnimationNodeKind::Clip) nodes of `graph`.
+fn update_nodes(
+    player: &mut AnimationGraphPlayer,
+    graph: &AnimationGraph,
+    clips: &Assets<AnimationClip>,
+    delta: f32,
+) {
+    player
+        .nodes
+        .resize_with(graph.nodes.len(), PlayingAnimation::default);
+    for (node, state) in graph.nodes.iter().zip(&mut player.nodes) {
+        let AnimationNodeKind::Clip(handle) = &node.kind else {
+            continue;
+        };
+        if &state.animation_clip != handle {
+            *state = PlayingAnimation {
+                animation_clip: handle.clone(),
+                ..Default::default()
+            };
+        }
+        state.repeat = RepeatAnimation::Forever;
+        state.speed = player.speed;
+        if let Some(clip) = clips.get(handle) {
+            state.update(delta, clip.duration);
+        }
+    }
+}
+
+/// The paths animated by the clips of `graph`, without duplicates.
+fn graph_paths<'a>(
+    graph: &'a AnimationGraph,
+    clips: &'a Assets<AnimationClip>,
+) -> impl Iterator<Item = &'a EntityPath> {
+    let paths: HashSet<_> = graph
+        .nodes
+        .iter()
+        .filter_map(|node| match &node.kind {
+            AnimationNodeKind::Clip(handle) => clips.get(handle),
+            _ => None,
+        })
+        .flat_map(|clip| clip.paths.keys())
+        .collect();
+    paths.into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::system::RunSystemOnce;
+    use bevy_hierarchy::BuildWorldChildren;
+
+    use super::*;
+    use crate::{Interpolation, Keyframes, VariableCurve};
+
+    fn path(names: &[&'static str]) -> EntityPath {
+        EntityPath {
+            parts: names.iter().map(|name| Name::new(*name)).collect(),
+        }
+    }
+
+    fn translation_curve(keyframes: Vec<(f32, Vec3)>) -> VariableCurve {
+        VariableCurve {
+            keyframe_timestamps: keyframes.iter().map(|(time, _)| *time).collect(),
+            keyframes: Keyframes::Translation(keyframes.iter().map(|(_, k)| *k).collect()),
+            interpolation: Interpolation::Linear,
+        }
+    }
+
+    /// Adds a clip translating each of the bones at `paths` to `translation`.
+    fn add_clip(
+        world: &mut World,
+        paths: &[&EntityPath],
+        translation: Vec3,
+    ) -> Handle<AnimationClip> {
+        let mut clip = AnimationClip::default();
+        for path in paths {
+            clip.add_curve_to_path((*path).clone(), translation_curve(vec![(0.0, translation)]));
+        }
+        world.resource_mut::<Assets<AnimationClip>>().add(clip)
+    }
+
+    /// Spawns a `root` entity with a `body` child, itself with an `arm` child.
+    fn setup() -> (World, Entity, Entity) {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Assets<AnimationClip>>();
+        world.init_resource::<Assets<AnimationGraph>>();
+        let mut arm = Entity::PLACEHOLDER;
+        let mut body = Entity::PLACEHOLDER;
+        world
+            .spawn((Name::new("root"), Transform::default()))
+            .with_children(|parent| {
+                body = parent
+                    .spawn((Name::new("body"), Transform::default()))
+                    .with_children(|parent| {
+                        arm = parent.spawn((Name::new("arm"), Transform::default())).id();
+                    })
+                    .id();
+            });
+        (world, body, arm)
+    }
+
+    fn play(world: &mut World, graph: AnimationGraph) {
+        let graph = world.resource_mut::<Assets<AnimationGraph>>().add(graph);
+        let root = world
+            .query_filtered::<Entity, Without<Parent>>()
+            .single(world);
+        world
+            .entity_mut(root)
+            .insert(AnimationGraphPlayer::new(graph));
+    }
+
+    fn translation(world: &World, entity: Entity) -> Vec3 {
+        world.get::<Transform>(entity).unwrap().translation
+    }
+
+    #[test]
+    fn blend_space() {
+        let (mut world, body, _) = setup();
+        let body_path = path(&["root", "body"]);
+        let walk = add_clip(&mut world, &[&body_path], Vec3::X);
+        let run = add_clip(&mut world, &[&body_path], Vec3::X * 3.0);
+
+        let mut graph = AnimationGraph::new();
+        let locomotion = graph.add_blend_space_1d("speed", 1.0, graph.root());
+        graph.add_node(
+            AnimationGraphNode::clip(walk).with_blend_position(1.0),
+            locomotion,
+        );
+        graph.add_node(
+            AnimationGraphNode::clip(run).with_blend_position(5.0),
+            locomotion,
+        );
+        play(&mut world, graph);
+
+        for (speed, x) in [(0.0, 1.0), (2.0, 1.5), (5.0, 3.0), (10.0, 3.0)] {
+            let mut players = world.query::<&mut AnimationGraphPlayer>();
+            players.single_mut(&mut world).set_parameter("speed", speed);
+            world.run_system_once(animation_graph_player);
+            assert_eq!(translation(&world, body), Vec3::X * x, "speed {speed}");
+        }
+    }
+
+    #[test]
+    fn masked_layers() {
+        let (mut world, body, arm) = setup();
+        let body_path = path(&["root", "body"]);
+        let arm_path = path(&["root", "body", "arm"]);
+        let base = add_clip(&mut world, &[&body_path, &arm_path], Vec3::X);
+        let wave = add_clip(&mut world, &[&arm_path], Vec3::Y);
+        let blended = add_clip(&mut world, &[&arm_path], Vec3::Z);
+
+        let mut graph = AnimationGraph::new();
+        graph.add_clip(base, 1.0, graph.root());
+        let upper_body = graph.add_node(
+            AnimationGraphNode::new(AnimationNodeKind::Blend)
+                .with_weight_parameter("upper_body")
+                .with_mask(AnimationMask::new([arm_path])),
+            graph.root(),
+        );
+        graph.add_clip(wave, 3.0, upper_body);
+        graph.add_clip(blended, 1.0, upper_body);
+        play(&mut world, graph);
+
+        world.run_system_once(animation_graph_player);
+        assert_eq!(translation(&world, body), Vec3::X);
+        assert_eq!(
+            translation(&world, arm),
+            Vec3::new(0.0, 0.75, 0.25),
+            "the masked layer overrides the base layer"
+        );
+
+        let mut players = world.query::<&mut AnimationGraphPlayer>();
+        players
+            .single_mut(&mut world)
+            .set_parameter("upper_body", 0.5);
+        world.run_system_once(animation_graph_player);
+        assert_eq!(translation(&world, arm), Vec3::new(0.5, 0.375, 0.125));
+    }
+
+    #[test]
+    fn additive_layer() {
+        let (mut world, body, _) = setup();
+        let body_path = path(&["root", "body"]);
+        let base = add_clip(&mut world, &[&body_path], Vec3::X);
+        let mut bob = AnimationClip::default();
+        bob.add_curve_to_path(
+            body_path,
+            translation_curve(vec![(0.0, Vec3::Y), (1.0, Vec3::Y * 3.0)]),
+        );
+        let bob = world.resource_mut::<Assets<AnimationClip>>().add(bob);
+
+        let mut graph = AnimationGraph::new();
+        graph.add_clip(base, 1.0, graph.root());
+        let additive = graph.add_additive(0.5, graph.root());
+        let bob = graph.add_clip(bob, 1.0, additive);
+        play(&mut world, graph);
+
+        world.run_system_once(animation_graph_player);
+        assert_eq!(translation(&world, body), Vec3::X);
+
+        let mut players = world.query::<&mut AnimationGraphPlayer>();
+        players.single_mut(&mut world).seek_to(bob, 0.5);
+        world.run_system_once(animation_graph_player);
+        assert_eq!(translation(&world, body), Vec3::new(1.0, 0.5, 0.0));
+    }
+
+    #[test]
+    fn additive_only_bone_stays_still() {
+        let (mut world, body, _) = setup();
+        let body_path = path(&["root", "body"]);
+        world.get_mut::<Transform>(body).unwrap().translation = Vec3::Z;
+        let mut bob = AnimationClip::default();
+        bob.add_curve_to_path(
+            body_path,
+            translation_curve(vec![(0.0, Vec3::Y), (1.0, Vec3::Y * 3.0)]),
+        );
+        let bob = world.resource_mut::<Assets<AnimationClip>>().add(bob);
+
+        let mut graph = AnimationGraph::new();
+        let additive = graph.add_additive(0.5, graph.root());
+        let bob = graph.add_clip(bob, 1.0, additive);
+        play(&mut world, graph);
+
+        let mut players = world.query::<&mut AnimationGraphPlayer>();
+        players.single_mut(&mut world).pause();
+        world.run_system_once(animation_graph_player);
+        players.single_mut(&mut world).seek_to(bob, 0.5);
+        for _ in 0..10 {
+            // mark the player as changed so that it applies the seek while paused
+            players.single_mut(&mut world).set_speed(1.0);
+            world.run_system_once(animation_graph_player);
+            assert_eq!(
+                translation(&world, body),
+                Vec3::new(0.0, 0.5, 1.0),
+                "the motion is added to the rest pose, not to the previous frame"
+            );
+        }
+    }
+}
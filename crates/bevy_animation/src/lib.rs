@@ -2,7 +2,12 @@
 
 #![warn(missing_docs)]
 
-use std::ops::Deref;
+pub mod event;
+pub mod graph;
+pub mod property;
+pub mod root_motion;
+
+use std::ops::{Add, Deref, Mul, Range};
 use std::time::Duration;
 
 use bevy_app::{App, Plugin, PostUpdate};
@@ -16,12 +21,21 @@ use bevy_render::mesh::morph::MorphWeights;
 use bevy_time::Time;
 use bevy_transform::{prelude::Transform, TransformSystem};
 use bevy_utils::{tracing::warn, HashMap};
+use event::{crossed_events, AnimationEvent, ClipEvent};
+use graph::{animation_graph_player, AnimationGraph, AnimationGraphPlayer, AnimationMask};
+use property::{animate_properties, PropertyCurve, PropertyKeyframes};
+use root_motion::{extract_root_motion, remove_root_motion, RootMotion};
 
 #[allow(missing_docs)]
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        AnimationClip, AnimationPlayer, AnimationPlugin, EntityPath, Keyframes, VariableCurve,
+        event::{AnimationEvent, ClipEvent},
+        graph::{AnimationGraph, AnimationGraphPlayer, AnimationMask, AnimationNodeIndex},
+        property::{PropertyCurve, PropertyKeyframes},
+        root_motion::RootMotion,
+        AnimationClip, AnimationPlayer, AnimationPlugin, EntityPath, Interpolation, Keyframes,
+        VariableCurve,
     };
 }
 
@@ -45,15 +59,244 @@ pub enum Keyframes {
     Weights(Vec<f32>),
 }
 
+/// Interpolation method used between the keyframes of a [`VariableCurve`].
+///
+/// This follows the [glTF design].
+///
+/// [glTF design]: https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html#interpolation
+#[derive(Reflect, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// Linear interpolation between the keyframes, with a spherical linear interpolation for
+    /// rotations.
+    #[default]
+    Linear,
+    /// The value of a keyframe is held until the next keyframe.
+    Step,
+    /// Cubic Hermite spline interpolation, with an in-tangent and an out-tangent for each
+    /// keyframe.
+    ///
+    /// Each keyframe is stored as three values in the [`Keyframes`]: its in-tangent, its value and
+    /// its out-tangent.
+    CubicSpline,
+}
+
 /// Describes how an attribute of a [`Transform`] or [`MorphWeights`] should be animated.
 ///
-/// `keyframe_timestamps` and `keyframes` should have the same length.
+/// `keyframe_timestamps` and `keyframes` should have the same length, except with the
+/// [`Interpolation::CubicSpline`] interpolation, where `keyframes` contains three values per
+/// keyframe.
 #[derive(Reflect, Clone, Debug)]
 pub struct VariableCurve {
     /// Timestamp for each of the keyframes.
     pub keyframe_timestamps: Vec<f32>,
     /// List of the keyframes.
     pub keyframes: Keyframes,
+    /// Interpolation method between the keyframes.
+    pub interpolation: Interpolation,
+}
+
+/// The value of a [`VariableCurve`] at a given time.
+enum CurveSample {
+    Rotation(Quat),
+    Translation(Vec3),
+    Scale(Vec3),
+    Weights(Vec<f32>),
+}
+
+/// The keyframes surrounding a time in a curve.
+#[derive(Clone, Copy, Debug)]
+struct KeyframeSpan {
+    /// Index of the keyframe before the time.
+    start: usize,
+    /// Index of the keyframe after the time. This is `start` for curves with a single keyframe.
+    end: usize,
+    /// Position of the time between the two keyframes, from `0.0` to `1.0`.
+    lerp: f32,
+}
+
+/// Finds the keyframes surrounding `seek_time` in `keyframe_timestamps`.
+///
+/// Returns `None` if the curve isn't started yet or is already finished at this time.
+fn find_keyframes(keyframe_timestamps: &[f32], seek_time: f32) -> Option<KeyframeSpan> {
+    // Some curves have only one keyframe used to set a value
+    if keyframe_timestamps.len() == 1 {
+        return Some(KeyframeSpan {
+            start: 0,
+            end: 0,
+            lerp: 0.0,
+        });
+    }
+
+    // Find the current keyframe
+    // PERF: finding the current keyframe can be optimised
+    let step_start = match keyframe_timestamps
+        .binary_search_by(|probe| probe.partial_cmp(&seek_time).unwrap())
+    {
+        Ok(n) if n >= keyframe_timestamps.len() - 1 => return None, // this curve is finished
+        Ok(i) => i,
+        Err(0) => return None, // this curve isn't started yet
+        Err(n) if n > keyframe_timestamps.len() - 1 => return None, // this curve is finished
+        Err(i) => i - 1,
+    };
+    let ts_start = keyframe_timestamps[step_start];
+    let ts_end = keyframe_timestamps[step_start + 1];
+    Some(KeyframeSpan {
+        start: step_start,
+        end: step_start + 1,
+        lerp: (seek_time - ts_start) / (ts_end - ts_start),
+    })
+}
+
+impl VariableCurve {
+    /// Samples the curve at `seek_time`.
+    ///
+    /// Returns `None` if the curve isn't started yet or is already finished at this time.
+    /// `target_count` is the number of morph targets of the animated entity, used for
+    /// [`Keyframes::Weights`].
+    fn sample(&self, seek_time: f32, target_count: usize) -> Option<CurveSample> {
+        let span = find_keyframes(&self.keyframe_timestamps, seek_time)?;
+        match self.interpolation {
+            Interpolation::Linear => {}
+            Interpolation::Step => return Some(self.keyframe_sample(span.start, target_count)),
+            Interpolation::CubicSpline => {
+                return Some(self.cubic_spline_sample(span, target_count))
+            }
+        }
+
+        let KeyframeSpan { start, end, lerp } = span;
+        Some(match &self.keyframes {
+            Keyframes::Rotation(keyframes) => {
+                let rot_start = keyframes[start];
+                let mut rot_end = keyframes[end];
+                // Choose the smallest angle for the rotation
+                if rot_end.dot(rot_start) < 0.0 {
+                    rot_end = -rot_end;
+                }
+                // Rotations are using a spherical linear interpolation
+                CurveSample::Rotation(rot_start.normalize().slerp(rot_end.normalize(), lerp))
+            }
+            Keyframes::Translation(keyframes) => {
+                let translation_start = keyframes[start];
+                let translation_end = keyframes[end];
+                CurveSample::Translation(translation_start.lerp(translation_end, lerp))
+            }
+            Keyframes::Scale(keyframes) => {
+                let scale_start = keyframes[start];
+                let scale_end = keyframes[end];
+                CurveSample::Scale(scale_start.lerp(scale_end, lerp))
+            }
+            Keyframes::Weights(keyframes) => {
+                let morph_start = get_keyframe(target_count, keyframes, start);
+                let morph_end = get_keyframe(target_count, keyframes, end);
+                CurveSample::Weights(
+                    morph_start
+                        .iter()
+                        .zip(morph_end)
+                        .map(|(a, b)| *a + lerp * (*b - *a))
+                        .collect(),
+                )
+            }
+        })
+    }
+
+    /// Samples the cubic spline between the keyframes of `span`.
+    fn cubic_spline_sample(&self, span: KeyframeSpan, target_count: usize) -> CurveSample {
+        let step_duration =
+            self.keyframe_timestamps[span.end] - self.keyframe_timestamps[span.start];
+        match &self.keyframes {
+            Keyframes::Rotation(keyframes) => CurveSample::Rotation(
+                cubic_spline_keyframes(|index| keyframes[index], span, step_duration).normalize(),
+            ),
+            Keyframes::Translation(keyframes) => CurveSample::Translation(cubic_spline_keyframes(
+                |index| keyframes[index],
+                span,
+                step_duration,
+            )),
+            Keyframes::Scale(keyframes) => CurveSample::Scale(cubic_spline_keyframes(
+                |index| keyframes[index],
+                span,
+                step_duration,
+            )),
+            Keyframes::Weights(keyframes) => CurveSample::Weights(
+                (0..target_count)
+                    .map(|target| {
+                        cubic_spline_keyframes(
+                            |index| keyframes[index * target_count + target],
+                            span,
+                            step_duration,
+                        )
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// The value of the keyframe at `index`.
+    fn keyframe_sample(&self, index: usize, target_count: usize) -> CurveSample {
+        let index = match self.interpolation {
+            Interpolation::Linear | Interpolation::Step => index,
+            Interpolation::CubicSpline => index * 3 + 1,
+        };
+        match &self.keyframes {
+            Keyframes::Rotation(keyframes) => CurveSample::Rotation(keyframes[index]),
+            Keyframes::Translation(keyframes) => CurveSample::Translation(keyframes[index]),
+            Keyframes::Scale(keyframes) => CurveSample::Scale(keyframes[index]),
+            Keyframes::Weights(keyframes) => {
+                CurveSample::Weights(get_keyframe(target_count, keyframes, index).to_vec())
+            }
+        }
+    }
+
+    /// The value of the first keyframe of the curve.
+    fn first_sample(&self, target_count: usize) -> CurveSample {
+        self.keyframe_sample(0, target_count)
+    }
+}
+
+/// Evaluates the cubic spline between the keyframes of `span`, which are `step_duration` seconds
+/// apart. `keyframe` returns the value at an index of the keyframes, where each keyframe is stored
+/// as its in-tangent, its value and its out-tangent.
+fn cubic_spline_keyframes<T>(
+    keyframe: impl Fn(usize) -> T,
+    span: KeyframeSpan,
+    step_duration: f32,
+) -> T
+where
+    T: Mul<f32, Output = T> + Add<Output = T>,
+{
+    cubic_spline_interpolation(
+        keyframe(span.start * 3 + 1),
+        keyframe(span.start * 3 + 2),
+        keyframe(span.end * 3),
+        keyframe(span.end * 3 + 1),
+        span.lerp,
+        step_duration,
+    )
+}
+
+/// Evaluates the cubic Hermite spline between two keyframes `step_duration` seconds apart, at
+/// `lerp` from `0.0` to `1.0` between them.
+///
+/// This follows the [glTF design].
+///
+/// [glTF design]: https://registry.khronos.org/glTF/specs/2.0/glTF-2.0.html#interpolation-cubic
+fn cubic_spline_interpolation<T>(
+    value_start: T,
+    tangent_out_start: T,
+    tangent_in_end: T,
+    value_end: T,
+    lerp: f32,
+    step_duration: f32,
+) -> T
+where
+    T: Mul<f32, Output = T> + Add<Output = T>,
+{
+    let lerp2 = lerp * lerp;
+    let lerp3 = lerp2 * lerp;
+    value_start * (2.0 * lerp3 - 3.0 * lerp2 + 1.0)
+        + tangent_out_start * (step_duration * (lerp3 - 2.0 * lerp2 + lerp))
+        + value_end * (-2.0 * lerp3 + 3.0 * lerp2)
+        + tangent_in_end * (step_duration * (lerp3 - lerp2))
 }
 
 /// Path to an entity, with [`Name`]s. Each entity in a path must have a name.
@@ -63,11 +306,14 @@ pub struct EntityPath {
     pub parts: Vec<Name>,
 }
 
-/// A list of [`VariableCurve`], and the [`EntityPath`] to which they apply.
+/// A list of [`VariableCurve`] and [`PropertyCurve`], and the [`EntityPath`] to which they apply,
+/// with the [`ClipEvent`]s fired during the animation.
 #[derive(Asset, Reflect, Clone, Debug, Default)]
 pub struct AnimationClip {
     curves: Vec<Vec<VariableCurve>>,
+    property_curves: Vec<Vec<PropertyCurve>>,
     paths: HashMap<EntityPath, usize>,
+    events: Vec<ClipEvent>,
     duration: f32,
 }
 
@@ -94,6 +340,30 @@ impl AnimationClip {
         self.paths.get(path).and_then(|id| self.curves.get(*id))
     }
 
+    /// [`PropertyCurve`]s for each bone. Indexed by the bone ID.
+    #[inline]
+    pub fn property_curves(&self) -> &Vec<Vec<PropertyCurve>> {
+        &self.property_curves
+    }
+
+    /// Gets the property curves for a bone.
+    ///
+    /// Returns `None` if the bone is invalid or has no property curves.
+    #[inline]
+    pub fn get_property_curves(&self, bone_id: usize) -> Option<&'_ Vec<PropertyCurve>> {
+        self.property_curves.get(bone_id)
+    }
+
+    /// Gets the property curves by it's [`EntityPath`].
+    ///
+    /// Returns `None` if the bone is invalid or has no property curves.
+    #[inline]
+    pub fn get_property_curves_by_path(&self, path: &EntityPath) -> Option<&'_ Vec<PropertyCurve>> {
+        self.paths
+            .get(path)
+            .and_then(|id| self.property_curves.get(*id))
+    }
+
     /// Duration of the clip, represented in seconds
     #[inline]
     pub fn duration(&self) -> f32 {
@@ -106,13 +376,50 @@ impl AnimationClip {
         self.duration = self
             .duration
             .max(*curve.keyframe_timestamps.last().unwrap_or(&0.0));
-        if let Some(bone_id) = self.paths.get(&path) {
-            self.curves[*bone_id].push(curve);
-        } else {
-            let idx = self.curves.len();
-            self.curves.push(vec![curve]);
-            self.paths.insert(path, idx);
-        }
+        let bone_id = self.bone_id(path);
+        self.curves[bone_id].push(curve);
+    }
+
+    /// Add a [`PropertyCurve`] to an [`EntityPath`].
+    pub fn add_property_curve_to_path(&mut self, path: EntityPath, curve: PropertyCurve) {
+        // Update the duration of the animation by this curve duration if it's longer
+        self.duration = self
+            .duration
+            .max(*curve.keyframe_timestamps().last().unwrap_or(&0.0));
+        let bone_id = self.bone_id(path);
+        self.property_curves[bone_id].push(curve);
+    }
+
+    /// [`ClipEvent`]s of the clip, sorted by time.
+    #[inline]
+    pub fn events(&self) -> &[ClipEvent] {
+        &self.events
+    }
+
+    /// Add a [`ClipEvent`] named `name` at `time`, in seconds.
+    ///
+    /// The [`AnimationPlayer`] sends an [`AnimationEvent`] each time its playback crosses it.
+    pub fn add_event(&mut self, time: f32, name: impl Into<String>) {
+        // Update the duration of the animation if the event is after its end
+        self.duration = self.duration.max(time);
+        let index = self.events.partition_point(|event| event.time <= time);
+        self.events.insert(
+            index,
+            ClipEvent {
+                time,
+                name: name.into(),
+            },
+        );
+    }
+
+    /// Gets the bone ID of an [`EntityPath`], adding the bone if needed.
+    fn bone_id(&mut self, path: EntityPath) -> usize {
+        let next_id = self.paths.len();
+        let bone_id = *self.paths.entry(path).or_insert(next_id);
+        let bone_count = self.paths.len();
+        self.curves.resize_with(bone_count, Vec::new);
+        self.property_curves.resize_with(bone_count, Vec::new);
+        bone_id
     }
 
     /// Whether this animation clip can run on entity with given [`Name`].
@@ -180,14 +487,18 @@ impl PlayingAnimation {
     }
 
     /// Update the animation given the delta time and the duration of the clip being played.
+    ///
+    /// Returns the range of seek time played, before it's wrapped to the clip duration.
     #[inline]
-    fn update(&mut self, delta: f32, clip_duration: f32) {
+    fn update(&mut self, delta: f32, clip_duration: f32) -> Range<f32> {
         if self.is_finished() {
-            return;
+            return self.seek_time..self.seek_time;
         }
 
         self.elapsed += delta;
+        let start = self.seek_time;
         self.seek_time += delta * self.speed;
+        let played = start..self.seek_time;
 
         let over_time = self.speed > 0.0 && self.seek_time >= clip_duration;
         let under_time = self.speed < 0.0 && self.seek_time < 0.0;
@@ -196,7 +507,8 @@ impl PlayingAnimation {
             self.completions += 1;
 
             if self.is_finished() {
-                return;
+                // The playback stops at the end of the clip
+                return start..played.end.clamp(0.0, clip_duration);
             }
         }
         if self.seek_time >= clip_duration {
@@ -206,6 +518,7 @@ impl PlayingAnimation {
         if self.seek_time < 0.0 {
             self.seek_time += clip_duration;
         }
+        played
     }
 
     /// Reset back to the initial state as if no time has elapsed.
@@ -241,6 +554,12 @@ pub struct AnimationPlayer {
     // Once a transition is finished, it will be automatically removed from the list
     #[reflect(ignore)]
     transitions: Vec<AnimationTransition>,
+
+    // Events crossed by the main animation during the last update, waiting to be sent.
+    #[reflect(ignore)]
+    pending_events: Vec<ClipEvent>,
+
+    root_motion: Option<EntityPath>,
 }
 
 impl AnimationPlayer {
@@ -397,6 +716,20 @@ impl AnimationPlayer {
     pub fn replay(&mut self) {
         self.animation.replay();
     }
+
+    /// Extract the motion of the root bone at `path` from the animations, instead of applying it
+    /// to the bone. See [`RootMotion`].
+    ///
+    /// Disabled with `None`, which is the default.
+    pub fn set_root_motion(&mut self, path: Option<EntityPath>) -> &mut Self {
+        self.root_motion = path;
+        self
+    }
+
+    /// The root bone whose motion is extracted from the animations, if any.
+    pub fn root_motion(&self) -> Option<&EntityPath> {
+        self.root_motion.as_ref()
+    }
 }
 
 fn entity_from_path(
@@ -454,19 +787,28 @@ fn entity_from_path(
     Some(current_entity)
 }
 
-/// Verify that there are no ancestors of a given entity that have an [`AnimationPlayer`].
-fn verify_no_ancestor_player(
-    player_parent: Option<&Parent>,
-    parents: &Query<(Has<AnimationPlayer>, Option<&Parent>)>,
-) -> bool {
+/// The ancestors of the animated entities, and whether they have an animation player.
+type PlayerParents<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Has<AnimationPlayer>,
+        Has<AnimationGraphPlayer>,
+        Option<&'static Parent>,
+    ),
+>;
+
+/// Verify that there are no ancestors of a given entity that have an [`AnimationPlayer`] or an
+/// [`AnimationGraphPlayer`].
+fn verify_no_ancestor_player(player_parent: Option<&Parent>, parents: &PlayerParents) -> bool {
     let Some(mut current) = player_parent.map(Parent::get) else {
         return true;
     };
     loop {
-        let Ok((has_player, parent)) = parents.get(current) else {
+        let Ok((has_player, has_graph_player, parent)) = parents.get(current) else {
             return true;
         };
-        if has_player {
+        if has_player || has_graph_player {
             return false;
         }
         if let Some(parent) = parent {
@@ -478,7 +820,7 @@ fn verify_no_ancestor_player(
 }
 
 /// System that will play all animations, using any entity with a [`AnimationPlayer`]
-/// and a [`Handle<AnimationClip>`] as an animation root
+/// and a [`Handle<AnimationClip>`] as an animation root, and send the [`AnimationEvent`]s crossed
 #[allow(clippy::too_many_arguments)]
 pub fn animation_player(
     time: Res<Time>,
@@ -487,16 +829,25 @@ pub fn animation_player(
     names: Query<&Name>,
     transforms: Query<&mut Transform>,
     morphs: Query<&mut MorphWeights>,
-    parents: Query<(Has<AnimationPlayer>, Option<&Parent>)>,
-    mut animation_players: Query<(Entity, Option<&Parent>, &mut AnimationPlayer)>,
+    parents: PlayerParents,
+    mut animation_players: Query<(
+        Entity,
+        Option<&Parent>,
+        &mut AnimationPlayer,
+        Option<&mut RootMotion>,
+    )>,
+    mut animation_events: EventWriter<AnimationEvent>,
 ) {
     animation_players
         .par_iter_mut()
-        .for_each(|(root, maybe_parent, mut player)| {
-            update_transitions(&mut player, &time);
+        .for_each(|(root, maybe_parent, mut player, root_motion)| {
+            // Fading out the transitions isn't a change to the player, so that the paused
+            // players are still skipped by `animate_properties`
+            update_transitions(player.bypass_change_detection(), &time);
             run_animation_player(
                 root,
                 player,
+                root_motion,
                 &time,
                 &animations,
                 &names,
@@ -507,32 +858,53 @@ pub fn animation_player(
                 &children,
             );
         });
+
+    // Events are collected by each player during the parallel iteration, to be sent in order
+    for (root, _, mut player, _) in &mut animation_players {
+        if player.pending_events.is_empty() {
+            continue;
+        }
+        let clip = player.animation.animation_clip.clone();
+        animation_events.send_batch(player.pending_events.drain(..).map(|event| AnimationEvent {
+            player: root,
+            clip: clip.clone(),
+            time: event.time,
+            name: event.name,
+        }));
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
 fn run_animation_player(
     root: Entity,
     mut player: Mut<AnimationPlayer>,
+    root_motion: Option<Mut<RootMotion>>,
     time: &Time,
     animations: &Assets<AnimationClip>,
     names: &Query<&Name>,
     transforms: &Query<&mut Transform>,
     morphs: &Query<&mut MorphWeights>,
     maybe_parent: Option<&Parent>,
-    parents: &Query<(Has<AnimationPlayer>, Option<&Parent>)>,
+    parents: &PlayerParents,
     children: &Query<&Children>,
 ) {
     let paused = player.paused;
     // Continue if paused unless the `AnimationPlayer` was changed
     // This allow the animation to still be updated if the player.elapsed field was manually updated in pause
     if paused && !player.is_changed() {
+        if let Some(mut root_motion) = root_motion {
+            root_motion.set_if_neq(RootMotion::default());
+        }
         return;
     }
 
+    let root_motion_path = player.root_motion.clone();
+
     // Apply the main animation
-    apply_animation(
+    let played = apply_animation(
         1.0,
         &mut player.animation,
+        root_motion_path.as_ref(),
         paused,
         root,
         time,
@@ -544,6 +916,20 @@ fn run_animation_player(
         parents,
         children,
     );
+    if let (Some(played), Some(animation_clip)) =
+        (played, animations.get(&player.animation.animation_clip))
+    {
+        crossed_events(
+            &animation_clip.events,
+            animation_clip.duration,
+            played.clone(),
+            player.animation.is_finished(),
+            |event| player.pending_events.push(event.clone()),
+        );
+        if let (Some(path), Some(mut root_motion)) = (&root_motion_path, root_motion) {
+            root_motion.set_if_neq(extract_root_motion(animation_clip, path, played));
+        }
+    }
 
     // Apply any potential fade-out transitions from previous animations
     for AnimationTransition {
@@ -555,6 +941,7 @@ fn run_animation_player(
         apply_animation(
             *current_weight,
             animation,
+            root_motion_path.as_ref(),
             paused,
             root,
             time,
@@ -597,6 +984,7 @@ fn get_keyframe(target_count: usize, keyframes: &[f32], key_index: usize) -> &[f
 fn apply_animation(
     weight: f32,
     animation: &mut PlayingAnimation,
+    root_motion: Option<&EntityPath>,
     paused: bool,
     root: Entity,
     time: &Time,
@@ -605,138 +993,83 @@ fn apply_animation(
     transforms: &Query<&mut Transform>,
     morphs: &Query<&mut MorphWeights>,
     maybe_parent: Option<&Parent>,
-    parents: &Query<(Has<AnimationPlayer>, Option<&Parent>)>,
+    parents: &PlayerParents,
     children: &Query<&Children>,
-) {
-    if let Some(animation_clip) = animations.get(&animation.animation_clip) {
-        // We don't return early because seek_to() may have been called on the animation player.
-        animation.update(
-            if paused { 0.0 } else { time.delta_seconds() },
-            animation_clip.duration,
-        );
+) -> Option<Range<f32>> {
+    let animation_clip = animations.get(&animation.animation_clip)?;
+    // We don't return early because seek_to() may have been called on the animation player.
+    let played = animation.update(
+        if paused { 0.0 } else { time.delta_seconds() },
+        animation_clip.duration,
+    );
 
-        if animation.path_cache.len() != animation_clip.paths.len() {
-            animation.path_cache = vec![Vec::new(); animation_clip.paths.len()];
-        }
-        if !verify_no_ancestor_player(maybe_parent, parents) {
-            warn!("Animation player on {:?} has a conflicting animation player on an ancestor. Cannot safely animate.", root);
-            return;
-        }
+    if animation.path_cache.len() != animation_clip.paths.len() {
+        animation.path_cache = vec![Vec::new(); animation_clip.paths.len()];
+    }
+    if !verify_no_ancestor_player(maybe_parent, parents) {
+        warn!("Animation player on {:?} has a conflicting animation player on an ancestor. Cannot safely animate.", root);
+        return Some(played);
+    }
 
-        let mut any_path_found = false;
-        for (path, bone_id) in &animation_clip.paths {
-            let cached_path = &mut animation.path_cache[*bone_id];
-            let curves = animation_clip.get_curves(*bone_id).unwrap();
-            let Some(target) = entity_from_path(root, path, children, names, cached_path) else {
-                continue;
-            };
-            any_path_found = true;
-            // SAFETY: The verify_no_ancestor_player check above ensures that two animation players cannot alias
-            // any of their descendant Transforms.
-            //
-            // The system scheduler prevents any other system from mutating Transforms at the same time,
-            // so the only way this fetch can alias is if two AnimationPlayers are targeting the same bone.
-            // This can only happen if there are two or more AnimationPlayers are ancestors to the same
-            // entities. By verifying that there is no other AnimationPlayer in the ancestors of a
-            // running AnimationPlayer before animating any entity, this fetch cannot alias.
-            //
-            // This means only the AnimationPlayers closest to the root of the hierarchy will be able
-            // to run their animation. Any players in the children or descendants will log a warning
-            // and do nothing.
-            let Ok(mut transform) = (unsafe { transforms.get_unchecked(target) }) else {
+    let mut any_path_found = false;
+    for (path, bone_id) in &animation_clip.paths {
+        let cached_path = &mut animation.path_cache[*bone_id];
+        let curves = animation_clip.get_curves(*bone_id).unwrap();
+        let Some(target) = entity_from_path(root, path, children, names, cached_path) else {
+            continue;
+        };
+        let is_root_motion = root_motion == Some(path);
+        any_path_found = true;
+        // SAFETY: The verify_no_ancestor_player check above ensures that two animation players cannot alias
+        // any of their descendant Transforms.
+        //
+        // The system scheduler prevents any other system from mutating Transforms at the same time,
+        // so the only way this fetch can alias is if two AnimationPlayers are targeting the same bone.
+        // This can only happen if there are two or more AnimationPlayers are ancestors to the same
+        // entities. By verifying that there is no other AnimationPlayer in the ancestors of a
+        // running AnimationPlayer before animating any entity, this fetch cannot alias.
+        //
+        // This means only the AnimationPlayers closest to the root of the hierarchy will be able
+        // to run their animation. Any players in the children or descendants will log a warning
+        // and do nothing.
+        let Ok(mut transform) = (unsafe { transforms.get_unchecked(target) }) else {
+            continue;
+        };
+        // SAFETY: As above, there can't be other AnimationPlayers with this target so this fetch can't alias
+        let mut morphs = unsafe { morphs.get_unchecked(target) };
+        let target_count = morphs.as_ref().map_or(0, |morphs| morphs.weights().len());
+        for curve in curves {
+            let Some(mut sample) = curve.sample(animation.seek_time, target_count) else {
                 continue;
             };
-            // SAFETY: As above, there can't be other AnimationPlayers with this target so this fetch can't alias
-            let mut morphs = unsafe { morphs.get_unchecked(target) };
-            for curve in curves {
-                // Some curves have only one keyframe used to set a transform
-                if curve.keyframe_timestamps.len() == 1 {
-                    match &curve.keyframes {
-                        Keyframes::Rotation(keyframes) => {
-                            transform.rotation = transform.rotation.slerp(keyframes[0], weight);
-                        }
-                        Keyframes::Translation(keyframes) => {
-                            transform.translation =
-                                transform.translation.lerp(keyframes[0], weight);
-                        }
-                        Keyframes::Scale(keyframes) => {
-                            transform.scale = transform.scale.lerp(keyframes[0], weight);
-                        }
-                        Keyframes::Weights(keyframes) => {
-                            if let Ok(morphs) = &mut morphs {
-                                let target_count = morphs.weights().len();
-                                lerp_morph_weights(
-                                    morphs.weights_mut(),
-                                    get_keyframe(target_count, keyframes, 0).iter().copied(),
-                                    weight,
-                                );
-                            }
-                        }
-                    }
-                    continue;
-                }
+            if is_root_motion {
+                sample = remove_root_motion(curve, sample);
+            }
 
-                // Find the current keyframe
-                // PERF: finding the current keyframe can be optimised
-                let step_start = match curve
-                    .keyframe_timestamps
-                    .binary_search_by(|probe| probe.partial_cmp(&animation.seek_time).unwrap())
-                {
-                    Ok(n) if n >= curve.keyframe_timestamps.len() - 1 => continue, // this curve is finished
-                    Ok(i) => i,
-                    Err(0) => continue, // this curve isn't started yet
-                    Err(n) if n > curve.keyframe_timestamps.len() - 1 => continue, // this curve is finished
-                    Err(i) => i - 1,
-                };
-                let ts_start = curve.keyframe_timestamps[step_start];
-                let ts_end = curve.keyframe_timestamps[step_start + 1];
-                let lerp = (animation.seek_time - ts_start) / (ts_end - ts_start);
-
-                // Apply the keyframe
-                match &curve.keyframes {
-                    Keyframes::Rotation(keyframes) => {
-                        let rot_start = keyframes[step_start];
-                        let mut rot_end = keyframes[step_start + 1];
-                        // Choose the smallest angle for the rotation
-                        if rot_end.dot(rot_start) < 0.0 {
-                            rot_end = -rot_end;
-                        }
-                        // Rotations are using a spherical linear interpolation
-                        let rot = rot_start.normalize().slerp(rot_end.normalize(), lerp);
-                        transform.rotation = transform.rotation.slerp(rot, weight);
-                    }
-                    Keyframes::Translation(keyframes) => {
-                        let translation_start = keyframes[step_start];
-                        let translation_end = keyframes[step_start + 1];
-                        let result = translation_start.lerp(translation_end, lerp);
-                        transform.translation = transform.translation.lerp(result, weight);
-                    }
-                    Keyframes::Scale(keyframes) => {
-                        let scale_start = keyframes[step_start];
-                        let scale_end = keyframes[step_start + 1];
-                        let result = scale_start.lerp(scale_end, lerp);
-                        transform.scale = transform.scale.lerp(result, weight);
-                    }
-                    Keyframes::Weights(keyframes) => {
-                        if let Ok(morphs) = &mut morphs {
-                            let target_count = morphs.weights().len();
-                            let morph_start = get_keyframe(target_count, keyframes, step_start);
-                            let morph_end = get_keyframe(target_count, keyframes, step_start + 1);
-                            let result = morph_start
-                                .iter()
-                                .zip(morph_end)
-                                .map(|(a, b)| *a + lerp * (*b - *a));
-                            lerp_morph_weights(morphs.weights_mut(), result, weight);
-                        }
+            // Apply the keyframe
+            match sample {
+                CurveSample::Rotation(rotation) => {
+                    transform.rotation = transform.rotation.slerp(rotation, weight);
+                }
+                CurveSample::Translation(translation) => {
+                    transform.translation = transform.translation.lerp(translation, weight);
+                }
+                CurveSample::Scale(scale) => {
+                    transform.scale = transform.scale.lerp(scale, weight);
+                }
+                CurveSample::Weights(weights) => {
+                    if let Ok(morphs) = &mut morphs {
+                        lerp_morph_weights(morphs.weights_mut(), weights.into_iter(), weight);
                     }
                 }
             }
         }
+    }
 
-        if !any_path_found {
-            warn!("Animation player on {root:?} did not match any entity paths.");
-        }
+    if !any_path_found {
+        warn!("Animation player on {root:?} did not match any entity paths.");
     }
+    Some(played)
 }
 
 fn update_transitions(player: &mut AnimationPlayer, time: &Time) {
@@ -754,10 +1087,73 @@ impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<AnimationClip>()
             .register_asset_reflect::<AnimationClip>()
+            .init_asset::<AnimationGraph>()
+            .register_asset_reflect::<AnimationGraph>()
             .register_type::<AnimationPlayer>()
+            .register_type::<AnimationGraphPlayer>()
+            .register_type::<AnimationMask>()
+            .register_type::<PropertyCurve>()
+            .register_type::<PropertyKeyframes>()
+            .register_type::<ClipEvent>()
+            .register_type::<RootMotion>()
+            .add_event::<AnimationEvent>()
             .add_systems(
                 PostUpdate,
-                animation_player.before(TransformSystem::TransformPropagate),
+                (animation_player, animation_graph_player, animate_properties)
+                    .chain()
+                    .before(TransformSystem::TransformPropagate),
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translation(sample: Option<CurveSample>) -> Vec3 {
+        match sample {
+            Some(CurveSample::Translation(translation)) => translation,
+            _ => panic!("expected a translation"),
+        }
+    }
+
+    #[test]
+    fn step_interpolation() {
+        let curve = VariableCurve {
+            keyframe_timestamps: vec![0.0, 1.0, 2.0],
+            keyframes: Keyframes::Translation(vec![Vec3::ZERO, Vec3::X, Vec3::Y]),
+            interpolation: Interpolation::Step,
+        };
+        assert_eq!(translation(curve.sample(0.5, 0)), Vec3::ZERO);
+        assert_eq!(translation(curve.sample(1.0, 0)), Vec3::X);
+        assert_eq!(translation(curve.sample(1.9, 0)), Vec3::X);
+    }
+
+    #[test]
+    fn cubic_spline_interpolation() {
+        // in-tangent, value and out-tangent of each keyframe
+        let mut curve = VariableCurve {
+            keyframe_timestamps: vec![0.0, 2.0],
+            keyframes: Keyframes::Translation(vec![
+                Vec3::ZERO,
+                Vec3::ZERO,
+                Vec3::ZERO,
+                Vec3::ZERO,
+                Vec3::X * 2.0,
+                Vec3::ZERO,
+            ]),
+            interpolation: Interpolation::CubicSpline,
+        };
+        // Flat tangents ease in and out of the keyframes
+        assert_eq!(translation(Some(curve.first_sample(0))), Vec3::ZERO);
+        assert!(translation(curve.sample(0.5, 0)).abs_diff_eq(Vec3::X * 0.3125, 1e-5));
+        assert!(translation(curve.sample(1.0, 0)).abs_diff_eq(Vec3::X, 1e-5));
+        assert!(translation(curve.sample(1.5, 0)).abs_diff_eq(Vec3::X * 1.6875, 1e-5));
+
+        // The tangents are scaled by the duration between the keyframes
+        if let Keyframes::Translation(keyframes) = &mut curve.keyframes {
+            keyframes[2] = Vec3::X;
+        }
+        assert!(translation(curve.sample(1.0, 0)).abs_diff_eq(Vec3::X * 1.25, 1e-5));
+    }
+}
@@ -0,0 +1,482 @@
+//! Animation of any reflected property of a component.
+//!
+//! A [`PropertyCurve`] targets a field of a component through a reflection path, like the
+//! `intensity` of a `PointLight` or the `color` of a `BackgroundColor`. It's added to an
+//! [`AnimationClip`] for an [`EntityPath`] with [`AnimationClip::add_property_curve_to_path`], and
+//! played by the [`AnimationPlayer`] like the other curves of the clip.
+//!
+//! The component must be registered in the [`AppTypeRegistry`] with its
+//! [`ReflectComponent`] type data, by deriving `Reflect` with `#[reflect(Component)]`.
+//!
+//! ```
+//! # use bevy_animation::{prelude::*, property::*};
+//! # use bevy_core::Name;
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_reflect::Reflect;
+//! #[derive(Component, Reflect, Default)]
+//! #[reflect(Component)]
+//! struct Glow {
+//!     intensity: f32,
+//! }
+//!
+//! let mut clip = AnimationClip::default();
+//! clip.add_property_curve_to_path(
+//!     EntityPath {
+//!         parts: vec![Name::new("lamp")],
+//!     },
+//!     PropertyCurve::new::<Glow>(
+//!         "intensity",
+//!         vec![0.0, 0.5, 1.0],
+//!         PropertyKeyframes::F32(vec![0.0, 10.0, 0.0]),
+//!     )
+//!     .unwrap(),
+//! );
+//! ```
+//!
+//! Property curves are only played by the [`AnimationPlayer`], not by the
+//! [`AnimationGraphPlayer`](crate::graph::AnimationGraphPlayer).
+
+use bevy_asset::Assets;
+use bevy_core::Name;
+use bevy_ecs::{prelude::*, reflect::AppTypeRegistry, system::SystemState};
+use bevy_hierarchy::{Children, Parent};
+use bevy_math::{Quat, Vec2, Vec3, Vec4};
+use bevy_reflect::{GetPath, ParsedPath, Reflect, ReflectPathError, TypePath, TypeRegistry};
+use bevy_render::color::Color;
+use bevy_utils::{tracing::warn, HashSet};
+
+use crate::{
+    entity_from_path, find_keyframes, verify_no_ancestor_player, AnimationClip, AnimationPlayer,
+    AnimationTransition, KeyframeSpan, PlayerParents,
+};
+
+/// List of keyframes for a property animated by a [`PropertyCurve`].
+///
+/// The variant must match the type of the animated property.
+#[derive(Reflect, Clone, Debug)]
+pub enum PropertyKeyframes {
+    /// Keyframes for an [`f32`] property.
+    F32(Vec<f32>),
+    /// Keyframes for a [`Vec2`] property.
+    Vec2(Vec<Vec2>),
+    /// Keyframes for a [`Vec3`] property.
+    Vec3(Vec<Vec3>),
+    /// Keyframes for a [`Vec4`] property.
+    Vec4(Vec<Vec4>),
+    /// Keyframes for a [`Quat`] property, interpolated spherically.
+    Quat(Vec<Quat>),
+    /// Keyframes for a [`Color`] property, interpolated in linear RGBA space.
+    Color(Vec<Color>),
+}
+
+/// A value that can be interpolated by a [`PropertyCurve`].
+trait Animatable: Reflect + Copy {
+    fn interpolate(start: Self, end: Self, t: f32) -> Self;
+}
+
+impl Animatable for f32 {
+    fn interpolate(start: Self, end: Self, t: f32) -> Self {
+        start + (end - start) * t
+    }
+}
+
+impl Animatable for Vec2 {
+    fn interpolate(start: Self, end: Self, t: f32) -> Self {
+        start.lerp(end, t)
+    }
+}
+
+impl Animatable for Vec3 {
+    fn interpolate(start: Self, end: Self, t: f32) -> Self {
+        start.lerp(end, t)
+    }
+}
+
+impl Animatable for Vec4 {
+    fn interpolate(start: Self, end: Self, t: f32) -> Self {
+        start.lerp(end, t)
+    }
+}
+
+impl Animatable for Quat {
+    fn interpolate(start: Self, end: Self, t: f32) -> Self {
+        start.normalize().slerp(end.normalize(), t)
+    }
+}
+
+impl Animatable for Color {
+    fn interpolate(start: Self, end: Self, t: f32) -> Self {
+        let start = Vec4::from(start.as_linear_rgba_f32());
+        let end = Vec4::from(end.as_linear_rgba_f32());
+        Color::rgba_linear_from_array(start.lerp(end, t))
+    }
+}
+
+impl PropertyKeyframes {
+    /// Interpolates `target` towards the value of the keyframes at `span`, by `weight`.
+    ///
+    /// Returns `false` if `target` doesn't have the type of the keyframes.
+    fn apply(&self, span: KeyframeSpan, weight: f32, target: &mut dyn Reflect) -> bool {
+        fn apply_keyframes<T: Animatable>(
+            keyframes: &[T],
+            span: KeyframeSpan,
+            weight: f32,
+            target: &mut dyn Reflect,
+        ) -> bool {
+            let Some(target) = target.downcast_mut::<T>() else {
+                return false;
+            };
+            let value = T::interpolate(keyframes[span.start], keyframes[span.end], span.lerp);
+            *target = T::interpolate(*target, value, weight);
+            true
+        }
+
+        match self {
+            PropertyKeyframes::F32(keyframes) => apply_keyframes(keyframes, span, weight, target),
+            PropertyKeyframes::Vec2(keyframes) => apply_keyframes(keyframes, span, weight, target),
+            PropertyKeyframes::Vec3(keyframes) => apply_keyframes(keyframes, span, weight, target),
+            PropertyKeyframes::Vec4(keyframes) => apply_keyframes(keyframes, span, weight, target),
+            PropertyKeyframes::Quat(keyframes) => apply_keyframes(keyframes, span, weight, target),
+            PropertyKeyframes::Color(keyframes) => apply_keyframes(keyframes, span, weight, target),
+        }
+    }
+}
+
+/// Describes how a reflected property of a component should be animated.
+///
+/// `keyframe_timestamps` and `keyframes` should have the same length.
+#[derive(Reflect, Clone, Debug)]
+pub struct PropertyCurve {
+    component: String,
+    path: String,
+    #[reflect(ignore)]
+    parsed_path: Option<ParsedPath>,
+    keyframe_timestamps: Vec<f32>,
+    keyframes: PropertyKeyframes,
+}
+
+impl PropertyCurve {
+    /// Creates a curve animating the property at `path` in the component `C`.
+    ///
+    /// `path` uses the syntax of [`GetPath`], like `"intensity"` or `"color.x"`.
+    pub fn new<C: Component + TypePath>(
+        path: &str,
+        keyframe_timestamps: Vec<f32>,
+        keyframes: PropertyKeyframes,
+    ) -> Result<Self, ReflectPathError<'_>> {
+        Self::from_type_path(C::type_path(), path, keyframe_timestamps, keyframes)
+    }
+
+    /// Creates a curve animating the property at `path` in the component with the given
+    /// [type path](TypePath::type_path).
+    pub fn from_type_path(
+        component: impl Into<String>,
+        path: &str,
+        keyframe_timestamps: Vec<f32>,
+        keyframes: PropertyKeyframes,
+    ) -> Result<Self, ReflectPathError<'_>> {
+        Ok(Self {
+            component: component.into(),
+            path: path.to_owned(),
+            parsed_path: Some(ParsedPath::parse(path)?),
+            keyframe_timestamps,
+            keyframes,
+        })
+    }
+
+    /// The type path of the animated component.
+    pub fn component(&self) -> &str {
+        &self.component
+    }
+
+    /// The path of the animated property in the component.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Timestamp for each of the keyframes.
+    pub fn keyframe_timestamps(&self) -> &[f32] {
+        &self.keyframe_timestamps
+    }
+
+    /// List of the keyframes.
+    pub fn keyframes(&self) -> &PropertyKeyframes {
+        &self.keyframes
+    }
+
+    /// Interpolates the property of `entity` towards the value of the curve at `span`, by
+    /// `weight`.
+    ///
+    /// Returns the reason why the property can't be animated, if any. The entity not having the
+    /// component isn't an error.
+    fn apply(
+        &self,
+        span: KeyframeSpan,
+        weight: f32,
+        entity: &mut EntityWorldMut,
+        type_registry: &TypeRegistry,
+    ) -> Result<(), String> {
+        let Some(reflect_component) = type_registry
+            .get_with_type_path(&self.component)
+            .and_then(|registration| registration.data::<ReflectComponent>())
+        else {
+            return Err(format!(
+                "Cannot animate {}: it isn't a registered component",
+                self.component
+            ));
+        };
+        let Some(mut component) = reflect_component.reflect_mut(entity) else {
+            return Ok(());
+        };
+
+        // The path isn't parsed yet if the curve was created through reflection
+        let parsed_path;
+        let path = match &self.parsed_path {
+            Some(path) => path,
+            None => match ParsedPath::parse(&self.path) {
+                Ok(path) => {
+                    parsed_path = path;
+                    &parsed_path
+                }
+                Err(err) => return Err(format!("Cannot animate {}: {err}", self.path)),
+            },
+        };
+        let property = component
+            .reflect_path_mut(path)
+            .map_err(|err| format!("Cannot animate {} of {}: {err}", self.path, self.component))?;
+        if !self.keyframes.apply(span, weight, property) {
+            return Err(format!(
+                "Cannot animate {} of {}: its type doesn't match the keyframes",
+                self.path, self.component
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The hierarchy and players read by [`animate_properties`].
+type PropertyPlayers<'w, 's> = (
+    Query<
+        'w,
+        's,
+        (
+            Entity,
+            Option<&'static Parent>,
+            &'static mut AnimationPlayer,
+        ),
+    >,
+    Query<'w, 's, &'static Children>,
+    Query<'w, 's, &'static Name>,
+    PlayerParents<'w, 's>,
+);
+
+/// System that will apply the [`PropertyCurve`]s of the clips played by all
+/// [`AnimationPlayer`]s, after [`animation_player`](crate::animation_player) has advanced them.
+///
+/// Each curve that can't be animated is only warned about once.
+pub fn animate_properties(
+    world: &mut World,
+    players: &mut SystemState<PropertyPlayers>,
+    mut warnings: Local<HashSet<String>>,
+) {
+    let type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = type_registry.read();
+    world.resource_scope(|world, clips: Mut<Assets<AnimationClip>>| {
+        let mut animated = Vec::new();
+        let (mut animation_players, children, names, parents) = players.get_mut(world);
+        for (root, maybe_parent, mut player) in &mut animation_players {
+            // Skip paused players unless they were changed, to still apply seeks, as for the
+            // transforms
+            if player.paused && !player.is_changed() {
+                continue;
+            }
+            // The conflicting players are already warned about by `animation_player`
+            if !verify_no_ancestor_player(maybe_parent, &parents) {
+                continue;
+            }
+            // Only the path caches are updated, which isn't a change to the player
+            let player = player.bypass_change_detection();
+            // The main animation is applied first, then the fade-out transitions on top, as for
+            // the transforms
+            let animations = std::iter::once((&mut player.animation, 1.0)).chain(
+                player.transitions.iter_mut().map(
+                    |AnimationTransition {
+                         current_weight,
+                         animation,
+                         ..
+                     }| (animation, *current_weight),
+                ),
+            );
+            for (animation, weight) in animations {
+                let Some(clip) = clips.get(&animation.animation_clip) else {
+                    continue;
+                };
+                if animation.path_cache.len() != clip.paths.len() {
+                    animation.path_cache = vec![Vec::new(); clip.paths.len()];
+                }
+                for (path, bone_id) in &clip.paths {
+                    let Some(curves) = clip.get_property_curves(*bone_id) else {
+                        continue;
+                    };
+                    if curves.is_empty() {
+                        continue;
+                    }
+                    let cached_path = &mut animation.path_cache[*bone_id];
+                    let Some(target) = entity_from_path(root, path, &children, &names, cached_path)
+                    else {
+                        continue;
+                    };
+                    for curve in curves {
+                        if let Some(span) =
+                            find_keyframes(&curve.keyframe_timestamps, animation.seek_time)
+                        {
+                            animated.push((target, curve, span, weight));
+                        }
+                    }
+                }
+            }
+        }
+
+        for (target, curve, span, weight) in animated {
+            if let Err(warning) =
+                curve.apply(span, weight, &mut world.entity_mut(target), &type_registry)
+            {
+                if warnings.insert(warning.clone()) {
+                    warn!("{warning}");
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_asset::Handle;
+    use bevy_ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::EntityPath;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Glow {
+        intensity: f32,
+        tint: Color,
+        offset: Vec3,
+    }
+
+    fn setup(clip: AnimationClip) -> (World, Entity) {
+        let mut world = World::new();
+        let type_registry = AppTypeRegistry::default();
+        type_registry.write().register::<Glow>();
+        world.insert_resource(type_registry);
+        let mut clips = Assets::<AnimationClip>::default();
+        let clip: Handle<AnimationClip> = clips.add(clip);
+        world.insert_resource(clips);
+
+        let mut player = AnimationPlayer::default();
+        player.start(clip).seek_to(0.5);
+        let entity = world
+            .spawn((Name::new("lamp"), Glow::default(), player))
+            .id();
+        (world, entity)
+    }
+
+    fn lamp() -> EntityPath {
+        EntityPath {
+            parts: vec![Name::new("lamp")],
+        }
+    }
+
+    #[test]
+    fn animate_reflected_fields() {
+        let mut clip = AnimationClip::default();
+        clip.add_property_curve_to_path(
+            lamp(),
+            PropertyCurve::new::<Glow>(
+                "intensity",
+                vec![0.0, 1.0],
+                PropertyKeyframes::F32(vec![0.0, 10.0]),
+            )
+            .unwrap(),
+        );
+        clip.add_property_curve_to_path(
+            lamp(),
+            PropertyCurve::new::<Glow>(
+                "tint",
+                vec![0.0, 1.0],
+                PropertyKeyframes::Color(vec![
+                    Color::BLACK,
+                    Color::rgba_linear(1.0, 0.5, 0.0, 1.0),
+                ]),
+            )
+            .unwrap(),
+        );
+        clip.add_property_curve_to_path(
+            lamp(),
+            PropertyCurve::new::<Glow>("offset.y", vec![0.0], PropertyKeyframes::F32(vec![3.0]))
+                .unwrap(),
+        );
+        assert_eq!(clip.duration(), 1.0);
+        let (mut world, lamp) = setup(clip);
+
+        world.run_system_once(animate_properties);
+        let glow = world.get::<Glow>(lamp).unwrap();
+        assert_eq!(glow.intensity, 5.0);
+        assert_eq!(glow.tint.as_linear_rgba_f32(), [0.5, 0.25, 0.0, 1.0]);
+        assert_eq!(glow.offset, Vec3::Y * 3.0);
+    }
+
+    #[test]
+    fn mismatched_curves_are_ignored() {
+        let mut clip = AnimationClip::default();
+        clip.add_property_curve_to_path(
+            lamp(),
+            PropertyCurve::new::<Glow>(
+                "intensity",
+                vec![0.0],
+                PropertyKeyframes::Vec3(vec![Vec3::ONE]),
+            )
+            .unwrap(),
+        );
+        clip.add_property_curve_to_path(
+            lamp(),
+            PropertyCurve::new::<Glow>("missing", vec![0.0], PropertyKeyframes::F32(vec![1.0]))
+                .unwrap(),
+        );
+        let (mut world, lamp) = setup(clip);
+
+        world.run_system_once(animate_properties);
+        assert_eq!(world.get::<Glow>(lamp).unwrap().intensity, 0.0);
+    }
+
+    #[test]
+    fn paused_players_are_skipped() {
+        let mut clip = AnimationClip::default();
+        clip.add_property_curve_to_path(
+            lamp(),
+            PropertyCurve::new::<Glow>(
+                "intensity",
+                vec![0.0, 1.0],
+                PropertyKeyframes::F32(vec![0.0, 10.0]),
+            )
+            .unwrap(),
+        );
+        let (mut world, lamp) = setup(clip);
+        world.get_mut::<AnimationPlayer>(lamp).unwrap().pause();
+        let mut system = IntoSystem::into_system(animate_properties);
+        system.initialize(&mut world);
+
+        // The player was changed, so its seek is applied while paused
+        system.run((), &mut world);
+        assert_eq!(world.get::<Glow>(lamp).unwrap().intensity, 5.0);
+
+        world.get_mut::<Glow>(lamp).unwrap().intensity = 1.0;
+        system.run((), &mut world);
+        assert_eq!(world.get::<Glow>(lamp).unwrap().intensity, 1.0);
+
+        world.get_mut::<AnimationPlayer>(lamp).unwrap().seek_to(0.8);
+        system.run((), &mut world);
+        assert_eq!(world.get::<Glow>(lamp).unwrap().intensity, 8.0);
+    }
+}
@@ -0,0 +1,343 @@
+//! Extraction of the motion of the root bone of an animation.
+//!
+//! Locomotion clips usually move the root bone of the skeleton, so a character walks away from
+//! its entity, then snaps back when the animation loops. When root motion is enabled on an
+//! [`AnimationPlayer`] with [`AnimationPlayer::set_root_motion`], the horizontal translation and
+//! the rotation around the Y axis of that bone are removed from the animated pose. They are
+//! written instead to the [`RootMotion`] component of the player each frame, so gameplay code or a
+//! physics controller can move the entity.
+//!
+//! ```
+//! # use bevy_animation::{prelude::*, root_motion::RootMotion};
+//! # use bevy_core::Name;
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_transform::prelude::Transform;
+//! fn setup(mut commands: Commands) {
+//!     let mut player = AnimationPlayer::default();
+//!     player.set_root_motion(Some(EntityPath {
+//!         parts: vec![Name::new("character"), Name::new("hips")],
+//!     }));
+//!     commands.spawn((Name::new("character"), player, RootMotion::default()));
+//! }
+//!
+//! fn move_characters(mut characters: Query<(&RootMotion, &mut Transform)>) {
+//!     for (motion, mut transform) in &mut characters {
+//!         let translation = transform.rotation * motion.translation;
+//!         transform.translation += translation;
+//!         transform.rotate_y(motion.yaw);
+//!     }
+//! }
+//! # bevy_ecs::system::assert_is_system(setup);
+//! # bevy_ecs::system::assert_is_system(move_characters);
+//! ```
+//!
+//! Only the motion of the main animation of the player is extracted: the root motion of the
+//! animations fading out during a transition is removed from their pose, but ignored. Root motion
+//! isn't supported by the [`AnimationGraphPlayer`](crate::graph::AnimationGraphPlayer).
+//!
+//! [`AnimationPlayer`]: crate::AnimationPlayer
+//! [`AnimationPlayer::set_root_motion`]: crate::AnimationPlayer::set_root_motion
+
+use std::f32::consts::{PI, TAU};
+use std::ops::Range;
+
+use bevy_ecs::{component::Component, reflect::ReflectComponent};
+use bevy_math::{EulerRot, Quat, Vec3};
+use bevy_reflect::Reflect;
+
+use crate::{AnimationClip, CurveSample, EntityPath, VariableCurve};
+
+/// The motion of the root bone extracted by the [`AnimationPlayer`](crate::AnimationPlayer) of
+/// the entity during the last frame, when root motion is enabled.
+///
+/// This component must be added to the entity of the player, otherwise the motion is discarded.
+#[derive(Component, Reflect, Clone, Copy, Debug, Default, PartialEq)]
+#[reflect(Component)]
+pub struct RootMotion {
+    /// Horizontal translation of the root bone, relative to the direction the entity faces.
+    pub translation: Vec3,
+    /// Rotation of the root bone around the Y axis, in radians.
+    pub yaw: f32,
+}
+
+impl RootMotion {
+    /// The motion of `self` followed by `next`.
+    fn then(self, next: RootMotion) -> RootMotion {
+        RootMotion {
+            translation: self.translation + Quat::from_rotation_y(self.yaw) * next.translation,
+            yaw: self.yaw + next.yaw,
+        }
+    }
+}
+
+/// The yaw of `rotation`, in radians.
+fn yaw(rotation: Quat) -> f32 {
+    rotation.to_euler(EulerRot::YXZ).0
+}
+
+/// Wraps `angle` to the range [-π, π].
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+/// Samples `curve` at `seek_time`, holding its first and last keyframes outside of its range.
+fn sample_held(curve: &VariableCurve, seek_time: f32) -> Option<CurveSample> {
+    let (first, last) = (
+        *curve.keyframe_timestamps.first()?,
+        curve.keyframe_timestamps.len() - 1,
+    );
+    if seek_time <= first {
+        return Some(curve.first_sample(0));
+    }
+    Some(
+        curve
+            .sample(seek_time, 0)
+            .unwrap_or_else(|| curve.keyframe_sample(last, 0)),
+    )
+}
+
+/// The translation and the yaw of the root bone animated by `curves` at `seek_time`.
+fn root_pose(curves: &[VariableCurve], seek_time: f32) -> (Vec3, f32) {
+    let mut pose = (Vec3::ZERO, 0.0);
+    for curve in curves {
+        match sample_held(curve, seek_time) {
+            Some(CurveSample::Translation(translation)) => pose.0 = translation,
+            Some(CurveSample::Rotation(rotation)) => pose.1 = yaw(rotation),
+            _ => {}
+        }
+    }
+    pose
+}
+
+/// The motion of the root bone animated by `curves` from `from` to `to`, within a single loop of
+/// the animation.
+fn segment_motion(curves: &[VariableCurve], from: f32, to: f32) -> RootMotion {
+    let (_, first_yaw) = root_pose(curves, 0.0);
+    let (translation_from, yaw_from) = root_pose(curves, from);
+    let (translation_to, yaw_to) = root_pose(curves, to);
+    let translation = translation_to - translation_from;
+    RootMotion {
+        // The entity has already turned by the yaw of the animation since its start
+        translation: Quat::from_rotation_y(first_yaw - yaw_from)
+            * Vec3::new(translation.x, 0.0, translation.z),
+        yaw: wrap_angle(yaw_to - yaw_from),
+    }
+}
+
+/// Extracts the motion of the root bone at `path` in `clip`, while playing the `played` range of
+/// seek time.
+///
+/// `played` is the range of seek time before it's wrapped to the duration of the clip, as
+/// returned when updating a `PlayingAnimation`.
+pub(crate) fn extract_root_motion(
+    clip: &AnimationClip,
+    path: &EntityPath,
+    played: Range<f32>,
+) -> RootMotion {
+    let duration = clip.duration;
+    let Some(curves) = clip.get_curves_by_path(path) else {
+        return RootMotion::default();
+    };
+    if duration <= 0.0 {
+        return RootMotion::default();
+    }
+
+    // Split the played range at each loop of the animation. Loops are counted with integers, as
+    // the loop boundaries computed from floats can fail to advance the time.
+    let Range { start, end } = played;
+    let first_loop = (start.min(end) / duration).floor() as i64;
+    let last_loop = (start.max(end) / duration).ceil() as i64;
+    let segment = |loop_index: i64| {
+        let loop_start = loop_index as f32 * duration;
+        let from = (start - loop_start).clamp(0.0, duration);
+        let to = (end - loop_start).clamp(0.0, duration);
+        (from != to).then(|| segment_motion(curves, from, to))
+    };
+    let mut motion = RootMotion::default();
+    if end > start {
+        for loop_index in first_loop..last_loop {
+            if let Some(segment) = segment(loop_index) {
+                motion = motion.then(segment);
+            }
+        }
+    } else {
+        for loop_index in (first_loop..last_loop).rev() {
+            if let Some(segment) = segment(loop_index) {
+                motion = motion.then(segment);
+            }
+        }
+    }
+    motion
+}
+
+/// Removes the horizontal translation and the yaw of `sample` relative to the first keyframe of
+/// `curve`, a curve of the root bone.
+pub(crate) fn remove_root_motion(curve: &VariableCurve, sample: CurveSample) -> CurveSample {
+    match (sample, curve.first_sample(0)) {
+        (CurveSample::Translation(translation), CurveSample::Translation(first)) => {
+            CurveSample::Translation(Vec3::new(first.x, translation.y, first.z))
+        }
+        (CurveSample::Rotation(rotation), CurveSample::Rotation(first)) => {
+            CurveSample::Rotation(Quat::from_rotation_y(yaw(first) - yaw(rotation)) * rotation)
+        }
+        (sample, _) => sample,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+    use std::time::Duration;
+
+    use bevy_asset::Assets;
+    use bevy_core::Name;
+    use bevy_ecs::prelude::*;
+    use bevy_hierarchy::BuildWorldChildren;
+    use bevy_time::Time;
+    use bevy_transform::prelude::Transform;
+
+    use super::*;
+    use crate::{
+        animation_player, event::AnimationEvent, AnimationPlayer, Interpolation, Keyframes,
+    };
+
+    fn hips() -> EntityPath {
+        EntityPath {
+            parts: vec![Name::new("character"), Name::new("hips")],
+        }
+    }
+
+    /// Spawns a character playing `clip` in a loop, with root motion enabled.
+    fn setup(clip: AnimationClip) -> (World, Schedule, Entity, Entity) {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<AnimationEvent>>();
+        let mut clips = Assets::<AnimationClip>::default();
+        let mut player = AnimationPlayer::default();
+        player
+            .start(clips.add(clip))
+            .repeat()
+            .set_root_motion(Some(hips()));
+        world.insert_resource(clips);
+        let hips = world.spawn((Name::new("hips"), Transform::default())).id();
+        let character = world
+            .spawn((Name::new("character"), player, RootMotion::default()))
+            .push_children(&[hips])
+            .id();
+
+        let mut schedule = Schedule::default();
+        schedule.add_systems(animation_player);
+        (world, schedule, character, hips)
+    }
+
+    fn step(world: &mut World, schedule: &mut Schedule, seconds: f32) {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        schedule.run(world);
+    }
+
+    #[test]
+    fn extract_translation_across_loops() {
+        let mut clip = AnimationClip::default();
+        clip.add_curve_to_path(
+            hips(),
+            VariableCurve {
+                keyframe_timestamps: vec![0.0, 1.0],
+                keyframes: Keyframes::Translation(vec![Vec3::Y, Vec3::new(0.0, 2.0, 2.0)]),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        let (mut world, mut schedule, character, hips) = setup(clip);
+
+        step(&mut world, &mut schedule, 0.5);
+        let motion = world.get::<RootMotion>(character).unwrap();
+        assert_eq!(motion.translation, Vec3::Z);
+        // The vertical translation stays in the pose
+        let transform = world.get::<Transform>(hips).unwrap();
+        assert_eq!(transform.translation, Vec3::new(0.0, 1.5, 0.0));
+
+        step(&mut world, &mut schedule, 0.75);
+        let motion = world.get::<RootMotion>(character).unwrap();
+        assert!(motion.translation.abs_diff_eq(Vec3::Z * 1.5, 1e-5));
+        let transform = world.get::<Transform>(hips).unwrap();
+        assert!(transform
+            .translation
+            .abs_diff_eq(Vec3::new(0.0, 1.25, 0.0), 1e-5));
+    }
+
+    #[test]
+    fn extract_yaw() {
+        let mut clip = AnimationClip::default();
+        clip.add_curve_to_path(
+            hips(),
+            VariableCurve {
+                keyframe_timestamps: vec![0.0, 1.0],
+                keyframes: Keyframes::Translation(vec![Vec3::ZERO, Vec3::X * 2.0]),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        clip.add_curve_to_path(
+            hips(),
+            VariableCurve {
+                keyframe_timestamps: vec![0.0, 1.0],
+                keyframes: Keyframes::Rotation(vec![
+                    Quat::IDENTITY,
+                    Quat::from_rotation_y(FRAC_PI_2),
+                ]),
+                interpolation: Interpolation::Linear,
+            },
+        );
+        let (mut world, mut schedule, character, hips) = setup(clip);
+
+        step(&mut world, &mut schedule, 0.5);
+        let motion = *world.get::<RootMotion>(character).unwrap();
+        assert!((motion.yaw - FRAC_PI_2 / 2.0).abs() < 1e-5);
+        assert!(motion.translation.abs_diff_eq(Vec3::X, 1e-5));
+        let transform = world.get::<Transform>(hips).unwrap();
+        assert!(transform.rotation.abs_diff_eq(Quat::IDENTITY, 1e-5));
+
+        // The translation is relative to the yaw the entity already turned by
+        step(&mut world, &mut schedule, 0.25);
+        let motion = *world.get::<RootMotion>(character).unwrap();
+        let expected = Quat::from_rotation_y(-FRAC_PI_2 / 2.0) * Vec3::X * 0.5;
+        assert!(motion.translation.abs_diff_eq(expected, 1e-5));
+
+        // Nothing moves while the animation is paused
+        world.get_mut::<AnimationPlayer>(character).unwrap().pause();
+        step(&mut world, &mut schedule, 0.25);
+        step(&mut world, &mut schedule, 0.25);
+        assert_eq!(
+            *world.get::<RootMotion>(character).unwrap(),
+            RootMotion::default()
+        );
+    }
+
+    #[test]
+    fn extract_several_loops_in_one_update() {
+        let mut clip = AnimationClip::default();
+        clip.add_curve_to_path(
+            hips(),
+            VariableCurve {
+                keyframe_timestamps: vec![0.0, 0.6848],
+                keyframes: Keyframes::Translation(vec![Vec3::ZERO, Vec3::Z * 0.6848]),
+                interpolation: Interpolation::Linear,
+            },
+        );
+
+        // The root bone moves forward by one unit per second of seek time
+        let motion = extract_root_motion(&clip, &hips(), 0.356..2.056);
+        assert!(motion.translation.abs_diff_eq(Vec3::Z * 1.7, 1e-4));
+        let motion = extract_root_motion(&clip, &hips(), 2.056..0.356);
+        assert!(motion.translation.abs_diff_eq(-Vec3::Z * 1.7, 1e-4));
+        let motion = extract_root_motion(&clip, &hips(), -1.0..2.5);
+        assert!(motion.translation.abs_diff_eq(Vec3::Z * 3.5, 1e-4));
+        let motion = extract_root_motion(&clip, &hips(), 2.5..-1.0);
+        assert!(motion.translation.abs_diff_eq(-Vec3::Z * 3.5, 1e-4));
+
+        let (mut world, mut schedule, character, _) = setup(clip);
+        step(&mut world, &mut schedule, 2.056);
+        let motion = world.get::<RootMotion>(character).unwrap();
+        assert!(motion.translation.abs_diff_eq(Vec3::Z * 2.056, 1e-4));
+    }
+}
@@ -3,9 +3,9 @@ pub use bevy_derive::AppLabel;
 use bevy_ecs::{
     prelude::*,
     schedule::{
-        apply_state_transition, common_conditions::run_once as run_once_condition,
-        run_enter_schedule, InternedScheduleLabel, IntoSystemConfigs, IntoSystemSetConfigs,
-        ScheduleBuildSettings, ScheduleLabel,
+        add_computed_state_transition_systems, add_state_transition_systems,
+        add_sub_state_transition_systems, FreelyMutableState, InternedScheduleLabel,
+        IntoSystemConfigs, IntoSystemSetConfigs, ScheduleBuildSettings, ScheduleLabel,
     },
 };
 use bevy_utils::{intern::Interned, thiserror::Error, tracing::debug, HashMap, HashSet};
@@ -355,28 +355,24 @@ impl App {
     }
 
     /// Adds [`State<S>`] and [`NextState<S>`] resources, [`OnEnter`] and [`OnExit`] schedules
-    /// for each state variant (if they don't already exist), an instance of [`apply_state_transition::<S>`] in
-    /// [`StateTransition`] so that transitions happen before [`Update`](crate::Update) and
-    /// a instance of [`run_enter_schedule::<S>`] in [`StateTransition`] with a
-    /// [`run_once`](`run_once_condition`) condition to run the on enter schedule of the
-    /// initial state.
+    /// for each state variant (if they don't already exist), and the systems applying the
+    /// transitions of `S` in [`StateTransition`] so that transitions happen before
+    /// [`Update`](crate::Update). The on enter schedule of the initial state runs during the first
+    /// [`StateTransition`].
+    ///
+    /// A [`StateTransitionEvent<S>`] is sent whenever the state changes.
     ///
     /// If you would like to control how other systems run based on the current state,
     /// you can emulate this behavior using the [`in_state`] [`Condition`].
     ///
     /// Note that you can also apply state transitions at other points in the schedule
-    /// by adding the [`apply_state_transition`] system manually.
-    pub fn add_state<S: States>(&mut self) -> &mut Self {
+    /// by adding the [`apply_state_transition`] system manually. These transitions don't update
+    /// the states depending on `S`, which are only updated in [`StateTransition`].
+    pub fn add_state<S: FreelyMutableState>(&mut self) -> &mut Self {
         self.init_resource::<State<S>>()
             .init_resource::<NextState<S>>()
-            .add_systems(
-                StateTransition,
-                (
-                    run_enter_schedule::<S>.run_if(run_once_condition()),
-                    apply_state_transition::<S>,
-                )
-                    .chain(),
-            );
+            .add_event::<StateTransitionEvent<S>>()
+            .edit_schedule(StateTransition, add_state_transition_systems::<S>);
 
         // The OnEnter, OnExit, and OnTransition schedules are lazily initialized
         // (i.e. when the first system is added to them), and World::try_run_schedule is used to fail
@@ -385,6 +381,29 @@ impl App {
         self
     }
 
+    /// Adds a [`ComputedStates`] type, whose [`State<S>`] is recomputed in [`StateTransition`]
+    /// whenever its sources change, running its [`OnExit`], [`OnTransition`] and [`OnEnter`]
+    /// schedules in dependency order with the other states.
+    ///
+    /// The sources of `S` must be added to the app with [`App::add_state`],
+    /// [`App::add_computed_state`] or [`App::add_sub_state`].
+    pub fn add_computed_state<S: ComputedStates>(&mut self) -> &mut Self {
+        self.add_event::<StateTransitionEvent<S>>()
+            .edit_schedule(StateTransition, add_computed_state_transition_systems::<S>)
+    }
+
+    /// Adds a [`SubStates`] type and its [`NextState<S>`] resource. Its [`State<S>`] is added or
+    /// removed in [`StateTransition`] whenever its sources change, and it can otherwise be changed
+    /// like any other state.
+    ///
+    /// The sources of `S` must be added to the app with [`App::add_state`],
+    /// [`App::add_computed_state`] or [`App::add_sub_state`].
+    pub fn add_sub_state<S: SubStates>(&mut self) -> &mut Self {
+        self.init_resource::<NextState<S>>()
+            .add_event::<StateTransitionEvent<S>>()
+            .edit_schedule(StateTransition, add_sub_state_transition_systems::<S>)
+    }
+
     /// Adds a system to the given schedule in this app's [`Schedules`].
     ///
     /// # Examples
@@ -1102,6 +1121,146 @@ mod tests {
         assert_eq!(app.world.entities().len(), 2);
     }
 
+    mod dependent_states {
+        use bevy_ecs::prelude::*;
+
+        use crate::App;
+
+        #[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+        enum AppState {
+            #[default]
+            Menu,
+            InGame,
+        }
+
+        #[derive(SubStates, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+        #[source(AppState = AppState::InGame)]
+        enum GamePhase {
+            #[default]
+            Running,
+            Paused,
+        }
+
+        #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+        struct ShowMenu;
+
+        impl ComputedStates for ShowMenu {
+            type SourceStates = (AppState, Option<GamePhase>);
+
+            fn compute((app_state, game_phase): (AppState, Option<GamePhase>)) -> Option<Self> {
+                (app_state == AppState::Menu || game_phase == Some(GamePhase::Paused))
+                    .then_some(ShowMenu)
+            }
+        }
+
+        #[derive(Resource, Default)]
+        struct Log(Vec<&'static str>);
+
+        fn log(message: &'static str) -> impl FnMut(ResMut<Log>) {
+            move |mut log: ResMut<Log>| log.0.push(message)
+        }
+
+        fn take_log(app: &mut App) -> Vec<&'static str> {
+            std::mem::take(&mut app.world.resource_mut::<Log>().0)
+        }
+
+        fn app() -> App {
+            let mut app = App::new();
+            app.init_resource::<Log>()
+                .add_state::<AppState>()
+                .add_sub_state::<GamePhase>()
+                .add_computed_state::<ShowMenu>()
+                .add_systems(OnEnter(AppState::Menu), log("enter Menu"))
+                .add_systems(OnExit(AppState::Menu), log("exit Menu"))
+                .add_systems(OnEnter(AppState::InGame), log("enter InGame"))
+                .add_systems(OnExit(AppState::InGame), log("exit InGame"))
+                .add_systems(OnEnter(GamePhase::Running), log("enter Running"))
+                .add_systems(OnExit(GamePhase::Running), log("exit Running"))
+                .add_systems(OnEnter(GamePhase::Paused), log("enter Paused"))
+                .add_systems(OnExit(GamePhase::Paused), log("exit Paused"))
+                .add_systems(OnEnter(ShowMenu), log("enter ShowMenu"))
+                .add_systems(OnExit(ShowMenu), log("exit ShowMenu"));
+            app
+        }
+
+        #[test]
+        fn dependent_states_follow_their_sources() {
+            let mut app = app();
+            app.update();
+            assert_eq!(take_log(&mut app), ["enter Menu", "enter ShowMenu"]);
+            assert!(app.world.get_resource::<State<GamePhase>>().is_none());
+
+            app.world
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::InGame);
+            app.update();
+            assert_eq!(
+                take_log(&mut app),
+                [
+                    "exit ShowMenu",
+                    "exit Menu",
+                    "enter InGame",
+                    "enter Running"
+                ]
+            );
+            assert_eq!(
+                *app.world.resource::<State<GamePhase>>(),
+                GamePhase::Running
+            );
+            assert!(app.world.get_resource::<State<ShowMenu>>().is_none());
+
+            app.world
+                .resource_mut::<NextState<GamePhase>>()
+                .set(GamePhase::Paused);
+            app.update();
+            assert_eq!(
+                take_log(&mut app),
+                ["exit Running", "enter Paused", "enter ShowMenu"]
+            );
+
+            app.world
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::Menu);
+            app.update();
+            assert_eq!(
+                take_log(&mut app),
+                ["exit Paused", "exit InGame", "enter Menu"]
+            );
+            assert!(app.world.get_resource::<State<GamePhase>>().is_none());
+            assert!(app.world.get_resource::<State<ShowMenu>>().is_some());
+
+            // a sub-state restarts from its default value
+            app.world
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::InGame);
+            app.update();
+            assert_eq!(
+                *app.world.resource::<State<GamePhase>>(),
+                GamePhase::Running
+            );
+        }
+
+        #[test]
+        fn sub_state_queued_before_it_exists() {
+            let mut app = app();
+            app.update();
+            take_log(&mut app);
+
+            app.world
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::InGame);
+            app.world
+                .resource_mut::<NextState<GamePhase>>()
+                .set(GamePhase::Paused);
+            app.update();
+            assert_eq!(
+                take_log(&mut app),
+                ["exit Menu", "enter InGame", "enter Paused"]
+            );
+            assert_eq!(*app.world.resource::<State<GamePhase>>(), GamePhase::Paused);
+        }
+    }
+
     #[test]
     fn test_derive_app_label() {
         use super::AppLabel;
@@ -1,5 +1,8 @@
 use crate as bevy_asset;
-use crate::{Asset, AssetEvent, AssetHandleProvider, AssetId, AssetServer, Handle, UntypedHandle};
+use crate::{
+    Asset, AssetEvent, AssetHandleProvider, AssetId, AssetServer, Handle, InternalAssetId,
+    UntypedHandle,
+};
 use bevy_ecs::{
     prelude::EventWriter,
     system::{Res, ResMut, Resource},
@@ -315,6 +318,11 @@ impl<A: Asset> Assets<A> {
 
     pub(crate) fn insert_with_uuid(&mut self, uuid: Uuid, asset: A) -> Option<A> {
         let result = self.hash_map.insert(uuid, asset);
+        self.handle_provider
+            .usage
+            .lock()
+            .stored
+            .insert(InternalAssetId::Uuid(uuid));
         if result.is_some() {
             self.queued_events
                 .push(AssetEvent::Modified { id: uuid.into() });
@@ -330,6 +338,11 @@ impl<A: Asset> Assets<A> {
         asset: A,
     ) -> Result<bool, InvalidGenerationError> {
         let replaced = self.dense_storage.insert(index, asset)?;
+        self.handle_provider
+            .usage
+            .lock()
+            .stored
+            .insert(InternalAssetId::Index(index));
         if replaced {
             self.queued_events
                 .push(AssetEvent::Modified { id: index.into() });
@@ -392,6 +405,11 @@ impl<A: Asset> Assets<A> {
     /// Note that this supports anything that implements `Into<AssetId<A>>`, which includes [`Handle`] and [`AssetId`].
     pub fn remove_untracked(&mut self, id: impl Into<AssetId<A>>) -> Option<A> {
         let id: AssetId<A> = id.into();
+        self.handle_provider
+            .usage
+            .lock()
+            .stored
+            .remove(&id.internal());
         match id {
             AssetId::Index { index, .. } => self.dense_storage.remove(index),
             AssetId::Uuid { uuid } => self.hash_map.remove(&uuid),
@@ -467,7 +485,13 @@ impl<A: Asset> Assets<A> {
         while let Ok(drop_event) = assets.handle_provider.drop_receiver.try_recv() {
             let id = drop_event.id;
             if !assets.contains(id.typed()) {
-                not_ready.push(drop_event);
+                let untyped_id = id.untyped(TypeId::of::<A>());
+                if drop_event.asset_server_managed && infos.load_queue.contains(untyped_id) {
+                    // The asset is still loading, so the drop cancels its load
+                    infos.process_handle_drop(untyped_id);
+                } else {
+                    not_ready.push(drop_event);
+                }
                 continue;
             }
             if drop_event.asset_server_managed {
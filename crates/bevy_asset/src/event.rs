@@ -1,4 +1,4 @@
-use crate::{Asset, AssetId};
+use crate::{Asset, AssetId, AssetPath, UntypedAssetId};
 use bevy_ecs::event::Event;
 use std::fmt::Debug;
 
@@ -75,3 +75,19 @@ impl<A: Asset> PartialEq for AssetEvent<A> {
 }
 
 impl<A: Asset> Eq for AssetEvent<A> {}
+
+/// Emitted once the [`AssetServer`](crate::AssetServer) has finished a hot reload, after the
+/// asset files that changed together have been reloaded, along with the assets whose loaders read
+/// them.
+///
+/// Systems that cache data derived from assets can use this event to rebuild it once per change,
+/// instead of once per [`AssetEvent::Modified`].
+#[derive(Event, Clone, Debug, Default)]
+pub struct AssetsReloaded {
+    /// The paths of the reloaded asset files, in the order they were reloaded. Each path comes
+    /// after the paths its loader read.
+    pub paths: Vec<AssetPath<'static>>,
+    /// The affected assets: the reloaded assets, their labeled assets, and the assets that depend
+    /// on them, directly or not. Each asset comes before its dependants.
+    pub assets: Vec<UntypedAssetId>,
+}
@@ -4,8 +4,9 @@ use crate::{
 };
 use bevy_ecs::prelude::*;
 use bevy_reflect::{Reflect, TypePath, Uuid};
-use bevy_utils::get_short_name;
+use bevy_utils::{get_short_name, HashMap, HashSet};
 use crossbeam_channel::{Receiver, Sender};
+use parking_lot::Mutex;
 use std::{
     any::TypeId,
     hash::{Hash, Hasher},
@@ -21,6 +22,25 @@ pub struct AssetHandleProvider {
     pub(crate) drop_sender: Sender<DropEvent>,
     pub(crate) drop_receiver: Receiver<DropEvent>,
     pub(crate) type_id: TypeId,
+    pub(crate) usage: Arc<Mutex<AssetUsage>>,
+}
+
+/// The assets stored in an [`Assets`](crate::Assets) collection, and the number of live
+/// [`StrongHandle`]s to each asset, shared by the collection and its handles.
+#[derive(Default)]
+pub(crate) struct AssetUsage {
+    pub(crate) stored: HashSet<InternalAssetId>,
+    pub(crate) strong_handles: HashMap<InternalAssetId, usize>,
+}
+
+impl AssetUsage {
+    /// Returns the stored assets that no live strong handle points to.
+    pub(crate) fn unreferenced(&self) -> impl Iterator<Item = InternalAssetId> + '_ {
+        self.stored
+            .iter()
+            .filter(|id| !self.strong_handles.contains_key(*id))
+            .copied()
+    }
 }
 
 pub(crate) struct DropEvent {
@@ -36,6 +56,7 @@ impl AssetHandleProvider {
             allocator,
             drop_sender,
             drop_receiver,
+            usage: Default::default(),
         }
     }
 
@@ -53,9 +74,11 @@ impl AssetHandleProvider {
         path: Option<AssetPath<'static>>,
         meta_transform: Option<MetaTransform>,
     ) -> Arc<StrongHandle> {
+        *self.usage.lock().strong_handles.entry(id).or_default() += 1;
         Arc::new(StrongHandle {
             id: id.untyped(self.type_id),
             drop_sender: self.drop_sender.clone(),
+            usage: self.usage.clone(),
             meta_transform,
             path,
             asset_server_managed,
@@ -90,10 +113,20 @@ pub struct StrongHandle {
     /// 2. configuration that must be repeatable when the asset is hot-reloaded
     pub(crate) meta_transform: Option<MetaTransform>,
     pub(crate) drop_sender: Sender<DropEvent>,
+    pub(crate) usage: Arc<Mutex<AssetUsage>>,
 }
 
 impl Drop for StrongHandle {
     fn drop(&mut self) {
+        let id = self.id.internal();
+        let mut usage = self.usage.lock();
+        if let Some(count) = usage.strong_handles.get_mut(&id) {
+            *count -= 1;
+            if *count == 0 {
+                usage.strong_handles.remove(&id);
+            }
+        }
+        drop(usage);
         let _ = self.drop_sender.send(DropEvent {
             id: self.id.internal(),
             asset_server_managed: self.asset_server_managed,
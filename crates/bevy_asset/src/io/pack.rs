@@ -0,0 +1,639 @@
+//! Asset packs: a single indexed archive file storing assets and their metadata.
+//!
+//! Shipping thousands of loose asset files slows down installation and loading on most
+//! platforms. An [`AssetPackWriter`] bundles assets and their `.meta` files into a single pack,
+//! usually from the output of the [`AssetProcessor`](crate::processor::AssetProcessor) with
+//! [`AssetProcessor::set_pack_output`](crate::processor::AssetProcessor::set_pack_output). An
+//! [`AssetPackReader`] then serves them as an [`AssetReader`]:
+//!
+//! ```no_run
+//! # use bevy_app::App;
+//! # use bevy_asset::io::{pack::AssetPackReader, AssetSource, AssetSourceId};
+//! # use bevy_asset::AssetApp;
+//! # let mut app = App::new();
+//! let reader = AssetPackReader::open("assets.pack").unwrap();
+//! app.register_asset_source(
+//!     AssetSourceId::Default,
+//!     AssetSource::build().with_reader(move || Box::new(reader.clone())),
+//! );
+//! ```
+//!
+//! A pack starts with a header, followed by the data of each entry. The index of the entries
+//! and a footer pointing to it are written at the end, so packs can be written in a single pass
+//! and entries are read with a single seek. Entries can be compressed with deflate when the
+//! `zlib` feature is enabled.
+
+use crate::io::{AssetReader, AssetReaderError, PathStream, Reader, VecReader};
+use bevy_utils::{BoxedFuture, HashMap, HashSet};
+use std::{
+    io::{Cursor, Read, Seek, SeekFrom, Write},
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+use thiserror::Error;
+
+/// Identifies a file as an asset pack, at both ends of the file.
+const MAGIC: &[u8; 8] = b"BEVYPACK";
+/// The version of the pack format written by [`AssetPackWriter`].
+const VERSION: u32 = 1;
+/// Size of the footer: index offset, entry count and magic.
+const FOOTER_SIZE: u64 = 8 + 4 + MAGIC.len() as u64;
+
+/// Errors that occur while reading or writing an asset pack.
+#[derive(Error, Debug)]
+pub enum AssetPackError {
+    /// Encountered an I/O error while reading or writing the pack.
+    #[error("encountered an io error while reading or writing an asset pack: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file isn't an asset pack.
+    #[error("the file is not an asset pack")]
+    InvalidMagic,
+    /// The pack was written with a version of the format that isn't supported.
+    #[error("unsupported asset pack version {0}")]
+    UnsupportedVersion(u32),
+    /// The index of the pack is corrupted.
+    #[error("the index of the asset pack is corrupted")]
+    InvalidIndex,
+    /// The path can't be stored in a pack, because it isn't valid UTF-8 or isn't relative.
+    #[error("the path {0:?} can't be stored in an asset pack")]
+    InvalidPath(PathBuf),
+    /// An entry was added twice to the pack.
+    #[error("the asset pack already contains {0:?}")]
+    DuplicateEntry(PathBuf),
+    /// The entry is compressed, but support for compression isn't enabled.
+    #[error("compressed asset packs require the `zlib` feature")]
+    CompressionUnsupported,
+}
+
+/// How the entries of an asset pack are compressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PackCompression {
+    /// Entries are stored as is.
+    #[default]
+    None,
+    /// Entries are compressed with deflate. This requires the `zlib` feature.
+    Deflate,
+}
+
+impl PackCompression {
+    fn to_u8(self) -> u8 {
+        match self {
+            PackCompression::None => 0,
+            PackCompression::Deflate => 1,
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(PackCompression::None),
+            1 => Some(PackCompression::Deflate),
+            _ => None,
+        }
+    }
+
+    fn compress(self, bytes: &[u8]) -> Result<Vec<u8>, AssetPackError> {
+        match self {
+            PackCompression::None => Ok(bytes.to_vec()),
+            #[cfg(feature = "zlib")]
+            PackCompression::Deflate => {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                Ok(encoder.finish()?)
+            }
+            #[cfg(not(feature = "zlib"))]
+            PackCompression::Deflate => Err(AssetPackError::CompressionUnsupported),
+        }
+    }
+
+    fn decompress(self, bytes: Vec<u8>, size: usize) -> Result<Vec<u8>, AssetPackError> {
+        match self {
+            PackCompression::None => Ok(bytes),
+            #[cfg(feature = "zlib")]
+            PackCompression::Deflate => {
+                // The size comes from the index, so it only bounds the output instead of being
+                // allocated up front. Reading a byte past it detects larger entries.
+                let mut decompressed = Vec::new();
+                flate2::read::DeflateDecoder::new(bytes.as_slice())
+                    .take((size as u64).saturating_add(1))
+                    .read_to_end(&mut decompressed)?;
+                if decompressed.len() != size {
+                    return Err(AssetPackError::InvalidIndex);
+                }
+                Ok(decompressed)
+            }
+            #[cfg(not(feature = "zlib"))]
+            PackCompression::Deflate => {
+                let _ = size;
+                Err(AssetPackError::CompressionUnsupported)
+            }
+        }
+    }
+}
+
+/// Where an entry is stored in a pack.
+#[derive(Clone, Copy, Debug)]
+struct PackEntry {
+    compression: PackCompression,
+    offset: u64,
+    stored_size: u64,
+    size: u64,
+}
+
+/// Whether an entry of the index stores an asset or its metadata.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum EntryKind {
+    Asset = 0,
+    Meta = 1,
+}
+
+/// Returns the path stored in a pack for `path`: its components joined with `/`.
+fn pack_path(path: &Path) -> Result<String, AssetPackError> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => components.push(
+                name.to_str()
+                    .ok_or_else(|| AssetPackError::InvalidPath(path.to_owned()))?,
+            ),
+            Component::CurDir => {}
+            _ => return Err(AssetPackError::InvalidPath(path.to_owned())),
+        }
+    }
+    if components.is_empty() {
+        return Err(AssetPackError::InvalidPath(path.to_owned()));
+    }
+    Ok(components.join("/"))
+}
+
+/// Writes assets and their metadata to an asset pack, in a single pass over `W`.
+///
+/// The pack is only valid once [`AssetPackWriter::finish`] has written its index.
+pub struct AssetPackWriter<W: Write> {
+    writer: W,
+    position: u64,
+    compression: PackCompression,
+    paths: HashSet<(EntryKind, String)>,
+    index: Vec<u8>,
+    entry_count: u32,
+}
+
+impl<W: Write> AssetPackWriter<W> {
+    /// Starts a new pack in `writer`, compressing its entries with `compression`.
+    ///
+    /// Entries that don't get smaller when compressed are stored as is.
+    pub fn new(mut writer: W, compression: PackCompression) -> Result<Self, AssetPackError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            position: MAGIC.len() as u64 + 4,
+            compression,
+            paths: HashSet::new(),
+            index: Vec::new(),
+            entry_count: 0,
+        })
+    }
+
+    /// Adds the asset `bytes` at the given `path` to the pack.
+    pub fn add_asset(&mut self, path: &Path, bytes: &[u8]) -> Result<(), AssetPackError> {
+        self.add_entry(EntryKind::Asset, path, bytes)
+    }
+
+    /// Adds the asset meta `bytes` for the asset at the given `path` to the pack.
+    /// This _should not_ include storage specific extensions like `.meta`.
+    pub fn add_meta(&mut self, path: &Path, bytes: &[u8]) -> Result<(), AssetPackError> {
+        self.add_entry(EntryKind::Meta, path, bytes)
+    }
+
+    fn add_entry(
+        &mut self,
+        kind: EntryKind,
+        path: &Path,
+        bytes: &[u8],
+    ) -> Result<(), AssetPackError> {
+        let stored_path = pack_path(path)?;
+        if !self.paths.insert((kind, stored_path.clone())) {
+            return Err(AssetPackError::DuplicateEntry(path.to_owned()));
+        }
+
+        let mut compression = self.compression;
+        let mut stored = compression.compress(bytes)?;
+        if stored.len() >= bytes.len() {
+            compression = PackCompression::None;
+            stored = bytes.to_vec();
+        }
+        self.writer.write_all(&stored)?;
+
+        self.index.push(kind as u8);
+        self.index.push(compression.to_u8());
+        self.index
+            .extend_from_slice(&(stored_path.len() as u32).to_le_bytes());
+        self.index.extend_from_slice(stored_path.as_bytes());
+        self.index.extend_from_slice(&self.position.to_le_bytes());
+        self.index
+            .extend_from_slice(&(stored.len() as u64).to_le_bytes());
+        self.index
+            .extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        self.position += stored.len() as u64;
+        self.entry_count += 1;
+        Ok(())
+    }
+
+    /// Writes the index of the pack and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, AssetPackError> {
+        self.writer.write_all(&self.index)?;
+        self.writer.write_all(&self.position.to_le_bytes())?;
+        self.writer.write_all(&self.entry_count.to_le_bytes())?;
+        self.writer.write_all(MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// The entries of a pack, and the directories containing them.
+#[derive(Default, Debug)]
+struct PackIndex {
+    assets: HashMap<PathBuf, PackEntry>,
+    metas: HashMap<PathBuf, PackEntry>,
+    directories: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+impl PackIndex {
+    /// Reads the index of the pack in `reader`.
+    fn read(mut reader: impl Read + Seek) -> Result<Self, AssetPackError> {
+        let mut magic = [0; MAGIC.len()];
+        let mut u32_bytes = [0; 4];
+        let mut u64_bytes = [0; 8];
+
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(AssetPackError::InvalidMagic);
+        }
+        reader.read_exact(&mut u32_bytes)?;
+        let version = u32::from_le_bytes(u32_bytes);
+        if version != VERSION {
+            return Err(AssetPackError::UnsupportedVersion(version));
+        }
+
+        let len = reader.seek(SeekFrom::End(0))?;
+        if len < MAGIC.len() as u64 + 4 + FOOTER_SIZE {
+            return Err(AssetPackError::InvalidIndex);
+        }
+        reader.seek(SeekFrom::Start(len - FOOTER_SIZE))?;
+        reader.read_exact(&mut u64_bytes)?;
+        let index_offset = u64::from_le_bytes(u64_bytes);
+        reader.read_exact(&mut u32_bytes)?;
+        let entry_count = u32::from_le_bytes(u32_bytes);
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(AssetPackError::InvalidMagic);
+        }
+        if index_offset > len - FOOTER_SIZE {
+            return Err(AssetPackError::InvalidIndex);
+        }
+
+        let mut index_bytes = vec![0; (len - FOOTER_SIZE - index_offset) as usize];
+        reader.seek(SeekFrom::Start(index_offset))?;
+        reader.read_exact(&mut index_bytes)?;
+        let mut index_reader = index_bytes.as_slice();
+        let mut index = PackIndex::default();
+        let mut directories: HashMap<PathBuf, HashSet<PathBuf>> = HashMap::new();
+        directories.insert(PathBuf::new(), HashSet::new());
+        for _ in 0..entry_count {
+            let mut header = [0; 2];
+            index_reader.read_exact(&mut header)?;
+            let compression =
+                PackCompression::from_u8(header[1]).ok_or(AssetPackError::InvalidIndex)?;
+            index_reader.read_exact(&mut u32_bytes)?;
+            let path_len = u32::from_le_bytes(u32_bytes) as usize;
+            if path_len > index_reader.len() {
+                return Err(AssetPackError::InvalidIndex);
+            }
+            let mut path = vec![0; path_len];
+            index_reader.read_exact(&mut path)?;
+            let path =
+                PathBuf::from(String::from_utf8(path).map_err(|_| AssetPackError::InvalidIndex)?);
+            let mut read_u64 = || -> Result<u64, AssetPackError> {
+                index_reader.read_exact(&mut u64_bytes)?;
+                Ok(u64::from_le_bytes(u64_bytes))
+            };
+            let entry = PackEntry {
+                compression,
+                offset: read_u64()?,
+                stored_size: read_u64()?,
+                size: read_u64()?,
+            };
+            let in_bounds = matches!(
+                entry.offset.checked_add(entry.stored_size),
+                Some(end) if end <= index_offset
+            );
+            if !in_bounds
+                || (entry.compression == PackCompression::None && entry.size != entry.stored_size)
+            {
+                return Err(AssetPackError::InvalidIndex);
+            }
+
+            match header[0] {
+                0 => {
+                    let mut child = path.clone();
+                    while let Some(parent) = child.parent() {
+                        let is_new = directories
+                            .entry(parent.to_owned())
+                            .or_default()
+                            .insert(child.clone());
+                        if !is_new {
+                            break;
+                        }
+                        child = parent.to_owned();
+                    }
+                    index.assets.insert(path, entry);
+                }
+                1 => {
+                    index.metas.insert(path, entry);
+                }
+                _ => return Err(AssetPackError::InvalidIndex),
+            }
+        }
+
+        index.directories = directories
+            .into_iter()
+            .map(|(directory, children)| {
+                let mut children: Vec<_> = children.into_iter().collect();
+                children.sort();
+                (directory, children)
+            })
+            .collect();
+        Ok(index)
+    }
+}
+
+/// Where the data of a pack is read from.
+#[derive(Clone)]
+enum PackData {
+    Bytes(Arc<[u8]>),
+    #[cfg(not(target_arch = "wasm32"))]
+    File(PathBuf),
+}
+
+/// An [`AssetReader`] serving the assets of an asset pack written by an [`AssetPackWriter`].
+///
+/// The index of the pack is read when the reader is created. Packs opened from a file are then
+/// read with a seek for each entry, while packs created from bytes are read from memory, for
+/// example for packs embedded in the binary or fetched on the web.
+///
+/// [`AssetPackReader`] is backed by an [`Arc`] so clones share the same index.
+#[derive(Clone)]
+pub struct AssetPackReader {
+    data: PackData,
+    index: Arc<PackIndex>,
+}
+
+impl AssetPackReader {
+    /// Creates a reader for the pack stored in `bytes`.
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>) -> Result<Self, AssetPackError> {
+        let bytes = bytes.into();
+        let index = PackIndex::read(Cursor::new(&*bytes))?;
+        Ok(Self {
+            data: PackData::Bytes(bytes),
+            index: Arc::new(index),
+        })
+    }
+
+    /// Opens the pack file at `path`. Entries are read from the file when they're loaded.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, AssetPackError> {
+        let path = path.as_ref();
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let index = PackIndex::read(file)?;
+        Ok(Self {
+            data: PackData::File(path.to_owned()),
+            index: Arc::new(index),
+        })
+    }
+
+    async fn read_entry(&self, entry: PackEntry) -> Result<Vec<u8>, AssetReaderError> {
+        let stored = match &self.data {
+            PackData::Bytes(bytes) => {
+                bytes[entry.offset as usize..(entry.offset + entry.stored_size) as usize].to_vec()
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            PackData::File(path) => {
+                use futures_lite::{AsyncReadExt, AsyncSeekExt};
+                let mut file = async_fs::File::open(path).await?;
+                file.seek(SeekFrom::Start(entry.offset)).await?;
+                let mut stored = vec![0; entry.stored_size as usize];
+                file.read_exact(&mut stored).await?;
+                stored
+            }
+        };
+        entry
+            .compression
+            .decompress(stored, entry.size as usize)
+            .map_err(|error| match error {
+                AssetPackError::Io(error) => AssetReaderError::Io(error),
+                error => AssetReaderError::Io(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    error,
+                )),
+            })
+    }
+}
+
+impl AssetReader for AssetPackReader {
+    fn read<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            let entry = *self
+                .index
+                .assets
+                .get(path)
+                .ok_or_else(|| AssetReaderError::NotFound(path.to_owned()))?;
+            let reader: Box<Reader> = Box::new(VecReader::new(self.read_entry(entry).await?));
+            Ok(reader)
+        })
+    }
+
+    fn read_meta<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            let entry = *self
+                .index
+                .metas
+                .get(path)
+                .ok_or_else(|| AssetReaderError::NotFound(path.to_owned()))?;
+            let reader: Box<Reader> = Box::new(VecReader::new(self.read_entry(entry).await?));
+            Ok(reader)
+        })
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<PathStream>, AssetReaderError>> {
+        Box::pin(async move {
+            let children = self
+                .index
+                .directories
+                .get(path)
+                .ok_or_else(|| AssetReaderError::NotFound(path.to_owned()))?;
+            let stream: Box<PathStream> = Box::new(futures_lite::stream::iter(children.clone()));
+            Ok(stream)
+        })
+    }
+
+    fn is_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<bool, AssetReaderError>> {
+        Box::pin(async move { Ok(self.index.directories.contains_key(path)) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::{future::block_on, AsyncReadExt, StreamExt};
+
+    fn write_pack(compression: PackCompression) -> Vec<u8> {
+        let mut writer = AssetPackWriter::new(Vec::new(), compression).unwrap();
+        writer
+            .add_asset(Path::new("a.txt"), "a".repeat(100).as_bytes())
+            .unwrap();
+        writer.add_meta(Path::new("a.txt"), b"a meta").unwrap();
+        writer.add_asset(Path::new("x/y/b.txt"), b"b").unwrap();
+        writer.add_asset(Path::new("x/c.txt"), b"c").unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn read(reader: &AssetPackReader, path: &str) -> Vec<u8> {
+        block_on(async {
+            let mut bytes = Vec::new();
+            reader
+                .read(Path::new(path))
+                .await
+                .unwrap()
+                .read_to_end(&mut bytes)
+                .await
+                .unwrap();
+            bytes
+        })
+    }
+
+    fn read_directory(reader: &AssetPackReader, path: &str) -> Vec<PathBuf> {
+        block_on(async {
+            reader
+                .read_directory(Path::new(path))
+                .await
+                .unwrap()
+                .collect()
+                .await
+        })
+    }
+
+    /// Writes a pack with a single `a.txt` asset, then changes its index entry with `corrupt`.
+    fn corrupted_pack(
+        compression: PackCompression,
+        corrupt: impl FnOnce(&mut [u8]),
+    ) -> Result<AssetPackReader, AssetPackError> {
+        let mut writer = AssetPackWriter::new(Vec::new(), compression).unwrap();
+        writer
+            .add_asset(Path::new("a.txt"), "a".repeat(100).as_bytes())
+            .unwrap();
+        let mut bytes = writer.finish().unwrap();
+        let entry_start = bytes.len() - FOOTER_SIZE as usize - (2 + 4 + "a.txt".len() + 3 * 8);
+        corrupt(&mut bytes[entry_start..]);
+        AssetPackReader::from_bytes(bytes)
+    }
+
+    #[test]
+    fn pack_round_trip() {
+        let reader = AssetPackReader::from_bytes(write_pack(PackCompression::None)).unwrap();
+
+        assert_eq!(read(&reader, "a.txt"), "a".repeat(100).as_bytes());
+        assert_eq!(read(&reader, "x/y/b.txt"), b"b");
+        let meta = block_on(reader.read_meta_bytes(Path::new("a.txt"))).unwrap();
+        assert_eq!(meta, b"a meta");
+        assert!(matches!(
+            block_on(reader.read_meta_bytes(Path::new("x/c.txt"))),
+            Err(AssetReaderError::NotFound(_))
+        ));
+        assert!(matches!(
+            block_on(reader.read(Path::new("missing.txt"))),
+            Err(AssetReaderError::NotFound(_))
+        ));
+
+        assert_eq!(
+            read_directory(&reader, ""),
+            [PathBuf::from("a.txt"), PathBuf::from("x")]
+        );
+        assert_eq!(
+            read_directory(&reader, "x"),
+            [PathBuf::from("x/c.txt"), PathBuf::from("x/y")]
+        );
+        assert!(block_on(reader.is_directory(Path::new("x/y"))).unwrap());
+        assert!(!block_on(reader.is_directory(Path::new("x/c.txt"))).unwrap());
+    }
+
+    #[cfg(feature = "zlib")]
+    #[test]
+    fn compressed_pack_round_trip() {
+        let bytes = write_pack(PackCompression::Deflate);
+        assert!(bytes.len() < write_pack(PackCompression::None).len());
+
+        let reader = AssetPackReader::from_bytes(bytes).unwrap();
+        assert_eq!(read(&reader, "a.txt"), "a".repeat(100).as_bytes());
+        // Entries that don't shrink are stored as is
+        assert_eq!(read(&reader, "x/c.txt"), b"c");
+
+        // The decompressed size of the index isn't trusted
+        for size in [u64::MAX, 99, 101] {
+            let reader = corrupted_pack(PackCompression::Deflate, |entry| {
+                entry[27..35].copy_from_slice(&size.to_le_bytes());
+            })
+            .unwrap();
+            assert!(block_on(reader.read(Path::new("a.txt"))).is_err());
+        }
+    }
+
+    #[test]
+    fn invalid_packs() {
+        let mut writer = AssetPackWriter::new(Vec::new(), PackCompression::None).unwrap();
+        writer.add_asset(Path::new("a.txt"), b"a").unwrap();
+        assert!(matches!(
+            writer.add_asset(Path::new("./a.txt"), b"a"),
+            Err(AssetPackError::DuplicateEntry(_))
+        ));
+        assert!(matches!(
+            writer.add_asset(Path::new("../b.txt"), b"b"),
+            Err(AssetPackError::InvalidPath(_))
+        ));
+
+        assert!(matches!(
+            AssetPackReader::from_bytes(b"not a pack at all, really".to_vec()),
+            Err(AssetPackError::InvalidMagic)
+        ));
+        let mut truncated = write_pack(PackCompression::None);
+        truncated.truncate(truncated.len() - 1);
+        assert!(AssetPackReader::from_bytes(truncated).is_err());
+
+        // Sizes and offsets of the index are checked against the file instead of being trusted
+        let corruptions: [fn(&mut [u8]); 4] = [
+            |entry| entry[2..6].copy_from_slice(&u32::MAX.to_le_bytes()),
+            |entry| entry[11..19].copy_from_slice(&u64::MAX.to_le_bytes()),
+            |entry| entry[19..27].copy_from_slice(&u64::MAX.to_le_bytes()),
+            |entry| entry[27..35].copy_from_slice(&u64::MAX.to_le_bytes()),
+        ];
+        for corrupt in corruptions {
+            assert!(matches!(
+                corrupted_pack(PackCompression::None, corrupt),
+                Err(AssetPackError::InvalidIndex)
+            ));
+        }
+        assert!(corrupted_pack(PackCompression::None, |_| {}).is_ok());
+    }
+}
@@ -17,6 +17,9 @@ pub struct ProcessorGatedReader {
     reader: Box<dyn AssetReader>,
     source: AssetSourceId<'static>,
     processor_data: Arc<AssetProcessorData>,
+    /// Set if reads happen while processing an asset, which lends its processing slot while
+    /// waiting.
+    pub(crate) lends_processing_slot: bool,
 }
 
 impl ProcessorGatedReader {
@@ -30,6 +33,17 @@ impl ProcessorGatedReader {
             source,
             processor_data,
             reader,
+            lends_processing_slot: false,
+        }
+    }
+
+    async fn wait_until_processed(&self, path: AssetPath<'static>) -> ProcessStatus {
+        if self.lends_processing_slot {
+            self.processor_data
+                .wait_until_processed_while_processing(path)
+                .await
+        } else {
+            self.processor_data.wait_until_processed(path).await
         }
     }
 
@@ -55,10 +69,7 @@ impl AssetReader for ProcessorGatedReader {
         Box::pin(async move {
             let asset_path = AssetPath::from(path.to_path_buf()).with_source(self.source.clone());
             trace!("Waiting for processing to finish before reading {asset_path}");
-            let process_result = self
-                .processor_data
-                .wait_until_processed(asset_path.clone())
-                .await;
+            let process_result = self.wait_until_processed(asset_path.clone()).await;
             match process_result {
                 ProcessStatus::Processed => {}
                 ProcessStatus::Failed | ProcessStatus::NonExistent => {
@@ -81,10 +92,7 @@ impl AssetReader for ProcessorGatedReader {
         Box::pin(async move {
             let asset_path = AssetPath::from(path.to_path_buf()).with_source(self.source.clone());
             trace!("Waiting for processing to finish before reading meta for {asset_path}",);
-            let process_result = self
-                .processor_data
-                .wait_until_processed(asset_path.clone())
-                .await;
+            let process_result = self.wait_until_processed(asset_path.clone()).await;
             match process_result {
                 ProcessStatus::Processed => {}
                 ProcessStatus::Failed | ProcessStatus::NonExistent => {
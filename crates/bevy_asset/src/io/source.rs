@@ -466,12 +466,20 @@ impl AssetSource {
     /// This will cause processed [`AssetReader`] futures (such as [`AssetReader::read`]) to wait until
     /// the [`AssetProcessor`](crate::AssetProcessor) has finished processing the requested asset.
     pub fn gate_on_processor(&mut self, processor_data: Arc<AssetProcessorData>) {
+        self.gate_on_processor_internal(processor_data, false);
+    }
+
+    /// Gates the processed [`AssetReader`] on the [`AssetProcessor`](crate::AssetProcessor), lending
+    /// the processing slot of the reading asset while waiting if `lends_processing_slot` is set.
+    fn gate_on_processor_internal(
+        &mut self,
+        processor_data: Arc<AssetProcessorData>,
+        lends_processing_slot: bool,
+    ) {
         if let Some(reader) = self.processed_reader.take() {
-            self.processed_reader = Some(Box::new(ProcessorGatedReader::new(
-                self.id(),
-                reader,
-                processor_data,
-            )));
+            let mut reader = ProcessorGatedReader::new(self.id(), reader, processor_data);
+            reader.lends_processing_slot = lends_processing_slot;
+            self.processed_reader = Some(Box::new(reader));
         }
     }
 }
@@ -532,6 +540,18 @@ impl AssetSources {
             source.gate_on_processor(processor_data.clone());
         }
     }
+
+    /// Gates the processed [`AssetReader`]s of the [`AssetServer`](crate::AssetServer) of the
+    /// [`AssetProcessor`](crate::AssetProcessor) itself, which only reads assets while
+    /// processing another asset. The processing asset lends its slot while waiting.
+    pub(crate) fn gate_on_processor_while_processing(
+        &mut self,
+        processor_data: Arc<AssetProcessorData>,
+    ) {
+        for source in self.iter_processed_mut() {
+            source.gate_on_processor_internal(processor_data.clone(), true);
+        }
+    }
 }
 
 /// An error returned when an [`AssetSource`] does not exist for a given id.
@@ -188,6 +188,7 @@ impl Plugin for AssetPlugin {
             .init_asset::<LoadedFolder>()
             .init_asset::<LoadedUntypedAsset>()
             .init_asset::<()>()
+            .add_event::<AssetsReloaded>()
             .configure_sets(
                 UpdateAssets,
                 TrackAssets.after(server::handle_internal_asset_events),
@@ -406,11 +407,12 @@ mod tests {
         io::{
             gated::{GateOpener, GatedReader},
             memory::{Dir, MemoryAssetReader},
-            AssetSource, AssetSourceId, Reader,
+            AssetSource, AssetSourceEvent, AssetSourceId, AssetWatcher, Reader,
         },
         loader::{AssetLoader, LoadContext},
         Asset, AssetApp, AssetEvent, AssetId, AssetPath, AssetPlugin, AssetServer, Assets,
-        DependencyLoadState, LoadState, RecursiveDependencyLoadState,
+        AssetsReloaded, DependencyLoadState, LoadPriority, LoadState, RecursiveDependencyLoadState,
+        UntypedAssetId,
     };
     use bevy_app::{App, Update};
     use bevy_core::TaskPoolPlugin;
@@ -420,11 +422,14 @@ mod tests {
         schedule::{LogLevel, ScheduleBuildSettings},
     };
     use bevy_log::LogPlugin;
-    use bevy_reflect::TypePath;
-    use bevy_utils::BoxedFuture;
-    use futures_lite::AsyncReadExt;
+    use bevy_reflect::{TypePath, Uuid};
+    use bevy_utils::{BoxedFuture, HashSet};
+    use futures_lite::{future::block_on, AsyncReadExt};
     use serde::{Deserialize, Serialize};
-    use std::path::Path;
+    use std::{
+        path::Path,
+        sync::{Arc, Mutex},
+    };
     use thiserror::Error;
 
     #[derive(Asset, TypePath, Debug)]
@@ -1171,6 +1176,257 @@ mod tests {
         });
     }
 
+    #[test]
+    fn dependency_graph_and_unused_assets() {
+        let dir = Dir::default();
+        let text = |text: &str, dependencies: &[&str]| {
+            format!(
+                "(text: {text:?}, dependencies: {dependencies:?}, embedded_dependencies: [], sub_texts: [])"
+            )
+        };
+        dir.insert_asset_text(Path::new("a.cool.ron"), &text("a", &["b.cool.ron"]));
+        dir.insert_asset_text(Path::new("b.cool.ron"), &text("b", &["c/c.cool.ron"]));
+        dir.insert_asset_text(Path::new("c/c.cool.ron"), &text("c", &[]));
+        dir.insert_asset_text(Path::new("c/unused.cool.ron"), &text("unused", &[]));
+
+        let (mut app, gate_opener) = test_app(dir);
+        app.init_asset::<CoolText>()
+            .init_asset::<SubText>()
+            .register_asset_loader(CoolTextLoader);
+        let asset_server = app.world.resource::<AssetServer>().clone();
+        let a: Handle<CoolText> = asset_server.load("a.cool.ron");
+        gate_opener.open("a.cool.ron");
+        gate_opener.open("b.cool.ron");
+        gate_opener.open("c/c.cool.ron");
+        run_app_until(&mut app, |_| {
+            (asset_server.recursive_dependency_load_state(&a)
+                == RecursiveDependencyLoadState::Loaded)
+                .then_some(())
+        });
+
+        let b_handle = asset_server.get_handle_untyped("b.cool.ron").unwrap();
+        let b = b_handle.id();
+        let c = asset_server
+            .get_handle_untyped("c/c.cool.ron")
+            .unwrap()
+            .id();
+        assert_eq!(
+            asset_server.get_dependencies(&a),
+            Some([b].into_iter().collect())
+        );
+        assert_eq!(
+            asset_server.get_recursive_dependencies(&a),
+            Some([b, c].into_iter().collect())
+        );
+        assert_eq!(
+            asset_server.get_dependants(c),
+            Some([b].into_iter().collect())
+        );
+        assert_eq!(asset_server.get_dependants(&a), Some(HashSet::new()));
+
+        // An asset inserted by id and an asset only kept by a weak handle are unreferenced
+        let inserted = AssetId::<CoolText>::from(Uuid::from_u128(0x0123_4567_89ab));
+        let cool_text = || CoolText {
+            text: "unreferenced".into(),
+            embedded: String::new(),
+            dependencies: Vec::new(),
+            sub_texts: Vec::new(),
+        };
+        let weak = {
+            let mut texts = app.world.resource_mut::<Assets<CoolText>>();
+            texts.insert(inserted, cool_text());
+            texts.add(cool_text()).clone_weak()
+        };
+
+        let report = block_on(asset_server.unused_asset_report()).unwrap();
+        let mut expected = vec![inserted.untyped(), weak.id().untyped()];
+        expected.sort_by_key(|id| id.internal());
+        assert_eq!(report.unreferenced_assets, expected);
+        assert_eq!(
+            report.unloaded_files,
+            [AssetPath::from("c/unused.cool.ron")]
+        );
+
+        // Dropping an asset removes it from the dependants of its dependencies that are still alive
+        drop(a);
+        run_app_until(&mut app, |_| {
+            asset_server
+                .get_dependants(b)
+                .unwrap()
+                .is_empty()
+                .then_some(())
+        });
+        drop(b_handle);
+    }
+
+    #[test]
+    fn load_priorities_and_cancellation() {
+        let dir = Dir::default();
+        for name in ["a", "b", "c", "d"] {
+            dir.insert_asset_text(
+                Path::new(&format!("{name}.cool.ron")),
+                &format!(
+                    "(text: {name:?}, dependencies: [], embedded_dependencies: [], sub_texts: [])"
+                ),
+            );
+        }
+
+        let (mut app, gate_opener) = test_app(dir);
+        app.init_asset::<CoolText>()
+            .init_asset::<SubText>()
+            .register_asset_loader(CoolTextLoader);
+        let asset_server = app.world.resource::<AssetServer>().clone();
+        asset_server.set_max_loads_in_flight(Some(1));
+
+        let a: Handle<CoolText> = asset_server.load("a.cool.ron");
+        let c: Handle<CoolText> = asset_server.load_with_priority("c.cool.ron", LoadPriority::LOW);
+        let d: Handle<CoolText> = asset_server.load("d.cool.ron");
+        let b: Handle<CoolText> = asset_server.load_with_priority("b.cool.ron", LoadPriority::HIGH);
+        gate_opener.open("b.cool.ron");
+        for _ in 0..10 {
+            app.update();
+        }
+        // `b` waits for the load of `a`, which waits on its gate
+        assert_eq!(asset_server.load_state(&b), LoadState::Loading);
+
+        // Dropping the handle of `d` cancels its queued load. It would never finish, as its gate
+        // stays closed, and would keep `c` from loading.
+        drop(d);
+        app.update();
+
+        gate_opener.open("a.cool.ron");
+        run_app_until(&mut app, |_| {
+            (asset_server.load_state(&b) == LoadState::Loaded).then_some(())
+        });
+        assert_eq!(asset_server.load_state(&a), LoadState::Loaded);
+        assert_eq!(asset_server.load_state(&c), LoadState::Loading);
+
+        gate_opener.open("c.cool.ron");
+        run_app_until(&mut app, |_| {
+            (asset_server.load_state(&c) == LoadState::Loaded).then_some(())
+        });
+    }
+
+    struct TestWatcher;
+
+    impl AssetWatcher for TestWatcher {}
+
+    #[test]
+    fn hot_reload_dependants() {
+        let dir = Dir::default();
+        dir.insert_asset_text(
+            Path::new("a.cool.ron"),
+            r#"(text: "a", dependencies: ["b.cool.ron"], embedded_dependencies: [], sub_texts: [])"#,
+        );
+        dir.insert_asset_text(
+            Path::new("b.cool.ron"),
+            r#"(text: "b", dependencies: [], embedded_dependencies: ["c.cool.ron"], sub_texts: ["sub"])"#,
+        );
+        dir.insert_asset_text(
+            Path::new("c.cool.ron"),
+            r#"(text: "c", dependencies: [], embedded_dependencies: [], sub_texts: [])"#,
+        );
+
+        let event_sender = Arc::new(Mutex::new(None));
+        let mut app = App::new();
+        let reader_dir = dir.clone();
+        let watcher_event_sender = event_sender.clone();
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build()
+                .with_reader(move || {
+                    Box::new(MemoryAssetReader {
+                        root: reader_dir.clone(),
+                    })
+                })
+                .with_watcher(move |sender| {
+                    *watcher_event_sender.lock().unwrap() = Some(sender);
+                    Some(Box::new(TestWatcher))
+                }),
+        )
+        .add_plugins((
+            TaskPoolPlugin::default(),
+            LogPlugin::default(),
+            AssetPlugin {
+                watch_for_changes_override: Some(true),
+                ..Default::default()
+            },
+        ))
+        .init_asset::<CoolText>()
+        .init_asset::<SubText>()
+        .register_asset_loader(CoolTextLoader);
+        let asset_server = app.world.resource::<AssetServer>().clone();
+
+        let a: Handle<CoolText> = asset_server.load("a.cool.ron");
+        let c: Handle<CoolText> = asset_server.load("c.cool.ron");
+        run_app_until(&mut app, |_| {
+            (asset_server.recursive_dependency_load_state(&a)
+                == RecursiveDependencyLoadState::Loaded
+                && asset_server.load_state(&c) == LoadState::Loaded)
+                .then_some(())
+        });
+        let b_id = get::<CoolText>(&app.world, a.id()).unwrap().dependencies[0].id();
+        let sub_id = get::<CoolText>(&app.world, b_id).unwrap().sub_texts[0].id();
+        assert_eq!(get::<CoolText>(&app.world, b_id).unwrap().embedded, "c");
+
+        // `b` embeds `c`, so it is reloaded after it, and `a` depends on `b`
+        dir.insert_asset_text(
+            Path::new("c.cool.ron"),
+            r#"(text: "c2", dependencies: [], embedded_dependencies: [], sub_texts: [])"#,
+        );
+        let sender = event_sender.lock().unwrap().clone().unwrap();
+        sender
+            .send(AssetSourceEvent::ModifiedAsset("c.cool.ron".into()))
+            .unwrap();
+        let mut reloaded = None;
+        run_app_until(&mut app, |world| {
+            reloaded = world
+                .resource_mut::<Events<AssetsReloaded>>()
+                .drain()
+                .next();
+            reloaded.as_ref().map(|_| ())
+        });
+        let reloaded = reloaded.unwrap();
+        assert_eq!(
+            reloaded.paths,
+            [AssetPath::from("c.cool.ron"), AssetPath::from("b.cool.ron")]
+        );
+        let position = |id: UntypedAssetId| {
+            reloaded
+                .assets
+                .iter()
+                .position(|reloaded_id| *reloaded_id == id)
+                .unwrap()
+        };
+        assert_eq!(reloaded.assets.len(), 4);
+        assert_eq!(position(c.id().untyped()), 0);
+        assert!(position(b_id.untyped()) < position(a.id().untyped()));
+        position(sub_id.untyped());
+        assert_eq!(get::<CoolText>(&app.world, b_id).unwrap().embedded, "c2");
+
+        // A manual reload reloads the same assets, and sends a single event too
+        dir.insert_asset_text(
+            Path::new("c.cool.ron"),
+            r#"(text: "c3", dependencies: [], embedded_dependencies: [], sub_texts: [])"#,
+        );
+        asset_server.reload("c.cool.ron");
+        let mut reloaded = Vec::new();
+        run_app_until(&mut app, |world| {
+            reloaded.extend(
+                world
+                    .resource_mut::<Events<AssetsReloaded>>()
+                    .drain()
+                    .map(|reloaded| reloaded.paths),
+            );
+            (!reloaded.is_empty()).then_some(())
+        });
+        assert_eq!(
+            reloaded,
+            [[AssetPath::from("c.cool.ron"), AssetPath::from("b.cool.ron")]]
+        );
+        assert_eq!(get::<CoolText>(&app.world, b_id).unwrap().embedded, "c3");
+    }
+
     #[test]
     fn ignore_system_ambiguities_on_assets() {
         let mut app = App::new();
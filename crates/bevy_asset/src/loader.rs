@@ -2,7 +2,7 @@ use crate::{
     io::{AssetReaderError, MissingAssetSourceError, MissingProcessedAssetReaderError, Reader},
     meta::{
         loader_settings_meta_transform, AssetHash, AssetMeta, AssetMetaDyn, ProcessedInfoMinimal,
-        Settings,
+        Settings, SettingsMigrations,
     },
     path::AssetPath,
     Asset, AssetLoadError, AssetServer, AssetServerMode, Assets, Handle, LoadedUntypedAsset,
@@ -39,6 +39,15 @@ pub trait AssetLoader: Send + Sync + 'static {
 
     /// Returns a list of extensions supported by this asset loader, without the preceding dot.
     fn extensions(&self) -> &[&str];
+
+    /// Returns the migrations of [`AssetLoader::Settings`] from their previous versions. Add a
+    /// migration whenever the settings change shape, so the existing `.meta` files still load.
+    fn settings_migrations() -> SettingsMigrations<Self::Settings>
+    where
+        Self: Sized,
+    {
+        SettingsMigrations::new()
+    }
 }
 
 /// Provides type-erased access to an [`AssetLoader`].
@@ -260,6 +269,8 @@ pub enum DeserializeMetaError {
     DeserializeSettings(#[from] SpannedError),
     #[error("Failed to deserialize minimal asset meta: {0:?}")]
     DeserializeMinimal(SpannedError),
+    #[error("Asset meta has settings version {version}, but the latest supported version is {supported}")]
+    UnsupportedSettingsVersion { version: u32, supported: u32 },
 }
 
 /// A context that provides access to assets in [`AssetLoader`]s, tracks dependencies, and collects asset load state.
@@ -460,7 +471,8 @@ impl<'a> LoadContext<'a> {
     pub fn load<'b, A: Asset>(&mut self, path: impl Into<AssetPath<'b>>) -> Handle<A> {
         let path = path.into().to_owned();
         let handle = if self.should_load_dependencies {
-            self.asset_server.load(path)
+            let priority = self.asset_server.get_load_priority(&self.asset_path);
+            self.asset_server.load_with_priority(path, priority)
         } else {
             self.asset_server.get_or_create_path_handle(path, None)
         };
@@ -475,7 +487,8 @@ impl<'a> LoadContext<'a> {
     ) -> Handle<LoadedUntypedAsset> {
         let path = path.into().to_owned();
         let handle = if self.should_load_dependencies {
-            self.asset_server.load_untyped(path)
+            let priority = self.asset_server.get_load_priority(&self.asset_path);
+            self.asset_server.load_untyped_with_priority(path, priority)
         } else {
             self.asset_server.get_or_create_path_handle(path, None)
         };
@@ -493,7 +506,12 @@ impl<'a> LoadContext<'a> {
     ) -> Handle<A> {
         let path = path.into();
         let handle = if self.should_load_dependencies {
-            self.asset_server.load_with_settings(path.clone(), settings)
+            let priority = self.asset_server.get_load_priority(&self.asset_path);
+            self.asset_server.load_with_meta_transform(
+                path.clone(),
+                Some(loader_settings_meta_transform(settings)),
+                priority,
+            )
         } else {
             self.asset_server
                 .get_or_create_path_handle(path, Some(loader_settings_meta_transform(settings)))
@@ -1,9 +1,17 @@
 use crate::{self as bevy_asset, DeserializeMetaError, VisitAssetDependencies};
-use crate::{loader::AssetLoader, processor::Process, Asset, AssetPath};
+use crate::{
+    loader::AssetLoader,
+    processor::{LoadAndSaveSettings, Process},
+    Asset, AssetPath,
+};
 use bevy_log::error;
 use downcast_rs::{impl_downcast, Downcast};
 use ron::ser::PrettyConfig;
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Deserialize, Serialize,
+};
+use std::{any::Any, marker::PhantomData};
 
 pub const META_FORMAT_VERSION: &str = "1.0";
 pub type MetaTransform = Box<dyn Fn(&mut dyn AssetMetaDyn) + Send + Sync>;
@@ -17,6 +25,11 @@ pub struct AssetMeta<L: AssetLoader, P: Process> {
     /// The version of the meta format being used. This will change whenever a breaking change is made to
     /// the meta format.
     pub meta_format_version: String,
+    /// The version of the settings of the [`AssetLoader`] or the [`Process`] of this asset, which is
+    /// the number of [`SettingsMigrations`] they declare. Older settings are migrated when the meta
+    /// is deserialized.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub settings_version: u32,
     /// Information produced by the [`AssetProcessor`] _after_ processing this asset.
     /// This will only exist alongside processed versions of assets. You should not manually set it in your asset source files.
     ///
@@ -29,19 +42,302 @@ pub struct AssetMeta<L: AssetLoader, P: Process> {
 
 impl<L: AssetLoader, P: Process> AssetMeta<L, P> {
     pub fn new(asset: AssetAction<L::Settings, P::Settings>) -> Self {
+        let settings_version = match &asset {
+            AssetAction::Load { .. } => L::settings_migrations().version(),
+            AssetAction::Process { .. } => P::settings_migrations().version(),
+            AssetAction::Ignore => 0,
+        };
         Self {
             meta_format_version: META_FORMAT_VERSION.to_string(),
+            settings_version,
             processed_info: None,
             asset,
         }
     }
 
-    /// Deserializes the given serialized byte representation of the asset meta.
+    /// Deserializes the given serialized byte representation of the asset meta, migrating its
+    /// settings if they were saved by an older version of the [`AssetLoader`] or [`Process`].
     pub fn deserialize(bytes: &[u8]) -> Result<Self, DeserializeMetaError> {
-        Ok(ron::de::from_bytes(bytes)?)
+        let minimal: AssetMetaMinimal =
+            ron::de::from_bytes(bytes).map_err(DeserializeMetaError::DeserializeMinimal)?;
+        let (steps, owner) = match minimal.asset {
+            AssetActionMinimal::Load { .. } => {
+                (L::settings_migrations().steps, SettingsOwner::Loader)
+            }
+            AssetActionMinimal::Process { .. } => {
+                (P::settings_migrations().steps, SettingsOwner::Processor)
+            }
+            AssetActionMinimal::Ignore => return Ok(ron::de::from_bytes(bytes)?),
+        };
+        let version = minimal.settings_version as usize;
+        if version > steps.len() {
+            return Err(DeserializeMetaError::UnsupportedSettingsVersion {
+                version: minimal.settings_version,
+                supported: steps.len() as u32,
+            });
+        }
+        if version == steps.len() {
+            return Ok(ron::de::from_bytes(bytes)?);
+        }
+        let mut settings = (steps[version].read)(bytes, owner)?;
+        for step in &steps[version..] {
+            settings = (step.migrate)(settings);
+        }
+        let meta: MigratingMeta<IgnoredAny, IgnoredAny> = ron::de::from_bytes(bytes)?;
+        let asset = match meta.asset {
+            AssetAction::Load { loader, .. } => AssetAction::Load {
+                loader,
+                settings: *settings
+                    .downcast()
+                    .expect("the last migration returns the latest settings"),
+            },
+            AssetAction::Process { processor, .. } => AssetAction::Process {
+                processor,
+                settings: *settings
+                    .downcast()
+                    .expect("the last migration returns the latest settings"),
+            },
+            AssetAction::Ignore => AssetAction::Ignore,
+        };
+        Ok(Self {
+            meta_format_version: meta.meta_format_version,
+            settings_version: steps.len() as u32,
+            processed_info: meta.processed_info,
+            asset,
+        })
     }
 }
 
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+/// Migrations of the settings of an [`AssetLoader`] or a [`Process`] from their previous versions,
+/// returned by [`AssetLoader::settings_migrations`] and [`Process::settings_migrations`].
+///
+/// `S` is the settings type of the latest version. Migrations start from the settings type of
+/// version 0 with [`SettingsMigrations::new`], and each call to [`SettingsMigrations::then`] adds
+/// a version. The settings of a `.meta` file with an older `settings_version` go through the
+/// migrations of the following versions when it is deserialized, and the [`AssetProcessor`]
+/// rewrites the migrated `.meta` files of the asset sources in place.
+///
+/// ```
+/// # use bevy_asset::meta::SettingsMigrations;
+/// # use serde::{Deserialize, Serialize};
+/// #[derive(Serialize, Deserialize)]
+/// struct SettingsV0 {
+///     size: u32,
+/// }
+///
+/// #[derive(Default, Serialize, Deserialize)]
+/// struct Settings {
+///     width: u32,
+///     height: u32,
+/// }
+///
+/// // In `AssetLoader::settings_migrations`
+/// let migrations: SettingsMigrations<Settings> =
+///     SettingsMigrations::<SettingsV0>::new().then(|old| Settings {
+///         width: old.size,
+///         height: old.size,
+///     });
+/// assert_eq!(migrations.version(), 1);
+/// ```
+///
+/// [`AssetProcessor`]: crate::processor::AssetProcessor
+pub struct SettingsMigrations<S> {
+    steps: Vec<MigrationStep>,
+    marker: PhantomData<fn() -> S>,
+}
+
+/// Migrates settings from a version to the next one.
+struct MigrationStep {
+    /// Reads the settings of the version migrated from, out of serialized meta.
+    read: Box<
+        dyn Fn(&[u8], SettingsOwner) -> Result<Box<dyn Any>, DeserializeMetaError> + Send + Sync,
+    >,
+    /// Migrates settings of the version migrated from to the next version.
+    migrate: Box<dyn Fn(Box<dyn Any>) -> Box<dyn Any> + Send + Sync>,
+}
+
+/// Where the settings to migrate are in the meta.
+#[derive(Clone, Copy)]
+enum SettingsOwner {
+    /// The settings of an [`AssetLoader`].
+    Loader,
+    /// The settings of a [`Process`].
+    Processor,
+    /// The [`LoadAndSaveSettings::loader_settings`] of a [`LoadAndSave`] processor.
+    ///
+    /// [`LoadAndSave`]: crate::processor::LoadAndSave
+    LoadAndSaveLoader,
+}
+
+impl<S: Serialize + DeserializeOwned + 'static> SettingsMigrations<S> {
+    /// Creates migrations without any version after `S`, the settings type of version 0.
+    pub fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Adds a version, with settings of type `T` migrated from the settings of the previous
+    /// version.
+    pub fn then<T: Serialize + DeserializeOwned + 'static>(
+        mut self,
+        migrate: impl Fn(S) -> T + Send + Sync + 'static,
+    ) -> SettingsMigrations<T> {
+        self.steps.push(MigrationStep {
+            read: Box::new(|bytes, owner| {
+                let settings: S = match owner {
+                    SettingsOwner::Loader => {
+                        let meta: MigratingMeta<S, IgnoredAny> = ron::de::from_bytes(bytes)?;
+                        meta.asset.into_loader_settings()
+                    }
+                    SettingsOwner::Processor => {
+                        let meta: MigratingMeta<IgnoredAny, S> = ron::de::from_bytes(bytes)?;
+                        meta.asset.into_process_settings()
+                    }
+                    SettingsOwner::LoadAndSaveLoader => {
+                        let meta: MigratingMeta<IgnoredAny, LoaderSettingsOnly<S>> =
+                            ron::de::from_bytes(bytes)?;
+                        meta.asset.into_process_settings().loader_settings
+                    }
+                };
+                Ok(Box::new(settings))
+            }),
+            migrate: Box::new(move |settings| {
+                let settings = settings
+                    .downcast::<S>()
+                    .expect("migrations are applied in order");
+                Box::new(migrate(*settings))
+            }),
+        });
+        SettingsMigrations {
+            steps: self.steps,
+            marker: PhantomData,
+        }
+    }
+
+    /// The version of the settings of type `S`.
+    pub fn version(&self) -> u32 {
+        self.steps.len() as u32
+    }
+
+    /// Lifts the migrations of the settings of an [`AssetLoader`] to the [`LoadAndSaveSettings`]
+    /// of a [`LoadAndSave`] processor using this loader, keeping their saver settings as they are.
+    ///
+    /// [`LoadAndSave`]: crate::processor::LoadAndSave
+    pub(crate) fn into_load_and_save<SaverSettings>(
+        self,
+    ) -> SettingsMigrations<LoadAndSaveSettings<S, SaverSettings>>
+    where
+        SaverSettings: DeserializeOwned + 'static,
+    {
+        /// Settings in the middle of their migration, with the loader settings of a past version.
+        struct Migrating<SaverSettings> {
+            loader_settings: Box<dyn Any>,
+            saver_settings: SaverSettings,
+        }
+
+        let last = self.steps.len().saturating_sub(1);
+        let steps = self
+            .steps
+            .into_iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let MigrationStep { read, migrate } = step;
+                MigrationStep {
+                    read: Box::new(move |bytes, _| {
+                        let meta: MigratingMeta<IgnoredAny, SaverSettingsOnly<SaverSettings>> =
+                            ron::de::from_bytes(bytes)?;
+                        Ok(Box::new(Migrating {
+                            loader_settings: read(bytes, SettingsOwner::LoadAndSaveLoader)?,
+                            saver_settings: meta.asset.into_process_settings().saver_settings,
+                        }))
+                    }),
+                    migrate: Box::new(move |settings| {
+                        let settings = settings
+                            .downcast::<Migrating<SaverSettings>>()
+                            .expect("migrations are applied in order");
+                        let loader_settings = migrate(settings.loader_settings);
+                        if index < last {
+                            return Box::new(Migrating {
+                                loader_settings,
+                                saver_settings: settings.saver_settings,
+                            });
+                        }
+                        Box::new(LoadAndSaveSettings {
+                            loader_settings: *loader_settings
+                                .downcast::<S>()
+                                .expect("the last migration returns the latest settings"),
+                            saver_settings: settings.saver_settings,
+                        })
+                    }),
+                }
+            })
+            .collect();
+        SettingsMigrations {
+            steps,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<S: Serialize + DeserializeOwned + 'static> Default for SettingsMigrations<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The serialized layout of [`AssetMeta`], for settings types that don't belong to an
+/// [`AssetLoader`] or a [`Process`] anymore.
+#[derive(Deserialize)]
+struct MigratingMeta<LoaderSettings, ProcessSettings> {
+    meta_format_version: String,
+    processed_info: Option<ProcessedInfo>,
+    asset: AssetAction<LoaderSettings, ProcessSettings>,
+}
+
+/// The loader settings of serialized [`LoadAndSaveSettings`], ignoring the saver settings.
+#[derive(Deserialize)]
+struct LoaderSettingsOnly<LoaderSettings> {
+    loader_settings: LoaderSettings,
+}
+
+/// The saver settings of serialized [`LoadAndSaveSettings`], ignoring the loader settings.
+#[derive(Deserialize)]
+struct SaverSettingsOnly<SaverSettings> {
+    saver_settings: SaverSettings,
+}
+
+impl<LoaderSettings, ProcessSettings> AssetAction<LoaderSettings, ProcessSettings> {
+    /// The settings of a [`Load`](AssetAction::Load) action, which is the only one with settings
+    /// of the loader being migrated.
+    fn into_loader_settings(self) -> LoaderSettings {
+        match self {
+            AssetAction::Load { settings, .. } => settings,
+            _ => unreachable!("loader settings are only migrated for load actions"),
+        }
+    }
+
+    /// The settings of a [`Process`](AssetAction::Process) action, which is the only one with
+    /// settings of the processor being migrated.
+    fn into_process_settings(self) -> ProcessSettings {
+        match self {
+            AssetAction::Process { settings, .. } => settings,
+            _ => unreachable!("processor settings are only migrated for process actions"),
+        }
+    }
+}
+
+fn serialize_ron(value: &impl Serialize) -> Vec<u8> {
+    ron::ser::to_string_pretty(value, PrettyConfig::default())
+        .expect("type is convertible to ron")
+        .into_bytes()
+}
+
 /// Configures how an asset source file should be handled by the asset system.
 #[derive(Serialize, Deserialize)]
 pub enum AssetAction<LoaderSettings, ProcessSettings> {
@@ -93,6 +389,8 @@ pub struct ProcessDependencyInfo {
 // using a type registry.
 #[derive(Serialize, Deserialize)]
 pub struct AssetMetaMinimal {
+    #[serde(default)]
+    pub settings_version: u32,
     pub asset: AssetActionMinimal,
 }
 
@@ -125,13 +423,13 @@ pub trait AssetMetaDyn: Downcast + Send + Sync {
     fn processed_info(&self) -> &Option<ProcessedInfo>;
     /// Returns a mutable reference to the [`ProcessedInfo`] if it exists.
     fn processed_info_mut(&mut self) -> &mut Option<ProcessedInfo>;
+    /// Returns the version of the settings, after any migration.
+    fn settings_version(&self) -> u32;
 }
 
 impl<L: AssetLoader, P: Process> AssetMetaDyn for AssetMeta<L, P> {
     fn serialize(&self) -> Vec<u8> {
-        ron::ser::to_string_pretty(&self, PrettyConfig::default())
-            .expect("type is convertible to ron")
-            .into_bytes()
+        serialize_ron(&self)
     }
     fn loader_settings(&self) -> Option<&dyn Settings> {
         if let AssetAction::Load { settings, .. } = &self.asset {
@@ -153,6 +451,9 @@ impl<L: AssetLoader, P: Process> AssetMetaDyn for AssetMeta<L, P> {
     fn processed_info_mut(&mut self) -> &mut Option<ProcessedInfo> {
         &mut self.processed_info
     }
+    fn settings_version(&self) -> u32 {
+        self.settings_version
+    }
 }
 
 impl_downcast!(AssetMetaDyn);
@@ -247,3 +548,120 @@ pub(crate) fn get_full_asset_hash(
     }
     *hasher.finalize().as_bytes()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{io::Reader, LoadContext};
+    use bevy_utils::BoxedFuture;
+
+    #[derive(Serialize, Deserialize)]
+    struct SettingsV0 {
+        size: u32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct SettingsV1 {
+        width: u32,
+        height: u32,
+    }
+
+    #[derive(Default, Serialize, Deserialize, Debug, PartialEq)]
+    struct TestSettings {
+        width: u32,
+        height: u32,
+        mipmaps: bool,
+    }
+
+    struct TestLoader;
+
+    impl AssetLoader for TestLoader {
+        type Asset = ();
+        type Settings = TestSettings;
+        type Error = std::io::Error;
+
+        fn load<'a>(
+            &'a self,
+            _reader: &'a mut Reader,
+            _settings: &'a Self::Settings,
+            _load_context: &'a mut LoadContext,
+        ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+            unreachable!()
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["test"]
+        }
+
+        fn settings_migrations() -> SettingsMigrations<Self::Settings> {
+            SettingsMigrations::<SettingsV0>::new()
+                .then(|old| SettingsV1 {
+                    width: old.size,
+                    height: old.size,
+                })
+                .then(|old| TestSettings {
+                    width: old.width,
+                    height: old.height,
+                    mipmaps: true,
+                })
+        }
+    }
+
+    fn settings(meta: &AssetMeta<TestLoader, ()>) -> &TestSettings {
+        meta.loader_settings()
+            .and_then(|settings| settings.downcast_ref())
+            .unwrap()
+    }
+
+    #[test]
+    fn migrate_settings() {
+        let meta = AssetMeta::<TestLoader, ()>::deserialize(
+            br#"(meta_format_version: "1.0", asset: Load(loader: "test", settings: (size: 4)))"#,
+        )
+        .unwrap();
+        let expected = TestSettings {
+            width: 4,
+            height: 4,
+            mipmaps: true,
+        };
+        assert_eq!(meta.settings_version, 2);
+        assert_eq!(settings(&meta), &expected);
+
+        // Migrated meta is saved with the latest version, and isn't migrated again
+        let meta =
+            AssetMeta::<TestLoader, ()>::deserialize(&AssetMetaDyn::serialize(&meta)).unwrap();
+        assert_eq!(meta.settings_version, 2);
+        assert_eq!(settings(&meta), &expected);
+
+        let meta = AssetMeta::<TestLoader, ()>::deserialize(
+            br#"(
+                meta_format_version: "1.0",
+                settings_version: 1,
+                asset: Load(loader: "test", settings: (width: 2, height: 3)),
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(settings(&meta).height, 3);
+
+        let result = AssetMeta::<TestLoader, ()>::deserialize(
+            br#"(
+                meta_format_version: "1.0",
+                settings_version: 3,
+                asset: Load(loader: "test", settings: (width: 2, height: 3, mipmaps: false)),
+            )"#,
+        );
+        assert!(matches!(
+            result,
+            Err(DeserializeMetaError::UnsupportedSettingsVersion {
+                version: 3,
+                supported: 2
+            })
+        ));
+
+        let meta = AssetMeta::<TestLoader, ()>::new(AssetAction::Load {
+            loader: "test".to_string(),
+            settings: TestSettings::default(),
+        });
+        assert_eq!(meta.settings_version, 2);
+    }
+}
@@ -0,0 +1,135 @@
+use parking_lot::Mutex;
+
+/// Limits the number of assets the [`AssetProcessor`](crate::processor::AssetProcessor) processes
+/// at the same time.
+///
+/// Processing an asset can wait on the processing of its dependencies, so assets lend their slot
+/// while they wait: they don't count towards the limit until the wait is over. Otherwise, assets
+/// waiting on dependencies that can't start could take every slot.
+pub(crate) struct ProcessingLimiter {
+    state: Mutex<LimiterState>,
+    released_sender: async_broadcast::Sender<()>,
+    /// Keeps the channel open without buffering messages.
+    _released_receiver: async_broadcast::InactiveReceiver<()>,
+}
+
+#[derive(Default)]
+struct LimiterState {
+    max: Option<usize>,
+    running: usize,
+    waiting: usize,
+}
+
+impl LimiterState {
+    fn has_room(&self) -> bool {
+        match self.max {
+            Some(max) => self.running < max + self.waiting,
+            None => true,
+        }
+    }
+}
+
+impl Default for ProcessingLimiter {
+    fn default() -> Self {
+        let (mut released_sender, released_receiver) = async_broadcast::broadcast(1);
+        // Waiters only need to know that a slot might be free, not how many times
+        released_sender.set_overflow(true);
+        Self {
+            state: Default::default(),
+            released_sender,
+            _released_receiver: released_receiver.deactivate(),
+        }
+    }
+}
+
+impl ProcessingLimiter {
+    pub(crate) fn max(&self) -> Option<usize> {
+        self.state.lock().max
+    }
+
+    pub(crate) fn set_max(&self, max: Option<usize>) {
+        self.state.lock().max = max;
+        self.notify();
+    }
+
+    /// Waits until there is room to process an asset, and takes a slot until the returned
+    /// [`ProcessingSlot`] is dropped.
+    pub(crate) async fn acquire(&self) -> ProcessingSlot<'_> {
+        loop {
+            let mut receiver = {
+                let mut state = self.state.lock();
+                if state.has_room() {
+                    state.running += 1;
+                    return ProcessingSlot { limiter: self };
+                }
+                // This receiver must be created prior to releasing the lock, so it can't miss a
+                // released slot. It only receives the slots released from now on.
+                self.released_sender.new_receiver()
+            };
+            let _ = receiver.recv().await;
+        }
+    }
+
+    /// Lends a slot while waiting on the processing of another asset, until the returned
+    /// [`LentSlot`] is dropped.
+    pub(crate) fn lend(&self) -> LentSlot<'_> {
+        self.state.lock().waiting += 1;
+        self.notify();
+        LentSlot { limiter: self }
+    }
+
+    fn notify(&self) {
+        let _ = self.released_sender.try_broadcast(());
+    }
+}
+
+/// A slot taken with [`ProcessingLimiter::acquire`].
+pub(crate) struct ProcessingSlot<'a> {
+    limiter: &'a ProcessingLimiter,
+}
+
+impl Drop for ProcessingSlot<'_> {
+    fn drop(&mut self) {
+        self.limiter.state.lock().running -= 1;
+        self.limiter.notify();
+    }
+}
+
+/// A slot lent with [`ProcessingLimiter::lend`].
+pub(crate) struct LentSlot<'a> {
+    limiter: &'a ProcessingLimiter,
+}
+
+impl Drop for LentSlot<'_> {
+    fn drop(&mut self) {
+        self.limiter.state.lock().waiting -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::future::{block_on, poll_once};
+
+    #[test]
+    fn waiting_assets_lend_their_slot() {
+        let limiter = ProcessingLimiter::default();
+        limiter.set_max(Some(1));
+
+        let first = block_on(limiter.acquire());
+        let mut second = Box::pin(limiter.acquire());
+        assert!(block_on(poll_once(&mut second)).is_none());
+
+        // The first asset waits on a dependency, which can start
+        let lent = limiter.lend();
+        let second = block_on(poll_once(&mut second)).unwrap();
+        drop(lent);
+
+        let mut third = Box::pin(limiter.acquire());
+        assert!(block_on(poll_once(&mut third)).is_none());
+        drop(first);
+        assert!(block_on(poll_once(&mut third)).is_none());
+        drop(second);
+        assert!(block_on(poll_once(&mut third)).is_some());
+    }
+}
@@ -1,3 +1,4 @@
+mod limiter;
 mod log;
 mod process;
 
@@ -6,8 +7,10 @@ pub use process::*;
 
 use crate::{
     io::{
+        pack::{AssetPackError, AssetPackWriter, PackCompression},
         AssetReader, AssetReaderError, AssetSource, AssetSourceBuilders, AssetSourceEvent,
         AssetSourceId, AssetSources, AssetWriter, AssetWriterError, MissingAssetSourceError,
+        MissingProcessedAssetReaderError,
     },
     meta::{
         get_asset_hash, get_full_asset_hash, AssetAction, AssetActionMinimal, AssetHash, AssetMeta,
@@ -17,12 +20,14 @@ use crate::{
     MissingAssetLoaderForExtensionError,
 };
 use bevy_ecs::prelude::*;
-use bevy_log::{debug, error, trace, warn};
+use bevy_log::{debug, error, info, trace, warn};
 use bevy_tasks::IoTaskPool;
-use bevy_utils::{BoxedFuture, HashMap, HashSet};
+use bevy_utils::{BoxedFuture, Duration, HashMap, HashSet, Instant};
 use futures_io::ErrorKind;
 use futures_lite::{AsyncReadExt, AsyncWriteExt, StreamExt};
-use parking_lot::RwLock;
+use limiter::ProcessingLimiter;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
 use std::{
     collections::VecDeque,
     path::{Path, PathBuf},
@@ -54,11 +59,16 @@ pub struct AssetProcessor {
 pub struct AssetProcessorData {
     pub(crate) asset_infos: async_lock::RwLock<ProcessorAssetInfos>,
     log: async_lock::RwLock<Option<ProcessorTransactionLog>>,
+    log_path: RwLock<PathBuf>,
     processors: RwLock<HashMap<&'static str, Arc<dyn ErasedProcessor>>>,
     /// Default processors for file extensions
     default_processors: RwLock<HashMap<String, &'static str>>,
     state: async_lock::RwLock<ProcessorState>,
     sources: AssetSources,
+    pack_output: RwLock<Option<AssetPackOutput>>,
+    limiter: ProcessingLimiter,
+    /// The reports of the assets processed since the processor last started processing.
+    reports: Mutex<HashMap<AssetPath<'static>, ProcessedAssetReport>>,
     initialized_sender: async_broadcast::Sender<()>,
     initialized_receiver: async_broadcast::Receiver<()>,
     finished_sender: async_broadcast::Sender<()>,
@@ -71,7 +81,7 @@ impl AssetProcessor {
         let data = Arc::new(AssetProcessorData::new(source.build_sources(true, false)));
         // The asset processor uses its own asset server with its own id space
         let mut sources = source.build_sources(false, false);
-        sources.gate_on_processor(data.clone());
+        sources.gate_on_processor_while_processing(data.clone());
         let server = AssetServer::new(sources, AssetServerMode::Processed, false);
         Self { server, data }
     }
@@ -89,6 +99,7 @@ impl AssetProcessor {
         if last_state != ProcessorState::Finished && state == ProcessorState::Finished {
             self.data.finished_sender.broadcast(()).await.unwrap();
         } else if last_state != ProcessorState::Processing && state == ProcessorState::Processing {
+            self.data.reports.lock().clear();
             self.data.initialized_sender.broadcast(()).await.unwrap();
         }
     }
@@ -177,6 +188,86 @@ impl AssetProcessor {
         debug!("Processing finished in {:?}", end_time - start_time);
     }
 
+    /// Processes all assets once, like [`AssetProcessor::process_assets`], and returns the
+    /// [`ProcessingReport`] of the run. Unlike [`AssetProcessor::start`], this doesn't listen for
+    /// changes afterwards, which makes it suitable for processing assets ahead of time, such as in
+    /// CI. Assets that haven't changed since the last run aren't processed again.
+    ///
+    /// ```no_run
+    /// # use bevy_app::App;
+    /// # use bevy_asset::{processor::AssetProcessor, AssetMode, AssetPlugin};
+    /// # use bevy_core::TaskPoolPlugin;
+    /// let mut app = App::new();
+    /// app.add_plugins((
+    ///     TaskPoolPlugin::default(),
+    ///     AssetPlugin {
+    ///         mode: AssetMode::Processed,
+    ///         ..Default::default()
+    ///     },
+    /// ));
+    /// // Register the asset loaders and processors here
+    /// let processor = app.world.resource::<AssetProcessor>().clone();
+    /// processor.set_max_concurrent_processing(Some(4));
+    /// let report = processor.process_once();
+    /// std::fs::write("processing_report.ron", report.to_ron()).unwrap();
+    /// if report.has_failures() {
+    ///     std::process::exit(1);
+    /// }
+    /// ```
+    #[cfg(all(not(target_arch = "wasm32"), feature = "multi-threaded"))]
+    pub fn process_once(&self) -> ProcessingReport {
+        self.process_assets();
+        bevy_tasks::block_on(self.processing_report())
+    }
+
+    /// Returns the report of the assets processed since the processor last started processing:
+    /// all the assets for the initial run, then the assets affected by each batch of changes.
+    pub async fn processing_report(&self) -> ProcessingReport {
+        let mut assets: Vec<_> = self.data.reports.lock().values().cloned().collect();
+        assets.sort_by_cached_key(|asset| asset.path.to_string());
+        for asset in &mut assets {
+            if asset.status == ProcessStatus::Processed {
+                asset.output_hash = self.get_processed_asset_hash(&asset.path).await;
+            }
+        }
+        ProcessingReport { assets }
+    }
+
+    /// Hashes the bytes of the processed version of the asset at `path`.
+    async fn get_processed_asset_hash(&self, path: &AssetPath<'static>) -> Option<AssetHash> {
+        let reader = self
+            .get_source(path.source())
+            .ok()?
+            .processed_reader()
+            .ok()?;
+        let _transaction_lock = {
+            let infos = self.data.asset_infos.read().await;
+            infos.get(path)?.file_transaction_lock.read_arc().await
+        };
+        let mut bytes = Vec::new();
+        reader
+            .read(path.path())
+            .await
+            .ok()?
+            .read_to_end(&mut bytes)
+            .await
+            .ok()?;
+        Some(*blake3::hash(&bytes).as_bytes())
+    }
+
+    /// Limits the number of assets processed at the same time, or removes the limit when `None`.
+    ///
+    /// Assets that wait on the processing of their dependencies don't count towards the limit
+    /// while they wait, so that their dependencies can be processed.
+    pub fn set_max_concurrent_processing(&self, max: Option<usize>) {
+        self.data.limiter.set_max(max);
+    }
+
+    /// The maximum number of assets processed at the same time, if limited.
+    pub fn max_concurrent_processing(&self) -> Option<usize> {
+        self.data.limiter.max()
+    }
+
     /// Listens for changes to assets in the source [`AssetSource`] and update state accordingly.
     // PERF: parallelize change event processing
     pub async fn listen_for_source_change_events(&self) {
@@ -406,9 +497,90 @@ impl AssetProcessor {
         self.try_reprocessing_queued().await;
         // clean up metadata in asset server
         self.server.data.infos.write().consume_handle_drop_events();
+        let pack_output = self.data.pack_output.read().clone();
+        if let Some(output) = pack_output {
+            if let Err(err) = self.write_pack_output(&output).await {
+                error!("Failed to write asset pack {:?}: {err}", output.path);
+            }
+        }
         self.set_state(ProcessorState::Finished).await;
     }
 
+    /// Sets the path of the [`ProcessorTransactionLog`] file, which is `imported_assets/log` in
+    /// the base path of the file assets by default. It's read and recreated when the processor
+    /// starts, so this must be set before [`AssetProcessor::start`].
+    pub fn set_log_path(&self, path: impl Into<PathBuf>) {
+        *self.data.log_path.write() = path.into();
+    }
+
+    /// Sets the asset pack written each time the processor finishes processing assets, or
+    /// disables it when `None`. See [`AssetProcessor::write_pack`].
+    pub fn set_pack_output(&self, output: Option<AssetPackOutput>) {
+        *self.data.pack_output.write() = output;
+    }
+
+    /// Writes all the processed assets of the `source` and their metadata to `writer`, then
+    /// finishes the pack. Paths are written in sorted order, so the same processed assets always
+    /// produce the same pack.
+    pub async fn write_pack<'a, W: std::io::Write>(
+        &self,
+        source: impl Into<AssetSourceId<'a>>,
+        mut writer: AssetPackWriter<W>,
+    ) -> Result<W, WritePackError> {
+        fn get_asset_paths<'a>(
+            reader: &'a dyn AssetReader,
+            path: PathBuf,
+            paths: &'a mut Vec<PathBuf>,
+        ) -> BoxedFuture<'a, Result<(), AssetReaderError>> {
+            Box::pin(async move {
+                if reader.is_directory(&path).await? {
+                    let mut path_stream = reader.read_directory(&path).await?;
+                    while let Some(child_path) = path_stream.next().await {
+                        get_asset_paths(reader, child_path, paths).await?;
+                    }
+                } else {
+                    paths.push(path);
+                }
+                Ok(())
+            })
+        }
+
+        let reader = self.get_source(source)?.processed_reader()?;
+        let mut paths = Vec::new();
+        get_asset_paths(reader, PathBuf::from(""), &mut paths).await?;
+        paths.sort();
+
+        for path in paths {
+            let mut bytes = Vec::new();
+            reader
+                .read(&path)
+                .await?
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(AssetReaderError::from)?;
+            writer.add_asset(&path, &bytes)?;
+            match reader.read_meta_bytes(&path).await {
+                Ok(meta_bytes) => writer.add_meta(&path, &meta_bytes)?,
+                Err(AssetReaderError::NotFound(_)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(writer.finish()?)
+    }
+
+    async fn write_pack_output(&self, output: &AssetPackOutput) -> Result<(), WritePackError> {
+        if let Some(parent) = output.path.parent() {
+            async_fs::create_dir_all(parent)
+                .await
+                .map_err(AssetPackError::from)?;
+        }
+        let file = std::fs::File::create(&output.path).map_err(AssetPackError::from)?;
+        let writer = AssetPackWriter::new(std::io::BufWriter::new(file), output.compression)?;
+        self.write_pack(output.source.clone(), writer).await?;
+        debug!("Wrote asset pack {:?}", output.path);
+        Ok(())
+    }
+
     #[allow(unused)]
     #[cfg(all(not(target_arch = "wasm32"), feature = "multi-threaded"))]
     fn process_assets_internal<'scope>(
@@ -650,9 +822,39 @@ impl AssetProcessor {
     /// [`ProcessorGatedReader`]: crate::io::processor_gated::ProcessorGatedReader
     async fn process_asset(&self, source: &AssetSource, path: PathBuf) {
         let asset_path = AssetPath::from(path).with_source(source.id());
+        let slot = self.data.limiter.acquire().await;
+        let start_time = Instant::now();
         let result = self.process_asset_internal(source, &asset_path).await;
+        let duration = start_time.elapsed();
+        drop(slot);
+
+        let skipped = matches!(result, Ok(ProcessResult::SkippedNotChanged));
+        let error = result.as_ref().err().map(|err| err.to_string());
         let mut infos = self.data.asset_infos.write().await;
-        infos.finish_processing(asset_path, result).await;
+        infos.finish_processing(asset_path.clone(), result).await;
+        // Assets that can't be processed, such as assets without a loader, don't get a status
+        let Some(info) = infos.get(&asset_path) else {
+            return;
+        };
+        let Some(status) = info.status else {
+            return;
+        };
+        let input_hash = match status {
+            ProcessStatus::Processed => info.processed_info.as_ref().map(|info| info.hash),
+            ProcessStatus::Failed | ProcessStatus::NonExistent => None,
+        };
+        self.data.reports.lock().insert(
+            asset_path.clone(),
+            ProcessedAssetReport {
+                path: asset_path,
+                status,
+                skipped,
+                duration,
+                input_hash,
+                output_hash: None,
+                error,
+            },
+        );
     }
 
     async fn process_asset_internal(
@@ -703,6 +905,28 @@ impl AssetProcessor {
                         (meta, None)
                     }
                 };
+                // Upgrade the meta file in place when its settings were migrated from an older version
+                let meta_bytes = if meta.settings_version() != minimal.settings_version {
+                    info!(
+                        "Migrated the settings of {asset_path} from version {} to version {}",
+                        minimal.settings_version,
+                        meta.settings_version()
+                    );
+                    let meta_bytes = meta.serialize();
+                    // Sources without a writer still process with the migrated settings
+                    match source.writer() {
+                        Ok(writer) => writer
+                            .write_meta_bytes(path, &meta_bytes)
+                            .await
+                            .map_err(writer_err)?,
+                        Err(_) => warn!(
+                            "Could not upgrade the meta file of {asset_path} in place, as its asset source has no writer"
+                        ),
+                    }
+                    meta_bytes
+                } else {
+                    meta_bytes
+                };
                 (meta, meta_bytes, processor)
             }
             Err(AssetReaderError::NotFound(_path)) => {
@@ -846,7 +1070,8 @@ impl AssetProcessor {
     }
 
     async fn validate_transaction_log_and_recover(&self) {
-        if let Err(err) = ProcessorTransactionLog::validate().await {
+        let log_path = self.data.log_path.read().clone();
+        if let Err(err) = ProcessorTransactionLog::validate(&log_path).await {
             let state_is_valid = match err {
                 ValidateLogError::ReadLogError(err) => {
                     error!("Failed to read processor log file. Processed assets cannot be validated so they must be re-generated {err}");
@@ -924,7 +1149,7 @@ impl AssetProcessor {
             }
         }
         let mut log = self.data.log.write().await;
-        *log = match ProcessorTransactionLog::new().await {
+        *log = match ProcessorTransactionLog::new(&log_path).await {
             Ok(log) => Some(log),
             Err(err) => panic!("Failed to initialize asset processor log. This cannot be recovered. Try restarting. If that doesn't work, try deleting processed asset folder. {}", err),
         };
@@ -948,14 +1173,35 @@ impl AssetProcessorData {
             initialized_receiver,
             state: async_lock::RwLock::new(ProcessorState::Initializing),
             log: Default::default(),
+            log_path: RwLock::new(ProcessorTransactionLog::default_path()),
             processors: Default::default(),
             asset_infos: Default::default(),
             default_processors: Default::default(),
+            pack_output: Default::default(),
+            limiter: Default::default(),
+            reports: Default::default(),
         }
     }
 
     /// Returns a future that will not finish until the path has been processed.
     pub async fn wait_until_processed(&self, path: AssetPath<'static>) -> ProcessStatus {
+        self.wait_until_processed_internal(path, false).await
+    }
+
+    /// Waits until the path has been processed, while processing another asset: the processing
+    /// asset may be waiting on it, so it lends its processing slot.
+    pub(crate) async fn wait_until_processed_while_processing(
+        &self,
+        path: AssetPath<'static>,
+    ) -> ProcessStatus {
+        self.wait_until_processed_internal(path, true).await
+    }
+
+    async fn wait_until_processed_internal(
+        &self,
+        path: AssetPath<'static>,
+        lend_slot: bool,
+    ) -> ProcessStatus {
         self.wait_until_initialized().await;
         let mut receiver = {
             let infos = self.asset_infos.write().await;
@@ -969,6 +1215,7 @@ impl AssetProcessorData {
                 None => return ProcessStatus::NonExistent,
             }
         };
+        let _lent_slot = lend_slot.then(|| self.limiter.lend());
         receiver.recv().await.unwrap()
     }
 
@@ -1017,13 +1264,64 @@ pub enum ProcessResult {
 }
 
 /// The final status of processing an asset
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
 pub enum ProcessStatus {
     Processed,
     Failed,
     NonExistent,
 }
 
+/// A report of the assets processed by the [`AssetProcessor`], returned by
+/// [`AssetProcessor::process_once`] and [`AssetProcessor::processing_report`].
+///
+/// It can be serialized, to be consumed by build pipelines.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessingReport {
+    /// The processed assets, sorted by path.
+    pub assets: Vec<ProcessedAssetReport>,
+}
+
+impl ProcessingReport {
+    /// Returns the assets that failed to process.
+    pub fn failures(&self) -> impl Iterator<Item = &ProcessedAssetReport> {
+        self.assets
+            .iter()
+            .filter(|asset| asset.status == ProcessStatus::Failed)
+    }
+
+    /// Returns `true` if any asset failed to process.
+    pub fn has_failures(&self) -> bool {
+        self.failures().next().is_some()
+    }
+
+    /// Serializes the report to RON.
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("type is convertible to ron")
+    }
+}
+
+/// The processing of an asset, listed in a [`ProcessingReport`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessedAssetReport {
+    /// The path of the asset source.
+    pub path: AssetPath<'static>,
+    /// The final status of processing the asset.
+    pub status: ProcessStatus,
+    /// `true` if the asset wasn't processed again, because neither the asset nor its process
+    /// dependencies changed since it was last processed.
+    pub skipped: bool,
+    /// The time spent processing the asset, including the time spent waiting on its process
+    /// dependencies.
+    pub duration: Duration,
+    /// The hash of the asset source and its meta, if the asset was processed.
+    pub input_hash: Option<AssetHash>,
+    /// The hash of the processed asset, if it was processed.
+    pub output_hash: Option<AssetHash>,
+    /// The error the processing failed with, if it failed.
+    pub error: Option<String>,
+}
+
 // NOTE: if you add new fields to this struct, make sure they are propagated (when relevant) in ProcessorAssetInfos::rename
 #[derive(Debug)]
 pub(crate) struct ProcessorAssetInfo {
@@ -1302,6 +1600,49 @@ pub enum ProcessorState {
     Finished,
 }
 
+/// An asset pack written by the [`AssetProcessor`] each time it finishes processing assets.
+/// See [`AssetProcessor::set_pack_output`].
+#[derive(Clone, Debug)]
+pub struct AssetPackOutput {
+    /// The source whose processed assets are packed.
+    pub source: AssetSourceId<'static>,
+    /// The path of the pack file.
+    pub path: PathBuf,
+    /// How the entries of the pack are compressed.
+    pub compression: PackCompression,
+}
+
+impl AssetPackOutput {
+    /// Packs the processed assets of the default source to the file at `path`, without
+    /// compression.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            source: AssetSourceId::Default,
+            path: path.into(),
+            compression: PackCompression::None,
+        }
+    }
+
+    /// Sets the [`PackCompression`] of the entries of the pack.
+    pub fn with_compression(mut self, compression: PackCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+}
+
+/// An error that occurs when writing the processed assets to an asset pack.
+#[derive(Error, Debug)]
+pub enum WritePackError {
+    #[error(transparent)]
+    MissingAssetSource(#[from] MissingAssetSourceError),
+    #[error(transparent)]
+    MissingProcessedAssetReader(#[from] MissingProcessedAssetReaderError),
+    #[error(transparent)]
+    AssetReaderError(#[from] AssetReaderError),
+    #[error(transparent)]
+    AssetPackError(#[from] AssetPackError),
+}
+
 /// An error that occurs when initializing the [`AssetProcessor`].
 #[derive(Error, Debug)]
 pub enum InitializeError {
@@ -1312,3 +1653,391 @@ pub enum InitializeError {
     #[error("Failed to validate asset log: {0}")]
     ValidateLogError(ValidateLogError),
 }
+
+#[cfg(all(test, not(target_arch = "wasm32"), feature = "multi-threaded"))]
+mod tests {
+    use super::*;
+    use crate::{
+        self as bevy_asset,
+        io::{
+            file::{FileAssetReader, FileAssetWriter},
+            pack::AssetPackReader,
+            Reader, Writer,
+        },
+        meta::SettingsMigrations,
+        saver::{AssetSaver, SavedAsset},
+        Asset, AssetLoader, LoadContext,
+    };
+    use bevy_reflect::TypePath;
+    use bevy_tasks::{AsyncComputeTaskPool, ComputeTaskPool, TaskPool};
+
+    #[derive(Asset, TypePath)]
+    struct Text(String);
+
+    /// Loads text, failing on the text "fail".
+    struct TextLoader;
+
+    impl AssetLoader for TextLoader {
+        type Asset = Text;
+        type Settings = ();
+        type Error = std::io::Error;
+
+        fn load<'a>(
+            &'a self,
+            reader: &'a mut Reader,
+            _settings: &'a Self::Settings,
+            _load_context: &'a mut LoadContext,
+        ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+            Box::pin(async move {
+                let mut text = String::new();
+                reader.read_to_string(&mut text).await?;
+                if text == "fail" {
+                    return Err(std::io::Error::new(ErrorKind::InvalidData, "failing text"));
+                }
+                Ok(Text(text))
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["txt"]
+        }
+    }
+
+    /// Saves text in uppercase.
+    struct UppercaseSaver;
+
+    impl AssetSaver for UppercaseSaver {
+        type Asset = Text;
+        type Settings = ();
+        type OutputLoader = TextLoader;
+        type Error = std::io::Error;
+
+        fn save<'a>(
+            &'a self,
+            writer: &'a mut Writer,
+            asset: SavedAsset<'a, Self::Asset>,
+            _settings: &'a Self::Settings,
+        ) -> BoxedFuture<'a, Result<(), Self::Error>> {
+            Box::pin(async move { writer.write_all(asset.0.to_uppercase().as_bytes()).await })
+        }
+    }
+
+    type Uppercase = LoadAndSave<TextLoader, UppercaseSaver>;
+
+    #[derive(Serialize, Deserialize)]
+    struct AppendSettingsV0 {
+        text: String,
+    }
+
+    #[derive(Default, Serialize, Deserialize)]
+    struct AppendSettings {
+        suffix: String,
+        repeat: usize,
+    }
+
+    /// Appends a suffix to text. Its first settings had a single text.
+    struct Append;
+
+    impl Process for Append {
+        type Settings = AppendSettings;
+        type OutputLoader = TextLoader;
+
+        fn process<'a>(
+            &'a self,
+            context: &'a mut ProcessContext,
+            meta: AssetMeta<(), Self>,
+            writer: &'a mut Writer,
+        ) -> BoxedFuture<'a, Result<(), ProcessError>> {
+            Box::pin(async move {
+                let AssetAction::Process { settings, .. } = meta.asset else {
+                    return Err(ProcessError::WrongMetaType);
+                };
+                let mut bytes = context.asset_bytes().to_vec();
+                bytes.extend(settings.suffix.repeat(settings.repeat).as_bytes());
+                writer
+                    .write_all(&bytes)
+                    .await
+                    .map_err(|err| ProcessError::AssetSaveError(Box::new(err)))
+            })
+        }
+
+        fn settings_migrations() -> SettingsMigrations<Self::Settings> {
+            SettingsMigrations::<AppendSettingsV0>::new().then(|old| AppendSettings {
+                suffix: old.text,
+                repeat: 1,
+            })
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct PrefixSettingsV0 {
+        text: String,
+    }
+
+    #[derive(Default, Serialize, Deserialize)]
+    struct PrefixSettings {
+        prefix: String,
+        separator: String,
+    }
+
+    /// Loads text with a prefix. Its first settings had a single text.
+    struct PrefixLoader;
+
+    impl AssetLoader for PrefixLoader {
+        type Asset = Text;
+        type Settings = PrefixSettings;
+        type Error = std::io::Error;
+
+        fn load<'a>(
+            &'a self,
+            reader: &'a mut Reader,
+            settings: &'a Self::Settings,
+            _load_context: &'a mut LoadContext,
+        ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+            Box::pin(async move {
+                let mut text = String::new();
+                reader.read_to_string(&mut text).await?;
+                Ok(Text(format!(
+                    "{}{}{text}",
+                    settings.prefix, settings.separator
+                )))
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["prefix"]
+        }
+
+        fn settings_migrations() -> SettingsMigrations<Self::Settings> {
+            SettingsMigrations::<PrefixSettingsV0>::new().then(|old| PrefixSettings {
+                prefix: old.text,
+                separator: " ".to_string(),
+            })
+        }
+    }
+
+    struct TestProcessor {
+        processor: AssetProcessor,
+        assets: PathBuf,
+        processed: PathBuf,
+    }
+
+    /// Creates a processor for the `files` written in a fresh `name` directory, which processes
+    /// the text files in uppercase. The source has a writer if `source_writer` is set.
+    fn test_processor(name: &str, files: &[(&str, &str)], source_writer: bool) -> TestProcessor {
+        let root = std::env::temp_dir().join("bevy_asset_processor_tests");
+        IoTaskPool::get_or_init(TaskPool::default);
+        ComputeTaskPool::get_or_init(TaskPool::default);
+        AsyncComputeTaskPool::get_or_init(TaskPool::default);
+
+        let dir = root.join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        let assets = dir.join("assets");
+        let processed = dir.join("processed");
+        for (path, contents) in files {
+            let path = assets.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+
+        let mut source = {
+            let (assets, processed) = (assets.clone(), processed.clone());
+            let processed_writer = processed.clone();
+            AssetSource::build()
+                .with_reader(move || Box::new(FileAssetReader::new(&assets)))
+                .with_processed_reader(move || Box::new(FileAssetReader::new(&processed)))
+                .with_processed_writer(move || {
+                    Some(Box::new(FileAssetWriter::new(&processed_writer)))
+                })
+        };
+        if source_writer {
+            let assets = assets.clone();
+            source = source.with_writer(move || Some(Box::new(FileAssetWriter::new(&assets))));
+        }
+        let mut builders = AssetSourceBuilders::default();
+        builders.insert(AssetSourceId::Default, source);
+        let processor = AssetProcessor::new(&mut builders);
+        // Keeps the transaction log out of the crate
+        processor.set_log_path(dir.join("log"));
+        processor.server().register_loader(TextLoader);
+        processor.register_processor::<Uppercase>(UppercaseSaver.into());
+        processor.set_default_processor::<Uppercase>("txt");
+        TestProcessor {
+            processor,
+            assets,
+            processed,
+        }
+    }
+
+    /// Counts the processed, skipped and failed assets of the report.
+    fn counts(report: &ProcessingReport) -> (usize, usize, usize) {
+        let count = |status, skipped| {
+            report
+                .assets
+                .iter()
+                .filter(|asset| asset.status == status && asset.skipped == skipped)
+                .count()
+        };
+        (
+            count(ProcessStatus::Processed, false),
+            count(ProcessStatus::Processed, true),
+            count(ProcessStatus::Failed, false),
+        )
+    }
+
+    #[test]
+    fn process_once_reports_processed_skipped_and_failed_assets() {
+        let test = test_processor(
+            "report",
+            &[("a.txt", "hello"), ("b.txt", "world"), ("c.txt", "fail")],
+            true,
+        );
+
+        let report = test.processor.process_once();
+        assert_eq!(counts(&report), (2, 0, 1));
+        assert!(report.has_failures());
+        let failures: Vec<_> = report.failures().collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].path, AssetPath::from("c.txt"));
+        assert!(failures[0].error.is_some());
+        for asset in report
+            .assets
+            .iter()
+            .filter(|asset| asset.path != failures[0].path)
+        {
+            assert!(asset.input_hash.is_some());
+            assert!(asset.output_hash.is_some());
+            assert!(asset.error.is_none());
+        }
+        assert_eq!(
+            std::fs::read_to_string(test.processed.join("a.txt")).unwrap(),
+            "HELLO"
+        );
+        let deserialized: ProcessingReport = ron::de::from_str(&report.to_ron()).unwrap();
+        assert_eq!(counts(&deserialized), (2, 0, 1));
+
+        // Nothing changed, so the processed assets are skipped
+        let report = test.processor.process_once();
+        assert_eq!(counts(&report), (0, 2, 1));
+
+        std::fs::write(test.assets.join("a.txt"), "hi").unwrap();
+        let report = test.processor.process_once();
+        assert_eq!(counts(&report), (1, 1, 1));
+        assert_eq!(
+            std::fs::read_to_string(test.processed.join("a.txt")).unwrap(),
+            "HI"
+        );
+    }
+
+    #[test]
+    fn process_migrates_old_settings() {
+        let old_meta = format!(
+            r#"(meta_format_version: "1.0", asset: Process(processor: "{}", settings: (text: "!")))"#,
+            std::any::type_name::<Append>()
+        );
+        for source_writer in [true, false] {
+            let name = if source_writer {
+                "migrate"
+            } else {
+                "migrate_without_writer"
+            };
+            let test = test_processor(
+                name,
+                &[("a.txt", "hello"), ("a.txt.meta", &old_meta)],
+                source_writer,
+            );
+            test.processor.register_processor(Append);
+
+            let report = test.processor.process_once();
+            assert_eq!(counts(&report), (1, 0, 0));
+            assert_eq!(
+                std::fs::read_to_string(test.processed.join("a.txt")).unwrap(),
+                "hello!"
+            );
+            let meta_bytes = std::fs::read(test.assets.join("a.txt.meta")).unwrap();
+            if source_writer {
+                // The meta file was upgraded in place
+                let minimal: AssetMetaMinimal = ron::de::from_bytes(&meta_bytes).unwrap();
+                assert_eq!(minimal.settings_version, 1);
+                let meta = AssetMeta::<(), Append>::deserialize(&meta_bytes).unwrap();
+                let AssetAction::Process { settings, .. } = meta.asset else {
+                    panic!("the meta file should still process the asset");
+                };
+                assert_eq!((settings.suffix.as_str(), settings.repeat), ("!", 1));
+            } else {
+                assert_eq!(meta_bytes, old_meta.as_bytes());
+            }
+
+            // The migrated settings hash the same on the next run
+            let report = test.processor.process_once();
+            assert_eq!(counts(&report), (0, 1, 0));
+        }
+    }
+
+    #[test]
+    fn load_and_save_migrates_old_loader_settings() {
+        type PrefixedUppercase = LoadAndSave<PrefixLoader, UppercaseSaver>;
+        let old_meta = format!(
+            r#"(
+                meta_format_version: "1.0",
+                asset: Process(
+                    processor: "{}",
+                    settings: (loader_settings: (text: "hi"), saver_settings: ()),
+                ),
+            )"#,
+            std::any::type_name::<PrefixedUppercase>()
+        );
+        let test = test_processor(
+            "migrate_load_and_save",
+            &[("a.prefix", "hello"), ("a.prefix.meta", &old_meta)],
+            true,
+        );
+        test.processor.server().register_loader(PrefixLoader);
+        test.processor
+            .register_processor::<PrefixedUppercase>(UppercaseSaver.into());
+
+        let report = test.processor.process_once();
+        assert_eq!(counts(&report), (1, 0, 0));
+        assert_eq!(
+            std::fs::read_to_string(test.processed.join("a.prefix")).unwrap(),
+            "HI HELLO"
+        );
+        let meta_bytes = std::fs::read(test.assets.join("a.prefix.meta")).unwrap();
+        let meta = AssetMeta::<(), PrefixedUppercase>::deserialize(&meta_bytes).unwrap();
+        assert_eq!(meta.settings_version, 1);
+        let AssetAction::Process { settings, .. } = meta.asset else {
+            panic!("the meta file should still process the asset");
+        };
+        assert_eq!(settings.loader_settings.prefix, "hi");
+        assert_eq!(settings.loader_settings.separator, " ");
+    }
+
+    #[test]
+    fn process_writes_pack_output() {
+        let test = test_processor("pack", &[("a.txt", "hello"), ("dir/b.txt", "world")], true);
+        let pack_path = test.processed.with_file_name("assets.pack");
+        test.processor
+            .set_pack_output(Some(AssetPackOutput::new(&pack_path)));
+
+        let report = test.processor.process_once();
+        assert_eq!(counts(&report), (2, 0, 0));
+
+        let reader = AssetPackReader::open(&pack_path).unwrap();
+        let read = |path: &str| {
+            bevy_tasks::block_on(async {
+                let mut bytes = Vec::new();
+                let mut asset = reader.read(Path::new(path)).await.unwrap();
+                asset.read_to_end(&mut bytes).await.unwrap();
+                bytes
+            })
+        };
+        assert_eq!(read("a.txt"), b"HELLO");
+        assert_eq!(read("dir/b.txt"), b"WORLD");
+        // The processed meta files are packed with their assets
+        let meta = bevy_tasks::block_on(reader.read_meta_bytes(Path::new("a.txt"))).unwrap();
+        assert_eq!(
+            meta,
+            std::fs::read(test.processed.join("a.txt.meta")).unwrap()
+        );
+    }
+}
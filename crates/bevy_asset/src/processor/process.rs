@@ -3,7 +3,10 @@ use crate::{
         AssetReaderError, AssetWriterError, MissingAssetWriterError,
         MissingProcessedAssetReaderError, MissingProcessedAssetWriterError, Writer,
     },
-    meta::{AssetAction, AssetMeta, AssetMetaDyn, ProcessDependencyInfo, ProcessedInfo, Settings},
+    meta::{
+        AssetAction, AssetMeta, AssetMetaDyn, ProcessDependencyInfo, ProcessedInfo, Settings,
+        SettingsMigrations,
+    },
     processor::AssetProcessor,
     saver::{AssetSaver, SavedAsset},
     AssetLoadError, AssetLoader, AssetPath, DeserializeMetaError, ErasedLoadedAsset,
@@ -32,6 +35,13 @@ pub trait Process: Send + Sync + Sized + 'static {
         meta: AssetMeta<(), Self>,
         writer: &'a mut Writer,
     ) -> BoxedFuture<'a, Result<<Self::OutputLoader as AssetLoader>::Settings, ProcessError>>;
+
+    /// Returns the migrations of [`Process::Settings`] from their previous versions. Add a
+    /// migration whenever the settings change shape, so the existing `.meta` files are upgraded
+    /// instead of failing to process.
+    fn settings_migrations() -> SettingsMigrations<Self::Settings> {
+        SettingsMigrations::new()
+    }
 }
 
 /// A flexible [`Process`] implementation that loads the source [`Asset`] using the `L` [`AssetLoader`], then
@@ -42,7 +52,8 @@ pub trait Process: Send + Sync + Sized + 'static {
 /// an [`AssetSaver`] that allows you to efficiently process that asset type when that is desirable by users. However you can
 /// also implement [`Process`] directly if [`LoadAndSave`] feels limiting or unnecessary.
 ///
-/// This uses [`LoadAndSaveSettings`] to configure the processor.
+/// This uses [`LoadAndSaveSettings`] to configure the processor. Its loader settings are migrated with the
+/// [`AssetLoader::settings_migrations`] of `L`.
 ///
 /// [`Asset`]: crate::Asset
 pub struct LoadAndSave<L: AssetLoader, S: AssetSaver<Asset = L::Asset>> {
@@ -142,6 +153,10 @@ impl<Loader: AssetLoader, Saver: AssetSaver<Asset = Loader::Asset>> Process
             Ok(output_settings)
         })
     }
+
+    fn settings_migrations() -> SettingsMigrations<Self::Settings> {
+        Loader::settings_migrations().into_load_and_save()
+    }
 }
 
 /// A type-erased variant of [`Process`] that enables interacting with processor implementations without knowing
@@ -183,7 +198,7 @@ impl<P: Process> ErasedProcessor for P {
     }
 
     fn deserialize_meta(&self, meta: &[u8]) -> Result<Box<dyn AssetMetaDyn>, DeserializeMetaError> {
-        let meta: AssetMeta<(), P> = ron::de::from_bytes(meta)?;
+        let meta = AssetMeta::<(), P>::deserialize(meta)?;
         Ok(Box::new(meta))
     }
 
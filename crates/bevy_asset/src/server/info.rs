@@ -1,3 +1,4 @@
+use super::load_queue::LoadQueue;
 use crate::{
     meta::{AssetHash, MetaTransform},
     Asset, AssetHandleProvider, AssetPath, DependencyLoadState, ErasedLoadedAsset, Handle,
@@ -10,6 +11,7 @@ use bevy_utils::{Entry, HashMap, HashSet};
 use crossbeam_channel::Sender;
 use std::{
     any::TypeId,
+    hash::Hash,
     sync::{Arc, Weak},
 };
 use thiserror::Error;
@@ -27,6 +29,10 @@ pub(crate) struct AssetInfo {
     failed_rec_dependencies: HashSet<UntypedAssetId>,
     dependants_waiting_on_load: HashSet<UntypedAssetId>,
     dependants_waiting_on_recursive_dep_load: HashSet<UntypedAssetId>,
+    /// The direct dependencies of this asset, as reported by its loader.
+    pub(crate) dependencies: HashSet<UntypedAssetId>,
+    /// The assets that directly depend on this asset.
+    pub(crate) dependants: HashSet<UntypedAssetId>,
     /// The asset paths required to load this asset. Hashes will only be set for processed assets.
     /// This is set using the value from [`LoadedAsset`].
     /// This will only be populated if [`AssetInfos::watching_for_changes`] is set to `true` to
@@ -54,9 +60,16 @@ impl AssetInfo {
             loader_dependencies: HashMap::default(),
             dependants_waiting_on_load: HashSet::default(),
             dependants_waiting_on_recursive_dep_load: HashSet::default(),
+            dependencies: HashSet::default(),
+            dependants: HashSet::default(),
             handle_drops_to_skip: 0,
         }
     }
+
+    /// Returns `true` if there is at least one live strong handle to this asset.
+    pub(crate) fn is_alive(&self) -> bool {
+        self.weak_handle.strong_count() > 0
+    }
 }
 
 #[derive(Default)]
@@ -72,6 +85,11 @@ pub(crate) struct AssetInfos {
     /// Tracks living labeled assets for a given source asset.
     /// This should only be set when watching for changes to avoid unnecessary work.
     pub(crate) living_labeled_assets: HashMap<AssetPath<'static>, HashSet<String>>,
+    /// The paths of the asset files loaded since startup, without labels, including loader dependencies.
+    /// This is used to report the asset files that are never loaded.
+    pub(crate) loaded_paths: HashSet<AssetPath<'static>>,
+    /// Schedules the loads of the assets, and cancels them when their handles are dropped.
+    pub(crate) load_queue: LoadQueue,
     pub(crate) handle_providers: HashMap<TypeId, AssetHandleProvider>,
     pub(crate) dependency_loaded_event_sender: HashMap<TypeId, fn(&mut World, UntypedAssetId)>,
 }
@@ -264,6 +282,10 @@ impl AssetInfos {
         self.infos.get_mut(&id)
     }
 
+    pub(crate) fn get_path_id(&self, path: &AssetPath) -> Option<UntypedAssetId> {
+        self.path_to_id.get(path).copied()
+    }
+
     pub(crate) fn get_path_handle(&self, path: AssetPath) -> Option<UntypedHandle> {
         let id = *self.path_to_id.get(&path)?;
         self.get_id_handle(id)
@@ -280,7 +302,7 @@ impl AssetInfos {
         let path = path.into();
         if let Some(id) = self.path_to_id.get(&path) {
             if let Some(info) = self.infos.get(id) {
-                return info.weak_handle.strong_count() > 0;
+                return info.is_alive();
             }
         }
         false
@@ -306,6 +328,7 @@ impl AssetInfos {
             &mut self.path_to_id,
             &mut self.loader_dependants,
             &mut self.living_labeled_assets,
+            &mut self.load_queue,
             self.watching_for_changes,
             id,
         )
@@ -319,7 +342,15 @@ impl AssetInfos {
         world: &mut World,
         sender: &Sender<InternalAssetEvent>,
     ) {
+        if !self.infos.contains_key(&loaded_asset_id) {
+            // The handles of the asset were dropped before its load could be cancelled
+            return;
+        }
         loaded_asset.value.insert(loaded_asset_id, world);
+        self.record_loaded_path(loaded_asset_id);
+        self.loaded_paths
+            .extend(loaded_asset.loader_dependencies.keys().cloned());
+        self.set_dependencies(loaded_asset_id, loaded_asset.dependencies.clone());
         let mut loading_deps = loaded_asset.dependencies;
         let mut failed_deps = HashSet::new();
         let mut loading_rec_deps = loading_deps.clone();
@@ -466,6 +497,67 @@ impl AssetInfos {
         }
     }
 
+    /// Records the path of the asset `id` as loaded, see [`AssetInfos::loaded_paths`].
+    fn record_loaded_path(&mut self, id: UntypedAssetId) {
+        if let Some(path) = self.infos.get(&id).and_then(|info| info.path.as_ref()) {
+            self.loaded_paths.insert(path.without_label().into_owned());
+        }
+    }
+
+    /// Replaces the direct dependencies of the asset `id`, and updates the dependants of the
+    /// previous and new dependencies.
+    fn set_dependencies(&mut self, id: UntypedAssetId, dependencies: HashSet<UntypedAssetId>) {
+        let Some(info) = self.infos.get_mut(&id) else {
+            return;
+        };
+        let previous_dependencies = std::mem::replace(&mut info.dependencies, dependencies.clone());
+        for dependency in previous_dependencies {
+            if let Some(info) = self.infos.get_mut(&dependency) {
+                info.dependants.remove(&id);
+            }
+        }
+        for dependency in dependencies {
+            if let Some(info) = self.infos.get_mut(&dependency) {
+                info.dependants.insert(id);
+            }
+        }
+    }
+
+    /// Orders the paths to reload after the `changed` paths: the changed paths, and the paths of
+    /// the assets whose loaders read them, directly or not. Each path comes before the paths that
+    /// were loaded from it.
+    pub(crate) fn reload_order(&self, changed: &[AssetPath<'static>]) -> Vec<AssetPath<'static>> {
+        dependencies_first(changed, |path| {
+            self.loader_dependants
+                .get(path)
+                .map(|dependants| dependants.iter().cloned().collect())
+                .unwrap_or_default()
+        })
+    }
+
+    /// The assets affected by the reload of `paths`: the assets at these paths, their labeled
+    /// assets, and the assets that depend on them, directly or not. Each asset comes before its
+    /// dependants.
+    pub(crate) fn reloaded_assets(&self, paths: &[AssetPath<'static>]) -> Vec<UntypedAssetId> {
+        let mut reloaded = Vec::new();
+        for path in paths {
+            reloaded.extend(self.get_path_id(path));
+            if let Some(labels) = self.living_labeled_assets.get(path) {
+                reloaded.extend(
+                    labels
+                        .iter()
+                        .filter_map(|label| self.get_path_id(&path.clone().with_label(label))),
+                );
+            }
+        }
+        dependencies_first(&reloaded, |id| {
+            self.infos
+                .get(id)
+                .map(|info| info.dependants.iter().copied().collect())
+                .unwrap_or_default()
+        })
+    }
+
     /// Recursively propagates loaded state up the dependency tree.
     fn propagate_loaded_state(
         infos: &mut AssetInfos,
@@ -524,10 +616,12 @@ impl AssetInfos {
     }
 
     pub(crate) fn process_asset_fail(&mut self, failed_id: UntypedAssetId) {
+        self.record_loaded_path(failed_id);
         let (dependants_waiting_on_load, dependants_waiting_on_rec_load) = {
-            let info = self
-                .get_mut(failed_id)
-                .expect("Asset info should always exist at this point");
+            let Some(info) = self.get_mut(failed_id) else {
+                // The handles of the asset were dropped before its load could be cancelled
+                return;
+            };
             info.load_state = LoadState::Failed;
             info.dep_load_state = DependencyLoadState::Failed;
             info.rec_dep_load_state = RecursiveDependencyLoadState::Failed;
@@ -555,6 +649,7 @@ impl AssetInfos {
         path_to_id: &mut HashMap<AssetPath<'static>, UntypedAssetId>,
         loader_dependants: &mut HashMap<AssetPath<'static>, HashSet<AssetPath<'static>>>,
         living_labeled_assets: &mut HashMap<AssetPath<'static>, HashSet<String>>,
+        load_queue: &mut LoadQueue,
         watching_for_changes: bool,
         id: UntypedAssetId,
     ) -> bool {
@@ -565,6 +660,13 @@ impl AssetInfos {
                     false
                 } else {
                     let info = entry.remove();
+                    // Nothing needs the asset anymore if it is still loading
+                    load_queue.cancel(id);
+                    for dependency in &info.dependencies {
+                        if let Some(dependency_info) = infos.get_mut(dependency) {
+                            dependency_info.dependants.remove(&id);
+                        }
+                    }
                     if let Some(path) = info.path {
                         if watching_for_changes {
                             for loader_dependency in info.loader_dependencies.keys() {
@@ -613,6 +715,7 @@ impl AssetInfos {
                         &mut self.path_to_id,
                         &mut self.loader_dependants,
                         &mut self.living_labeled_assets,
+                        &mut self.load_queue,
                         self.watching_for_changes,
                         id.untyped(provider.type_id),
                     );
@@ -633,6 +736,38 @@ pub(crate) enum HandleLoadingMode {
     Force,
 }
 
+/// Sorts `roots` and everything reachable from them through `dependants`, so that each node comes
+/// before its dependants. Roots that don't depend on each other keep their order. Cycles are
+/// broken arbitrarily.
+fn dependencies_first<T: Clone + Eq + Hash>(
+    roots: &[T],
+    mut dependants: impl FnMut(&T) -> Vec<T>,
+) -> Vec<T> {
+    let mut visited = HashSet::new();
+    let mut post_order = Vec::new();
+    // Roots are visited last to first, so that they keep their order once the post order is
+    // reversed
+    let mut stack: Vec<(T, bool)> = roots.iter().map(|root| (root.clone(), false)).collect();
+    while let Some((node, visited_dependants)) = stack.pop() {
+        if visited_dependants {
+            post_order.push(node);
+            continue;
+        }
+        if !visited.insert(node.clone()) {
+            continue;
+        }
+        let next = dependants(&node);
+        stack.push((node, true));
+        stack.extend(
+            next.into_iter()
+                .filter(|dependant| !visited.contains(dependant))
+                .map(|dependant| (dependant, false)),
+        );
+    }
+    post_order.reverse();
+    post_order
+}
+
 #[derive(Error, Debug)]
 #[error("Cannot allocate a handle because no handle provider exists for asset type {0:?}")]
 pub struct MissingHandleProviderError(TypeId);
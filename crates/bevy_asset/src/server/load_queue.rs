@@ -0,0 +1,312 @@
+use crate::UntypedAssetId;
+use bevy_utils::HashMap;
+use parking_lot::Mutex;
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Poll, Waker},
+};
+
+/// The priority of an asset load. When the number of loads in flight is limited with
+/// [`AssetServer::set_max_loads_in_flight`], queued loads with a higher priority start first, and
+/// loads with the same priority start in the order they were requested.
+///
+/// The dependencies loaded by an asset loader inherit the priority of the asset being loaded.
+///
+/// [`AssetServer::set_max_loads_in_flight`]: crate::AssetServer::set_max_loads_in_flight
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LoadPriority(pub i32);
+
+impl LoadPriority {
+    /// A priority for loads that can wait, such as background prefetching.
+    pub const LOW: Self = Self(-100);
+    /// The priority of [`AssetServer::load`](crate::AssetServer::load).
+    pub const NORMAL: Self = Self(0);
+    /// A priority for loads that are needed as soon as possible.
+    pub const HIGH: Self = Self(100);
+}
+
+/// Cancels an asset load that is in flight.
+#[derive(Clone, Default)]
+pub(crate) struct CancelToken(Arc<CancelState>);
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl CancelToken {
+    fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Release);
+        if let Some(waker) = self.0.waker.lock().take() {
+            waker.wake();
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Runs `future` until it completes, or until this token is cancelled. The future is dropped
+    /// at its next await point when cancelled, and `None` is returned.
+    pub(crate) async fn run<T>(&self, future: impl Future<Output = T>) -> Option<T> {
+        let cancelled = futures_lite::future::poll_fn(|cx| {
+            if self.is_cancelled() {
+                return Poll::Ready(None);
+            }
+            *self.0.waker.lock() = Some(cx.waker().clone());
+            // The token may have been cancelled before the waker was stored
+            if self.is_cancelled() {
+                Poll::Ready(None)
+            } else {
+                Poll::Pending
+            }
+        });
+        futures_lite::future::or(cancelled, async { Some(future.await) }).await
+    }
+}
+
+/// Starts a queued load, which must stop when the given token is cancelled.
+pub(crate) type StartLoad = Box<dyn FnOnce(CancelToken) + Send + Sync>;
+
+/// A load that is ready to start, once the lock on the queue has been released.
+pub(crate) struct ReadyLoad {
+    start: StartLoad,
+    token: CancelToken,
+}
+
+impl ReadyLoad {
+    pub(crate) fn start(self) {
+        (self.start)(self.token);
+    }
+}
+
+struct PendingLoad {
+    id: UntypedAssetId,
+    priority: LoadPriority,
+    sequence: u64,
+    start: StartLoad,
+}
+
+struct InFlightLoad {
+    priority: LoadPriority,
+    token: CancelToken,
+}
+
+/// Schedules the loads of the [`AssetServer`](crate::AssetServer), by priority, with an optional
+/// limit on the number of loads in flight.
+///
+/// Loads aren't started by the queue itself: they are moved to a list of ready loads, which must
+/// be started with [`LoadQueue::take_ready`] after releasing the lock on the queue. The single
+/// threaded task pool runs spawned tasks right away, and loads lock the queue.
+#[derive(Default)]
+pub(crate) struct LoadQueue {
+    max_in_flight: Option<usize>,
+    pending: Vec<PendingLoad>,
+    in_flight: HashMap<UntypedAssetId, InFlightLoad>,
+    ready: Vec<ReadyLoad>,
+    next_sequence: u64,
+}
+
+impl LoadQueue {
+    /// Queues the load of the asset `id`, making it ready right away if there is room for it.
+    pub(crate) fn push(&mut self, id: UntypedAssetId, priority: LoadPriority, start: StartLoad) {
+        self.pending.push(PendingLoad {
+            id,
+            priority,
+            sequence: self.next_sequence,
+            start,
+        });
+        self.next_sequence += 1;
+        self.start_pending();
+    }
+
+    /// Marks the load of the asset `id` as finished, making room for the next queued load.
+    pub(crate) fn finish(&mut self, id: UntypedAssetId) {
+        if self.in_flight.remove(&id).is_some() {
+            self.start_pending();
+        }
+    }
+
+    /// Cancels the load of the asset `id`, whether it is queued or in flight.
+    pub(crate) fn cancel(&mut self, id: UntypedAssetId) {
+        if let Some(index) = self.pending.iter().position(|load| load.id == id) {
+            self.pending.swap_remove(index);
+        }
+        if let Some(load) = self.in_flight.remove(&id) {
+            load.token.cancel();
+            self.start_pending();
+        }
+    }
+
+    /// Changes the priority of the load of the asset `id`, if it is still queued.
+    pub(crate) fn set_priority(&mut self, id: UntypedAssetId, priority: LoadPriority) {
+        if let Some(load) = self.pending.iter_mut().find(|load| load.id == id) {
+            load.priority = priority;
+        }
+    }
+
+    /// Returns `true` if the load of the asset `id` is queued or in flight.
+    pub(crate) fn contains(&self, id: UntypedAssetId) -> bool {
+        self.in_flight.contains_key(&id) || self.pending.iter().any(|load| load.id == id)
+    }
+
+    /// The priority of the load of the asset `id`, if it is queued or in flight.
+    pub(crate) fn priority(&self, id: UntypedAssetId) -> Option<LoadPriority> {
+        match self.in_flight.get(&id) {
+            Some(load) => Some(load.priority),
+            None => self
+                .pending
+                .iter()
+                .find(|load| load.id == id)
+                .map(|load| load.priority),
+        }
+    }
+
+    pub(crate) fn max_in_flight(&self) -> Option<usize> {
+        self.max_in_flight
+    }
+
+    pub(crate) fn set_max_in_flight(&mut self, max_in_flight: Option<usize>) {
+        self.max_in_flight = max_in_flight;
+        self.start_pending();
+    }
+
+    /// Takes the loads that must be started.
+    pub(crate) fn take_ready(&mut self) -> Vec<ReadyLoad> {
+        std::mem::take(&mut self.ready)
+    }
+
+    fn has_room(&self) -> bool {
+        match self.max_in_flight {
+            Some(max_in_flight) => self.in_flight.len() < max_in_flight,
+            None => true,
+        }
+    }
+
+    fn start_pending(&mut self) {
+        while self.has_room() {
+            // Highest priority first, then first requested
+            let Some(index) = self
+                .pending
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, load)| (load.priority, std::cmp::Reverse(load.sequence)))
+                .map(|(index, _)| index)
+            else {
+                break;
+            };
+            let load = self.pending.swap_remove(index);
+            let token = CancelToken::default();
+            self.in_flight.insert(
+                load.id,
+                InFlightLoad {
+                    priority: load.priority,
+                    token: token.clone(),
+                },
+            );
+            self.ready.push(ReadyLoad {
+                start: load.start,
+                token,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AssetId;
+    use bevy_utils::Uuid;
+    use futures_lite::future::block_on;
+
+    fn id(index: u128) -> UntypedAssetId {
+        AssetId::<crate::LoadedFolder>::Uuid {
+            uuid: Uuid::from_u128(index),
+        }
+        .untyped()
+    }
+
+    /// Queues a load recording the index of the asset in `started` when it starts.
+    fn push(
+        queue: &mut LoadQueue,
+        started: &Arc<Mutex<Vec<(u128, CancelToken)>>>,
+        index: u128,
+        priority: LoadPriority,
+    ) {
+        let started = started.clone();
+        queue.push(
+            id(index),
+            priority,
+            Box::new(move |token| started.lock().push((index, token))),
+        );
+    }
+
+    /// Starts the ready loads, and returns the indices of all the loads started so far.
+    fn started_indices(
+        queue: &mut LoadQueue,
+        started: &Mutex<Vec<(u128, CancelToken)>>,
+    ) -> Vec<u128> {
+        for load in queue.take_ready() {
+            load.start();
+        }
+        started.lock().iter().map(|(index, _)| *index).collect()
+    }
+
+    #[test]
+    fn loads_start_by_priority() {
+        let started = Arc::default();
+        let mut queue = LoadQueue::default();
+        queue.set_max_in_flight(Some(1));
+
+        push(&mut queue, &started, 0, LoadPriority::NORMAL);
+        push(&mut queue, &started, 1, LoadPriority::LOW);
+        push(&mut queue, &started, 2, LoadPriority::NORMAL);
+        push(&mut queue, &started, 3, LoadPriority::HIGH);
+        assert_eq!(started_indices(&mut queue, &started), [0]);
+        assert_eq!(queue.priority(id(1)), Some(LoadPriority::LOW));
+
+        queue.set_priority(id(1), LoadPriority(1000));
+        queue.finish(id(0));
+        queue.finish(id(1));
+        queue.finish(id(3));
+        assert_eq!(started_indices(&mut queue, &started), [0, 1, 3, 2]);
+
+        queue.finish(id(2));
+        assert_eq!(queue.priority(id(2)), None);
+        queue.set_max_in_flight(None);
+        for index in 4..8 {
+            push(&mut queue, &started, index, LoadPriority::LOW);
+        }
+        assert_eq!(
+            started_indices(&mut queue, &started),
+            [0, 1, 3, 2, 4, 5, 6, 7]
+        );
+    }
+
+    #[test]
+    fn cancelled_loads_make_room() {
+        let started = Arc::default();
+        let mut queue = LoadQueue::default();
+        queue.set_max_in_flight(Some(1));
+
+        push(&mut queue, &started, 0, LoadPriority::NORMAL);
+        push(&mut queue, &started, 1, LoadPriority::NORMAL);
+        push(&mut queue, &started, 2, LoadPriority::NORMAL);
+
+        // A queued load never starts
+        queue.cancel(id(1));
+        // An in flight load is cancelled, and the next one starts
+        queue.cancel(id(0));
+        assert_eq!(started_indices(&mut queue, &started), [0, 2]);
+        let token = started.lock()[0].1.clone();
+        assert!(token.is_cancelled());
+        assert_eq!(block_on(token.run(async { 42 })), None);
+        let token = started.lock()[1].1.clone();
+        assert_eq!(block_on(token.run(async { 42 })), Some(42));
+    }
+}
@@ -1,4 +1,5 @@
 mod info;
+mod load_queue;
 
 use crate::{
     folder::LoadedFolder,
@@ -12,17 +13,20 @@ use crate::{
         MetaTransform, Settings,
     },
     path::AssetPath,
-    Asset, AssetEvent, AssetHandleProvider, AssetId, Assets, DeserializeMetaError,
+    Asset, AssetEvent, AssetHandleProvider, AssetId, Assets, AssetsReloaded, DeserializeMetaError,
     ErasedLoadedAsset, Handle, LoadedUntypedAsset, UntypedAssetId, UntypedHandle,
 };
 use bevy_ecs::prelude::*;
-use bevy_log::{error, info, warn};
+use bevy_log::{debug, error, info, warn};
 use bevy_tasks::IoTaskPool;
 use bevy_utils::{CowArc, HashMap, HashSet};
 use crossbeam_channel::{Receiver, Sender};
 use futures_lite::StreamExt;
 use info::*;
+pub use load_queue::LoadPriority;
+use load_queue::StartLoad;
 use parking_lot::RwLock;
+use std::future::Future;
 use std::path::PathBuf;
 use std::{any::TypeId, path::Path, sync::Arc};
 use thiserror::Error;
@@ -238,7 +242,20 @@ impl AssetServer {
     /// The asset load will fail and an error will be printed to the logs if the asset stored at `path` is not of type `A`.
     #[must_use = "not using the returned strong handle may result in the unexpected release of the asset"]
     pub fn load<'a, A: Asset>(&self, path: impl Into<AssetPath<'a>>) -> Handle<A> {
-        self.load_with_meta_transform(path, None)
+        self.load_with_meta_transform(path, None, LoadPriority::NORMAL)
+    }
+
+    /// Begins loading an [`Asset`] of type `A` stored at `path` with the given [`LoadPriority`]. See [`AssetServer::load`].
+    ///
+    /// The priority only matters when the number of loads in flight is limited with [`AssetServer::set_max_loads_in_flight`]:
+    /// queued loads with a higher priority start first.
+    #[must_use = "not using the returned strong handle may result in the unexpected release of the asset"]
+    pub fn load_with_priority<'a, A: Asset>(
+        &self,
+        path: impl Into<AssetPath<'a>>,
+        priority: LoadPriority,
+    ) -> Handle<A> {
+        self.load_with_meta_transform(path, None, priority)
     }
 
     /// Begins loading an [`Asset`] of type `A` stored at `path`. The given `settings` function will override the asset's
@@ -250,13 +267,18 @@ impl AssetServer {
         path: impl Into<AssetPath<'a>>,
         settings: impl Fn(&mut S) + Send + Sync + 'static,
     ) -> Handle<A> {
-        self.load_with_meta_transform(path, Some(loader_settings_meta_transform(settings)))
+        self.load_with_meta_transform(
+            path,
+            Some(loader_settings_meta_transform(settings)),
+            LoadPriority::NORMAL,
+        )
     }
 
-    fn load_with_meta_transform<'a, A: Asset>(
+    pub(crate) fn load_with_meta_transform<'a, A: Asset>(
         &self,
         path: impl Into<AssetPath<'a>>,
         meta_transform: Option<MetaTransform>,
+        priority: LoadPriority,
     ) -> Handle<A> {
         let path = path.into().into_owned();
         let (handle, should_load) = self.data.infos.write().get_or_create_path_handle::<A>(
@@ -266,18 +288,86 @@ impl AssetServer {
         );
 
         if should_load {
-            let owned_handle = Some(handle.clone().untyped());
-            let server = self.clone();
+            let id = handle.id().untyped();
+            self.queue_load(id, priority, move |server| async move {
+                // Only hold a weak handle, so the load is cancelled when the returned handles are dropped
+                let weak_handle = Some(UntypedHandle::Weak(id));
+                if let Err(err) = server.load_internal(weak_handle, path, false, None).await {
+                    error!("{}", err);
+                }
+            });
+        }
+
+        handle
+    }
+
+    /// Queues the load of the asset `id`. The future returned by `load` runs on the [`IoTaskPool`] once the load starts,
+    /// and is dropped if the load is cancelled.
+    fn queue_load<F: Future<Output = ()> + Send + 'static>(
+        &self,
+        id: UntypedAssetId,
+        priority: LoadPriority,
+        load: impl FnOnce(AssetServer) -> F + Send + Sync + 'static,
+    ) {
+        let server = self.clone();
+        let start: StartLoad = Box::new(move |token| {
             IoTaskPool::get()
                 .spawn(async move {
-                    if let Err(err) = server.load_internal(owned_handle, path, false, None).await {
-                        error!("{}", err);
+                    if token.run(load(server.clone())).await.is_none() {
+                        debug!("Cancelled the load of asset {id:?}");
                     }
+                    server.data.infos.write().load_queue.finish(id);
+                    server.start_ready_loads();
                 })
                 .detach();
+        });
+        self.data.infos.write().load_queue.push(id, priority, start);
+        self.start_ready_loads();
+    }
+
+    /// Starts the queued loads that are ready to start.
+    fn start_ready_loads(&self) {
+        let ready_loads = self.data.infos.write().load_queue.take_ready();
+        for load in ready_loads {
+            load.start();
         }
+    }
 
-        handle
+    /// Changes the [`LoadPriority`] of the asset with the given `id`. This has no effect if the load of the asset has already
+    /// started.
+    pub fn set_load_priority(&self, id: impl Into<UntypedAssetId>, priority: LoadPriority) {
+        self.data
+            .infos
+            .write()
+            .load_queue
+            .set_priority(id.into(), priority);
+    }
+
+    /// Limits the number of asset loads in flight, or removes the limit when `None`. Loads requested while the limit is
+    /// reached are queued, and start by [`LoadPriority`] as the loads in flight finish. There is no limit by default.
+    ///
+    /// A load is cancelled when all the handles of the asset are dropped, whether it is queued or in flight.
+    pub fn set_max_loads_in_flight(&self, max_loads_in_flight: Option<usize>) {
+        self.data
+            .infos
+            .write()
+            .load_queue
+            .set_max_in_flight(max_loads_in_flight);
+        self.start_ready_loads();
+    }
+
+    /// Returns the limit on the number of asset loads in flight. See [`AssetServer::set_max_loads_in_flight`].
+    pub fn max_loads_in_flight(&self) -> Option<usize> {
+        self.data.infos.read().load_queue.max_in_flight()
+    }
+
+    /// Returns the priority of the load of the asset at `path`, or [`LoadPriority::NORMAL`] if it isn't loading.
+    pub(crate) fn get_load_priority(&self, path: &AssetPath) -> LoadPriority {
+        let infos = self.data.infos.read();
+        infos
+            .get_path_id(path)
+            .and_then(|id| infos.load_queue.priority(id))
+            .unwrap_or_default()
     }
 
     /// Asynchronously load an asset that you do not know the type of statically. If you _do_ know the type of the asset,
@@ -316,6 +406,14 @@ impl AssetServer {
     /// required to figure out the asset type before a handle can be created.
     #[must_use = "not using the returned strong handle may result in the unexpected release of the assets"]
     pub fn load_untyped<'a>(&self, path: impl Into<AssetPath<'a>>) -> Handle<LoadedUntypedAsset> {
+        self.load_untyped_with_priority(path, LoadPriority::NORMAL)
+    }
+
+    pub(crate) fn load_untyped_with_priority<'a>(
+        &self,
+        path: impl Into<AssetPath<'a>>,
+        priority: LoadPriority,
+    ) -> Handle<LoadedUntypedAsset> {
         let path = path.into().into_owned();
         let untyped_source = AssetSourceId::Name(match path.source() {
             AssetSourceId::Default => CowArc::Borrowed(UNTYPED_SOURCE_SUFFIX),
@@ -337,25 +435,22 @@ impl AssetServer {
         }
         let id = handle.id().untyped();
 
-        let server = self.clone();
-        IoTaskPool::get()
-            .spawn(async move {
-                match server.load_untyped_async(path).await {
-                    Ok(handle) => server.send_asset_event(InternalAssetEvent::Loaded {
-                        id,
-                        loaded_asset: LoadedAsset::new_with_dependencies(
-                            LoadedUntypedAsset { handle },
-                            None,
-                        )
-                        .into(),
-                    }),
-                    Err(err) => {
-                        error!("{err}");
-                        server.send_asset_event(InternalAssetEvent::Failed { id });
-                    }
+        self.queue_load(id, priority, move |server| async move {
+            match server.load_untyped_async(path).await {
+                Ok(handle) => server.send_asset_event(InternalAssetEvent::Loaded {
+                    id,
+                    loaded_asset: LoadedAsset::new_with_dependencies(
+                        LoadedUntypedAsset { handle },
+                        None,
+                    )
+                    .into(),
+                }),
+                Err(err) => {
+                    error!("{err}");
+                    server.send_asset_event(InternalAssetEvent::Failed { id });
                 }
-            })
-            .detach();
+            }
+        });
 
         handle
     }
@@ -448,9 +543,19 @@ impl AssetServer {
             (handle.clone().unwrap(), path.clone())
         };
 
-        if let Some(meta_transform) = base_handle.meta_transform() {
+        // Queued loads only hold a weak handle, which doesn't carry the meta transform
+        let strong_base_handle = match &base_handle {
+            UntypedHandle::Strong(_) => None,
+            UntypedHandle::Weak(id) => self.data.infos.read().get_id_handle(*id),
+        };
+        if let Some(meta_transform) = strong_base_handle
+            .as_ref()
+            .unwrap_or(&base_handle)
+            .meta_transform()
+        {
             (*meta_transform)(&mut *meta);
         }
+        drop(strong_base_handle);
 
         match self
             .load_with_meta_loader_and_reader(&base_path, meta, &*loader, &mut *reader, true, false)
@@ -495,16 +600,35 @@ impl AssetServer {
     }
 
     /// Kicks off a reload of the asset stored at the given path. This will only reload the asset if it currently loaded.
+    ///
+    /// The assets whose loaders read the asset file are reloaded after it, when the server is
+    /// watching for changes, and a single [`AssetsReloaded`] event is sent once they are all
+    /// reloaded, as for hot reloads.
     pub fn reload<'a>(&self, path: impl Into<AssetPath<'a>>) {
-        let server = self.clone();
         let path = path.into().into_owned();
+        let paths = self.data.infos.read().reload_order(&[path]);
+        self.reload_batch(paths);
+    }
+
+    /// Reloads the given paths one after the other, in order, then sends a single
+    /// [`AssetsReloaded`] event for all of them. Paths that aren't loaded anymore are skipped.
+    fn reload_batch(&self, paths: Vec<AssetPath<'static>>) {
+        let server = self.clone();
         IoTaskPool::get()
             .spawn(async move {
-                if server.data.infos.read().should_reload(&path) {
-                    info!("Reloading {path} because it has changed");
-                    if let Err(err) = server.load_internal(None, path, true, None).await {
-                        error!("{}", err);
+                let mut reloaded = Vec::new();
+                for path in paths {
+                    if !server.data.infos.read().should_reload(&path) {
+                        continue;
                     }
+                    info!("Reloading {path}");
+                    match server.load_internal(None, path.clone(), true, None).await {
+                        Ok(_) => reloaded.push(path),
+                        Err(err) => error!("{}", err),
+                    }
+                }
+                if !reloaded.is_empty() {
+                    server.send_asset_event(InternalAssetEvent::Reloaded { paths: reloaded });
                 }
             })
             .detach();
@@ -741,6 +865,104 @@ impl AssetServer {
         Some(info.path.as_ref()?.clone())
     }
 
+    /// Returns the direct dependencies of the asset with the given `id`, as reported by its
+    /// [`AssetLoader`], or `None` if the asset isn't tracked by this server.
+    pub fn get_dependencies(
+        &self,
+        id: impl Into<UntypedAssetId>,
+    ) -> Option<HashSet<UntypedAssetId>> {
+        let infos = self.data.infos.read();
+        Some(infos.get(id.into())?.dependencies.clone())
+    }
+
+    /// Returns the dependencies of the asset with the given `id`, and their own dependencies
+    /// recursively, or `None` if the asset isn't tracked by this server.
+    pub fn get_recursive_dependencies(
+        &self,
+        id: impl Into<UntypedAssetId>,
+    ) -> Option<HashSet<UntypedAssetId>> {
+        let infos = self.data.infos.read();
+        let mut dependencies = HashSet::new();
+        let mut to_visit: Vec<_> = infos.get(id.into())?.dependencies.iter().copied().collect();
+        while let Some(dependency) = to_visit.pop() {
+            if dependencies.insert(dependency) {
+                if let Some(info) = infos.get(dependency) {
+                    to_visit.extend(info.dependencies.iter().copied());
+                }
+            }
+        }
+        Some(dependencies)
+    }
+
+    /// Returns the assets that directly depend on the asset with the given `id`, or `None` if the
+    /// asset isn't tracked by this server.
+    pub fn get_dependants(&self, id: impl Into<UntypedAssetId>) -> Option<HashSet<UntypedAssetId>> {
+        let infos = self.data.infos.read();
+        Some(infos.get(id.into())?.dependants.clone())
+    }
+
+    /// Lists the assets that are likely unused: the assets stored in an [`Assets`] collection of a
+    /// registered asset type that no live strong [`Handle`] points to, and the asset files of the
+    /// default [`AssetSource`] that were never loaded since this server started, either directly or
+    /// as a dependency of another asset.
+    ///
+    /// Assets inserted by id or only kept with weak handles are never freed, so they are reported
+    /// as unreferenced. Assets whose last strong handle was just dropped are also reported until
+    /// their collection frees them.
+    ///
+    /// Files are only reported relative to what the app has loaded so far, so this should be
+    /// called after going through all of its content, for example at the end of a playthrough
+    /// test. `.meta` files are ignored.
+    pub async fn unused_asset_report(&self) -> Result<UnusedAssetReport, AssetLoadError> {
+        fn list_files<'a>(
+            path: &'a Path,
+            reader: &'a dyn AssetReader,
+            files: &'a mut Vec<PathBuf>,
+        ) -> bevy_utils::BoxedFuture<'a, Result<(), AssetReaderError>> {
+            Box::pin(async move {
+                let mut path_stream = reader.read_directory(path).await?;
+                while let Some(child_path) = path_stream.next().await {
+                    if reader.is_directory(&child_path).await? {
+                        list_files(&child_path, reader, files).await?;
+                    } else if !child_path.extension().map(|e| e == "meta").unwrap_or(false) {
+                        files.push(child_path);
+                    }
+                }
+                Ok(())
+            })
+        }
+
+        let source = self.get_source(AssetSourceId::Default)?;
+        let reader = match self.data.mode {
+            AssetServerMode::Unprocessed => source.reader(),
+            AssetServerMode::Processed => source.processed_reader()?,
+        };
+        let mut files = Vec::new();
+        list_files(Path::new(""), reader, &mut files).await?;
+
+        files.sort();
+
+        let infos = self.data.infos.read();
+        let unloaded_files = files
+            .into_iter()
+            .map(AssetPath::from)
+            .filter(|path| !infos.loaded_paths.contains(path))
+            .collect();
+        let unreferenced_assets = infos
+            .handle_providers
+            .values()
+            .flat_map(|provider| {
+                let mut ids: Vec<_> = provider.usage.lock().unreferenced().collect();
+                ids.sort();
+                ids.into_iter().map(|id| id.untyped(provider.type_id))
+            })
+            .collect();
+        Ok(UnusedAssetReport {
+            unreferenced_assets,
+            unloaded_files,
+        })
+    }
+
     /// Returns the [`AssetServerMode`] this server is currently in.
     pub fn mode(&self) -> AssetServerMode {
         self.data.mode
@@ -897,18 +1119,9 @@ pub fn handle_internal_asset_events(world: &mut World) {
                     sender(world, id);
                 }
                 InternalAssetEvent::Failed { id } => infos.process_asset_fail(id),
-            }
-        }
-
-        fn queue_ancestors(
-            asset_path: &AssetPath,
-            infos: &AssetInfos,
-            paths_to_reload: &mut HashSet<AssetPath<'static>>,
-        ) {
-            if let Some(dependants) = infos.loader_dependants.get(asset_path) {
-                for dependant in dependants {
-                    paths_to_reload.insert(dependant.to_owned());
-                    queue_ancestors(dependant, infos, paths_to_reload);
+                InternalAssetEvent::Reloaded { paths } => {
+                    let assets = infos.reloaded_assets(&paths);
+                    world.send_event(AssetsReloaded { paths, assets });
                 }
             }
         }
@@ -926,15 +1139,16 @@ pub fn handle_internal_asset_events(world: &mut World) {
             }
         };
 
-        let mut paths_to_reload = HashSet::new();
+        let mut changed_paths = Vec::new();
         let mut handle_event = |source: AssetSourceId<'static>, event: AssetSourceEvent| {
             match event {
                 // TODO: if the asset was processed and the processed file was changed, the first modified event
                 // should be skipped?
                 AssetSourceEvent::ModifiedAsset(path) | AssetSourceEvent::ModifiedMeta(path) => {
                     let path = AssetPath::from(path).with_source(source);
-                    queue_ancestors(&path, &infos, &mut paths_to_reload);
-                    paths_to_reload.insert(path);
+                    if !changed_paths.contains(&path) {
+                        changed_paths.push(path);
+                    }
                 }
                 AssetSourceEvent::RenamedFolder { old, new } => {
                     reload_parent_folders(old, &source);
@@ -969,8 +1183,13 @@ pub fn handle_internal_asset_events(world: &mut World) {
             }
         }
 
-        for path in paths_to_reload {
-            server.reload(path);
+        let paths_to_reload = infos.reload_order(&changed_paths);
+
+        // Dropped handles may have cancelled loads in flight, making room for queued loads
+        drop(infos);
+        server.start_ready_loads();
+        if !paths_to_reload.is_empty() {
+            server.reload_batch(paths_to_reload);
         }
     });
 }
@@ -1005,6 +1224,21 @@ pub(crate) enum InternalAssetEvent {
     Failed {
         id: UntypedAssetId,
     },
+    /// Sent after the loads of a hot reload, once they have all been sent.
+    Reloaded {
+        paths: Vec<AssetPath<'static>>,
+    },
+}
+
+/// Assets that are likely unused, listed by [`AssetServer::unused_asset_report`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnusedAssetReport {
+    /// The assets stored in an [`Assets`] collection that no live strong [`Handle`] points to,
+    /// sorted by id for each asset type.
+    pub unreferenced_assets: Vec<UntypedAssetId>,
+    /// The asset files of the default [`AssetSource`] that weren't loaded since the
+    /// [`AssetServer`] started.
+    pub unloaded_files: Vec<AssetPath<'static>>,
 }
 
 /// The load state of an asset.
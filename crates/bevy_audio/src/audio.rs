@@ -1,8 +1,9 @@
-use crate::{AudioSource, Decodable};
+use crate::{AudioSource, Decodable, SpatialRenderer};
 use bevy_asset::{Asset, Handle};
 use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::prelude::*;
 use bevy_math::Vec3;
+use std::time::Duration;
 
 /// Defines the volume to play an audio source at.
 #[derive(Clone, Copy, Debug)]
@@ -86,11 +87,18 @@ pub struct PlaybackSettings {
     pub paused: bool,
     /// Enables spatial audio for this source.
     ///
-    /// See also: [`SpatialListener`].
-    ///
-    /// Note: Bevy does not currently support HRTF or any other high-quality 3D sound rendering
-    /// features. Spatial audio is implemented via simple left-right stereo panning.
+    /// See also: [`SpatialListener`], and [`SpatialEmitter`](crate::SpatialEmitter) for the
+    /// distance attenuation, directivity and doppler effect of the source.
     pub spatial: bool,
+    /// Start playing at this time on the [`AudioClock`](crate::AudioClock), rather than as soon
+    /// as possible.
+    ///
+    /// If the audio source isn't ready by then, it starts as soon as it's ready.
+    pub start_at: Option<Duration>,
+    /// Fade in over this duration when starting to play.
+    ///
+    /// See also: [`AudioSinkPlayback::fade_to`](crate::AudioSinkPlayback::fade_to).
+    pub fade_in: Option<Duration>,
 }
 
 impl Default for PlaybackSettings {
@@ -108,6 +116,8 @@ impl PlaybackSettings {
         speed: 1.0,
         paused: false,
         spatial: false,
+        start_at: None,
+        fade_in: None,
     };
 
     /// Will play the associated audio source in a loop.
@@ -117,6 +127,8 @@ impl PlaybackSettings {
         speed: 1.0,
         paused: false,
         spatial: false,
+        start_at: None,
+        fade_in: None,
     };
 
     /// Will play the associated audio source once and despawn the entity afterwards.
@@ -126,6 +138,8 @@ impl PlaybackSettings {
         speed: 1.0,
         paused: false,
         spatial: false,
+        start_at: None,
+        fade_in: None,
     };
 
     /// Will play the associated audio source once and remove the audio components afterwards.
@@ -135,6 +149,8 @@ impl PlaybackSettings {
         speed: 1.0,
         paused: false,
         spatial: false,
+        start_at: None,
+        fade_in: None,
     };
 
     /// Helper to start in a paused state.
@@ -160,6 +176,18 @@ impl PlaybackSettings {
         self.spatial = spatial;
         self
     }
+
+    /// Helper to start playing at a time on the [`AudioClock`](crate::AudioClock).
+    pub const fn with_start_at(mut self, start_at: Duration) -> Self {
+        self.start_at = Some(start_at);
+        self
+    }
+
+    /// Helper to fade in when starting to play.
+    pub const fn with_fade_in(mut self, fade_in: Duration) -> Self {
+        self.fade_in = Some(fade_in);
+        self
+    }
 }
 
 /// Settings for the listener for spatial audio sources.
@@ -172,6 +200,10 @@ pub struct SpatialListener {
     pub left_ear_offset: Vec3,
     /// Right ear position relative to the `GlobalTransform`.
     pub right_ear_offset: Vec3,
+    /// How spatial audio sources are rendered to the ears.
+    pub renderer: SpatialRenderer,
+    /// The speed of sound, in world units per second, for the doppler effect.
+    pub speed_of_sound: f32,
 }
 
 impl Default for SpatialListener {
@@ -189,8 +221,16 @@ impl SpatialListener {
         SpatialListener {
             left_ear_offset: Vec3::X * gap / -2.0,
             right_ear_offset: Vec3::X * gap / 2.0,
+            renderer: SpatialRenderer::default(),
+            speed_of_sound: 343.0,
         }
     }
+
+    /// Helper to set the [`SpatialRenderer`].
+    pub fn with_renderer(mut self, renderer: SpatialRenderer) -> Self {
+        self.renderer = renderer;
+        self
+    }
 }
 
 /// Use this [`Resource`] to control the global volume of all audio with a [`Volume::Relative`] volume.
@@ -251,6 +291,9 @@ pub type AudioBundle = AudioSourceBundle<AudioSource>;
 ///
 /// When Bevy begins the audio playback, an [`AudioSink`][crate::AudioSink] component will be
 /// added to the entity. You can use that component to control the audio settings during playback.
+///
+/// The sound plays into the [`AudioMixer`](crate::AudioMixer) bus of the entity's
+/// [`AudioBus`](crate::AudioBus) component, or into the master bus without one.
 #[derive(Bundle)]
 pub struct AudioSourceBundle<Source = AudioSource>
 where
@@ -1,17 +1,24 @@
 use crate::{
-    AudioSourceBundle, Decodable, GlobalVolume, PlaybackMode, PlaybackSettings, SpatialAudioSink,
-    SpatialListener, SpatialScale, Volume,
+    clock::PlaybackControl,
+    mixer::MixerGraph,
+    spatial::{ListenerState, SpatialParams},
+    AudioBus, AudioClock, AudioSinkPlayback, AudioSourceBundle, Decodable, GlobalVolume,
+    PlaybackMode, PlaybackSettings, SpatialAudioSink, SpatialEmitter, SpatialListener,
+    SpatialScale, Volume,
 };
 use bevy_asset::{Asset, Assets, Handle};
 use bevy_ecs::{prelude::*, system::SystemParam};
 use bevy_math::Vec3;
+use bevy_time::Time;
 use bevy_transform::prelude::GlobalTransform;
 use bevy_utils::tracing::warn;
-use rodio::{OutputStream, OutputStreamHandle, Sink, Source, SpatialSink};
+use rodio::{OutputStream, OutputStreamHandle, Source};
+use std::sync::Mutex;
 
 use crate::AudioSink;
 
-/// Used internally to play audio on the current "audio device"
+/// Used internally to play audio on the current "audio device", or offline with
+/// [`AudioBackend::Offline`](crate::AudioBackend::Offline)
 ///
 /// ## Note
 ///
@@ -26,6 +33,31 @@ use crate::AudioSink;
 #[derive(Resource)]
 pub(crate) struct AudioOutput {
     stream_handle: Option<OutputStreamHandle>,
+    /// The output of the master bus, rendered by [`render_offline_audio`] with the offline backend.
+    pub(crate) offline: Option<Mutex<Option<OutputSource>>>,
+}
+
+pub(crate) type OutputSource = Box<dyn Source<Item = f32> + Send>;
+
+impl AudioOutput {
+    /// An output which doesn't use an audio device.
+    pub(crate) fn offline() -> Self {
+        Self {
+            stream_handle: None,
+            offline: Some(Mutex::default()),
+        }
+    }
+
+    /// Plays the output of the master bus.
+    pub(crate) fn play(&self, source: impl Source<Item = f32> + Send + 'static) {
+        if let Some(stream_handle) = &self.stream_handle {
+            if let Err(err) = stream_handle.play_raw(source) {
+                warn!("Error playing the master audio bus: {err:?}");
+            }
+        } else if let Some(offline) = &self.offline {
+            *offline.lock().unwrap() = Some(Box::new(source));
+        }
+    }
 }
 
 impl Default for AudioOutput {
@@ -35,11 +67,13 @@ impl Default for AudioOutput {
             std::mem::forget(stream);
             Self {
                 stream_handle: Some(stream_handle),
+                offline: None,
             }
         } else {
             warn!("No audio device found.");
             Self {
                 stream_handle: None,
+                offline: None,
             }
         }
     }
@@ -85,6 +119,27 @@ impl<'w, 's> EarPositions<'w, 's> {
         (left_ear, right_ear)
     }
 
+    /// Gets the listener as seen by spatial audio sources, moving at `velocity`.
+    pub(crate) fn listener(&self, velocity: Vec3) -> ListenerState {
+        let (left_ear, right_ear) = self.get();
+        let (renderer, speed_of_sound) = self
+            .query
+            .iter()
+            .next()
+            .map(|(_, _, settings)| (settings.renderer, settings.speed_of_sound))
+            .unwrap_or_else(|| {
+                let settings = SpatialListener::default();
+                (settings.renderer, settings.speed_of_sound)
+            });
+        ListenerState {
+            left_ear,
+            right_ear,
+            velocity,
+            renderer,
+            speed_of_sound,
+        }
+    }
+
     pub(crate) fn multiple_listeners(&self) -> bool {
         self.query.iter().len() > 1
     }
@@ -97,17 +152,21 @@ impl<'w, 's> EarPositions<'w, 's> {
 /// [`AudioSink`]/[`SpatialAudioSink`] component.
 ///
 /// This system detects such entities, checks if their source asset
-/// data is available, and creates/inserts the sink.
+/// data is available, and creates/inserts the sink, playing into the
+/// entity's [`AudioBus`].
 pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
-    audio_output: Res<AudioOutput>,
     audio_sources: Res<Assets<Source>>,
     global_volume: Res<GlobalVolume>,
+    mixer_graph: Res<MixerGraph>,
+    clock: Res<AudioClock>,
     query_nonplaying: Query<
         (
             Entity,
             &Handle<Source>,
             &PlaybackSettings,
             Option<&GlobalTransform>,
+            Option<&SpatialEmitter>,
+            Option<&AudioBus>,
         ),
         (Without<AudioSink>, Without<SpatialAudioSink>),
     >,
@@ -116,17 +175,12 @@ pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
 ) where
     f32: rodio::cpal::FromSample<Source::DecoderItem>,
 {
-    let Some(stream_handle) = audio_output.stream_handle.as_ref() else {
-        // audio output unavailable; cannot play sound
-        return;
-    };
-
-    for (entity, source_handle, settings, maybe_emitter_transform) in &query_nonplaying {
+    for (entity, source_handle, settings, maybe_emitter_transform, emitter_settings, bus) in
+        &query_nonplaying
+    {
         if let Some(audio_source) = audio_sources.get(source_handle) {
             // audio data is available (has loaded), begin playback and insert sink component
             if settings.spatial {
-                let (left_ear, right_ear) = ear_positions.get();
-
                 // We can only use one `SpatialListener`. If there are more than that, then
                 // the user may have made a mistake.
                 if ear_positions.multiple_listeners() {
@@ -136,106 +190,82 @@ pub(crate) fn play_queued_audio_system<Source: Asset + Decodable>(
                     );
                 }
 
-                let emitter_translation = maybe_emitter_transform
-                    .map(|t| (t.translation() * ear_positions.scale.0).into())
+                let (emitter, emitter_forward) = maybe_emitter_transform
+                    .map(|t| (t.translation() * ear_positions.scale.0, t.forward()))
                     .unwrap_or_else(|| {
                         warn!("Spatial AudioBundle with no GlobalTransform component. Using zero.");
-                        Vec3::ZERO.into()
+                        (Vec3::ZERO, Vec3::NEG_Z)
                     });
 
-                match SpatialSink::try_new(
-                    stream_handle,
-                    emitter_translation,
-                    left_ear.into(),
-                    right_ear.into(),
-                ) {
-                    Ok(sink) => {
-                        sink.set_speed(settings.speed);
-                        match settings.volume {
-                            Volume::Relative(vol) => {
-                                sink.set_volume(vol.0 * global_volume.volume.0);
-                            }
-                            Volume::Absolute(vol) => sink.set_volume(vol.0),
-                        }
-                        if settings.paused {
-                            sink.pause();
-                        }
-                        match settings.mode {
-                            PlaybackMode::Loop => {
-                                sink.append(audio_source.decoder().repeat_infinite());
-                                commands.entity(entity).insert(SpatialAudioSink { sink });
-                            }
-                            PlaybackMode::Once => {
-                                sink.append(audio_source.decoder());
-                                commands.entity(entity).insert(SpatialAudioSink { sink });
-                            }
-                            PlaybackMode::Despawn => {
-                                sink.append(audio_source.decoder());
-                                commands
-                                    .entity(entity)
-                                    // PERF: insert as bundle to reduce archetype moves
-                                    .insert((SpatialAudioSink { sink }, PlaybackDespawnMarker));
-                            }
-                            PlaybackMode::Remove => {
-                                sink.append(audio_source.decoder());
-                                commands
-                                    .entity(entity)
-                                    // PERF: insert as bundle to reduce archetype moves
-                                    .insert((SpatialAudioSink { sink }, PlaybackRemoveMarker));
-                            }
-                        };
-                    }
-                    Err(err) => {
-                        warn!("Error playing spatial sound: {err:?}");
-                    }
+                let (sink, output) = SpatialAudioSink::new_idle(
+                    PlaybackControl::new(clock.clone(), settings.fade_in),
+                    SpatialParams {
+                        emitter,
+                        emitter_forward,
+                        emitter_velocity: Vec3::ZERO,
+                        listener: ear_positions.listener(Vec3::ZERO),
+                        settings: emitter_settings.copied().unwrap_or_default(),
+                    },
+                );
+                mixer_graph.play(bus, output, settings.start_at);
+                apply_settings(&sink, settings, &global_volume);
+                match settings.mode {
+                    PlaybackMode::Loop => sink.append(audio_source.decoder().repeat_infinite()),
+                    _ => sink.append(audio_source.decoder()),
                 }
+                insert_sink(&mut commands, entity, sink, settings.mode);
             } else {
-                match Sink::try_new(stream_handle) {
-                    Ok(sink) => {
-                        sink.set_speed(settings.speed);
-                        match settings.volume {
-                            Volume::Relative(vol) => {
-                                sink.set_volume(vol.0 * global_volume.volume.0);
-                            }
-                            Volume::Absolute(vol) => sink.set_volume(vol.0),
-                        }
-                        if settings.paused {
-                            sink.pause();
-                        }
-                        match settings.mode {
-                            PlaybackMode::Loop => {
-                                sink.append(audio_source.decoder().repeat_infinite());
-                                commands.entity(entity).insert(AudioSink { sink });
-                            }
-                            PlaybackMode::Once => {
-                                sink.append(audio_source.decoder());
-                                commands.entity(entity).insert(AudioSink { sink });
-                            }
-                            PlaybackMode::Despawn => {
-                                sink.append(audio_source.decoder());
-                                commands
-                                    .entity(entity)
-                                    // PERF: insert as bundle to reduce archetype moves
-                                    .insert((AudioSink { sink }, PlaybackDespawnMarker));
-                            }
-                            PlaybackMode::Remove => {
-                                sink.append(audio_source.decoder());
-                                commands
-                                    .entity(entity)
-                                    // PERF: insert as bundle to reduce archetype moves
-                                    .insert((AudioSink { sink }, PlaybackRemoveMarker));
-                            }
-                        };
-                    }
-                    Err(err) => {
-                        warn!("Error playing sound: {err:?}");
-                    }
+                let (sink, output) =
+                    AudioSink::new_idle(PlaybackControl::new(clock.clone(), settings.fade_in));
+                mixer_graph.play(bus, output, settings.start_at);
+                apply_settings(&sink, settings, &global_volume);
+                match settings.mode {
+                    PlaybackMode::Loop => sink.append(audio_source.decoder().repeat_infinite()),
+                    _ => sink.append(audio_source.decoder()),
                 }
+                insert_sink(&mut commands, entity, sink, settings.mode);
             }
         }
     }
 }
 
+/// Applies the initial [`PlaybackSettings`] to a new sink.
+fn apply_settings(
+    sink: &impl AudioSinkPlayback,
+    settings: &PlaybackSettings,
+    global_volume: &GlobalVolume,
+) {
+    sink.set_speed(settings.speed);
+    match settings.volume {
+        Volume::Relative(vol) => sink.set_volume(vol.0 * global_volume.volume.0),
+        Volume::Absolute(vol) => sink.set_volume(vol.0),
+    }
+    if settings.paused {
+        sink.pause();
+    }
+}
+
+/// Inserts a new sink, with the marker matching its [`PlaybackMode`].
+fn insert_sink(commands: &mut Commands, entity: Entity, sink: impl Component, mode: PlaybackMode) {
+    match mode {
+        PlaybackMode::Loop | PlaybackMode::Once => {
+            commands.entity(entity).insert(sink);
+        }
+        PlaybackMode::Despawn => {
+            commands
+                .entity(entity)
+                // PERF: insert as bundle to reduce archetype moves
+                .insert((sink, PlaybackDespawnMarker));
+        }
+        PlaybackMode::Remove => {
+            commands
+                .entity(entity)
+                // PERF: insert as bundle to reduce archetype moves
+                .insert((sink, PlaybackRemoveMarker));
+        }
+    }
+}
+
 pub(crate) fn cleanup_finished_audio<T: Decodable + Asset>(
     mut commands: Commands,
     query_nonspatial_despawn: Query<
@@ -283,21 +313,31 @@ pub(crate) fn cleanup_finished_audio<T: Decodable + Asset>(
 
 /// Run Condition to only play audio if the audio output is available
 pub(crate) fn audio_output_available(audio_output: Res<AudioOutput>) -> bool {
-    audio_output.stream_handle.is_some()
+    audio_output.stream_handle.is_some() || audio_output.offline.is_some()
 }
 
-/// Updates spatial audio sinks when emitter positions change.
+/// Updates spatial audio sinks when emitters move.
 pub(crate) fn update_emitter_positions(
-    mut emitters: Query<(&mut GlobalTransform, &SpatialAudioSink), Changed<GlobalTransform>>,
+    emitters: Query<(&GlobalTransform, &SpatialAudioSink)>,
     spatial_scale: Res<SpatialScale>,
+    time: Res<Time>,
 ) {
-    for (transform, sink) in emitters.iter_mut() {
+    for (transform, sink) in &emitters {
         let translation = transform.translation() * spatial_scale.0;
-        sink.set_emitter_position(translation);
+        sink.move_emitter(translation, transform.forward(), time.delta_seconds());
+    }
+}
+
+/// Updates spatial audio sinks when their [`SpatialEmitter`] changes.
+pub(crate) fn update_emitter_settings(
+    emitters: Query<(&SpatialEmitter, &SpatialAudioSink), Changed<SpatialEmitter>>,
+) {
+    for (settings, sink) in &emitters {
+        sink.set_emitter_settings(*settings);
     }
 }
 
-/// Updates spatial audio sink ear positions when spatial listeners change.
+/// Updates spatial audio sinks when spatial listeners change or move.
 pub(crate) fn update_listener_positions(
     mut emitters: Query<&SpatialAudioSink>,
     changed_listener: Query<
@@ -308,14 +348,30 @@ pub(crate) fn update_listener_positions(
         ),
     >,
     ear_positions: EarPositions,
+    time: Res<Time>,
+    mut previous: Local<Option<(Vec3, Vec3)>>,
 ) {
-    if !ear_positions.scale.is_changed() && changed_listener.is_empty() {
+    let (left_ear, right_ear) = ear_positions.get();
+    let center = (left_ear + right_ear) / 2.0;
+    let (velocity, velocity_changed) = match *previous {
+        Some((previous_center, previous_velocity)) => {
+            let velocity = if time.delta_seconds() > 0.0 {
+                (center - previous_center) / time.delta_seconds()
+            } else {
+                Vec3::ZERO
+            };
+            (velocity, velocity != previous_velocity)
+        }
+        None => (Vec3::ZERO, false),
+    };
+    *previous = Some((center, velocity));
+
+    if !ear_positions.scale.is_changed() && changed_listener.is_empty() && !velocity_changed {
         return;
     }
 
-    let (left_ear, right_ear) = ear_positions.get();
-
+    let listener = ear_positions.listener(velocity);
     for sink in emitters.iter_mut() {
-        sink.set_ears_position(left_ear, right_ear);
+        sink.set_listener(listener);
     }
 }
@@ -0,0 +1,248 @@
+use bevy_ecs::system::Resource;
+use rodio::Source;
+use std::{
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// The sample rate of the [`AudioClock`].
+pub(crate) const CLOCK_RATE: u32 = 44100;
+
+/// The clock of the audio thread: the time of the audio being mixed.
+///
+/// This advances with the audio actually mixed, one sample at a time, rather than with the frames
+/// of the game. Use it to start sounds and fades at exact times, with
+/// [`PlaybackSettings::with_start_at`](crate::PlaybackSettings::with_start_at) and
+/// [`AudioSinkPlayback::fade_to_at`](crate::AudioSinkPlayback::fade_to_at).
+///
+/// The clock doesn't advance when there is no audio output.
+#[derive(Resource, Clone, Default)]
+pub struct AudioClock {
+    frames: Arc<AtomicU64>,
+    /// The frame of the audio being mixed on the audio thread. Buses other than the master bus
+    /// mix blocks ahead of it, so this runs ahead of `frames` while they do.
+    mixing_frame: Arc<AtomicU64>,
+}
+
+impl AudioClock {
+    /// The time of the audio being mixed.
+    pub fn now(&self) -> Duration {
+        Self::time_at(self.frames())
+    }
+
+    /// The number of frames of audio mixed so far.
+    pub fn frames(&self) -> u64 {
+        self.frames.load(Ordering::Relaxed)
+    }
+
+    /// The number of frames per second of the clock.
+    pub fn sample_rate(&self) -> u32 {
+        CLOCK_RATE
+    }
+
+    pub(crate) fn set_frames(&self, frames: u64) {
+        self.frames.store(frames, Ordering::Relaxed);
+        self.set_mixing_frame(frames);
+    }
+
+    /// The frame of the audio being mixed, for the sources played on the audio thread.
+    pub(crate) fn mixing_frame(&self) -> u64 {
+        self.mixing_frame.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_mixing_frame(&self, frame: u64) {
+        self.mixing_frame.store(frame, Ordering::Relaxed);
+    }
+
+    /// The frame at `time`.
+    pub(crate) fn frame_at(time: Duration) -> u64 {
+        (time.as_secs_f64() * CLOCK_RATE as f64).round() as u64
+    }
+
+    /// The time at `frame`.
+    pub(crate) fn time_at(frame: u64) -> Duration {
+        Duration::from_secs_f64(frame as f64 / CLOCK_RATE as f64)
+    }
+}
+
+/// A change of the fade level of a sink, on the [`AudioClock`].
+#[derive(Clone, Copy)]
+struct Fade {
+    from: f32,
+    to: f32,
+    /// The frame the fade starts at, or `None` to start when the source starts playing.
+    start: Option<u64>,
+    frames: u64,
+}
+
+impl Fade {
+    fn level_at(&self, frame: u64) -> f32 {
+        let start = self.start.unwrap_or(frame);
+        if frame < start {
+            self.from
+        } else if frame - start >= self.frames {
+            self.to
+        } else {
+            let t = (frame - start) as f32 / self.frames as f32;
+            self.from + (self.to - self.from) * t
+        }
+    }
+}
+
+/// The playback of a sink, shared with the audio thread.
+pub(crate) struct PlaybackControl {
+    clock: AudioClock,
+    /// The playback position, in nanoseconds.
+    position: AtomicU64,
+    fade: Mutex<Fade>,
+    /// Incremented when the fade changes, so sources only lock it when needed.
+    fade_generation: AtomicU32,
+    level: AtomicU32,
+}
+
+impl PlaybackControl {
+    /// Creates the playback of a sink, fading in over `fade_in` from the start of its source.
+    pub(crate) fn new(clock: AudioClock, fade_in: Option<Duration>) -> Self {
+        let fade = match fade_in {
+            Some(fade_in) => Fade {
+                from: 0.0,
+                to: 1.0,
+                start: None,
+                frames: AudioClock::frame_at(fade_in),
+            },
+            None => Fade {
+                from: 1.0,
+                to: 1.0,
+                start: None,
+                frames: 0,
+            },
+        };
+        Self {
+            clock,
+            position: AtomicU64::new(0),
+            level: AtomicU32::new(fade.from.to_bits()),
+            fade: Mutex::new(fade),
+            fade_generation: AtomicU32::new(0),
+        }
+    }
+
+    pub(crate) fn position(&self) -> Duration {
+        Duration::from_nanos(self.position.load(Ordering::Relaxed))
+    }
+
+    pub(crate) fn fade_level(&self) -> f32 {
+        f32::from_bits(self.level.load(Ordering::Relaxed))
+    }
+
+    /// Fades from the current level to `level`, starting at `start` on the [`AudioClock`], or
+    /// right away.
+    pub(crate) fn fade_to(&self, level: f32, start: Option<Duration>, duration: Duration) {
+        let start = start.map_or_else(|| self.clock.frames(), AudioClock::frame_at);
+        let mut fade = self.fade.lock().unwrap();
+        *fade = Fade {
+            from: fade.level_at(start.max(self.clock.frames())),
+            to: level,
+            start: Some(start),
+            frames: AudioClock::frame_at(duration),
+        };
+        self.fade_generation.fetch_add(1, Ordering::Release);
+    }
+}
+
+/// Tracks the playback position of a source, and applies the fades of its sink.
+pub(crate) struct ControlledSource<S> {
+    input: S,
+    control: Arc<PlaybackControl>,
+    fade: Option<Fade>,
+    fade_generation: u32,
+    gain: f32,
+    /// The index of the next sample in the current frame.
+    channel: u16,
+    frames: u64,
+}
+
+impl<S> ControlledSource<S>
+where
+    S: Source<Item = f32>,
+{
+    pub(crate) fn new(input: S, control: Arc<PlaybackControl>) -> Self {
+        Self {
+            input,
+            control,
+            fade: None,
+            fade_generation: 0,
+            gain: 1.0,
+            channel: 0,
+            frames: 0,
+        }
+    }
+
+    fn start_frame(&mut self) {
+        let now = self.control.clock.mixing_frame();
+        let generation = self.control.fade_generation.load(Ordering::Acquire);
+        if self.fade.is_none() || generation != self.fade_generation {
+            // A fade without a start time starts now, for the game too
+            let mut fade = self.control.fade.lock().unwrap();
+            fade.start.get_or_insert(now);
+            self.fade = Some(*fade);
+            self.fade_generation = generation;
+        }
+        if let Some(fade) = &self.fade {
+            self.gain = fade.level_at(now);
+        }
+        self.control
+            .level
+            .store(self.gain.to_bits(), Ordering::Relaxed);
+
+        self.frames += 1;
+        let position =
+            self.frames as u128 * 1_000_000_000 / self.input.sample_rate().max(1) as u128;
+        self.control
+            .position
+            .store(position as u64, Ordering::Relaxed);
+    }
+}
+
+impl<S> Iterator for ControlledSource<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.input.next()?;
+        if self.channel == 0 {
+            self.start_frame();
+        }
+        self.channel = (self.channel + 1) % self.input.channels().max(1);
+        Some(sample * self.gain)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<S> Source for ControlledSource<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
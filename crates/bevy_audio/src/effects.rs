@@ -0,0 +1,311 @@
+use std::{f32::consts::TAU, time::Duration};
+
+/// An effect applied to the audio of a mixer bus.
+///
+/// Effects run on the audio thread, on blocks of interleaved samples. Add them to a bus with
+/// [`MixerBus::set_effects`](crate::MixerBus::set_effects).
+pub trait AudioEffect: Send + Sync + 'static {
+    /// Processes a block of interleaved samples in place.
+    fn process(&mut self, samples: &mut [f32], channels: u16, sample_rate: u32);
+}
+
+/// A low-pass filter, which attenuates frequencies above its cutoff.
+///
+/// Useful to muffle sounds, for example when the game is paused or the player is under water.
+pub struct LowPass {
+    /// The cutoff frequency, in hertz.
+    pub cutoff: f32,
+    state: Vec<f32>,
+}
+
+impl LowPass {
+    /// Creates a low-pass filter with the given cutoff frequency, in hertz.
+    pub fn new(cutoff: f32) -> Self {
+        Self {
+            cutoff,
+            state: Vec::new(),
+        }
+    }
+}
+
+impl AudioEffect for LowPass {
+    fn process(&mut self, samples: &mut [f32], channels: u16, sample_rate: u32) {
+        let alpha = 1.0 - (-TAU * self.cutoff / sample_rate as f32).exp();
+        self.state.resize(channels as usize, 0.0);
+        for frame in samples.chunks_exact_mut(channels as usize) {
+            for (sample, filtered) in frame.iter_mut().zip(&mut self.state) {
+                *filtered += alpha * (*sample - *filtered);
+                *sample = *filtered;
+            }
+        }
+    }
+}
+
+/// Delays of the comb filters of [`Reverb`], in samples at 44100 Hz.
+const COMB_TUNINGS: [usize; 8] = [1116, 1188, 1277, 1356, 1422, 1491, 1557, 1617];
+/// Delays of the all-pass filters of [`Reverb`], in samples at 44100 Hz.
+const ALLPASS_TUNINGS: [usize; 4] = [556, 441, 341, 225];
+/// Offset added to the delays of each channel, to decorrelate them.
+const STEREO_SPREAD: usize = 23;
+
+/// A reverb, simulating the reflections of a room.
+///
+/// This is based on the Freeverb algorithm.
+pub struct Reverb {
+    /// The size of the room, between `0.0` and `1.0`. Larger rooms have longer tails.
+    pub room_size: f32,
+    /// How much high frequencies are absorbed by the room, between `0.0` and `1.0`.
+    pub damping: f32,
+    /// The amount of reverberated sound in the output, between `0.0` and `1.0`.
+    pub wet: f32,
+    channels: Vec<ReverbChannel>,
+    sample_rate: u32,
+}
+
+impl Default for Reverb {
+    fn default() -> Self {
+        Self {
+            room_size: 0.5,
+            damping: 0.5,
+            wet: 0.3,
+            channels: Vec::new(),
+            sample_rate: 0,
+        }
+    }
+}
+
+struct ReverbChannel {
+    combs: Vec<DelayLine>,
+    allpasses: Vec<DelayLine>,
+}
+
+struct DelayLine {
+    buffer: Vec<f32>,
+    index: usize,
+    filtered: f32,
+}
+
+impl DelayLine {
+    fn new(delay: usize) -> Self {
+        Self {
+            buffer: vec![0.0; delay.max(1)],
+            index: 0,
+            filtered: 0.0,
+        }
+    }
+
+    fn comb(&mut self, input: f32, feedback: f32, damping: f32) -> f32 {
+        let output = self.buffer[self.index];
+        self.filtered = output * (1.0 - damping) + self.filtered * damping;
+        self.buffer[self.index] = input + self.filtered * feedback;
+        self.index = (self.index + 1) % self.buffer.len();
+        output
+    }
+
+    fn allpass(&mut self, input: f32) -> f32 {
+        let delayed = self.buffer[self.index];
+        self.buffer[self.index] = input + delayed * 0.5;
+        self.index = (self.index + 1) % self.buffer.len();
+        delayed - input
+    }
+}
+
+impl AudioEffect for Reverb {
+    fn process(&mut self, samples: &mut [f32], channels: u16, sample_rate: u32) {
+        if self.channels.len() != channels as usize || self.sample_rate != sample_rate {
+            let scale = sample_rate as f32 / 44100.0;
+            let delay = |tuning: usize, channel: usize| {
+                ((tuning + channel * STEREO_SPREAD) as f32 * scale) as usize
+            };
+            self.channels = (0..channels as usize)
+                .map(|channel| ReverbChannel {
+                    combs: COMB_TUNINGS
+                        .iter()
+                        .map(|&tuning| DelayLine::new(delay(tuning, channel)))
+                        .collect(),
+                    allpasses: ALLPASS_TUNINGS
+                        .iter()
+                        .map(|&tuning| DelayLine::new(delay(tuning, channel)))
+                        .collect(),
+                })
+                .collect();
+            self.sample_rate = sample_rate;
+        }
+
+        let feedback = self.room_size.clamp(0.0, 1.0) * 0.28 + 0.7;
+        let damping = self.damping.clamp(0.0, 1.0) * 0.4;
+        let wet = self.wet.clamp(0.0, 1.0);
+        for frame in samples.chunks_exact_mut(channels as usize) {
+            for (sample, channel) in frame.iter_mut().zip(&mut self.channels) {
+                let input = *sample * 0.015;
+                let mut output = channel
+                    .combs
+                    .iter_mut()
+                    .map(|comb| comb.comb(input, feedback, damping))
+                    .sum();
+                for allpass in &mut channel.allpasses {
+                    output = allpass.allpass(output);
+                }
+                *sample = *sample * (1.0 - wet) + output * 3.0 * wet;
+            }
+        }
+    }
+}
+
+/// A compressor, which reduces the volume of the loudest sounds.
+///
+/// Useful to keep a busy mix from clipping.
+pub struct Compressor {
+    /// The level above which the volume is reduced, in decibels.
+    pub threshold: f32,
+    /// How much the level above the threshold is reduced: with a ratio of `4.0`, a sound `8`
+    /// decibels above the threshold ends up `2` decibels above it.
+    pub ratio: f32,
+    /// How fast the volume is reduced when the level goes above the threshold.
+    pub attack: Duration,
+    /// How fast the volume is restored when the level goes back below the threshold.
+    pub release: Duration,
+    /// A gain applied after the compression, in decibels.
+    pub makeup_gain: f32,
+    envelope: f32,
+}
+
+impl Compressor {
+    /// Creates a compressor with the given threshold, in decibels, and ratio.
+    pub fn new(threshold: f32, ratio: f32) -> Self {
+        Self {
+            threshold,
+            ratio,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for Compressor {
+    fn default() -> Self {
+        Self {
+            threshold: -18.0,
+            ratio: 4.0,
+            attack: Duration::from_millis(10),
+            release: Duration::from_millis(100),
+            makeup_gain: 0.0,
+            envelope: 0.0,
+        }
+    }
+}
+
+/// The coefficient of a one-pole smoothing filter reaching its target in about `time`.
+pub(crate) fn smoothing_coefficient(time: Duration, steps_per_second: f32) -> f32 {
+    let steps = time.as_secs_f32() * steps_per_second;
+    if steps > 0.0 {
+        (-1.0 / steps).exp()
+    } else {
+        0.0
+    }
+}
+
+impl AudioEffect for Compressor {
+    fn process(&mut self, samples: &mut [f32], channels: u16, sample_rate: u32) {
+        let attack = smoothing_coefficient(self.attack, sample_rate as f32);
+        let release = smoothing_coefficient(self.release, sample_rate as f32);
+        let slope = 1.0 - 1.0 / self.ratio.max(1.0);
+        for frame in samples.chunks_exact_mut(channels as usize) {
+            let peak = frame
+                .iter()
+                .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+            let coefficient = if peak > self.envelope {
+                attack
+            } else {
+                release
+            };
+            self.envelope = peak + (self.envelope - peak) * coefficient;
+
+            let level = 20.0 * self.envelope.max(1e-6).log10();
+            let reduction = (level - self.threshold).max(0.0) * slope;
+            let gain = 10.0f32.powf((self.makeup_gain - reduction) / 20.0);
+            for sample in frame {
+                *sample *= gain;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    /// One second of a mono sine wave at `frequency`, with an amplitude of `1.0`.
+    fn sine(frequency: f32) -> Vec<f32> {
+        (0..SAMPLE_RATE)
+            .map(|frame| (TAU * frequency * frame as f32 / SAMPLE_RATE as f32).sin())
+            .collect()
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples
+            .iter()
+            .fold(0.0, |peak, sample| sample.abs().max(peak))
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        let sum: f32 = samples.iter().map(|sample| sample * sample).sum();
+        (sum / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn low_pass_attenuates_high_frequencies() {
+        let mut low_pass = LowPass::new(200.0);
+        let mut high = sine(10_000.0);
+        low_pass.process(&mut high, 1, SAMPLE_RATE);
+        // Past the response of the filter to the start of the sound
+        assert!(peak(&high[4410..]) < 0.05);
+
+        let mut low_pass = LowPass::new(200.0);
+        let mut dc = vec![0.5; SAMPLE_RATE as usize];
+        low_pass.process(&mut dc, 1, SAMPLE_RATE);
+        assert!(dc[4410..].iter().all(|sample| (sample - 0.5).abs() < 1e-3));
+    }
+
+    #[test]
+    fn compressor_reduces_gain_above_the_threshold() {
+        let mut compressor = Compressor::new(-20.0, 4.0);
+        let mut loud = vec![1.0; SAMPLE_RATE as usize];
+        compressor.process(&mut loud, 1, SAMPLE_RATE);
+        // 20 decibels above the threshold end up 5 decibels above it
+        let expected = 10.0f32.powf(-15.0 / 20.0);
+        assert!(loud[4410..]
+            .iter()
+            .all(|sample| (sample - expected).abs() < 1e-3));
+
+        let mut compressor = Compressor::new(-20.0, 4.0);
+        let mut quiet = sine(440.0)
+            .into_iter()
+            .map(|sample| sample * 0.05)
+            .collect::<Vec<_>>();
+        let original = quiet.clone();
+        compressor.process(&mut quiet, 1, SAMPLE_RATE);
+        assert_eq!(quiet, original);
+    }
+
+    #[test]
+    fn reverb_tail_decays_after_an_impulse() {
+        let mut reverb = Reverb {
+            wet: 1.0,
+            ..Default::default()
+        };
+        let mut samples = vec![0.0; 2 * SAMPLE_RATE as usize];
+        samples[0] = 1.0;
+        reverb.process(&mut samples, 1, SAMPLE_RATE);
+
+        let window = |start: f32| {
+            let start = (start * SAMPLE_RATE as f32) as usize;
+            rms(&samples[start..start + SAMPLE_RATE as usize / 4])
+        };
+        let early = window(0.05);
+        let late = window(1.5);
+        assert!(late > 0.0, "the reverb has a tail");
+        assert!(late < early / 4.0, "the tail decays");
+    }
+}
@@ -26,26 +26,41 @@
 mod audio;
 mod audio_output;
 mod audio_source;
+mod clock;
+mod effects;
+mod mixer;
+mod offline;
 mod pitch;
 mod sinks;
+mod spatial;
+mod streaming;
 
 #[allow(missing_docs)]
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
-        AudioBundle, AudioSink, AudioSinkPlayback, AudioSource, AudioSourceBundle, Decodable,
-        GlobalVolume, Pitch, PitchBundle, PlaybackSettings, SpatialAudioSink, SpatialListener,
+        AudioBundle, AudioBus, AudioClock, AudioMixer, AudioSink, AudioSinkPlayback, AudioSource,
+        AudioSourceBundle, Decodable, GlobalVolume, Pitch, PitchBundle, PlaybackSettings,
+        SpatialAudioSink, SpatialEmitter, SpatialListener, StreamingAudio, StreamingAudioBundle,
     };
 }
 
 pub use audio::*;
 pub use audio_source::*;
+pub use clock::AudioClock;
+pub use effects::*;
+pub use mixer::{AudioBus, AudioMixer, Ducking, MixerBus};
+pub use offline::{AudioBackend, AudioCapture};
 pub use pitch::*;
 
 pub use rodio::cpal::Sample as CpalSample;
 pub use rodio::source::Source;
 pub use rodio::Sample;
 pub use sinks::*;
+pub use spatial::{DistanceModel, EmitterCone, SpatialEmitter, SpatialRenderer};
+pub use streaming::{
+    StreamingAudio, StreamingAudioBundle, StreamingAudioUnderrun, StreamingDecoder,
+};
 
 use bevy_app::prelude::*;
 use bevy_asset::{Asset, AssetApp};
@@ -53,6 +68,9 @@ use bevy_ecs::prelude::*;
 use bevy_transform::TransformSystem;
 
 use audio_output::*;
+use mixer::*;
+use offline::render_offline_audio;
+use streaming::report_streaming_underruns;
 
 /// Set for the audio playback systems, so they can share a run condition
 #[derive(SystemSet, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
@@ -68,6 +86,8 @@ pub struct AudioPlugin {
     /// The scale factor applied to the positions of audio sources and listeners for
     /// spatial audio.
     pub spatial_scale: SpatialScale,
+    /// Where audio is played.
+    pub backend: AudioBackend,
 }
 
 impl Plugin for AudioPlugin {
@@ -80,7 +100,31 @@ impl Plugin for AudioPlugin {
                     .run_if(audio_output_available)
                     .after(TransformSystem::TransformPropagate), // For spatial audio transforms
             )
-            .init_resource::<AudioOutput>();
+            .init_resource::<AudioClock>()
+            .init_resource::<AudioMixer>()
+            .init_resource::<MixerGraph>()
+            .add_systems(
+                PostUpdate,
+                (
+                    update_mixer,
+                    // Registered once, as they track the velocity of emitters and listeners
+                    update_emitter_positions,
+                    update_emitter_settings,
+                    update_listener_positions,
+                )
+                    .in_set(AudioPlaySet),
+            );
+
+        match self.backend {
+            AudioBackend::Device => {
+                app.init_resource::<AudioOutput>();
+            }
+            AudioBackend::Offline => {
+                app.insert_resource(AudioOutput::offline())
+                    .init_resource::<AudioCapture>()
+                    .add_systems(PostUpdate, render_offline_audio.after(AudioPlaySet));
+            }
+        }
 
         #[cfg(any(feature = "mp3", feature = "flac", feature = "wav", feature = "vorbis"))]
         {
@@ -89,6 +133,9 @@ impl Plugin for AudioPlugin {
         }
 
         app.add_audio_source::<Pitch>();
+        app.add_audio_source::<StreamingAudio>()
+            .add_event::<StreamingAudioUnderrun>()
+            .add_systems(PostUpdate, report_streaming_underruns);
     }
 }
 
@@ -100,11 +147,11 @@ impl AddAudioSource for App {
     {
         self.init_asset::<T>().add_systems(
             PostUpdate,
-            play_queued_audio_system::<T>.in_set(AudioPlaySet),
+            play_queued_audio_system::<T>
+                .in_set(AudioPlaySet)
+                .after(update_mixer),
         );
         self.add_systems(PostUpdate, cleanup_finished_audio::<T>.in_set(AudioPlaySet));
-        self.add_systems(PostUpdate, update_emitter_positions.in_set(AudioPlaySet));
-        self.add_systems(PostUpdate, update_listener_positions.in_set(AudioPlaySet));
         self
     }
 }
@@ -0,0 +1,741 @@
+use crate::{
+    clock::CLOCK_RATE, effects::smoothing_coefficient, AudioClock, AudioEffect, AudioOutput,
+    VolumeLevel,
+};
+use bevy_ecs::prelude::*;
+use bevy_utils::{tracing::warn, HashMap, HashSet};
+use rodio::{
+    dynamic_mixer::{self, DynamicMixer, DynamicMixerController},
+    Source,
+};
+use std::{
+    borrow::Cow,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// The number of channels of the mixer buses.
+pub(crate) const CHANNELS: u16 = 2;
+/// The sample rate of the mixer buses, which drive the [`AudioClock`].
+const SAMPLE_RATE: u32 = CLOCK_RATE;
+/// The number of frames mixed at once by a bus. Volume changes and effects apply per block.
+const BLOCK_FRAMES: usize = 256;
+
+/// The [`AudioMixer`] bus an audio entity plays into.
+///
+/// Add this component next to an [`AudioSourceBundle`](crate::AudioSourceBundle) to route its
+/// sound. Entities without it play into [`AudioBus::MASTER`].
+#[derive(Component, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AudioBus(pub Cow<'static, str>);
+
+impl AudioBus {
+    /// The bus every other bus plays into.
+    pub const MASTER: Self = Self(Cow::Borrowed("master"));
+    /// A bus for music.
+    pub const MUSIC: Self = Self(Cow::Borrowed("music"));
+    /// A bus for sound effects.
+    pub const SFX: Self = Self(Cow::Borrowed("sfx"));
+    /// A bus for dialogue.
+    pub const VOICE: Self = Self(Cow::Borrowed("voice"));
+    /// A bus for user interface sounds.
+    pub const UI: Self = Self(Cow::Borrowed("ui"));
+
+    /// Creates a bus with the given name.
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self(name.into())
+    }
+}
+
+impl Default for AudioBus {
+    fn default() -> Self {
+        Self::MASTER
+    }
+}
+
+/// Lowers the volume of a bus while another bus is playing, such as music under dialogue.
+#[derive(Clone, Debug)]
+pub struct Ducking {
+    /// The bus whose sound lowers the volume.
+    pub trigger: AudioBus,
+    /// The volume multiplier applied while the trigger bus is playing.
+    pub volume: VolumeLevel,
+    /// The peak amplitude above which the trigger bus is considered playing.
+    pub threshold: f32,
+    /// How long it takes to lower the volume.
+    pub attack: Duration,
+    /// How long it takes to restore the volume.
+    pub release: Duration,
+}
+
+impl Ducking {
+    /// Lowers the volume of a bus to `volume` while `trigger` is playing.
+    pub fn new(trigger: AudioBus, volume: f32) -> Self {
+        Self {
+            trigger,
+            volume: VolumeLevel::new(volume),
+            threshold: 0.01,
+            attack: Duration::from_millis(50),
+            release: Duration::from_millis(500),
+        }
+    }
+}
+
+/// The settings of a bus of the [`AudioMixer`].
+pub struct MixerBus {
+    parent: Option<AudioBus>,
+    /// The volume of the bus, applied on top of the volume of its sources.
+    pub volume: VolumeLevel,
+    /// Silences the bus, and the buses playing into it.
+    pub muted: bool,
+    /// Silences every bus which isn't soloed, except the buses playing into a soloed bus and
+    /// the buses a soloed bus plays into.
+    ///
+    /// Sources playing directly into the parents of a soloed bus are still heard.
+    pub solo: bool,
+    /// Lowers the volume of the bus while another bus is playing.
+    pub ducking: Option<Ducking>,
+    new_effects: Option<Vec<Box<dyn AudioEffect>>>,
+}
+
+impl MixerBus {
+    fn new(parent: Option<AudioBus>) -> Self {
+        Self {
+            parent,
+            volume: VolumeLevel::default(),
+            muted: false,
+            solo: false,
+            ducking: None,
+            new_effects: None,
+        }
+    }
+
+    /// The bus this bus plays into, or `None` for [`AudioBus::MASTER`].
+    pub fn parent(&self) -> Option<&AudioBus> {
+        self.parent.as_ref()
+    }
+
+    /// Replaces the chain of effects applied to the sound of this bus, in order.
+    ///
+    /// The effects are moved to the audio thread, and replace the current ones the next time the
+    /// mixer is updated.
+    pub fn set_effects(&mut self, effects: Vec<Box<dyn AudioEffect>>) {
+        self.new_effects = Some(effects);
+    }
+}
+
+/// Groups audio into buses, which can be controlled together.
+///
+/// Every bus plays into a parent bus, up to [`AudioBus::MASTER`]. By default, the mixer has
+/// [`AudioBus::MUSIC`], [`AudioBus::SFX`], [`AudioBus::VOICE`] and [`AudioBus::UI`] buses
+/// playing into the master bus. Audio entities choose their bus with the [`AudioBus`] component.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_audio::{AudioBus, AudioMixer, Ducking, LowPass};
+/// fn setup_mixer(mut mixer: ResMut<AudioMixer>) {
+///     let music = mixer.bus_mut(&AudioBus::MUSIC).unwrap();
+///     music.volume = bevy_audio::VolumeLevel::new(0.8);
+///     music.ducking = Some(Ducking::new(AudioBus::VOICE, 0.3));
+///
+///     mixer
+///         .add_bus(AudioBus::new("ambience"), AudioBus::SFX)
+///         .set_effects(vec![Box::new(LowPass::new(2000.0))]);
+/// }
+/// ```
+#[derive(Resource)]
+pub struct AudioMixer {
+    buses: HashMap<AudioBus, MixerBus>,
+}
+
+impl Default for AudioMixer {
+    fn default() -> Self {
+        let mut mixer = Self {
+            buses: HashMap::default(),
+        };
+        mixer.buses.insert(AudioBus::MASTER, MixerBus::new(None));
+        for bus in [
+            AudioBus::MUSIC,
+            AudioBus::SFX,
+            AudioBus::VOICE,
+            AudioBus::UI,
+        ] {
+            mixer.add_bus(bus, AudioBus::MASTER);
+        }
+        mixer
+    }
+}
+
+impl AudioMixer {
+    /// Adds a bus playing into `parent`, and returns its settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bus` already exists, or if `parent` doesn't exist.
+    pub fn add_bus(&mut self, bus: AudioBus, parent: AudioBus) -> &mut MixerBus {
+        assert!(
+            self.buses.contains_key(&parent),
+            "The parent bus {parent:?} does not exist."
+        );
+        assert!(
+            !self.buses.contains_key(&bus),
+            "The bus {bus:?} already exists."
+        );
+        self.buses
+            .entry(bus)
+            .or_insert_with(|| MixerBus::new(Some(parent)))
+    }
+
+    /// Gets the settings of `bus`.
+    pub fn bus(&self, bus: &AudioBus) -> Option<&MixerBus> {
+        self.buses.get(bus)
+    }
+
+    /// Gets the settings of `bus` mutably.
+    pub fn bus_mut(&mut self, bus: &AudioBus) -> Option<&mut MixerBus> {
+        self.buses.get_mut(bus)
+    }
+
+    /// Iterates over the buses of the mixer.
+    pub fn iter(&self) -> impl Iterator<Item = (&AudioBus, &MixerBus)> {
+        self.buses.iter()
+    }
+
+    /// Iterates over `bus` and the buses it plays into.
+    fn ancestors<'a>(&'a self, bus: &'a AudioBus) -> impl Iterator<Item = &'a AudioBus> {
+        std::iter::successors(Some(bus), |bus| {
+            self.buses.get(*bus).and_then(|settings| settings.parent())
+        })
+    }
+
+    /// Returns the buses which aren't silenced by a soloed bus.
+    fn audible_buses(&self) -> HashSet<AudioBus> {
+        let soloed: Vec<&AudioBus> = self
+            .buses
+            .iter()
+            .filter(|(_, settings)| settings.solo)
+            .map(|(bus, _)| bus)
+            .collect();
+        if soloed.is_empty() {
+            return self.buses.keys().cloned().collect();
+        }
+        let mut audible: HashSet<AudioBus> = soloed
+            .iter()
+            .flat_map(|bus| self.ancestors(bus))
+            .cloned()
+            .collect();
+        for bus in self.buses.keys() {
+            if self.ancestors(bus).any(|bus| self.buses[bus].solo) {
+                audible.insert(bus.clone());
+            }
+        }
+        audible
+    }
+}
+
+/// The state of a bus, shared with the audio thread.
+struct BusState {
+    volume: AtomicU32,
+    audible: AtomicBool,
+    /// The peak amplitude of the last block mixed by the bus.
+    peak: AtomicU32,
+    processing: Mutex<BusProcessing>,
+    /// Sources waiting to start playing, with the frame of the [`AudioClock`] they start at.
+    scheduled: Mutex<Vec<(u64, ScheduledSource)>>,
+}
+
+type ScheduledSource = Box<dyn Source<Item = f32> + Send>;
+
+#[derive(Default)]
+struct BusProcessing {
+    effects: Vec<Box<dyn AudioEffect>>,
+    ducking: Option<(Arc<BusState>, Ducking)>,
+}
+
+impl Default for BusState {
+    fn default() -> Self {
+        Self {
+            volume: AtomicU32::new(1.0f32.to_bits()),
+            audible: AtomicBool::new(true),
+            peak: AtomicU32::new(0.0f32.to_bits()),
+            processing: Default::default(),
+            scheduled: Default::default(),
+        }
+    }
+}
+
+impl BusState {
+    fn load(value: &AtomicU32) -> f32 {
+        f32::from_bits(value.load(Ordering::Relaxed))
+    }
+
+    fn store(value: &AtomicU32, new: f32) {
+        value.store(new.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// The output of a bus: the sum of its sources, with its effects, volume and ducking applied.
+///
+/// This never ends, so buses stay alive while they have nothing to play.
+struct BusOutput {
+    controller: Arc<DynamicMixerController<f32>>,
+    mixer: DynamicMixer<f32>,
+    state: Arc<BusState>,
+    clock: AudioClock,
+    /// The number of frames mixed, if this is the master bus, which drives the clock.
+    clock_frames: Option<u64>,
+    block: Vec<f32>,
+    position: usize,
+    gain: f32,
+    ducking_gain: f32,
+}
+
+impl BusOutput {
+    fn mix_block(&mut self) {
+        let channels = CHANNELS as usize;
+        // Other buses are mixed while their parent mixes, so they start at the frame it mixes
+        let block_start = self
+            .clock_frames
+            .unwrap_or_else(|| self.clock.mixing_frame());
+        let block_end = block_start + BLOCK_FRAMES as u64;
+        let mut starting: Vec<(u64, ScheduledSource)> = Vec::new();
+        {
+            let mut scheduled = self.state.scheduled.lock().unwrap();
+            if scheduled.iter().any(|(start, _)| *start < block_end) {
+                let waiting;
+                (starting, waiting) = std::mem::take(&mut *scheduled)
+                    .into_iter()
+                    .partition(|(start, _)| *start < block_end);
+                *scheduled = waiting;
+            }
+        }
+        // Last to start first, so they can be popped
+        starting.sort_by_key(|(start, _)| std::cmp::Reverse(*start));
+
+        self.block.clear();
+        for frame in block_start..block_end {
+            // The sources of the bus fade and start at the frame they're mixed for
+            if self.clock_frames.is_some() {
+                self.clock.set_frames(frame);
+            } else {
+                self.clock.set_mixing_frame(frame);
+            }
+            while starting.last().is_some_and(|(start, _)| *start <= frame) {
+                let (_, source) = starting.pop().unwrap();
+                self.controller.add(source);
+            }
+            for _ in 0..channels {
+                self.block.push(self.mixer.next().unwrap_or(0.0));
+            }
+        }
+        match &mut self.clock_frames {
+            Some(frames) => *frames = block_end,
+            // The parent bus is still mixing the first frame of the block
+            None => self.clock.set_mixing_frame(block_start),
+        }
+        self.position = 0;
+
+        let blocks_per_second = SAMPLE_RATE as f32 / BLOCK_FRAMES as f32;
+        let mut processing = self.state.processing.lock().unwrap();
+        for effect in &mut processing.effects {
+            effect.process(&mut self.block, CHANNELS, SAMPLE_RATE);
+        }
+        self.ducking_gain = match &processing.ducking {
+            Some((trigger, ducking)) => {
+                let target = if BusState::load(&trigger.peak) > ducking.threshold {
+                    ducking.volume.get()
+                } else {
+                    1.0
+                };
+                let time = if target < self.ducking_gain {
+                    ducking.attack
+                } else {
+                    ducking.release
+                };
+                let coefficient = smoothing_coefficient(time, blocks_per_second);
+                target + (self.ducking_gain - target) * coefficient
+            }
+            None => 1.0,
+        };
+        drop(processing);
+
+        let target = if self.state.audible.load(Ordering::Relaxed) {
+            BusState::load(&self.state.volume) * self.ducking_gain
+        } else {
+            0.0
+        };
+        // Ramp the gain over the block, to avoid clicks on sudden changes
+        let step = (target - self.gain) / BLOCK_FRAMES as f32;
+        let mut peak = 0.0f32;
+        for (index, frame) in self.block.chunks_exact_mut(channels).enumerate() {
+            let gain = self.gain + step * (index + 1) as f32;
+            for sample in frame {
+                *sample *= gain;
+                peak = peak.max(sample.abs());
+            }
+        }
+        self.gain = target;
+        BusState::store(&self.state.peak, peak);
+    }
+}
+
+impl Iterator for BusOutput {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.position == self.block.len() {
+            self.mix_block();
+        }
+        let sample = self.block[self.position];
+        self.position += 1;
+        Some(sample)
+    }
+}
+
+impl Source for BusOutput {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        CHANNELS
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+struct BusNode {
+    controller: Arc<DynamicMixerController<f32>>,
+    state: Arc<BusState>,
+}
+
+/// The buses of the [`AudioMixer`] playing on the audio thread.
+#[derive(Resource, Default)]
+pub(crate) struct MixerGraph {
+    nodes: HashMap<AudioBus, BusNode>,
+}
+
+impl MixerGraph {
+    /// Plays `source` into `bus`, or into the master bus if `bus` is `None`.
+    ///
+    /// The source starts at `start` on the [`AudioClock`], or right away.
+    pub(crate) fn play(
+        &self,
+        bus: Option<&AudioBus>,
+        source: impl Source<Item = f32> + Send + 'static,
+        start: Option<Duration>,
+    ) {
+        let bus = bus.unwrap_or(&AudioBus::MASTER);
+        let node = self.nodes.get(bus).or_else(|| {
+            if *bus != AudioBus::MASTER {
+                warn!("The audio bus {bus:?} does not exist. Playing into the master bus.");
+            }
+            self.nodes.get(&AudioBus::MASTER)
+        });
+        let Some(node) = node else {
+            warn!("The master audio bus isn't playing yet. The sound is skipped.");
+            return;
+        };
+        match start {
+            Some(start) => node
+                .state
+                .scheduled
+                .lock()
+                .unwrap()
+                .push((AudioClock::frame_at(start), Box::new(source))),
+            None => node.controller.add(source),
+        }
+    }
+
+    /// Starts playing `bus` and its parents, if they aren't playing yet.
+    fn start_bus(
+        &mut self,
+        bus: &AudioBus,
+        mixer: &AudioMixer,
+        audio_output: &AudioOutput,
+        clock: &AudioClock,
+    ) {
+        if self.nodes.contains_key(bus) {
+            return;
+        }
+        let parent = mixer.buses[bus].parent();
+        if let Some(parent) = parent {
+            self.start_bus(parent, mixer, audio_output, clock);
+        }
+
+        let (controller, mixer) = dynamic_mixer::mixer(CHANNELS, SAMPLE_RATE);
+        let state = Arc::new(BusState::default());
+        let output = BusOutput {
+            controller: controller.clone(),
+            mixer,
+            state: state.clone(),
+            clock: clock.clone(),
+            clock_frames: parent.is_none().then_some(clock.frames()),
+            block: Vec::new(),
+            position: 0,
+            gain: 1.0,
+            ducking_gain: 1.0,
+        };
+        match parent {
+            Some(parent) => self.nodes[parent].controller.add(output),
+            None => audio_output.play(output),
+        }
+        self.nodes
+            .insert(bus.clone(), BusNode { controller, state });
+    }
+}
+
+/// Applies the changes of the [`AudioMixer`] to the buses playing on the audio thread.
+pub(crate) fn update_mixer(
+    audio_output: Res<AudioOutput>,
+    clock: Res<AudioClock>,
+    mut mixer: ResMut<AudioMixer>,
+    mut graph: ResMut<MixerGraph>,
+) {
+    if !mixer.is_changed() {
+        return;
+    }
+
+    let buses: Vec<AudioBus> = mixer.buses.keys().cloned().collect();
+    for bus in &buses {
+        graph.start_bus(bus, &mixer, &audio_output, &clock);
+    }
+
+    let audible = mixer.audible_buses();
+    // Taking the new effects must not trigger another update
+    for (bus, settings) in mixer.bypass_change_detection().buses.iter_mut() {
+        let state = &graph.nodes[bus].state;
+        BusState::store(&state.volume, settings.volume.get());
+        state
+            .audible
+            .store(!settings.muted && audible.contains(bus), Ordering::Relaxed);
+
+        let mut processing = state.processing.lock().unwrap();
+        if let Some(effects) = settings.new_effects.take() {
+            processing.effects = effects;
+        }
+        processing.ducking = settings.ducking.as_ref().and_then(|ducking| {
+            let trigger = graph.nodes.get(&ducking.trigger);
+            if trigger.is_none() {
+                warn!(
+                    "The audio bus {:?} ducking {bus:?} does not exist.",
+                    ducking.trigger
+                );
+            }
+            trigger.map(|trigger| (trigger.state.clone(), ducking.clone()))
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        offline::tests::offline_app, AudioCapture, PlaybackSettings, StreamingAudio,
+        StreamingAudioBundle,
+    };
+    use bevy_app::App;
+    use bevy_asset::Assets;
+    use std::ops::Range;
+
+    /// Plays a constant `level` into `bus` for `duration`.
+    fn play_constant(app: &mut App, bus: AudioBus, level: f32, duration: Duration) {
+        let stream = StreamingAudio::new(1, SAMPLE_RATE, duration);
+        let frames = (duration.as_secs_f64() * SAMPLE_RATE as f64) as usize;
+        stream.push(&vec![level; frames]);
+        stream.finish();
+        let source = app
+            .world
+            .resource_mut::<Assets<StreamingAudio>>()
+            .add(stream);
+        app.world.spawn((
+            StreamingAudioBundle {
+                source,
+                ..Default::default()
+            },
+            bus,
+        ));
+    }
+
+    fn peak(app: &App, range: Range<u64>) -> f32 {
+        app.world
+            .resource::<AudioCapture>()
+            .peak(Duration::from_millis(range.start)..Duration::from_millis(range.end))
+    }
+
+    /// Plays `music_level` into the music bus and `sfx_level` into the sfx bus for 500 ms, with
+    /// the mixer set up by `setup`, then returns the peak level while they played.
+    fn mixed_peak(setup: impl FnOnce(&mut AudioMixer), music_level: f32, sfx_level: f32) -> f32 {
+        let mut app = offline_app();
+        setup(&mut app.world.resource_mut::<AudioMixer>());
+        let duration = Duration::from_millis(500);
+        play_constant(&mut app, AudioBus::MUSIC, music_level, duration);
+        play_constant(&mut app, AudioBus::SFX, sfx_level, duration);
+        for _ in 0..6 {
+            app.update();
+        }
+        peak(&app, 100..400)
+    }
+
+    #[test]
+    fn play_before_the_master_bus_starts() {
+        // The sound is skipped instead of panicking
+        let source = rodio::source::Zero::<f32>::new(CHANNELS, SAMPLE_RATE);
+        MixerGraph::default().play(Some(&AudioBus::SFX), source, None);
+    }
+
+    #[test]
+    fn audible_buses_with_solo() {
+        let mut mixer = AudioMixer::default();
+        mixer.add_bus(AudioBus::new("score"), AudioBus::MUSIC);
+        mixer.add_bus(AudioBus::new("ambience"), AudioBus::SFX);
+        assert_eq!(mixer.audible_buses().len(), 7);
+
+        mixer.bus_mut(&AudioBus::MUSIC).unwrap().solo = true;
+        let audible = mixer.audible_buses();
+        // The buses playing into music and the buses it plays into
+        for bus in [AudioBus::MASTER, AudioBus::MUSIC, AudioBus::new("score")] {
+            assert!(audible.contains(&bus), "{bus:?} should be audible");
+        }
+        assert_eq!(audible.len(), 3);
+
+        mixer.bus_mut(&AudioBus::new("ambience")).unwrap().solo = true;
+        let audible = mixer.audible_buses();
+        assert!(audible.contains(&AudioBus::SFX));
+        assert!(audible.contains(&AudioBus::new("ambience")));
+        assert!(!audible.contains(&AudioBus::VOICE));
+        assert!(!audible.contains(&AudioBus::UI));
+        assert_eq!(audible.len(), 5);
+    }
+
+    #[test]
+    fn bus_volume_mute_and_solo() {
+        let unchanged = mixed_peak(|_| {}, 0.4, 0.2);
+        assert!((unchanged - 0.6).abs() < 1e-3);
+
+        let lowered = mixed_peak(
+            |mixer| mixer.bus_mut(&AudioBus::MUSIC).unwrap().volume = VolumeLevel::new(0.5),
+            0.4,
+            0.2,
+        );
+        assert!((lowered - 0.4).abs() < 1e-3);
+
+        let muted = mixed_peak(
+            |mixer| mixer.bus_mut(&AudioBus::MUSIC).unwrap().muted = true,
+            0.4,
+            0.2,
+        );
+        assert!((muted - 0.2).abs() < 1e-3);
+
+        let soloed = mixed_peak(
+            |mixer| mixer.bus_mut(&AudioBus::MUSIC).unwrap().solo = true,
+            0.4,
+            0.2,
+        );
+        assert!((soloed - 0.4).abs() < 1e-3);
+
+        // Muting the master bus silences every bus
+        let silenced = mixed_peak(
+            |mixer| mixer.bus_mut(&AudioBus::MASTER).unwrap().muted = true,
+            0.4,
+            0.2,
+        );
+        assert_eq!(silenced, 0.0);
+    }
+
+    /// Scales the samples of a bus.
+    struct Gain(f32);
+
+    impl AudioEffect for Gain {
+        fn process(&mut self, samples: &mut [f32], _channels: u16, _sample_rate: u32) {
+            for sample in samples {
+                *sample *= self.0;
+            }
+        }
+    }
+
+    #[test]
+    fn bus_effects() {
+        let halved = mixed_peak(
+            |mixer| {
+                mixer
+                    .bus_mut(&AudioBus::MUSIC)
+                    .unwrap()
+                    .set_effects(vec![Box::new(Gain(0.5))]);
+            },
+            0.4,
+            0.2,
+        );
+        // Only the music is halved
+        assert!((halved - 0.4).abs() < 1e-3);
+    }
+
+    #[test]
+    fn ducking_lowers_the_volume_while_the_trigger_plays() {
+        let mut app = offline_app();
+        {
+            let mut mixer = app.world.resource_mut::<AudioMixer>();
+            let music = mixer.bus_mut(&AudioBus::MUSIC).unwrap();
+            music.ducking = Some(Ducking {
+                attack: Duration::ZERO,
+                release: Duration::ZERO,
+                ..Ducking::new(AudioBus::VOICE, 0.25)
+            });
+        }
+        play_constant(&mut app, AudioBus::MUSIC, 0.8, Duration::from_millis(800));
+        play_constant(&mut app, AudioBus::VOICE, 0.1, Duration::from_millis(300));
+        for _ in 0..9 {
+            app.update();
+        }
+
+        // The music is ducked under the voice, then restored once the voice ends
+        assert!((peak(&app, 50..250) - (0.8 * 0.25 + 0.1)).abs() < 1e-3);
+        assert!((peak(&app, 350..750) - 0.8).abs() < 1e-3);
+    }
+
+    #[test]
+    fn scheduled_fade_in_on_a_bus() {
+        let mut app = offline_app();
+        let stream = StreamingAudio::new(1, SAMPLE_RATE, Duration::from_millis(300));
+        stream.push(&vec![1.0; SAMPLE_RATE as usize * 3 / 10]);
+        stream.finish();
+        let source = app
+            .world
+            .resource_mut::<Assets<StreamingAudio>>()
+            .add(stream);
+        app.world.spawn((
+            StreamingAudioBundle {
+                source,
+                settings: PlaybackSettings::ONCE
+                    .with_start_at(Duration::from_millis(200))
+                    .with_fade_in(Duration::from_millis(100)),
+            },
+            AudioBus::MUSIC,
+        ));
+        for _ in 0..6 {
+            app.update();
+        }
+
+        assert_eq!(peak(&app, 0..200), 0.0);
+        // The fade starts with the sound and rises every frame, not every block of the bus
+        let fade_frames = SAMPLE_RATE as usize / 10;
+        let capture = app.world.resource::<AudioCapture>();
+        let fade = capture.samples_in(Duration::from_millis(200)..Duration::from_millis(300));
+        for (frame, sample) in fade.iter().step_by(CHANNELS as usize).enumerate() {
+            let expected = frame as f32 / fade_frames as f32;
+            assert!(
+                (sample - expected).abs() < 1e-3,
+                "frame {frame} of the fade is at {sample} instead of {expected}"
+            );
+        }
+        assert!((peak(&app, 300..500) - 1.0).abs() < 1e-3);
+    }
+}
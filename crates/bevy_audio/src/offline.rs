@@ -0,0 +1,331 @@
+use crate::{audio_output::AudioOutput, clock::CLOCK_RATE, mixer::CHANNELS};
+use bevy_ecs::prelude::*;
+use bevy_time::Time;
+use std::{io, ops::Range, path::Path, time::Duration};
+
+/// Where the [`AudioPlugin`](crate::AudioPlugin) plays audio.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AudioBackend {
+    /// Plays audio on the default audio device. Nothing is played if there is none.
+    #[default]
+    Device,
+    /// Mixes audio into the [`AudioCapture`] resource, at the pace of [`Time`], without an audio
+    /// device.
+    ///
+    /// This is useful to test audio on machines without sound hardware, or to capture the audio
+    /// of a replay. With a manual [`TimeUpdateStrategy`](bevy_time::TimeUpdateStrategy), the
+    /// captured audio is deterministic.
+    Offline,
+}
+
+/// The audio mixed with [`AudioBackend::Offline`].
+///
+/// Each frame, the audio for the time elapsed since the previous frame is mixed and appended
+/// to the capture. The time of the capture matches the [`AudioClock`](crate::AudioClock), even
+/// after it's [cleared](Self::clear).
+///
+/// ```no_run
+/// # use bevy_app::App;
+/// # use bevy_asset::AssetPlugin;
+/// # use bevy_audio::{AudioBackend, AudioCapture, AudioPlugin};
+/// # use bevy_core::TaskPoolPlugin;
+/// # use bevy_time::TimePlugin;
+/// # use std::time::Duration;
+/// let mut app = App::new();
+/// app.add_plugins((
+///     TaskPoolPlugin::default(),
+///     TimePlugin,
+///     AssetPlugin::default(),
+///     AudioPlugin {
+///         backend: AudioBackend::Offline,
+///         ..Default::default()
+///     },
+/// ));
+/// // Play sounds and update the app...
+/// app.update();
+///
+/// let capture = app.world.resource::<AudioCapture>();
+/// assert!(capture.peak(Duration::from_millis(500)..Duration::from_secs(1)) > 0.1);
+/// capture.save_wav("replay.wav").unwrap();
+/// ```
+#[derive(Resource)]
+pub struct AudioCapture {
+    samples: Vec<f32>,
+    /// The number of frames cleared before the first of the samples.
+    start_frame: u64,
+    channels: u16,
+    sample_rate: u32,
+    /// The fraction of a frame left to mix, as frames don't last a whole number of samples.
+    pending_frames: f64,
+}
+
+impl Default for AudioCapture {
+    fn default() -> Self {
+        Self {
+            samples: Vec::new(),
+            start_frame: 0,
+            channels: CHANNELS,
+            sample_rate: CLOCK_RATE,
+            pending_frames: 0.0,
+        }
+    }
+}
+
+impl AudioCapture {
+    /// The interleaved samples mixed so far, starting at [`start`](Self::start).
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// The number of channels of the samples.
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// The number of frames per second of the samples.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// The time of the first of the [`samples`](Self::samples), which is zero unless the capture
+    /// was [cleared](Self::clear).
+    pub fn start(&self) -> Duration {
+        Duration::from_secs_f64(self.start_frame as f64 / self.sample_rate as f64)
+    }
+
+    /// The duration of the audio mixed so far, from [`start`](Self::start).
+    pub fn duration(&self) -> Duration {
+        let frames = self.samples.len() / self.channels as usize;
+        Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
+    }
+
+    /// The interleaved samples mixed between two times.
+    pub fn samples_in(&self, range: Range<Duration>) -> &[f32] {
+        let sample = |time: Duration| {
+            let frame = (time.as_secs_f64() * self.sample_rate as f64).round() as u64;
+            let frame = frame.saturating_sub(self.start_frame) as usize;
+            (frame * self.channels as usize).min(self.samples.len())
+        };
+        let end = sample(range.end);
+        &self.samples[sample(range.start).min(end)..end]
+    }
+
+    /// The highest amplitude between two times, `0.0` being silence.
+    pub fn peak(&self, range: Range<Duration>) -> f32 {
+        self.samples_in(range)
+            .iter()
+            .fold(0.0, |peak, sample| sample.abs().max(peak))
+    }
+
+    /// The root mean square of the amplitude between two times: the average level of the audio.
+    pub fn rms(&self, range: Range<Duration>) -> f32 {
+        let samples = self.samples_in(range);
+        if samples.is_empty() {
+            return 0.0;
+        }
+        let sum: f32 = samples.iter().map(|sample| sample * sample).sum();
+        (sum / samples.len() as f32).sqrt()
+    }
+
+    /// Discards the audio mixed so far. The audio mixed next still starts at the current time of
+    /// the [`AudioClock`](crate::AudioClock).
+    pub fn clear(&mut self) {
+        self.start_frame += (self.samples.len() / self.channels as usize) as u64;
+        self.samples.clear();
+    }
+
+    /// Encodes the audio mixed so far as a 16-bit WAV file.
+    pub fn to_wav(&self) -> Vec<u8> {
+        let data_len = (self.samples.len() * 2) as u32;
+        let block_align = self.channels * 2;
+        let mut wav = Vec::with_capacity(44 + data_len as usize);
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVE");
+        wav.extend_from_slice(b"fmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        // Integer PCM
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&self.channels.to_le_bytes());
+        wav.extend_from_slice(&self.sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(self.sample_rate * block_align as u32).to_le_bytes());
+        wav.extend_from_slice(&block_align.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for sample in &self.samples {
+            let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            wav.extend_from_slice(&sample.to_le_bytes());
+        }
+        wav
+    }
+
+    /// Writes the audio mixed so far to a 16-bit WAV file.
+    pub fn save_wav(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_wav())
+    }
+}
+
+/// Mixes the audio for the time elapsed since the previous frame into the [`AudioCapture`].
+pub(crate) fn render_offline_audio(
+    audio_output: Res<AudioOutput>,
+    time: Res<Time>,
+    mut capture: ResMut<AudioCapture>,
+) {
+    let Some(offline) = &audio_output.offline else {
+        return;
+    };
+    let mut output = offline.lock().unwrap();
+    let Some(output) = output.as_mut() else {
+        return;
+    };
+
+    let capture = &mut *capture;
+    capture.channels = output.channels();
+    capture.sample_rate = output.sample_rate();
+    capture.pending_frames += time.delta_seconds_f64() * capture.sample_rate as f64;
+    let frames = capture.pending_frames as usize;
+    capture.pending_frames -= frames as f64;
+    // The master bus never ends
+    let samples = frames * capture.channels as usize;
+    capture.samples.extend(output.by_ref().take(samples));
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        AudioPlugin, Pitch, PitchBundle, PlaybackSettings, StreamingAudio, StreamingAudioBundle,
+    };
+    use bevy_app::App;
+    use bevy_asset::{AssetPlugin, Assets};
+    use bevy_core::TaskPoolPlugin;
+    use bevy_time::{TimePlugin, TimeUpdateStrategy};
+
+    /// Frames of the app last 100 ms, after the first frame which has no elapsed time.
+    pub(crate) fn offline_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            TimePlugin,
+            AssetPlugin::default(),
+            AudioPlugin {
+                backend: AudioBackend::Offline,
+                ..Default::default()
+            },
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )));
+        app
+    }
+
+    fn millis(range: Range<u64>) -> Range<Duration> {
+        Duration::from_millis(range.start)..Duration::from_millis(range.end)
+    }
+
+    #[test]
+    fn capture_pitch() {
+        let mut app = offline_app();
+        let pitch = app
+            .world
+            .resource_mut::<Assets<Pitch>>()
+            .add(Pitch::new(441.0, Duration::from_millis(500)));
+        app.world.spawn(PitchBundle {
+            source: pitch,
+            settings: PlaybackSettings::ONCE,
+        });
+        for _ in 0..11 {
+            app.update();
+        }
+
+        let capture = app.world.resource::<AudioCapture>();
+        assert_eq!(capture.channels(), CHANNELS);
+        assert_eq!(capture.sample_rate(), CLOCK_RATE);
+        assert_eq!(capture.duration(), Duration::from_secs(1));
+        // A full scale sine wave, then silence once the pitch ends
+        assert!(capture.peak(millis(100..400)) > 0.95);
+        assert!((capture.rms(millis(100..400)) - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.02);
+        assert_eq!(capture.peak(millis(700..1000)), 0.0);
+    }
+
+    #[test]
+    fn clear_keeps_the_time_of_the_capture() {
+        let mut app = offline_app();
+        let pitch = app
+            .world
+            .resource_mut::<Assets<Pitch>>()
+            .add(Pitch::new(441.0, Duration::from_millis(500)));
+        app.world.spawn(PitchBundle {
+            source: pitch,
+            settings: PlaybackSettings::ONCE,
+        });
+        for _ in 0..4 {
+            app.update();
+        }
+        app.world.resource_mut::<AudioCapture>().clear();
+        for _ in 0..7 {
+            app.update();
+        }
+
+        let capture = app.world.resource::<AudioCapture>();
+        assert_eq!(capture.start(), Duration::from_millis(300));
+        assert_eq!(capture.duration(), Duration::from_millis(700));
+        assert!(capture.samples_in(millis(0..300)).is_empty());
+        assert!(capture.peak(millis(300..400)) > 0.95);
+        assert_eq!(capture.peak(millis(700..1000)), 0.0);
+    }
+
+    #[test]
+    fn capture_streaming_audio() {
+        let mut app = offline_app();
+        let stream = StreamingAudio::new(1, CLOCK_RATE, Duration::from_secs(1));
+        // 200 ms at half scale, then a quarter scale
+        stream.push(&vec![0.5; CLOCK_RATE as usize / 5]);
+        stream.push(&vec![-0.25; CLOCK_RATE as usize / 5]);
+        stream.finish();
+        let source = app
+            .world
+            .resource_mut::<Assets<StreamingAudio>>()
+            .add(stream);
+        app.world.spawn(StreamingAudioBundle {
+            source,
+            settings: PlaybackSettings::ONCE,
+        });
+        for _ in 0..7 {
+            app.update();
+        }
+
+        let capture = app.world.resource::<AudioCapture>();
+        assert_eq!(capture.duration(), Duration::from_millis(600));
+        let start = capture
+            .samples()
+            .iter()
+            .position(|sample| *sample != 0.0)
+            .unwrap() as u64
+            / CHANNELS as u64
+            * 1000
+            / CLOCK_RATE as u64;
+        let at = |range: Range<u64>| millis(start + range.start..start + range.end);
+        assert!((capture.peak(at(10..190)) - 0.5).abs() < 1e-3);
+        assert!((capture.rms(at(210..390)) - 0.25).abs() < 1e-3);
+        assert_eq!(capture.peak(at(410..600)), 0.0);
+
+        let wav = capture.to_wav();
+        let data_len = capture.samples().len() as u32 * 2;
+        assert_eq!(wav.len(), 44 + data_len as usize);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(wav[4..8], (36 + data_len).to_le_bytes());
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        assert_eq!(wav[16..20], 16u32.to_le_bytes());
+        // Integer PCM, in stereo at the clock rate
+        assert_eq!(wav[20..22], 1u16.to_le_bytes());
+        assert_eq!(wav[22..24], CHANNELS.to_le_bytes());
+        assert_eq!(wav[24..28], CLOCK_RATE.to_le_bytes());
+        assert_eq!(wav[28..32], (CLOCK_RATE * 4).to_le_bytes());
+        assert_eq!(wav[32..34], 4u16.to_le_bytes());
+        assert_eq!(wav[34..36], 16u16.to_le_bytes());
+        assert_eq!(&wav[36..40], b"data");
+        assert_eq!(wav[40..44], data_len.to_le_bytes());
+    }
+}
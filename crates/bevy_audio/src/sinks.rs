@@ -1,7 +1,17 @@
 use bevy_ecs::component::Component;
 use bevy_math::Vec3;
 use bevy_transform::prelude::Transform;
-use rodio::{Sink, SpatialSink};
+use rodio::{cpal::FromSample, queue::SourcesQueueOutput, Sample, Sink, Source};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{
+    clock::{ControlledSource, PlaybackControl},
+    spatial::{ListenerState, SpatialParams, SpatialSource},
+    SpatialEmitter,
+};
 
 /// Common interactions with an audio sink.
 pub trait AudioSinkPlayback {
@@ -71,6 +81,60 @@ pub trait AudioSinkPlayback {
 
     /// Returns true if this sink has no more sounds to play.
     fn empty(&self) -> bool;
+
+    /// Gets the playback position of the source of this sink.
+    ///
+    /// This is measured on the audio thread in the time of the source, so it isn't affected by
+    /// [`set_speed`](Self::set_speed). Looping sources keep counting past their end.
+    fn position(&self) -> Duration;
+
+    /// Gets the fade level of the sink, which multiplies its [`volume`](Self::volume).
+    fn fade_level(&self) -> f32;
+
+    /// Fades the sink to `level` over `duration`, starting right away.
+    ///
+    /// The fade level multiplies the [`volume`](Self::volume) of the sink. It starts at `1.0`, or
+    /// at `0.0` with [`PlaybackSettings::fade_in`](crate::PlaybackSettings::fade_in). Fades are
+    /// applied sample by sample on the audio thread.
+    fn fade_to(&self, level: f32, duration: Duration);
+
+    /// Fades the sink to `level` over `duration`, starting at `start` on the
+    /// [`AudioClock`](crate::AudioClock).
+    ///
+    /// To crossfade between two tracks, start the new track and fade out the current one at the
+    /// same time:
+    ///
+    /// ```
+    /// # use bevy_ecs::prelude::*;
+    /// # use bevy_asset::AssetServer;
+    /// # use bevy_audio::{AudioBundle, AudioClock, AudioSink, AudioSinkPlayback, PlaybackSettings};
+    /// # use std::time::Duration;
+    /// # #[derive(Component)]
+    /// # struct Music;
+    /// fn crossfade(
+    ///     mut commands: Commands,
+    ///     asset_server: Res<AssetServer>,
+    ///     clock: Res<AudioClock>,
+    ///     music: Query<&AudioSink, With<Music>>,
+    /// ) {
+    ///     // Leave the new track some time to be ready
+    ///     let start = clock.now() + Duration::from_millis(100);
+    ///     let duration = Duration::from_secs(2);
+    ///     for sink in &music {
+    ///         sink.fade_to_at(0.0, start, duration);
+    ///     }
+    ///     commands.spawn((
+    ///         AudioBundle {
+    ///             source: asset_server.load("next_track.ogg"),
+    ///             settings: PlaybackSettings::LOOP
+    ///                 .with_start_at(start)
+    ///                 .with_fade_in(duration),
+    ///         },
+    ///         Music,
+    ///     ));
+    /// }
+    /// ```
+    fn fade_to_at(&self, level: f32, start: Duration, duration: Duration);
 }
 
 /// Used to control audio during playback.
@@ -86,6 +150,27 @@ pub trait AudioSinkPlayback {
 #[derive(Component)]
 pub struct AudioSink {
     pub(crate) sink: Sink,
+    control: Arc<PlaybackControl>,
+}
+
+impl AudioSink {
+    /// Creates a sink that isn't playing anywhere yet, and the output its sources play into.
+    pub(crate) fn new_idle(control: PlaybackControl) -> (Self, SourcesQueueOutput<f32>) {
+        let (sink, output) = Sink::new_idle();
+        let control = Arc::new(control);
+        (Self { sink, control }, output)
+    }
+
+    /// Appends a source to the queue of sources to play.
+    pub(crate) fn append<S>(&self, source: S)
+    where
+        S: Source + Send + 'static,
+        S::Item: Sample + Send,
+        f32: FromSample<S::Item>,
+    {
+        let source = ControlledSource::new(source.convert_samples(), self.control.clone());
+        self.sink.append::<ControlledSource<_>>(source);
+    }
 }
 
 impl AudioSinkPlayback for AudioSink {
@@ -124,6 +209,22 @@ impl AudioSinkPlayback for AudioSink {
     fn empty(&self) -> bool {
         self.sink.empty()
     }
+
+    fn position(&self) -> Duration {
+        self.control.position()
+    }
+
+    fn fade_level(&self) -> f32 {
+        self.control.fade_level()
+    }
+
+    fn fade_to(&self, level: f32, duration: Duration) {
+        self.control.fade_to(level, None, duration);
+    }
+
+    fn fade_to_at(&self, level: f32, start: Duration, duration: Duration) {
+        self.control.fade_to(level, Some(start), duration);
+    }
 }
 
 /// Used to control spatial audio during playback.
@@ -138,7 +239,9 @@ impl AudioSinkPlayback for AudioSink {
 /// that source is unchanged, that translates to the audio restarting.
 #[derive(Component)]
 pub struct SpatialAudioSink {
-    pub(crate) sink: SpatialSink,
+    pub(crate) sink: Sink,
+    control: Arc<PlaybackControl>,
+    params: Arc<Mutex<SpatialParams>>,
 }
 
 impl AudioSinkPlayback for SpatialAudioSink {
@@ -177,13 +280,57 @@ impl AudioSinkPlayback for SpatialAudioSink {
     fn empty(&self) -> bool {
         self.sink.empty()
     }
+
+    fn position(&self) -> Duration {
+        self.control.position()
+    }
+
+    fn fade_level(&self) -> f32 {
+        self.control.fade_level()
+    }
+
+    fn fade_to(&self, level: f32, duration: Duration) {
+        self.control.fade_to(level, None, duration);
+    }
+
+    fn fade_to_at(&self, level: f32, start: Duration, duration: Duration) {
+        self.control.fade_to(level, Some(start), duration);
+    }
 }
 
 impl SpatialAudioSink {
+    /// Creates a sink that isn't playing anywhere yet, and the output its sources play into.
+    pub(crate) fn new_idle(
+        control: PlaybackControl,
+        params: SpatialParams,
+    ) -> (Self, SourcesQueueOutput<f32>) {
+        let (sink, output) = Sink::new_idle();
+        let sink = Self {
+            sink,
+            control: Arc::new(control),
+            params: Arc::new(Mutex::new(params)),
+        };
+        (sink, output)
+    }
+
+    /// Appends a source to the queue of sources to play.
+    pub(crate) fn append<S>(&self, source: S)
+    where
+        S: Source + Send + 'static,
+        S::Item: Sample + Send,
+        f32: FromSample<S::Item>,
+    {
+        let source = ControlledSource::new(source.convert_samples(), self.control.clone());
+        let source = SpatialSource::new(source, self.params.clone());
+        // Without the type, the bounds of this method would apply to the spatial source
+        self.sink.append::<SpatialSource<_>>(source);
+    }
+
     /// Set the two ears position.
     pub fn set_ears_position(&self, left_position: Vec3, right_position: Vec3) {
-        self.sink.set_left_ear_position(left_position.to_array());
-        self.sink.set_right_ear_position(right_position.to_array());
+        let mut params = self.params.lock().unwrap();
+        params.listener.left_ear = left_position;
+        params.listener.right_ear = right_position;
     }
 
     /// Set the listener position, with an ear on each side separated by `gap`.
@@ -194,8 +341,44 @@ impl SpatialAudioSink {
         );
     }
 
+    /// Set the velocity of the listener, for the doppler effect.
+    pub fn set_listener_velocity(&self, velocity: Vec3) {
+        self.params.lock().unwrap().listener.velocity = velocity;
+    }
+
     /// Set the emitter position.
     pub fn set_emitter_position(&self, position: Vec3) {
-        self.sink.set_emitter_position(position.to_array());
+        self.params.lock().unwrap().emitter = position;
+    }
+
+    /// Set the direction the emitter faces, for its [`EmitterCone`](crate::EmitterCone).
+    pub fn set_emitter_forward(&self, forward: Vec3) {
+        self.params.lock().unwrap().emitter_forward = forward;
+    }
+
+    /// Set the velocity of the emitter, for the doppler effect.
+    pub fn set_emitter_velocity(&self, velocity: Vec3) {
+        self.params.lock().unwrap().emitter_velocity = velocity;
+    }
+
+    /// Set the settings of the emitter.
+    pub fn set_emitter_settings(&self, settings: SpatialEmitter) {
+        self.params.lock().unwrap().settings = settings;
+    }
+
+    /// Moves the emitter, deriving its velocity from its previous position.
+    pub(crate) fn move_emitter(&self, position: Vec3, forward: Vec3, delta_seconds: f32) {
+        let mut params = self.params.lock().unwrap();
+        params.emitter_velocity = if delta_seconds > 0.0 {
+            (position - params.emitter) / delta_seconds
+        } else {
+            Vec3::ZERO
+        };
+        params.emitter = position;
+        params.emitter_forward = forward;
+    }
+
+    pub(crate) fn set_listener(&self, listener: ListenerState) {
+        self.params.lock().unwrap().listener = listener;
     }
 }
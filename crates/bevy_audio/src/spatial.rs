@@ -0,0 +1,555 @@
+use bevy_ecs::component::Component;
+use bevy_math::Vec3;
+use rodio::{source::UniformSourceIterator, Source};
+use std::{
+    f32::consts::TAU,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// How the volume of a spatial audio source decreases with its distance to the listener.
+///
+/// The distance is clamped between [`SpatialEmitter::min_distance`] and
+/// [`SpatialEmitter::max_distance`] first, so the volume is at its maximum closer than the minimum
+/// distance, and stops decreasing past the maximum distance.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DistanceModel {
+    /// The volume is `min / (min + rolloff * (distance - min))`.
+    Inverse {
+        /// How fast the volume decreases.
+        rolloff: f32,
+    },
+    /// The volume is `1 - rolloff * (distance - min) / (max - min)`. This requires a finite
+    /// [`SpatialEmitter::max_distance`].
+    Linear {
+        /// How fast the volume decreases. With `1.0`, the source is silent at the maximum distance.
+        rolloff: f32,
+    },
+    /// The volume is `(distance / min) ^ -rolloff`. A rolloff of `2.0` is the inverse-square law
+    /// of sound in open air.
+    Exponential {
+        /// How fast the volume decreases.
+        rolloff: f32,
+    },
+}
+
+/// Makes a spatial audio source directional: it's loudest in front of the emitter, along the
+/// forward direction of its `GlobalTransform`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EmitterCone {
+    /// The angle of the cone where the volume is at its maximum, in radians.
+    pub inner_angle: f32,
+    /// The angle of the cone outside of which the volume is [`EmitterCone::outer_volume`], in
+    /// radians. Between both cones, the volume is interpolated.
+    pub outer_angle: f32,
+    /// The volume multiplier outside of the outer cone.
+    pub outer_volume: f32,
+}
+
+/// Settings of a spatial audio source.
+///
+/// Add this component next to an [`AudioSourceBundle`](crate::AudioSourceBundle) with
+/// [`PlaybackSettings::spatial`](crate::PlaybackSettings::spatial) enabled. Sources without it use
+/// the default settings. Changes to this component apply to playing audio.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct SpatialEmitter {
+    /// How the volume decreases with the distance to the listener.
+    pub distance_model: DistanceModel,
+    /// The distance under which the volume is at its maximum.
+    pub min_distance: f32,
+    /// The distance past which the volume stops decreasing.
+    pub max_distance: f32,
+    /// Makes the source directional.
+    pub cone: Option<EmitterCone>,
+    /// Scales the doppler effect: the change of pitch caused by the movement of the emitter and
+    /// listener. `0.0` disables it.
+    pub doppler_factor: f32,
+}
+
+impl Default for SpatialEmitter {
+    fn default() -> Self {
+        Self {
+            distance_model: DistanceModel::Exponential { rolloff: 2.0 },
+            min_distance: 1.0,
+            max_distance: f32::INFINITY,
+            cone: None,
+            doppler_factor: 1.0,
+        }
+    }
+}
+
+impl SpatialEmitter {
+    /// The volume multiplier at `distance` from the listener.
+    pub fn attenuation(&self, distance: f32) -> f32 {
+        let min = self.min_distance.max(f32::EPSILON);
+        let max = self.max_distance.max(min);
+        let distance = distance.clamp(min, max);
+        let volume = match self.distance_model {
+            DistanceModel::Inverse { rolloff } => min / (min + rolloff * (distance - min)),
+            DistanceModel::Linear { rolloff } => {
+                if max > min {
+                    1.0 - rolloff * (distance - min) / (max - min)
+                } else {
+                    1.0
+                }
+            }
+            DistanceModel::Exponential { rolloff } => (distance / min).powf(-rolloff),
+        };
+        volume.clamp(0.0, 1.0)
+    }
+
+    /// The volume multiplier of the [`EmitterCone`] for a listener in the direction `to_listener`.
+    fn cone_volume(&self, forward: Vec3, to_listener: Vec3) -> f32 {
+        let Some(cone) = self.cone else {
+            return 1.0;
+        };
+        let angle = forward.angle_between(to_listener);
+        if !angle.is_finite() {
+            return 1.0;
+        }
+        let inner = cone.inner_angle / 2.0;
+        let outer = (cone.outer_angle / 2.0).max(inner);
+        if angle <= inner {
+            1.0
+        } else if angle >= outer {
+            cone.outer_volume
+        } else {
+            let t = (angle - inner) / (outer - inner);
+            1.0 + (cone.outer_volume - 1.0) * t
+        }
+    }
+}
+
+/// How spatial audio sources are rendered to the ears of the [`SpatialListener`](crate::SpatialListener).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpatialRenderer {
+    /// Pans sources between the left and right ears.
+    #[default]
+    Panning,
+    /// Simulates the head of the listener between its ears: sounds reach the far ear later, and
+    /// are muffled by the head. This is best heard with headphones.
+    ///
+    /// This is based on a spherical model of the head, rather than on measured head-related
+    /// transfer functions (HRTF).
+    Binaural,
+}
+
+/// The listener, as seen by spatial audio sources.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ListenerState {
+    pub(crate) left_ear: Vec3,
+    pub(crate) right_ear: Vec3,
+    pub(crate) velocity: Vec3,
+    pub(crate) renderer: SpatialRenderer,
+    pub(crate) speed_of_sound: f32,
+}
+
+/// The state of a spatial audio source, shared with the audio thread.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SpatialParams {
+    pub(crate) emitter: Vec3,
+    pub(crate) emitter_forward: Vec3,
+    pub(crate) emitter_velocity: Vec3,
+    pub(crate) listener: ListenerState,
+    pub(crate) settings: SpatialEmitter,
+}
+
+/// The radius of the head of the listener, in meters, for [`SpatialRenderer::Binaural`].
+const HEAD_RADIUS: f32 = 0.0875;
+/// The speed of sound in air, in meters per second, for the delay between the ears.
+const SPEED_OF_SOUND_IN_AIR: f32 = 343.0;
+/// The cutoff frequency of the head shadow for a source right beside the far ear.
+const HEAD_SHADOW_CUTOFF: f32 = 1500.0;
+/// The range of pitch changes of the doppler effect, which also limits the effect of teleports.
+const DOPPLER_RANGE: (f32, f32) = (0.5, 2.0);
+
+/// What each ear hears of a spatial audio source.
+#[derive(Clone, Copy, Default)]
+struct EarParams {
+    volume: f32,
+    /// The delay of the sound, in samples.
+    delay: f32,
+    /// The coefficient of the low-pass filter of the head shadow, `1.0` meaning no filtering.
+    lowpass: f32,
+}
+
+impl SpatialParams {
+    /// Computes what the ears hear, and the pitch change of the doppler effect.
+    fn ears(&self, sample_rate: u32) -> (EarParams, EarParams, f32) {
+        let listener = &self.listener;
+        let center = (listener.left_ear + listener.right_ear) / 2.0;
+        let to_emitter = self.emitter - center;
+        let distance = to_emitter.length();
+        let volume = self.settings.attenuation(distance)
+            * self.settings.cone_volume(self.emitter_forward, -to_emitter);
+
+        // Positive when the emitter is on the right of the listener
+        let ear_axis = (listener.right_ear - listener.left_ear).normalize_or_zero();
+        let side = if distance > 0.0 {
+            (to_emitter / distance).dot(ear_axis).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+
+        let (left, right) = match listener.renderer {
+            SpatialRenderer::Panning => {
+                let pan = |side: f32| ((side + 1.0) / 4.0 + 0.5).min(1.0);
+                (
+                    EarParams {
+                        volume: volume * pan(-side),
+                        delay: 0.0,
+                        lowpass: 1.0,
+                    },
+                    EarParams {
+                        volume: volume * pan(side),
+                        delay: 0.0,
+                        lowpass: 1.0,
+                    },
+                )
+            }
+            SpatialRenderer::Binaural => {
+                // Woodworth's formula for the delay between the ears
+                let azimuth = side.asin().abs();
+                let delay = HEAD_RADIUS / SPEED_OF_SOUND_IN_AIR
+                    * (azimuth + azimuth.sin())
+                    * sample_rate as f32;
+                let cutoff = 20000.0 + (HEAD_SHADOW_CUTOFF - 20000.0) * side.abs();
+                let lowpass = (1.0 - (-TAU * cutoff / sample_rate as f32).exp()).min(1.0);
+                let near = EarParams {
+                    volume,
+                    delay: 0.0,
+                    lowpass: 1.0,
+                };
+                let far = EarParams {
+                    volume,
+                    delay,
+                    lowpass,
+                };
+                if side >= 0.0 {
+                    (far, near)
+                } else {
+                    (near, far)
+                }
+            }
+        };
+
+        let pitch = if self.settings.doppler_factor > 0.0 && distance > 0.0 {
+            // Speeds along the line from the emitter to the listener
+            let direction = -to_emitter / distance;
+            let factor = self.settings.doppler_factor;
+            let emitter_speed = self.emitter_velocity.dot(direction) * factor;
+            let listener_speed = listener.velocity.dot(direction) * factor;
+            let speed_of_sound = listener.speed_of_sound;
+            // An emitter approaching faster than sound gets the highest pitch
+            let approach = (speed_of_sound - emitter_speed).max(0.0);
+            ((speed_of_sound - listener_speed) / approach).clamp(DOPPLER_RANGE.0, DOPPLER_RANGE.1)
+        } else {
+            1.0
+        };
+        let pitch = if pitch.is_finite() { pitch } else { 1.0 };
+        (left, right, pitch)
+    }
+}
+
+/// The number of frames between two reads of the [`SpatialParams`].
+const UPDATE_FRAMES: u32 = 64;
+/// The number of past samples kept for the delay between the ears. This must be longer than the
+/// delay at the highest sample rates.
+const HISTORY: usize = 256;
+/// How fast the volumes and delays reach their new value, per frame. This avoids clicks.
+const SMOOTHING: f32 = 0.005;
+
+#[derive(Default)]
+struct Ear {
+    current: EarParams,
+    target: EarParams,
+    filtered: f32,
+}
+
+impl Ear {
+    fn process(&mut self, history: &[f32], newest: usize) -> f32 {
+        let current = &mut self.current;
+        current.volume += (self.target.volume - current.volume) * SMOOTHING;
+        current.delay += (self.target.delay - current.delay) * SMOOTHING;
+        current.lowpass += (self.target.lowpass - current.lowpass) * SMOOTHING;
+
+        let delay = current.delay.clamp(0.0, (HISTORY - 2) as f32);
+        let whole = delay as usize;
+        let fraction = delay - whole as f32;
+        let later = history[(newest + HISTORY - whole) % HISTORY];
+        let earlier = history[(newest + HISTORY - whole - 1) % HISTORY];
+        let delayed = later + (earlier - later) * fraction;
+
+        self.filtered += current.lowpass * (delayed - self.filtered);
+        self.filtered * current.volume
+    }
+}
+
+/// Renders a source to the ears of the listener, as a stereo source.
+///
+/// The channels of the input are mixed down to mono first.
+pub(crate) struct SpatialSource<I>
+where
+    I: Source<Item = f32>,
+{
+    input: UniformSourceIterator<I, f32>,
+    channels: u16,
+    sample_rate: u32,
+    params: Arc<Mutex<SpatialParams>>,
+    frames_until_update: u32,
+    /// The doppler effect changes the pitch by reading through the input faster or slower.
+    pitch: f32,
+    position: f32,
+    previous: f32,
+    current: f32,
+    history: Vec<f32>,
+    newest: usize,
+    left: Ear,
+    right: Ear,
+    right_sample: Option<f32>,
+}
+
+impl<I> SpatialSource<I>
+where
+    I: Source<Item = f32>,
+{
+    pub(crate) fn new(input: I, params: Arc<Mutex<SpatialParams>>) -> Self {
+        let channels = input.channels();
+        let sample_rate = input.sample_rate();
+        let mut source = Self {
+            input: UniformSourceIterator::new(input, channels, sample_rate),
+            channels,
+            sample_rate,
+            params,
+            frames_until_update: UPDATE_FRAMES,
+            pitch: 1.0,
+            position: 0.0,
+            previous: 0.0,
+            current: 0.0,
+            history: vec![0.0; HISTORY],
+            newest: 0,
+            left: Ear::default(),
+            right: Ear::default(),
+            right_sample: None,
+        };
+        source.update();
+        source.left.current = source.left.target;
+        source.right.current = source.right.target;
+        source
+    }
+
+    fn update(&mut self) {
+        // Don't wait on the game: the previous values are good enough until the next update
+        let Ok(params) = self.params.try_lock() else {
+            return;
+        };
+        let (left, right, pitch) = params.ears(self.sample_rate);
+        self.left.target = left;
+        self.right.target = right;
+        self.pitch = pitch;
+    }
+
+    fn next_input_frame(&mut self) -> Option<f32> {
+        let mut sum = 0.0;
+        for _ in 0..self.channels {
+            sum += self.input.next()?;
+        }
+        Some(sum / self.channels as f32)
+    }
+}
+
+impl<I> Iterator for SpatialSource<I>
+where
+    I: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if let Some(sample) = self.right_sample.take() {
+            return Some(sample);
+        }
+
+        if self.frames_until_update == 0 {
+            self.update();
+            self.frames_until_update = UPDATE_FRAMES;
+        }
+        self.frames_until_update -= 1;
+
+        self.position += self.pitch;
+        while self.position >= 1.0 {
+            self.position -= 1.0;
+            self.previous = self.current;
+            self.current = self.next_input_frame()?;
+        }
+        self.newest = (self.newest + 1) % HISTORY;
+        self.history[self.newest] = self.previous + (self.current - self.previous) * self.position;
+
+        self.right_sample = Some(self.right.process(&self.history, self.newest));
+        Some(self.left.process(&self.history, self.newest))
+    }
+}
+
+impl<I> Source for SpatialSource<I>
+where
+    I: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        2
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    fn assert_near(value: f32, expected: f32) {
+        assert!(
+            (value - expected).abs() < 1e-5,
+            "expected {expected}, got {value}"
+        );
+    }
+
+    #[test]
+    fn distance_models() {
+        let emitter = |distance_model| SpatialEmitter {
+            distance_model,
+            min_distance: 1.0,
+            max_distance: 11.0,
+            ..Default::default()
+        };
+        // The volume at 0.5, 1, 6, 11 and 20 meters
+        let distances = [0.5, 1.0, 6.0, 11.0, 20.0];
+        let cases = [
+            (
+                DistanceModel::Inverse { rolloff: 1.0 },
+                [1.0, 1.0, 1.0 / 6.0, 1.0 / 11.0, 1.0 / 11.0],
+            ),
+            (
+                DistanceModel::Linear { rolloff: 1.0 },
+                [1.0, 1.0, 0.5, 0.0, 0.0],
+            ),
+            (
+                DistanceModel::Linear { rolloff: 0.5 },
+                [1.0, 1.0, 0.75, 0.5, 0.5],
+            ),
+            (
+                DistanceModel::Exponential { rolloff: 2.0 },
+                [1.0, 1.0, 1.0 / 36.0, 1.0 / 121.0, 1.0 / 121.0],
+            ),
+        ];
+        for (model, volumes) in cases {
+            let emitter = emitter(model);
+            for (distance, volume) in distances.into_iter().zip(volumes) {
+                assert_near(emitter.attenuation(distance), volume);
+            }
+        }
+
+        // Without a maximum distance, the linear model doesn't attenuate
+        let unbounded = SpatialEmitter {
+            distance_model: DistanceModel::Linear { rolloff: 1.0 },
+            ..Default::default()
+        };
+        assert_eq!(unbounded.attenuation(100.0), 1.0);
+    }
+
+    #[test]
+    fn cone_volume() {
+        let emitter = SpatialEmitter {
+            cone: Some(EmitterCone {
+                inner_angle: PI / 2.0,
+                outer_angle: PI,
+                outer_volume: 0.2,
+            }),
+            ..Default::default()
+        };
+        let at_angle = |angle: f32| Vec3::new(angle.cos(), angle.sin(), 0.0);
+        let volume = |angle: f32| emitter.cone_volume(Vec3::X, at_angle(angle));
+
+        // Inside the inner cone
+        assert_near(volume(0.0), 1.0);
+        assert_near(volume(PI / 4.0), 1.0);
+        // Interpolated between the cones
+        assert_near(volume(PI * 3.0 / 8.0), 0.6);
+        // Outside the outer cone
+        assert_near(volume(PI / 2.0), 0.2);
+        assert_near(volume(PI), 0.2);
+        // At the emitter
+        assert_eq!(emitter.cone_volume(Vec3::X, Vec3::ZERO), 1.0);
+
+        let omnidirectional = SpatialEmitter::default();
+        assert_eq!(omnidirectional.cone_volume(Vec3::X, Vec3::NEG_X), 1.0);
+    }
+
+    /// The doppler pitch of an emitter 10 meters in front of the listener.
+    fn doppler_pitch(emitter_velocity: Vec3, listener_velocity: Vec3, doppler_factor: f32) -> f32 {
+        let params = SpatialParams {
+            emitter: Vec3::new(0.0, 0.0, -10.0),
+            emitter_forward: Vec3::Z,
+            emitter_velocity,
+            listener: ListenerState {
+                left_ear: Vec3::new(-0.1, 0.0, 0.0),
+                right_ear: Vec3::new(0.1, 0.0, 0.0),
+                velocity: listener_velocity,
+                renderer: SpatialRenderer::Panning,
+                speed_of_sound: 343.0,
+            },
+            settings: SpatialEmitter {
+                doppler_factor,
+                ..Default::default()
+            },
+        };
+        params.ears(44100).2
+    }
+
+    #[test]
+    fn doppler_pitch_direction_and_clamping() {
+        let towards_listener = Vec3::new(0.0, 0.0, 34.3);
+        // Approaching raises the pitch, moving away lowers it
+        assert_near(
+            doppler_pitch(towards_listener, Vec3::ZERO, 1.0),
+            343.0 / 308.7,
+        );
+        assert_near(
+            doppler_pitch(-towards_listener, Vec3::ZERO, 1.0),
+            343.0 / 377.3,
+        );
+        assert_near(
+            doppler_pitch(Vec3::ZERO, -towards_listener, 1.0),
+            377.3 / 343.0,
+        );
+        assert_near(
+            doppler_pitch(Vec3::ZERO, towards_listener, 1.0),
+            308.7 / 343.0,
+        );
+        // Moving sideways doesn't change the pitch
+        assert_near(doppler_pitch(Vec3::X * 50.0, Vec3::X * 20.0, 1.0), 1.0);
+        // The doppler factor scales the speeds
+        assert_near(
+            doppler_pitch(towards_listener, Vec3::ZERO, 0.5),
+            343.0 / 325.85,
+        );
+        assert_eq!(doppler_pitch(towards_listener, Vec3::ZERO, 0.0), 1.0);
+
+        // The pitch is clamped, including for emitters faster than sound
+        assert_eq!(doppler_pitch(towards_listener * 9.0, Vec3::ZERO, 1.0), 2.0);
+        assert_eq!(doppler_pitch(towards_listener * 20.0, Vec3::ZERO, 1.0), 2.0);
+        assert_eq!(
+            doppler_pitch(-towards_listener * 20.0, Vec3::ZERO, 1.0),
+            0.5
+        );
+        assert_eq!(doppler_pitch(Vec3::ZERO, towards_listener * 20.0, 1.0), 0.5);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::{AudioSourceBundle, Decodable};
+use bevy_asset::{Asset, AssetId, Assets};
+use bevy_ecs::prelude::*;
+use bevy_reflect::TypePath;
+use bevy_utils::tracing::warn;
+use rodio::Source;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+/// A source of audio whose samples are pushed while it plays, for sound generated by the game.
+///
+/// Push interleaved samples with [`StreamingAudio::push`], for example each frame from the state
+/// of an engine, or when voice chat packets arrive. The samples are queued in a ring buffer that
+/// the audio thread reads without locking. When the buffer runs dry, silence is played and an
+/// underrun is counted; a [`StreamingAudioUnderrun`] event is sent on the next frame. Push enough
+/// samples to cover more than a frame of the game to avoid underruns.
+///
+/// Pushing only needs a shared reference, so a system can push into a stream of
+/// `Res<Assets<StreamingAudio>>`, and clones of the asset push into the same stream from any
+/// thread.
+///
+/// ```
+/// # use bevy_asset::{Assets, Handle};
+/// # use bevy_audio::StreamingAudio;
+/// # use bevy_ecs::prelude::*;
+/// # use std::f32::consts::TAU;
+/// #[derive(Resource)]
+/// struct Engine {
+///     stream: Handle<StreamingAudio>,
+///     rpm: f32,
+///     phase: f32,
+/// }
+///
+/// fn synthesize_engine(mut engine: ResMut<Engine>, streams: Res<Assets<StreamingAudio>>) {
+///     let Some(stream) = streams.get(&engine.stream) else {
+///         return;
+///     };
+///     // Keep 50 ms of audio queued
+///     let frequency = engine.rpm / 60.0;
+///     let queued = stream.queued_frames();
+///     let frames = (stream.sample_rate() as usize / 20).saturating_sub(queued);
+///     let samples: Vec<f32> = (0..frames)
+///         .map(|_| {
+///             engine.phase = (engine.phase + frequency / stream.sample_rate() as f32) % 1.0;
+///             (engine.phase * TAU).sin() * 0.5
+///         })
+///         .collect();
+///     stream.push(&samples);
+/// }
+/// ```
+#[derive(Asset, TypePath, Clone)]
+pub struct StreamingAudio {
+    channels: u16,
+    sample_rate: u32,
+    stream: Arc<Stream>,
+}
+
+impl StreamingAudio {
+    /// Creates a stream of `channels` interleaved channels at `sample_rate` frames per second,
+    /// which queues up to `capacity` of audio.
+    pub fn new(channels: u16, sample_rate: u32, capacity: Duration) -> Self {
+        let channels = channels.max(1);
+        let frames = (capacity.as_secs_f64() * sample_rate as f64).ceil() as usize;
+        Self {
+            channels,
+            sample_rate,
+            stream: Arc::new(Stream {
+                buffer: RingBuffer::new(frames.max(1) * channels as usize),
+                producer: Mutex::new(()),
+                consumer: AtomicBool::new(false),
+                started: AtomicBool::new(false),
+                finished: AtomicBool::new(false),
+                underruns: AtomicU64::new(0),
+                missing_frames: AtomicU64::new(0),
+                reported_underruns: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// The number of interleaved channels of the samples.
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// The number of frames per second of the samples.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Queues interleaved samples, returning how many were queued.
+    ///
+    /// Only whole frames are queued, and samples that don't fit in the buffer are dropped.
+    pub fn push(&self, samples: &[f32]) -> usize {
+        let channels = self.channels as usize;
+        // Producers are serialized, the audio thread never waits on them
+        let _producer = self.stream.producer.lock().unwrap();
+        let free = self.stream.buffer.free() / channels * channels;
+        let len = free.min(samples.len() / channels * channels);
+        self.stream.buffer.push(&samples[..len]);
+        if len > 0 {
+            self.stream.started.store(true, Ordering::Relaxed);
+        }
+        len
+    }
+
+    /// The number of frames queued and not yet played.
+    pub fn queued_frames(&self) -> usize {
+        self.stream.buffer.len() / self.channels as usize
+    }
+
+    /// The duration of the audio queued and not yet played.
+    pub fn queued(&self) -> Duration {
+        Duration::from_secs_f64(self.queued_frames() as f64 / self.sample_rate.max(1) as f64)
+    }
+
+    /// The number of frames that can be queued before the buffer is full.
+    pub fn free_frames(&self) -> usize {
+        self.stream.buffer.free() / self.channels as usize
+    }
+
+    /// Ends the stream once the queued samples are played, finishing its playback.
+    pub fn finish(&self) {
+        self.stream.finished.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the stream was [finished](Self::finish).
+    pub fn is_finished(&self) -> bool {
+        self.stream.finished.load(Ordering::Relaxed)
+    }
+
+    /// The number of times the buffer ran dry while playing.
+    ///
+    /// Silence played before the first samples are pushed doesn't count.
+    pub fn underruns(&self) -> u64 {
+        self.stream.underruns.load(Ordering::Relaxed)
+    }
+
+    /// The duration of the silence played because the buffer was dry.
+    pub fn missing(&self) -> Duration {
+        let frames = self.stream.missing_frames.load(Ordering::Relaxed);
+        Duration::from_secs_f64(frames as f64 / self.sample_rate.max(1) as f64)
+    }
+}
+
+impl Decodable for StreamingAudio {
+    type DecoderItem = f32;
+    type Decoder = StreamingDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        // The ring buffer has a single consumer
+        let active = !self.stream.consumer.swap(true, Ordering::Acquire);
+        if !active {
+            warn!("A StreamingAudio can only be played by one entity at a time, ignoring the other playbacks.");
+        }
+        StreamingDecoder {
+            stream: self.stream.clone(),
+            channels: self.channels,
+            sample_rate: self.sample_rate,
+            channel: 0,
+            silent: true,
+            starved: false,
+            active,
+        }
+    }
+}
+
+/// Bundle for playing a [`StreamingAudio`]
+pub type StreamingAudioBundle = AudioSourceBundle<StreamingAudio>;
+
+/// Sent when the buffer of a [`StreamingAudio`] ran dry since the previous frame.
+#[derive(Event, Clone, Debug)]
+pub struct StreamingAudioUnderrun {
+    /// The stream that ran dry.
+    pub id: AssetId<StreamingAudio>,
+    /// The number of times the buffer ran dry since the previous event.
+    pub count: u64,
+}
+
+/// Sends a [`StreamingAudioUnderrun`] for the streams that ran dry since the previous frame.
+pub(crate) fn report_streaming_underruns(
+    streams: Res<Assets<StreamingAudio>>,
+    mut underruns: EventWriter<StreamingAudioUnderrun>,
+) {
+    for (id, audio) in streams.iter() {
+        let count = audio.underruns();
+        let reported = audio
+            .stream
+            .reported_underruns
+            .swap(count, Ordering::Relaxed);
+        if count > reported {
+            underruns.send(StreamingAudioUnderrun {
+                id,
+                count: count - reported,
+            });
+        }
+    }
+}
+
+/// The state of a [`StreamingAudio`], shared by its clones and its decoder.
+struct Stream {
+    buffer: RingBuffer,
+    producer: Mutex<()>,
+    /// Set while a decoder reads the buffer.
+    consumer: AtomicBool,
+    /// Set once samples were pushed, so the silence before doesn't count as underruns.
+    started: AtomicBool,
+    finished: AtomicBool,
+    underruns: AtomicU64,
+    missing_frames: AtomicU64,
+    reported_underruns: AtomicU64,
+}
+
+/// A lock-free ring buffer of samples, for one producer and one consumer.
+struct RingBuffer {
+    slots: Box<[AtomicU32]>,
+    /// The number of samples pushed so far, wrapping.
+    write: AtomicUsize,
+    /// The number of samples popped so far, wrapping.
+    read: AtomicUsize,
+}
+
+impl RingBuffer {
+    fn new(capacity: usize) -> Self {
+        Self {
+            slots: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+            write: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+        }
+    }
+
+    fn len(&self) -> usize {
+        let write = self.write.load(Ordering::Acquire);
+        write.wrapping_sub(self.read.load(Ordering::Acquire))
+    }
+
+    fn free(&self) -> usize {
+        self.slots.len() - self.len()
+    }
+
+    /// Pushes samples that fit in the free space. Must only be called by one producer at a time.
+    fn push(&self, samples: &[f32]) {
+        let write = self.write.load(Ordering::Relaxed);
+        for (i, sample) in samples.iter().enumerate() {
+            let slot = write.wrapping_add(i) % self.slots.len();
+            self.slots[slot].store(sample.to_bits(), Ordering::Relaxed);
+        }
+        // Publishes the samples to the consumer
+        self.write
+            .store(write.wrapping_add(samples.len()), Ordering::Release);
+    }
+
+    /// Pops a sample. Must only be called by one consumer at a time.
+    fn pop(&self) -> Option<f32> {
+        let read = self.read.load(Ordering::Relaxed);
+        if self.write.load(Ordering::Acquire) == read {
+            return None;
+        }
+        let sample = self.slots[read % self.slots.len()].load(Ordering::Relaxed);
+        // Releases the slot to the producer
+        self.read.store(read.wrapping_add(1), Ordering::Release);
+        Some(f32::from_bits(sample))
+    }
+}
+
+/// Plays the samples pushed into a [`StreamingAudio`], and silence when its buffer is dry.
+pub struct StreamingDecoder {
+    stream: Arc<Stream>,
+    channels: u16,
+    sample_rate: u32,
+    /// The index of the next sample in the current frame.
+    channel: u16,
+    /// Set if the current frame is silent, as the buffer was dry when it started.
+    silent: bool,
+    /// Set while the buffer is dry after samples were played.
+    starved: bool,
+    /// Unset if another decoder reads the stream.
+    active: bool,
+}
+
+impl Iterator for StreamingDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if !self.active {
+            return None;
+        }
+        if self.channel == 0 {
+            // Frames are pushed whole, so a frame can be read once its first sample is queued
+            self.silent = self.stream.buffer.len() == 0;
+            if !self.silent {
+                self.starved = false;
+            } else if self.stream.finished.load(Ordering::Relaxed) {
+                return None;
+            } else {
+                if !self.starved && self.stream.started.load(Ordering::Relaxed) {
+                    self.starved = true;
+                    self.stream.underruns.fetch_add(1, Ordering::Relaxed);
+                }
+                if self.starved {
+                    self.stream.missing_frames.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        self.channel = (self.channel + 1) % self.channels;
+        if self.silent {
+            Some(0.0)
+        } else {
+            self.stream.buffer.pop().or(Some(0.0))
+        }
+    }
+}
+
+impl Source for StreamingDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Drop for StreamingDecoder {
+    fn drop(&mut self) {
+        if self.active {
+            // Discards the rest of a partly read frame, so the next decoder starts on a frame
+            if self.channel != 0 && !self.silent {
+                for _ in self.channel..self.channels {
+                    self.stream.buffer.pop();
+                }
+            }
+            self.stream.consumer.store(false, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn take(decoder: &mut StreamingDecoder, samples: usize) -> Vec<f32> {
+        decoder.by_ref().take(samples).collect()
+    }
+
+    #[test]
+    fn push_drops_whole_frames_when_full() {
+        let stream = StreamingAudio::new(2, 4, Duration::from_secs(1));
+        assert_eq!(stream.free_frames(), 4);
+        // The incomplete frame is dropped
+        assert_eq!(stream.push(&[1.0, 2.0, 3.0]), 2);
+        // Only 3 of the 5 frames fit
+        assert_eq!(stream.push(&[0.5; 10]), 6);
+        assert_eq!(stream.queued_frames(), 4);
+        assert_eq!(stream.queued(), Duration::from_secs(1));
+        assert_eq!(stream.free_frames(), 0);
+        assert_eq!(stream.push(&[4.0, 5.0]), 0);
+
+        let mut decoder = stream.decoder();
+        assert_eq!(
+            take(&mut decoder, 8),
+            [1.0, 2.0, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5]
+        );
+    }
+
+    #[test]
+    fn ring_buffer_wraps_around() {
+        let buffer = RingBuffer::new(4);
+        for round in 0..5 {
+            let samples = [round as f32, round as f32 + 0.25, round as f32 + 0.5];
+            buffer.push(&samples);
+            assert_eq!(buffer.len(), 3);
+            assert_eq!(buffer.free(), 1);
+            let popped: Vec<_> = (0..3).map(|_| buffer.pop().unwrap()).collect();
+            assert_eq!(popped, samples);
+            assert_eq!(buffer.pop(), None);
+        }
+    }
+
+    #[test]
+    fn underruns_count_once_started() {
+        let stream = StreamingAudio::new(2, 4, Duration::from_secs(1));
+        let mut decoder = stream.decoder();
+
+        // Nothing was pushed yet, so the silence isn't an underrun
+        assert_eq!(take(&mut decoder, 4), [0.0; 4]);
+        assert_eq!(stream.underruns(), 0);
+        assert_eq!(stream.missing(), Duration::ZERO);
+
+        stream.push(&[1.0, -1.0, 0.5, -0.5]);
+        assert_eq!(take(&mut decoder, 4), [1.0, -1.0, 0.5, -0.5]);
+        // The buffer runs dry for 2 frames
+        assert_eq!(take(&mut decoder, 4), [0.0; 4]);
+        assert_eq!(stream.underruns(), 1);
+        assert_eq!(stream.missing(), Duration::from_millis(500));
+
+        stream.push(&[0.25, 0.25]);
+        assert_eq!(take(&mut decoder, 2), [0.25, 0.25]);
+        assert_eq!(take(&mut decoder, 2), [0.0; 2]);
+        assert_eq!(stream.underruns(), 2);
+        assert_eq!(stream.missing(), Duration::from_millis(750));
+    }
+
+    #[test]
+    fn finish_ends_the_decoder() {
+        let stream = StreamingAudio::new(2, 4, Duration::from_secs(1));
+        let decoder = stream.decoder();
+        stream.push(&[1.0, 1.0]);
+        stream.finish();
+        assert!(stream.is_finished());
+
+        // The queued samples are played before the decoder ends
+        assert_eq!(decoder.collect::<Vec<_>>(), [1.0, 1.0]);
+        assert_eq!(stream.underruns(), 0);
+    }
+
+    #[test]
+    fn single_decoder_reads_the_stream() {
+        let stream = StreamingAudio::new(2, 4, Duration::from_secs(1));
+        stream.push(&[1.0, 1.0, 2.0, 2.0]);
+        let mut decoder = stream.decoder();
+
+        let mut second = stream.clone().decoder();
+        assert_eq!(second.next(), None);
+        // Dropping an inactive decoder doesn't release the stream
+        drop(second);
+        assert_eq!(stream.decoder().next(), None);
+
+        assert_eq!(take(&mut decoder, 2), [1.0, 1.0]);
+        drop(decoder);
+        let mut decoder = stream.decoder();
+        assert_eq!(take(&mut decoder, 4), [2.0, 2.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn dropping_a_decoder_mid_frame_keeps_the_channels() {
+        let stream = StreamingAudio::new(2, 4, Duration::from_secs(1));
+        stream.push(&[1.0, -1.0, 2.0, -2.0]);
+
+        let mut decoder = stream.decoder();
+        assert_eq!(take(&mut decoder, 1), [1.0]);
+        drop(decoder);
+        // The right sample of the first frame is discarded with the decoder
+        let mut decoder = stream.decoder();
+        assert_eq!(take(&mut decoder, 2), [2.0, -2.0]);
+    }
+}
@@ -1,12 +1,20 @@
 use proc_macro::TokenStream;
 use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::quote;
-use syn::{parse_macro_input, parse_quote, DeriveInput, Ident, LitStr, Path, Result};
+use syn::{
+    parse_macro_input, parse_quote, Data, DataStruct, DeriveInput, Ident, LitStr, Path, Result,
+    Type,
+};
 
 pub fn derive_event(input: TokenStream) -> TokenStream {
     let mut ast = parse_macro_input!(input as DeriveInput);
     let bevy_ecs_path: Path = crate::bevy_ecs_path();
 
+    let attrs = match parse_event_attr(&ast) {
+        Ok(attrs) => attrs,
+        Err(e) => return e.into_compile_error().into(),
+    };
+
     ast.generics
         .make_where_clause()
         .predicates
@@ -15,9 +23,20 @@ pub fn derive_event(input: TokenStream) -> TokenStream {
     let struct_name = &ast.ident;
     let (impl_generics, type_generics, where_clause) = &ast.generics.split_for_impl();
 
+    let traversal = attrs
+        .traversal
+        .map(|traversal| quote! { #traversal })
+        .unwrap_or_else(|| quote! { () });
+    let auto_propagate = attrs.auto_propagate;
+
     TokenStream::from(quote! {
         impl #impl_generics #bevy_ecs_path::event::Event for #struct_name #type_generics #where_clause {
         }
+
+        impl #impl_generics #bevy_ecs_path::event::EntityEvent for #struct_name #type_generics #where_clause {
+            type Traversal = #traversal;
+            const AUTO_PROPAGATE: bool = #auto_propagate;
+        }
     })
 }
 
@@ -58,18 +77,157 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
     let struct_name = &ast.ident;
     let (impl_generics, type_generics, where_clause) = &ast.generics.split_for_impl();
 
+    let relationship = match derive_relationship(&ast, &attrs, &bevy_ecs_path) {
+        Ok(relationship) => relationship,
+        Err(e) => return e.into_compile_error().into(),
+    };
+    let register_component_hooks = if attrs.relationship.is_some() {
+        quote! {
+            fn register_component_hooks(hooks: &mut #bevy_ecs_path::component::ComponentHooks) {
+                hooks
+                    .on_insert(<Self as #bevy_ecs_path::relationship::Relationship>::on_insert)
+                    .on_replace(<Self as #bevy_ecs_path::relationship::Relationship>::on_replace);
+            }
+        }
+    } else if attrs.relationship_target.is_some() {
+        quote! {
+            fn register_component_hooks(hooks: &mut #bevy_ecs_path::component::ComponentHooks) {
+                hooks.on_replace(<Self as #bevy_ecs_path::relationship::RelationshipTarget>::on_replace);
+            }
+        }
+    } else {
+        quote! {}
+    };
+
     TokenStream::from(quote! {
         impl #impl_generics #bevy_ecs_path::component::Component for #struct_name #type_generics #where_clause {
             type Storage = #storage;
+
+            #register_component_hooks
         }
+
+        #relationship
     })
 }
 
+/// Generates the [`Relationship`] or [`RelationshipTarget`] impl requested by the
+/// `relationship` or `relationship_target` attribute, if any.
+fn derive_relationship(
+    ast: &DeriveInput,
+    attrs: &Attrs,
+    bevy_ecs_path: &Path,
+) -> Result<TokenStream2> {
+    if attrs.relationship.is_none() && attrs.relationship_target.is_none() {
+        return Ok(quote! {});
+    }
+    let Data::Struct(DataStruct { fields, .. }) = &ast.data else {
+        return Err(syn::Error::new(
+            ast.ident.span(),
+            "Relationship components must be structs.",
+        ));
+    };
+    if fields.len() != 1 {
+        return Err(syn::Error::new(
+            ast.ident.span(),
+            "Relationship components must have exactly one field.",
+        ));
+    }
+    let field = fields.iter().next().unwrap();
+    let (field_access, constructor) = match &field.ident {
+        Some(ident) => (quote! { #ident }, quote! { Self { #ident: value } }),
+        None => (quote! { 0 }, quote! { Self(value) }),
+    };
+    let field_type = &field.ty;
+
+    let struct_name = &ast.ident;
+    let (impl_generics, type_generics, where_clause) = &ast.generics.split_for_impl();
+
+    if let Some(relationship_target) = &attrs.relationship {
+        Ok(quote! {
+            impl #impl_generics #bevy_ecs_path::relationship::Relationship for #struct_name #type_generics #where_clause {
+                type RelationshipTarget = #relationship_target;
+
+                #[inline(always)]
+                fn get(&self) -> #bevy_ecs_path::entity::Entity {
+                    self.#field_access
+                }
+
+                #[inline]
+                fn from(value: #bevy_ecs_path::entity::Entity) -> Self {
+                    #constructor
+                }
+            }
+        })
+    } else {
+        let relationship = attrs.relationship_target.as_ref().unwrap();
+        Ok(quote! {
+            impl #impl_generics #bevy_ecs_path::relationship::RelationshipTarget for #struct_name #type_generics #where_clause {
+                type Relationship = #relationship;
+                type Collection = #field_type;
+
+                #[inline]
+                fn collection(&self) -> &Self::Collection {
+                    &self.#field_access
+                }
+
+                #[inline]
+                fn collection_mut_risky(&mut self) -> &mut Self::Collection {
+                    &mut self.#field_access
+                }
+
+                #[inline]
+                fn from_collection_risky(value: Self::Collection) -> Self {
+                    #constructor
+                }
+            }
+        })
+    }
+}
+
 pub const COMPONENT: &str = "component";
 pub const STORAGE: &str = "storage";
 
+pub const RELATIONSHIP: &str = "relationship";
+pub const RELATIONSHIP_TARGET: &str = "relationship_target";
+
+pub const EVENT: &str = "event";
+pub const TRAVERSAL: &str = "traversal";
+pub const AUTO_PROPAGATE: &str = "auto_propagate";
+
+struct EventAttrs {
+    traversal: Option<Type>,
+    auto_propagate: bool,
+}
+
+fn parse_event_attr(ast: &DeriveInput) -> Result<EventAttrs> {
+    let mut attrs = EventAttrs {
+        traversal: None,
+        auto_propagate: false,
+    };
+
+    for meta in ast.attrs.iter().filter(|a| a.path().is_ident(EVENT)) {
+        meta.parse_nested_meta(|nested| {
+            if nested.path.is_ident(TRAVERSAL) {
+                attrs.traversal = Some(nested.value()?.parse::<Type>()?);
+                Ok(())
+            } else if nested.path.is_ident(AUTO_PROPAGATE) {
+                attrs.auto_propagate = true;
+                Ok(())
+            } else {
+                Err(nested.error("Unsupported attribute"))
+            }
+        })?;
+    }
+
+    Ok(attrs)
+}
+
 struct Attrs {
     storage: StorageTy,
+    /// The `RelationshipTarget` of this component, if it is a `Relationship`.
+    relationship: Option<Type>,
+    /// The `Relationship` of this component, if it is a `RelationshipTarget`.
+    relationship_target: Option<Type>,
 }
 
 #[derive(Clone, Copy)]
@@ -85,6 +243,8 @@ const SPARSE_SET: &str = "SparseSet";
 fn parse_component_attr(ast: &DeriveInput) -> Result<Attrs> {
     let mut attrs = Attrs {
         storage: StorageTy::Table,
+        relationship: None,
+        relationship_target: None,
     };
 
     for meta in ast.attrs.iter().filter(|a| a.path().is_ident(COMPONENT)) {
@@ -106,6 +266,39 @@ fn parse_component_attr(ast: &DeriveInput) -> Result<Attrs> {
         })?;
     }
 
+    for meta in ast.attrs.iter().filter(|a| a.path().is_ident(RELATIONSHIP)) {
+        meta.parse_nested_meta(|nested| {
+            if nested.path.is_ident(RELATIONSHIP_TARGET) {
+                attrs.relationship = Some(nested.value()?.parse::<Type>()?);
+                Ok(())
+            } else {
+                Err(nested.error("Unsupported attribute"))
+            }
+        })?;
+    }
+
+    for meta in ast
+        .attrs
+        .iter()
+        .filter(|a| a.path().is_ident(RELATIONSHIP_TARGET))
+    {
+        meta.parse_nested_meta(|nested| {
+            if nested.path.is_ident(RELATIONSHIP) {
+                attrs.relationship_target = Some(nested.value()?.parse::<Type>()?);
+                Ok(())
+            } else {
+                Err(nested.error("Unsupported attribute"))
+            }
+        })?;
+    }
+
+    if attrs.relationship.is_some() && attrs.relationship_target.is_some() {
+        return Err(syn::Error::new(
+            ast.ident.span(),
+            "A component can't be both a relationship and a relationship target.",
+        ));
+    }
+
     Ok(attrs)
 }
 
@@ -1,21 +1,92 @@
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, DeriveInput};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, DeriveInput, Pat, Path, Result, Token, Type,
+};
 
 use crate::bevy_ecs_path;
 
+fn states_path() -> Path {
+    let mut path = bevy_ecs_path();
+    path.segments.push(format_ident!("schedule").into());
+    path
+}
+
 pub fn derive_states(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let generics = ast.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let mut trait_path = bevy_ecs_path();
-    trait_path.segments.push(format_ident!("schedule").into());
-    trait_path.segments.push(format_ident!("States").into());
+    let states_path = states_path();
     let struct_name = &ast.ident;
 
     quote! {
-        impl #impl_generics #trait_path for #struct_name #ty_generics #where_clause {}
+        impl #impl_generics #states_path::States for #struct_name #ty_generics #where_clause {}
+
+        impl #impl_generics #states_path::FreelyMutableState for #struct_name #ty_generics #where_clause {}
+    }
+    .into()
+}
+
+/// The `#[source(SourceState = pattern)]` attribute of `#[derive(SubStates)]`.
+struct Source {
+    source_type: Type,
+    source_value: Pat,
+}
+
+impl Parse for Source {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let source_type = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let source_value = Pat::parse_multi_with_leading_vert(input)?;
+        Ok(Source {
+            source_type,
+            source_value,
+        })
+    }
+}
+
+pub fn derive_substates(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let Some(attr) = ast.attrs.iter().find(|attr| attr.path().is_ident("source")) else {
+        return syn::Error::new_spanned(
+            &ast.ident,
+            "SubStates require a source: `#[source(SourceState = SourceState::Variant)]`",
+        )
+        .into_compile_error()
+        .into();
+    };
+    let Source {
+        source_type,
+        source_value,
+    } = match attr.parse_args() {
+        Ok(source) => source,
+        Err(err) => return err.into_compile_error().into(),
+    };
+
+    let generics = ast.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let states_path = states_path();
+    let struct_name = &ast.ident;
+
+    quote! {
+        impl #impl_generics #states_path::SubStates for #struct_name #ty_generics #where_clause {
+            type SourceStates = #source_type;
+
+            fn should_exist(sources: #source_type) -> Option<Self> {
+                if matches!(sources, #source_value) {
+                    Some(Self::default())
+                } else {
+                    None
+                }
+            }
+        }
+
+        impl #impl_generics #states_path::States for #struct_name #ty_generics #where_clause {}
+
+        impl #impl_generics #states_path::FreelyMutableState for #struct_name #ty_generics #where_clause {}
     }
     .into()
 }
@@ -559,8 +559,12 @@ impl BundleInfo {
                     new_sparse_set_components
                 };
             };
-            let new_archetype_id =
-                archetypes.get_id_or_insert(table_id, table_components, sparse_set_components);
+            let new_archetype_id = archetypes.get_id_or_insert(
+                components,
+                table_id,
+                table_components,
+                sparse_set_components,
+            );
             // add an edge from the old archetype to the new archetype
             archetypes[archetype_id].edges_mut().insert_add_bundle(
                 self.id,
@@ -928,3 +932,302 @@ fn initialize_dynamic_bundle(
 
     (id, storage_types)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_ecs;
+    use crate::prelude::*;
+
+    #[derive(Component)]
+    struct A;
+
+    #[derive(Component)]
+    struct B;
+
+    #[derive(Component)]
+    struct C;
+
+    #[derive(Component)]
+    struct D;
+
+    #[derive(Resource, Default)]
+    struct R(usize);
+
+    impl R {
+        #[track_caller]
+        fn assert_order(&mut self, count: usize) {
+            assert_eq!(count, self.0);
+            self.0 += 1;
+        }
+    }
+
+    #[test]
+    fn component_hook_order_spawn_despawn() {
+        let mut world = World::new();
+        world.init_resource::<R>();
+        world
+            .register_component_hooks::<A>()
+            .on_add(|mut world, _, _| world.resource_mut::<R>().assert_order(0))
+            .on_insert(|mut world, _, _| world.resource_mut::<R>().assert_order(1))
+            .on_remove(|mut world, _, _| world.resource_mut::<R>().assert_order(2));
+
+        let entity = world.spawn(A).id();
+        world.despawn(entity);
+        assert_eq!(3, world.resource::<R>().0);
+    }
+
+    #[test]
+    fn component_hook_order_insert_remove() {
+        let mut world = World::new();
+        world.init_resource::<R>();
+        world
+            .register_component_hooks::<A>()
+            .on_add(|mut world, _, _| world.resource_mut::<R>().assert_order(0))
+            .on_insert(|mut world, _, _| world.resource_mut::<R>().assert_order(1))
+            .on_remove(|mut world, _, _| world.resource_mut::<R>().assert_order(2));
+
+        let mut entity = world.spawn_empty();
+        entity.insert(A);
+        entity.remove::<A>();
+        assert_eq!(3, world.resource::<R>().0);
+    }
+
+    #[test]
+    fn component_hook_order_replace() {
+        let mut world = World::new();
+        world
+            .register_component_hooks::<A>()
+            .on_insert(|mut world, _, _| {
+                if let Some(mut r) = world.get_resource_mut::<R>() {
+                    r.assert_order(0);
+                }
+            });
+
+        let entity = world.spawn(A).id();
+        world.init_resource::<R>();
+        world.entity_mut(entity).insert(A);
+        assert_eq!(1, world.resource::<R>().0);
+    }
+
+    #[test]
+    fn component_hook_order_recursive() {
+        let mut world = World::new();
+        world.init_resource::<R>();
+        world
+            .register_component_hooks::<A>()
+            .on_add(|mut world, entity, _| {
+                world.resource_mut::<R>().assert_order(0);
+                world.commands().entity(entity).insert(B);
+            })
+            .on_remove(|mut world, entity, _| {
+                world.resource_mut::<R>().assert_order(2);
+                world.commands().entity(entity).remove::<B>();
+            });
+
+        world
+            .register_component_hooks::<B>()
+            .on_add(|mut world, entity, _| {
+                world.resource_mut::<R>().assert_order(1);
+                world.commands().entity(entity).remove::<A>();
+            })
+            .on_remove(|mut world, _, _| {
+                world.resource_mut::<R>().assert_order(3);
+            });
+
+        let entity = world.spawn(A).id();
+        world.flush_commands();
+        let entity = world.get_entity(entity).unwrap();
+        assert!(!entity.contains::<A>());
+        assert!(!entity.contains::<B>());
+        assert_eq!(4, world.resource::<R>().0);
+    }
+
+    #[test]
+    fn component_hook_order_recursive_multiple() {
+        let mut world = World::new();
+        world.init_resource::<R>();
+        world
+            .register_component_hooks::<A>()
+            .on_add(|mut world, entity, _| {
+                world.resource_mut::<R>().assert_order(0);
+                world.commands().entity(entity).insert(B).insert(C);
+            });
+
+        world
+            .register_component_hooks::<B>()
+            .on_add(|mut world, entity, _| {
+                world.resource_mut::<R>().assert_order(1);
+                world.commands().entity(entity).insert(D);
+            });
+
+        world
+            .register_component_hooks::<C>()
+            .on_add(|mut world, _, _| {
+                world.resource_mut::<R>().assert_order(3);
+            });
+
+        world
+            .register_component_hooks::<D>()
+            .on_add(|mut world, _, _| {
+                world.resource_mut::<R>().assert_order(2);
+            });
+
+        world.spawn(A);
+        world.flush_commands();
+        assert_eq!(4, world.resource::<R>().0);
+    }
+
+    #[test]
+    fn component_hooks_run_from_commands() {
+        let mut world = World::new();
+        world.init_resource::<R>();
+        world
+            .register_component_hooks::<A>()
+            .on_add(|mut world, entity, _| {
+                world.resource_mut::<R>().assert_order(0);
+                world.commands().entity(entity).insert(B);
+            });
+
+        let mut queue = bevy_ecs::system::CommandQueue::default();
+        let entity = Commands::new(&mut queue, &world).spawn(A).id();
+        queue.apply(&mut world);
+        assert!(world.entity(entity).contains::<B>());
+        assert_eq!(1, world.resource::<R>().0);
+    }
+
+    #[test]
+    fn component_hooks_spawn_batch() {
+        let mut world = World::new();
+        world.init_resource::<R>();
+        world
+            .register_component_hooks::<A>()
+            .on_add(|mut world, _, _| world.resource_mut::<R>().0 += 1);
+
+        let entities = world.spawn_batch([A, A, A]).collect::<Vec<_>>();
+        assert_eq!(3, entities.len());
+        assert_eq!(3, world.resource::<R>().0);
+    }
+
+    #[test]
+    fn component_hooks_spawning_entities() {
+        let mut world = World::new();
+        world.init_resource::<R>();
+        world
+            .register_component_hooks::<A>()
+            .on_add(|mut world, _, _| {
+                world.resource_mut::<R>().0 += 1;
+                world.commands().spawn_empty();
+            })
+            .on_remove(|mut world, _, _| {
+                world.resource_mut::<R>().0 += 1;
+                world.commands().spawn(B);
+            });
+
+        let entities = world.spawn_batch([A, A, A]).collect::<Vec<_>>();
+        assert_eq!(3, world.resource::<R>().0);
+        assert_eq!(6, world.entities().len());
+
+        let spawned = world.spawn_empty().id();
+        world
+            .insert_or_spawn_batch([(entities[0], A), (spawned, A)])
+            .unwrap();
+        assert_eq!(4, world.resource::<R>().0);
+        assert_eq!(8, world.entities().len());
+
+        world.despawn(entities[0]);
+        world.despawn(spawned);
+        assert_eq!(6, world.resource::<R>().0);
+        assert_eq!(2, world.query::<&B>().iter(&world).count());
+    }
+
+    // `on_replace` runs between `on_insert` and `on_remove`, and before an existing value is
+    // overwritten, so relationships can unlink the previous target.
+    #[test]
+    fn component_hook_order_with_replace() {
+        let mut world = World::new();
+        world.init_resource::<R>();
+        world
+            .register_component_hooks::<A>()
+            .on_add(|mut world, _, _| world.resource_mut::<R>().assert_order(0))
+            .on_insert(|mut world, _, _| {
+                // Runs on spawn, then after `on_replace` when the value is overwritten
+                let mut r = world.resource_mut::<R>();
+                let order = if r.0 < 2 { 1 } else { 3 };
+                r.assert_order(order);
+            })
+            .on_replace(|mut world, _, _| {
+                // Runs before the value is overwritten, then before `on_remove` on despawn
+                let mut r = world.resource_mut::<R>();
+                let order = if r.0 < 4 { 2 } else { 4 };
+                r.assert_order(order);
+            })
+            .on_remove(|mut world, _, _| world.resource_mut::<R>().assert_order(5));
+
+        let entity = world.spawn(A).id();
+        assert_eq!(2, world.resource::<R>().0);
+        world.entity_mut(entity).insert(A);
+        assert_eq!(4, world.resource::<R>().0);
+        world.despawn(entity);
+        assert_eq!(6, world.resource::<R>().0);
+    }
+
+    // Structural changes made through the `World` apply the commands queued by hooks before
+    // returning, so relationships are consistent without calling `World::flush_commands`.
+    #[test]
+    fn component_hook_commands_applied_automatically() {
+        let mut world = World::new();
+        world
+            .register_component_hooks::<A>()
+            .on_add(|mut world, entity, _| {
+                world.commands().entity(entity).insert(B);
+            });
+
+        let entity = world.spawn(A).id();
+        assert!(world.entity(entity).contains::<B>());
+        let entity = world.spawn_empty().insert(A).id();
+        assert!(world.entity(entity).contains::<B>());
+    }
+
+    #[test]
+    fn component_hook_despawning_entity() {
+        let mut world = World::new();
+        world
+            .register_component_hooks::<A>()
+            .on_add(|mut world, entity, _| {
+                world.commands().entity(entity).despawn();
+            });
+
+        let entity = world.spawn(A);
+        assert!(entity.is_despawned());
+        let id = entity.id();
+        entity.despawn();
+        assert!(world.get_entity(id).is_none());
+
+        let mut entity = world.spawn_empty();
+        entity.insert(A);
+        assert!(entity.is_despawned());
+        let id = entity.id();
+        assert!(world.get_entity(id).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn component_hook_despawned_entity_access() {
+        let mut world = World::new();
+        world
+            .register_component_hooks::<A>()
+            .on_add(|mut world, entity, _| {
+                world.commands().entity(entity).despawn();
+            });
+
+        world.spawn(A).insert(B);
+    }
+
+    #[test]
+    #[should_panic]
+    fn component_hooks_registered_after_use() {
+        let mut world = World::new();
+        world.spawn(A);
+        world.register_component_hooks::<A>().on_add(|_, _, _| {});
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate as bevy_ecs;
 use crate::system::{Local, Res, ResMut, Resource, SystemParam};
+use crate::traversal::Traversal;
 pub use bevy_ecs_macros::Event;
 use bevy_utils::detailed_trace;
 use std::ops::{Deref, DerefMut};
@@ -16,9 +17,46 @@ use std::{
 /// A type that can be stored in an [`Events<E>`] resource
 /// You can conveniently access events using the [`EventReader`] and [`EventWriter`] system parameter.
 ///
+/// Events can also be [triggered](crate::world::World::trigger) to run [observers](crate::observer)
+/// immediately. To be triggered on entities, an event must also implement [`EntityEvent`], which
+/// `#[derive(Event)]` does.
+///
 /// Events must be thread-safe.
 pub trait Event: Send + Sync + 'static {}
 
+/// An [`Event`] that can be [triggered on entities](crate::world::World::trigger_targets), and
+/// propagate from these entities to further ones according to its [`EntityEvent::Traversal`].
+///
+/// # Deriving
+///
+/// `#[derive(Event)]` implements this trait along with [`Event`]. The propagation behavior of the
+/// event can be configured through the `event` attribute:
+///
+/// ```
+/// # use bevy_ecs::{prelude::*, traversal::Traversal, world::EntityRef};
+/// # #[derive(Component)]
+/// # struct Owner(Entity);
+/// # impl Traversal for Owner {
+/// #     fn traverse(entity: EntityRef) -> Option<Entity> {
+/// #         entity.get::<Owner>().map(|owner| owner.0)
+/// #     }
+/// # }
+/// #[derive(Event)]
+/// #[event(traversal = Owner, auto_propagate)]
+/// struct Damaged(f32);
+/// ```
+///
+/// Without the attribute, the event doesn't propagate: its [`EntityEvent::Traversal`] is `()`.
+pub trait EntityEvent: Event {
+    /// The [`Traversal`] used to find the next entity this event propagates to when triggered
+    /// on an entity. `()` disables propagation.
+    type Traversal: Traversal;
+
+    /// Whether a triggered event propagates along its [`EntityEvent::Traversal`] by default.
+    /// Observers can override this for each entity the event visits with [`Trigger::propagate`](crate::observer::Trigger::propagate).
+    const AUTO_PROPAGATE: bool = false;
+}
+
 /// An `EventId` uniquely identifies an event stored in a specific [`World`].
 ///
 /// An `EventId` can among other things be used to trace the flow of an event from the point it was
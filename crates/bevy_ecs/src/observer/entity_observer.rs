@@ -0,0 +1,72 @@
+use crate::{
+    component::{Component, ComponentHooks, SparseStorage},
+    entity::Entity,
+    world::{DeferredWorld, World},
+};
+
+use super::Observer;
+
+/// Tracks the [`Observer`]s watching an entity.
+///
+/// Added automatically when an observer watches the entity. When the entity is despawned,
+/// its observers stop watching it, and observers left without any watched entity are
+/// despawned as well.
+#[derive(Default, Debug)]
+pub struct ObservedBy(pub(crate) Vec<Entity>);
+
+impl ObservedBy {
+    /// Returns the observers watching this entity.
+    pub fn get(&self) -> &[Entity] {
+        &self.0
+    }
+}
+
+impl Component for ObservedBy {
+    type Storage = SparseStorage;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks.on_remove(|mut world, entity, _| {
+            let observers = std::mem::take(&mut world.get_mut::<ObservedBy>(entity).unwrap().0);
+            for observer in observers {
+                unwatch(&mut world, observer, entity);
+            }
+        });
+    }
+}
+
+/// Records that `observer` watches `target`, despawning the observer right away if `target`
+/// doesn't exist anymore.
+pub(crate) fn watch(world: &mut DeferredWorld, observer: Entity, target: Entity) {
+    if let Some(mut observed_by) = world.get_mut::<ObservedBy>(target) {
+        observed_by.0.push(observer);
+        return;
+    }
+    // inserting a component is a structural change, so it has to be deferred
+    world.commands().add(move |world: &mut World| {
+        if let Some(mut target) = world.get_entity_mut(target) {
+            if let Some(mut observed_by) = target.get_mut::<ObservedBy>() {
+                observed_by.0.push(observer);
+            } else {
+                target.insert(ObservedBy(vec![observer]));
+            }
+        } else {
+            unwatch(&mut DeferredWorld::from(world), observer, target);
+        }
+    });
+}
+
+/// Stops `observer` from watching `target`, despawning it if it doesn't watch any other entity.
+fn unwatch(world: &mut DeferredWorld, observer: Entity, target: Entity) {
+    let Some(mut observer_component) = world.get_mut::<Observer>(observer) else {
+        return;
+    };
+    observer_component
+        .entities
+        .retain(|&entity| entity != target);
+    let event = observer_component.event;
+    let despawn = observer_component.entities.is_empty();
+    world.observers_mut().unwatch(event, observer, target);
+    if despawn {
+        world.commands().entity(observer).despawn();
+    }
+}
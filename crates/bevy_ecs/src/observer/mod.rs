@@ -0,0 +1,669 @@
+//! Types for creating and running observers: systems that run immediately when an [`Event`] is
+//! [triggered](World::trigger).
+//!
+//! Unlike [`Events<E>`](crate::event::Events), which are buffered and read by systems at a later
+//! point in the schedule, a triggered event is handled right away by every [`Observer`] watching
+//! for it. An event can be triggered on its own, or targeted at one or more entities, in which
+//! case only the global observers and the observers watching those entities run.
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! #[derive(Event)]
+//! struct Explode;
+//!
+//! #[derive(Component)]
+//! struct Health(u32);
+//!
+//! let mut world = World::new();
+//! let entity = world.spawn(Health(10)).id();
+//!
+//! // Only runs when `Explode` is triggered on `entity`.
+//! world.entity_mut(entity).observe(|trigger: Trigger<Explode>, mut query: Query<&mut Health>| {
+//!     let target = trigger.entity().unwrap();
+//!     query.get_mut(target).unwrap().0 = 0;
+//! });
+//!
+//! world.trigger_targets(Explode, entity);
+//! assert_eq!(world.get::<Health>(entity).unwrap().0, 0);
+//! ```
+
+mod entity_observer;
+mod trigger_targets;
+
+pub use entity_observer::ObservedBy;
+pub use trigger_targets::TriggerTargets;
+
+use crate::{
+    change_detection::{DetectChangesMut, ResMut},
+    component::{Component, ComponentHooks, ComponentId, SparseStorage, Tick},
+    entity::Entity,
+    event::{EntityEvent, Event},
+    system::{
+        BoxedSystem, Commands, EntityCommands, IntoSystem, Resource, SystemMeta, SystemParam,
+        SystemParamFunction,
+    },
+    traversal::Traversal,
+    world::{unsafe_world_cell::UnsafeWorldCell, EntityRef, EntityWorldMut, World},
+    TypeIdMap,
+};
+use bevy_utils::{all_tuples, HashMap};
+use std::{any::TypeId, fmt::Debug};
+
+/// The event an [`Observer`] was triggered with, along with the entity it targets.
+///
+/// This is a [`SystemParam`] that is only valid in observer systems: using it in a regular
+/// system will panic when that system runs.
+pub struct Trigger<'w, E: Event> {
+    state: ResMut<'w, TriggerState<E>>,
+}
+
+impl<'w, E: Event> Trigger<'w, E> {
+    /// Returns the event that was triggered.
+    pub fn event(&self) -> &E {
+        &self.state.event
+    }
+
+    /// Returns a mutable reference to the triggered event.
+    ///
+    /// Changes are visible to the observers that run afterwards, including observers on the
+    /// entities the event propagates to.
+    pub fn event_mut(&mut self) -> &mut E {
+        &mut self.state.bypass_change_detection().event
+    }
+
+    /// Returns the entity the event is currently being triggered on, or `None` if the event
+    /// was triggered without a target.
+    ///
+    /// While an event propagates, this is the entity currently visited rather than the
+    /// original target.
+    pub fn entity(&self) -> Option<Entity> {
+        self.state.entity
+    }
+
+    /// Enables or disables propagation of the event to the next entity along its
+    /// [`EntityEvent::Traversal`].
+    ///
+    /// Propagation starts out as [`EntityEvent::AUTO_PROPAGATE`] for each targeted entity.
+    /// It has no effect for events triggered without a target.
+    pub fn propagate(&mut self, should_propagate: bool) {
+        self.state.bypass_change_detection().propagate = should_propagate;
+    }
+
+    /// Returns whether the event will propagate to the next entity once all observers of the
+    /// current one have run.
+    pub fn get_propagate(&self) -> bool {
+        self.state.propagate
+    }
+}
+
+impl<'w, E: Event + Debug> Debug for Trigger<'w, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Trigger")
+            .field("event", self.event())
+            .field("entity", &self.entity())
+            .field("propagate", &self.get_propagate())
+            .finish()
+    }
+}
+
+/// Resource holding the event currently being triggered, read through [`Trigger`].
+struct TriggerState<E: Event> {
+    event: E,
+    entity: Option<Entity>,
+    propagate: bool,
+}
+
+impl<E: Event> Resource for TriggerState<E> {}
+
+// SAFETY: this impl defers to `ResMut`, which initializes and validates the correct world access.
+unsafe impl<'w, E: Event> SystemParam for Trigger<'w, E> {
+    type State = ComponentId;
+    type Item<'world, 'state> = Trigger<'world, E>;
+
+    fn init_state(world: &mut World, system_meta: &mut SystemMeta) -> Self::State {
+        ResMut::<TriggerState<E>>::init_state(world, system_meta)
+    }
+
+    #[inline]
+    unsafe fn get_param<'world, 'state>(
+        state: &'state mut Self::State,
+        system_meta: &SystemMeta,
+        world: UnsafeWorldCell<'world>,
+        change_tick: Tick,
+    ) -> Self::Item<'world, 'state> {
+        Trigger {
+            // SAFETY: the caller upholds the safety requirements of `ResMut::get_param`
+            state: unsafe {
+                ResMut::<TriggerState<E>>::get_param(state, system_meta, world, change_tick)
+            },
+        }
+    }
+}
+
+/// Conversion trait for systems that can be used as an [`Observer`] of the [`Event`] `E`.
+///
+/// This is implemented for functions and closures whose first parameter is a [`Trigger<E>`],
+/// followed by any number of other [`SystemParam`]s.
+pub trait IntoObserverSystem<E: Event, Marker>: Send + 'static {
+    /// Turns this value into a boxed observer system.
+    fn into_observer_system(self) -> BoxedSystem;
+}
+
+macro_rules! impl_into_observer_system {
+    ($($param: ident),*) => {
+        impl<E, Func, $($param),*> IntoObserverSystem<E, fn(Trigger<E>, $($param,)*)> for Func
+        where
+            E: Event,
+            $($param: SystemParam + 'static,)*
+            Func: SystemParamFunction<fn(Trigger<'static, E>, $($param,)*) -> (), In = (), Out = ()>,
+        {
+            fn into_observer_system(self) -> BoxedSystem {
+                Box::new(IntoSystem::into_system(self))
+            }
+        }
+    };
+}
+
+all_tuples!(impl_into_observer_system, 0, 15, P);
+
+/// A [`Component`] that turns its entity into an observer: a system that runs whenever its
+/// [`Event`] is [triggered](World::trigger).
+///
+/// An observer without watched entities is global: it runs for every trigger of its event.
+/// An observer watching entities only runs when the event targets one of them, and is
+/// despawned once all of them are despawned.
+///
+/// Observers are usually created through [`World::observe`], [`EntityWorldMut::observe`] or
+/// their [`Commands`] equivalents. The watched entities can only be set before the observer
+/// is spawned.
+pub struct Observer {
+    system: Option<BoxedSystem>,
+    initialized: bool,
+    event: TypeId,
+    entities: Vec<Entity>,
+}
+
+impl Observer {
+    /// Creates a new global observer from the given system.
+    pub fn new<E: Event, M>(system: impl IntoObserverSystem<E, M>) -> Self {
+        Self {
+            system: Some(system.into_observer_system()),
+            initialized: false,
+            event: TypeId::of::<E>(),
+            entities: Vec::new(),
+        }
+    }
+
+    /// Makes this observer watch `entity`: it will only run when its event targets one of
+    /// its watched entities.
+    pub fn with_entity(mut self, entity: Entity) -> Self {
+        self.watch_entity(entity);
+        self
+    }
+
+    /// Makes this observer watch `entity`: it will only run when its event targets one of
+    /// its watched entities.
+    ///
+    /// Watching entities has no effect once the observer has been spawned.
+    pub fn watch_entity(&mut self, entity: Entity) {
+        if !self.entities.contains(&entity) {
+            self.entities.push(entity);
+        }
+    }
+
+    /// Returns the entities watched by this observer. Empty for global observers.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+}
+
+impl Component for Observer {
+    type Storage = SparseStorage;
+
+    fn register_component_hooks(hooks: &mut ComponentHooks) {
+        hooks
+            .on_add(|mut world, entity, _| {
+                let observer = world.get::<Observer>(entity).unwrap();
+                let event = observer.event;
+                let targets = observer.entities.clone();
+                world.observers_mut().register(event, entity, &targets);
+                for target in targets {
+                    entity_observer::watch(&mut world, entity, target);
+                }
+            })
+            .on_remove(|mut world, entity, _| {
+                let observer = world.get::<Observer>(entity).unwrap();
+                let event = observer.event;
+                let targets = observer.entities.clone();
+                world.observers_mut().unregister(event, entity, &targets);
+                for target in targets {
+                    if let Some(mut observed_by) = world.get_mut::<ObservedBy>(target) {
+                        observed_by.0.retain(|&observer| observer != entity);
+                    }
+                }
+            });
+    }
+}
+
+/// Observers registered for a single [`Event`] type.
+#[derive(Default, Debug)]
+struct CachedObservers {
+    /// Observers that run for every trigger of the event.
+    global: Vec<Entity>,
+    /// Observers that only run when the event targets a given entity.
+    entity_observers: HashMap<Entity, Vec<Entity>>,
+}
+
+/// Index of every [`Observer`] in a [`World`], by event type.
+#[derive(Default, Debug)]
+pub(crate) struct Observers {
+    cache: TypeIdMap<CachedObservers>,
+}
+
+impl Observers {
+    fn register(&mut self, event: TypeId, observer: Entity, targets: &[Entity]) {
+        let cache = self.cache.entry(event).or_default();
+        if targets.is_empty() {
+            cache.global.push(observer);
+        } else {
+            for &target in targets {
+                cache
+                    .entity_observers
+                    .entry(target)
+                    .or_default()
+                    .push(observer);
+            }
+        }
+    }
+
+    fn unregister(&mut self, event: TypeId, observer: Entity, targets: &[Entity]) {
+        let Some(cache) = self.cache.get_mut(&event) else {
+            return;
+        };
+        if targets.is_empty() {
+            cache.global.retain(|&e| e != observer);
+        } else {
+            for target in targets {
+                self.unwatch(event, observer, *target);
+            }
+        }
+    }
+
+    fn unwatch(&mut self, event: TypeId, observer: Entity, target: Entity) {
+        let Some(cache) = self.cache.get_mut(&event) else {
+            return;
+        };
+        if let Some(observers) = cache.entity_observers.get_mut(&target) {
+            observers.retain(|&e| e != observer);
+            if observers.is_empty() {
+                cache.entity_observers.remove(&target);
+            }
+        }
+    }
+
+    /// Returns `true` if there are any observers for the event type.
+    fn has_observers(&self, event: TypeId) -> bool {
+        self.cache
+            .get(&event)
+            .is_some_and(|cache| !cache.global.is_empty() || !cache.entity_observers.is_empty())
+    }
+
+    /// Returns the observers to run when the event is triggered on `target`.
+    fn get(&self, event: TypeId, target: Option<Entity>) -> Vec<Entity> {
+        let Some(cache) = self.cache.get(&event) else {
+            return Vec::new();
+        };
+        let mut observers = cache.global.clone();
+        if let Some(entity_observers) = target.and_then(|t| cache.entity_observers.get(&t)) {
+            observers.extend_from_slice(entity_observers);
+        }
+        observers
+    }
+}
+
+impl World {
+    /// Spawns a global [`Observer`] running `system` each time its [`Event`] is triggered,
+    /// and returns its entity.
+    pub fn observe<E: Event, M>(
+        &mut self,
+        system: impl IntoObserverSystem<E, M>,
+    ) -> EntityWorldMut {
+        self.spawn(Observer::new(system))
+    }
+
+    /// Triggers `event`, running all global observers of its type before returning.
+    ///
+    /// [`Trigger::entity`] is `None` for these observers.
+    pub fn trigger<E: Event>(&mut self, event: E) {
+        self.trigger_internal(event, &[], false, |_| None);
+    }
+
+    /// Triggers `event` on each of the `targets`, running all global observers and the
+    /// observers watching each target before returning.
+    ///
+    /// If the event propagates (see [`EntityEvent::Traversal`]), observers on the entities reached
+    /// from each target are run as well, in order.
+    /// If `targets` is empty, this is the same as [`World::trigger`].
+    pub fn trigger_targets<E: EntityEvent>(&mut self, event: E, targets: impl TriggerTargets) {
+        self.trigger_internal(
+            event,
+            targets.entities(),
+            E::AUTO_PROPAGATE,
+            E::Traversal::traverse,
+        );
+    }
+
+    /// Triggers `event` on the `targets`, propagating it with `traverse`.
+    fn trigger_internal<E: Event>(
+        &mut self,
+        event: E,
+        targets: &[Entity],
+        auto_propagate: bool,
+        traverse: fn(EntityRef) -> Option<Entity>,
+    ) {
+        let event_type = TypeId::of::<E>();
+        if !self.observers.has_observers(event_type) {
+            return;
+        }
+        let previous = self.remove_resource::<TriggerState<E>>();
+        self.insert_resource(TriggerState {
+            event,
+            entity: None,
+            propagate: false,
+        });
+
+        if targets.is_empty() {
+            self.run_observers(event_type, None);
+        }
+        for &target in targets {
+            let mut current = target;
+            self.resource_mut::<TriggerState<E>>().propagate = auto_propagate;
+            loop {
+                self.resource_mut::<TriggerState<E>>().entity = Some(current);
+                self.run_observers(event_type, Some(current));
+                if !self.resource::<TriggerState<E>>().propagate {
+                    break;
+                }
+                match self.get_entity(current).and_then(traverse) {
+                    Some(next) => current = next,
+                    None => break,
+                }
+            }
+        }
+
+        self.remove_resource::<TriggerState<E>>();
+        if let Some(previous) = previous {
+            self.insert_resource(previous);
+        }
+    }
+
+    fn run_observers(&mut self, event_type: TypeId, target: Option<Entity>) {
+        for observer in self.observers.get(event_type, target) {
+            // take ownership of the system, so that it can run with access to the world
+            let Some(mut observer_component) = self.get_mut::<Observer>(observer) else {
+                continue;
+            };
+            // `None` while the observer is running, in which case it doesn't run recursively
+            let Some(mut system) = observer_component.system.take() else {
+                continue;
+            };
+            let initialized = std::mem::replace(&mut observer_component.initialized, true);
+
+            if !initialized {
+                system.initialize(self);
+            }
+            system.run((), self);
+            system.apply_deferred(self);
+
+            // return ownership of the system (if the observer still exists)
+            if let Some(mut observer_component) = self.get_mut::<Observer>(observer) {
+                observer_component.system = Some(system);
+            }
+        }
+    }
+}
+
+impl<'w> EntityWorldMut<'w> {
+    /// Spawns an [`Observer`] watching this entity, which runs `system` each time its
+    /// [`Event`] is triggered on this entity.
+    ///
+    /// The observer is despawned along with this entity.
+    pub fn observe<E: Event, M>(&mut self, system: impl IntoObserverSystem<E, M>) -> &mut Self {
+        let entity = self.id();
+        self.world_scope(|world| {
+            world.spawn(Observer::new(system).with_entity(entity));
+        });
+        self
+    }
+}
+
+impl<'w, 's> Commands<'w, 's> {
+    /// Spawns a global [`Observer`] running `system` each time its [`Event`] is triggered,
+    /// and returns the [`EntityCommands`] of the observer.
+    pub fn observe<E: Event, M>(
+        &mut self,
+        system: impl IntoObserverSystem<E, M>,
+    ) -> EntityCommands<'w, 's, '_> {
+        self.spawn(Observer::new(system))
+    }
+
+    /// Triggers `event` when this command is applied, running all global observers of its type.
+    ///
+    /// See [`World::trigger`].
+    pub fn trigger<E: Event>(&mut self, event: E) {
+        self.add(move |world: &mut World| world.trigger(event));
+    }
+
+    /// Triggers `event` on each of the `targets` when this command is applied.
+    ///
+    /// See [`World::trigger_targets`].
+    pub fn trigger_targets<E: EntityEvent>(&mut self, event: E, targets: impl TriggerTargets) {
+        self.add(move |world: &mut World| world.trigger_targets(event, targets));
+    }
+}
+
+impl<'w, 's, 'a> EntityCommands<'w, 's, 'a> {
+    /// Spawns an [`Observer`] watching this entity, which runs `system` each time its
+    /// [`Event`] is triggered on this entity.
+    ///
+    /// See [`EntityWorldMut::observe`].
+    pub fn observe<E: Event, M>(&mut self, system: impl IntoObserverSystem<E, M>) -> &mut Self {
+        let observer = Observer::new(system).with_entity(self.id());
+        self.commands().spawn(observer);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_ecs;
+    use crate::prelude::*;
+    use crate::traversal::Traversal;
+    use crate::world::EntityRef;
+
+    #[derive(Event)]
+    struct EventA;
+
+    #[derive(Event)]
+    struct EventB(usize);
+
+    #[derive(Component)]
+    struct ChildOf(Entity);
+
+    impl Traversal for ChildOf {
+        fn traverse(entity: EntityRef) -> Option<Entity> {
+            entity.get::<ChildOf>().map(|parent| parent.0)
+        }
+    }
+
+    #[derive(Event)]
+    #[event(traversal = ChildOf, auto_propagate)]
+    struct Bubbling;
+
+    /// An event implemented by hand, which can't be triggered on entities.
+    struct Manual;
+
+    impl Event for Manual {}
+
+    #[derive(Resource, Default)]
+    struct Order(Vec<&'static str>);
+
+    impl Order {
+        fn observed(&mut self, name: &'static str) {
+            self.0.push(name);
+        }
+    }
+
+    #[test]
+    fn observer_manual_event() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+        world.observe(|_: Trigger<Manual>, mut order: ResMut<Order>| {
+            order.observed("manual");
+        });
+        world.trigger(Manual);
+        assert_eq!(world.resource::<Order>().0, ["manual"]);
+    }
+
+    #[test]
+    fn observer_global() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+        world.observe(|trigger: Trigger<EventA>, mut order: ResMut<Order>| {
+            assert!(trigger.entity().is_none());
+            order.observed("global");
+        });
+
+        world.trigger(EventA);
+        world.trigger(EventB(0));
+        assert_eq!(vec!["global"], world.resource::<Order>().0);
+    }
+
+    #[test]
+    fn observer_entity() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+        let a = world.spawn_empty().id();
+        let b = world.spawn_empty().id();
+        world
+            .entity_mut(a)
+            .observe(move |trigger: Trigger<EventA>, mut order: ResMut<Order>| {
+                assert_eq!(Some(a), trigger.entity());
+                order.observed("a");
+            });
+        world.observe(|_: Trigger<EventA>, mut order: ResMut<Order>| order.observed("global"));
+
+        world.trigger_targets(EventA, a);
+        world.trigger_targets(EventA, b);
+        assert_eq!(vec!["global", "a", "global"], world.resource::<Order>().0);
+    }
+
+    #[test]
+    fn observer_event_mut() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+        world.observe(|mut trigger: Trigger<EventB>| trigger.event_mut().0 += 1);
+        world.observe(|trigger: Trigger<EventB>, mut order: ResMut<Order>| {
+            assert_eq!(1, trigger.event().0);
+            order.observed("second");
+        });
+
+        world.trigger(EventB(0));
+        assert_eq!(vec!["second"], world.resource::<Order>().0);
+    }
+
+    #[test]
+    fn observer_despawn_with_entity() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+        let entity = world.spawn_empty().id();
+        world
+            .entity_mut(entity)
+            .observe(|_: Trigger<EventA>, mut order: ResMut<Order>| order.observed("entity"));
+        let observer = world.query::<(Entity, &Observer)>().single(&world).0;
+
+        world.despawn(entity);
+        assert!(world.get_entity(observer).is_none());
+        world.trigger_targets(EventA, entity);
+        assert!(world.resource::<Order>().0.is_empty());
+    }
+
+    #[test]
+    fn observer_watching_despawned_entity() {
+        let mut world = World::new();
+        let entity = world.spawn_empty().id();
+        world.despawn(entity);
+
+        let observer = world.spawn(Observer::new(|_: Trigger<EventA>| {}).with_entity(entity));
+        assert!(observer.is_despawned());
+        assert!(world.query::<&Observer>().iter(&world).next().is_none());
+    }
+
+    #[test]
+    fn observer_despawn_observer() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+        let observer = world
+            .observe(|_: Trigger<EventA>, mut order: ResMut<Order>| order.observed("global"))
+            .id();
+
+        world.despawn(observer);
+        world.trigger(EventA);
+        assert!(world.resource::<Order>().0.is_empty());
+    }
+
+    #[test]
+    fn observer_propagating() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+        let parent = world.spawn_empty().id();
+        let child = world.spawn(ChildOf(parent)).id();
+        world
+            .entity_mut(parent)
+            .observe(|_: Trigger<Bubbling>, mut order: ResMut<Order>| order.observed("parent"));
+        world
+            .entity_mut(child)
+            .observe(|_: Trigger<Bubbling>, mut order: ResMut<Order>| order.observed("child"));
+
+        world.trigger_targets(Bubbling, child);
+        assert_eq!(vec!["child", "parent"], world.resource::<Order>().0);
+    }
+
+    #[test]
+    fn observer_propagating_halt() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+        let parent = world.spawn_empty().id();
+        let child = world.spawn(ChildOf(parent)).id();
+        world
+            .entity_mut(parent)
+            .observe(|_: Trigger<Bubbling>, mut order: ResMut<Order>| order.observed("parent"));
+        world.entity_mut(child).observe(
+            |mut trigger: Trigger<Bubbling>, mut order: ResMut<Order>| {
+                order.observed("child");
+                trigger.propagate(false);
+            },
+        );
+
+        world.trigger_targets(Bubbling, child);
+        assert_eq!(vec!["child"], world.resource::<Order>().0);
+    }
+
+    #[test]
+    fn observer_from_commands() {
+        let mut world = World::new();
+        world.init_resource::<Order>();
+        world.observe(|_: Trigger<EventA>, mut commands: Commands| {
+            commands.trigger(EventB(1));
+        });
+        world.observe(|trigger: Trigger<EventB>, mut order: ResMut<Order>| {
+            assert_eq!(1, trigger.event().0);
+            order.observed("b");
+        });
+
+        let mut queue = bevy_ecs::system::CommandQueue::default();
+        Commands::new(&mut queue, &world).trigger(EventA);
+        queue.apply(&mut world);
+        assert_eq!(vec!["b"], world.resource::<Order>().0);
+    }
+}
@@ -0,0 +1,25 @@
+use crate::entity::Entity;
+
+/// Represents the entities an event is [triggered](crate::world::World::trigger_targets) on.
+pub trait TriggerTargets: Send + Sync + 'static {
+    /// The entities the event should be triggered on, in order.
+    fn entities(&self) -> &[Entity];
+}
+
+impl TriggerTargets for Entity {
+    fn entities(&self) -> &[Entity] {
+        std::slice::from_ref(self)
+    }
+}
+
+impl TriggerTargets for Vec<Entity> {
+    fn entities(&self) -> &[Entity] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> TriggerTargets for [Entity; N] {
+    fn entities(&self) -> &[Entity] {
+        self.as_slice()
+    }
+}
@@ -3,7 +3,7 @@ use crate::{
     entity::{Entity, EntityMapper, MapEntities},
     world::World,
 };
-use bevy_reflect::FromType;
+use bevy_reflect::{FromType, Reflect};
 use bevy_utils::EntityHashMap;
 
 /// For a specific type of component, this maps any fields with values of type [`Entity`] to a new world.
@@ -15,6 +15,7 @@ use bevy_utils::EntityHashMap;
 pub struct ReflectMapEntities {
     map_all_entities: fn(&mut World, &mut EntityMapper),
     map_entities: fn(&mut World, &mut EntityMapper, &[Entity]),
+    map_reflected: fn(&mut dyn Reflect, &mut EntityMapper) -> bool,
 }
 
 impl ReflectMapEntities {
@@ -51,6 +52,25 @@ impl ReflectMapEntities {
             (self.map_entities)(world, mapper, entities);
         });
     }
+
+    /// Applies [`MapEntities`] behavior to a component value before it is inserted in the world.
+    ///
+    /// Unlike [`map_entities`](Self::map_entities), the [component hooks](crate::component::ComponentHooks)
+    /// of the component see the mapped entities when it is inserted, which is required for
+    /// [relationships](crate::relationship) to be indexed correctly.
+    ///
+    /// Returns `false` if `value` isn't a concrete instance of the component type, in which case it is
+    /// left untouched. Dynamic values can be converted with [`ReflectFromReflect`](bevy_reflect::ReflectFromReflect) first.
+    pub fn map_reflected(
+        &self,
+        value: &mut dyn Reflect,
+        world: &mut World,
+        entity_map: &mut EntityHashMap<Entity, Entity>,
+    ) -> bool {
+        EntityMapper::world_scope(entity_map, world, |_, mapper| {
+            (self.map_reflected)(value, mapper)
+        })
+    }
 }
 
 impl<C: Component + MapEntities> FromType<C> for ReflectMapEntities {
@@ -75,6 +95,14 @@ impl<C: Component + MapEntities> FromType<C> for ReflectMapEntities {
                     }
                 }
             },
+            map_reflected: |value, entity_mapper| {
+                // Downcasting through `Any` doesn't require `C: Reflect`
+                let Some(component) = value.as_any_mut().downcast_mut::<C>() else {
+                    return false;
+                };
+                component.map_entities(entity_mapper);
+                true
+            },
         }
     }
 }
@@ -0,0 +1,324 @@
+//! Relationships between entities, kept in sync in both directions.
+//!
+//! A relationship is made of two components:
+//! - a [`Relationship`] component, stored on the *source* entity and pointing at a single *target*
+//!   entity, like "this unit is *owned by* that player".
+//! - a [`RelationshipTarget`] component, stored on the target entity and listing all the sources
+//!   that point to it, like "this player *owns* these units".
+//!
+//! Only the [`Relationship`] side should be modified: inserting, replacing or removing it
+//! automatically updates the [`RelationshipTarget`] of the target entity, which is inserted when it
+//! gets its first source and removed when it loses its last one. Despawning either side cleans up
+//! the other: a despawned source is removed from its target, and the sources of a despawned target
+//! lose their [`Relationship`] component.
+//!
+//! Both components are usually defined with `#[derive(Component)]`:
+//!
+//! ```
+//! # use bevy_ecs::{prelude::*, relationship::RelationshipTarget};
+//! /// The player owning this unit.
+//! #[derive(Component)]
+//! #[relationship(relationship_target = Owns)]
+//! struct OwnedBy(Entity);
+//!
+//! /// The units owned by this player.
+//! #[derive(Component)]
+//! #[relationship_target(relationship = OwnedBy)]
+//! struct Owns(Vec<Entity>);
+//!
+//! let mut world = World::new();
+//! let player = world.spawn_empty().id();
+//! let unit = world.spawn(OwnedBy(player)).id();
+//! assert_eq!(world.get::<Owns>(player).unwrap().collection(), &vec![unit]);
+//!
+//! world.despawn(unit);
+//! assert!(world.get::<Owns>(player).is_none());
+//! ```
+//!
+//! The [`Relationship`] component must have a single [`Entity`] field, and the
+//! [`RelationshipTarget`] component a single field implementing [`RelationshipSourceCollection`].
+//!
+//! Mutating either component in place (through [`Mut`](crate::change_detection::Mut) or a mutable
+//! query) bypasses the [component hooks](crate::component::ComponentHooks) that keep the two sides in
+//! sync: to change the target of a relationship, insert a new [`Relationship`] component instead.
+
+mod relationship_source_collection;
+
+pub use relationship_source_collection::RelationshipSourceCollection;
+
+use crate::{
+    component::{Component, ComponentId},
+    entity::Entity,
+    world::{DeferredWorld, World},
+};
+use bevy_utils::tracing::warn;
+
+/// The source side of a relationship: a [`Component`] pointing at a single target entity.
+///
+/// The target entity's [`Self::RelationshipTarget`] component is kept in sync by the
+/// [`Relationship::on_insert`] and [`Relationship::on_replace`] hooks, which
+/// `#[derive(Component)]` registers when given `#[relationship(relationship_target = ...)]`.
+///
+/// See the [module docs](crate::relationship) for more information.
+pub trait Relationship: Component + Sized {
+    /// The [`Component`] tracking all the sources of this relationship on the target entity.
+    type RelationshipTarget: RelationshipTarget<Relationship = Self>;
+
+    /// Gets the target entity of this relationship.
+    fn get(&self) -> Entity;
+
+    /// Creates this relationship from the target entity.
+    fn from(entity: Entity) -> Self;
+
+    /// The `on_insert` [`ComponentHook`](crate::component::ComponentHook) of this component,
+    /// adding `entity` to the sources of its new target.
+    fn on_insert(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+        let target = world.get::<Self>(entity).unwrap().get();
+        if target == entity {
+            warn!(
+                "The {} relationship on {entity:?} points to itself: removing it.",
+                std::any::type_name::<Self>()
+            );
+            world.commands().entity(entity).remove::<Self>();
+            return;
+        }
+        if world.get_entity(target).is_none() {
+            warn!(
+                "The {} relationship on {entity:?} points to {target:?}, which doesn't exist: removing it.",
+                std::any::type_name::<Self>()
+            );
+            world.commands().entity(entity).remove::<Self>();
+            return;
+        }
+        if let Some(mut relationship_target) = world.get_mut::<Self::RelationshipTarget>(target) {
+            let collection = relationship_target.collection_mut_risky();
+            if !collection.contains(entity) {
+                collection.add(entity);
+            }
+            return;
+        }
+        // inserting the relationship target is a structural change, so it has to be deferred
+        world.commands().add(move |world: &mut World| {
+            // the relationship may have changed since this command was queued
+            if world.get::<Self>(entity).map(Self::get) != Some(target) {
+                return;
+            }
+            let Some(mut target) = world.get_entity_mut(target) else {
+                return;
+            };
+            if let Some(mut relationship_target) = target.get_mut::<Self::RelationshipTarget>() {
+                let collection = relationship_target.collection_mut_risky();
+                if !collection.contains(entity) {
+                    collection.add(entity);
+                }
+            } else {
+                let mut collection =
+                    <Self::RelationshipTarget as RelationshipTarget>::Collection::with_capacity(1);
+                collection.add(entity);
+                target.insert(Self::RelationshipTarget::from_collection_risky(collection));
+            }
+        });
+    }
+
+    /// The `on_replace` [`ComponentHook`](crate::component::ComponentHook) of this component,
+    /// removing `entity` from the sources of its previous target.
+    fn on_replace(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+        let target = world.get::<Self>(entity).unwrap().get();
+        let Some(mut relationship_target) = world.get_mut::<Self::RelationshipTarget>(target)
+        else {
+            return;
+        };
+        relationship_target.collection_mut_risky().remove(entity);
+        if !relationship_target.collection().is_empty() {
+            return;
+        }
+        world.commands().add(move |world: &mut World| {
+            // sources may have been added since this command was queued
+            let Some(mut target) = world.get_entity_mut(target) else {
+                return;
+            };
+            if target
+                .get::<Self::RelationshipTarget>()
+                .is_some_and(|relationship_target| relationship_target.collection().is_empty())
+            {
+                target.remove::<Self::RelationshipTarget>();
+            }
+        });
+    }
+}
+
+/// The target side of a relationship: a [`Component`] listing all the entities whose
+/// [`Self::Relationship`] points to this entity.
+///
+/// This component is managed by its [`Relationship`]: it shouldn't be inserted or mutated directly.
+/// When it is replaced or removed, which includes despawning the entity, the
+/// [`RelationshipTarget::on_replace`] hook removes the relationship from all its sources, unless
+/// they are still listed in the new value.
+///
+/// See the [module docs](crate::relationship) for more information.
+pub trait RelationshipTarget: Component + Sized {
+    /// The [`Component`] stored on each source of this relationship.
+    type Relationship: Relationship<RelationshipTarget = Self>;
+    /// The collection storing the sources of this relationship.
+    type Collection: RelationshipSourceCollection;
+
+    /// Returns the collection of sources of this relationship.
+    fn collection(&self) -> &Self::Collection;
+
+    /// Returns a mutable reference to the collection of sources of this relationship.
+    ///
+    /// Modifying it directly desynchronizes the relationship: this is meant for [`Relationship`]
+    /// hooks, or for reordering the sources.
+    fn collection_mut_risky(&mut self) -> &mut Self::Collection;
+
+    /// Creates this component from a collection of sources.
+    ///
+    /// The sources' [`Relationship`] components aren't updated: this is meant for [`Relationship`]
+    /// hooks.
+    fn from_collection_risky(collection: Self::Collection) -> Self;
+
+    /// Iterates over the sources of this relationship.
+    fn iter(&self) -> std::iter::Copied<std::slice::Iter<'_, Entity>> {
+        self.collection().as_slice().iter().copied()
+    }
+
+    /// The `on_replace` [`ComponentHook`](crate::component::ComponentHook) of this component,
+    /// removing the [`Self::Relationship`] of its sources.
+    fn on_replace(mut world: DeferredWorld, entity: Entity, _: ComponentId) {
+        let sources = world
+            .get::<Self>(entity)
+            .unwrap()
+            .iter()
+            .collect::<Vec<_>>();
+        if sources.is_empty() {
+            return;
+        }
+        // removing components is a structural change, so it has to be deferred
+        world.commands().add(move |world: &mut World| {
+            for source in sources {
+                // skip sources that are still listed, e.g. if this component was re-inserted
+                if world
+                    .get::<Self>(entity)
+                    .is_some_and(|target| target.collection().contains(source))
+                {
+                    continue;
+                }
+                let Some(mut source) = world.get_entity_mut(source) else {
+                    continue;
+                };
+                if source
+                    .get::<Self::Relationship>()
+                    .is_some_and(|relationship| relationship.get() == entity)
+                {
+                    source.remove::<Self::Relationship>();
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate as bevy_ecs;
+    use crate::prelude::*;
+    use crate::relationship::RelationshipTarget;
+
+    #[derive(Component)]
+    #[relationship(relationship_target = Likes)]
+    struct LikedBy(Entity);
+
+    #[derive(Component)]
+    #[relationship_target(relationship = LikedBy)]
+    struct Likes(Vec<Entity>);
+
+    fn sources(world: &World, target: Entity) -> Option<Vec<Entity>> {
+        world
+            .get::<Likes>(target)
+            .map(|likes| likes.iter().collect())
+    }
+
+    #[test]
+    fn relationship_insert() {
+        let mut world = World::new();
+        let target = world.spawn_empty().id();
+        let a = world.spawn(LikedBy(target)).id();
+        let b = world.spawn_empty().insert(LikedBy(target)).id();
+        assert_eq!(Some(vec![a, b]), sources(&world, target));
+    }
+
+    #[test]
+    fn relationship_replace() {
+        let mut world = World::new();
+        let first = world.spawn_empty().id();
+        let second = world.spawn_empty().id();
+        let a = world.spawn(LikedBy(first)).id();
+        let b = world.spawn(LikedBy(first)).id();
+
+        world.entity_mut(a).insert(LikedBy(second));
+        assert_eq!(Some(vec![b]), sources(&world, first));
+        assert_eq!(Some(vec![a]), sources(&world, second));
+
+        world.entity_mut(a).insert(LikedBy(second));
+        assert_eq!(Some(vec![a]), sources(&world, second));
+    }
+
+    #[test]
+    fn relationship_remove() {
+        let mut world = World::new();
+        let target = world.spawn_empty().id();
+        let a = world.spawn(LikedBy(target)).id();
+        let b = world.spawn(LikedBy(target)).id();
+
+        world.entity_mut(a).remove::<LikedBy>();
+        assert_eq!(Some(vec![b]), sources(&world, target));
+        world.entity_mut(b).remove::<LikedBy>();
+        assert_eq!(None, sources(&world, target));
+    }
+
+    #[test]
+    fn relationship_despawn_source() {
+        let mut world = World::new();
+        let target = world.spawn_empty().id();
+        let a = world.spawn(LikedBy(target)).id();
+
+        world.despawn(a);
+        assert_eq!(None, sources(&world, target));
+    }
+
+    #[test]
+    fn relationship_despawn_target() {
+        let mut world = World::new();
+        let target = world.spawn_empty().id();
+        let a = world.spawn(LikedBy(target)).id();
+        let b = world.spawn(LikedBy(target)).id();
+
+        world.despawn(target);
+        assert!(world.get::<LikedBy>(a).is_none());
+        assert!(world.get::<LikedBy>(b).is_none());
+    }
+
+    #[test]
+    fn relationship_invalid_target() {
+        let mut world = World::new();
+        let despawned = world.spawn_empty().id();
+        world.despawn(despawned);
+
+        let a = world.spawn(LikedBy(despawned)).id();
+        assert!(world.get::<LikedBy>(a).is_none());
+        let b = world.spawn_empty().id();
+        world.entity_mut(b).insert(LikedBy(b));
+        assert!(world.get::<LikedBy>(b).is_none());
+    }
+
+    #[test]
+    fn relationship_from_commands() {
+        let mut world = World::new();
+        let target = world.spawn_empty().id();
+        let mut queue = bevy_ecs::system::CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let a = commands.spawn(LikedBy(target)).id();
+        let b = commands.spawn(LikedBy(target)).id();
+        queue.apply(&mut world);
+        assert_eq!(Some(vec![a, b]), sources(&world, target));
+    }
+}
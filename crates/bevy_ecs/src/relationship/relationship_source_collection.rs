@@ -0,0 +1,72 @@
+use crate::entity::Entity;
+use smallvec::SmallVec;
+
+/// The collection of sources stored in a [`RelationshipTarget`](super::RelationshipTarget).
+pub trait RelationshipSourceCollection {
+    /// Creates an empty collection with room for at least `capacity` sources.
+    fn with_capacity(capacity: usize) -> Self;
+
+    /// Adds `entity` to the collection.
+    fn add(&mut self, entity: Entity);
+
+    /// Removes `entity` from the collection, if present.
+    fn remove(&mut self, entity: Entity);
+
+    /// Returns the sources in the collection, in order.
+    fn as_slice(&self) -> &[Entity];
+
+    /// Returns `true` if the collection contains `entity`.
+    fn contains(&self, entity: Entity) -> bool {
+        self.as_slice().contains(&entity)
+    }
+
+    /// Returns the number of sources in the collection.
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    /// Returns `true` if the collection is empty.
+    fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+}
+
+impl RelationshipSourceCollection for Vec<Entity> {
+    fn with_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity)
+    }
+
+    fn add(&mut self, entity: Entity) {
+        self.push(entity);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(index) = self.iter().position(|&source| source == entity) {
+            Vec::remove(self, index);
+        }
+    }
+
+    fn as_slice(&self) -> &[Entity] {
+        self
+    }
+}
+
+impl<const N: usize> RelationshipSourceCollection for SmallVec<[Entity; N]> {
+    fn with_capacity(capacity: usize) -> Self {
+        SmallVec::with_capacity(capacity)
+    }
+
+    fn add(&mut self, entity: Entity) {
+        self.push(entity);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        if let Some(index) = self.iter().position(|&source| source == entity) {
+            SmallVec::remove(self, index);
+        }
+    }
+
+    fn as_slice(&self) -> &[Entity] {
+        self
+    }
+}
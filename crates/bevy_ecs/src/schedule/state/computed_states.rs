@@ -0,0 +1,58 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use super::{StateSet, States};
+
+/// States whose value is computed from one or more source [`States`].
+///
+/// The [`State<Self>`](super::State) resource is recomputed whenever the sources change during
+/// [`StateTransitionSteps::DependentTransitions`](super::StateTransitionSteps), and only exists
+/// while [`ComputedStates::compute`] returns `Some`. Computed states can't be changed through
+/// [`NextState`](super::NextState), but have [`OnEnter`](super::OnEnter),
+/// [`OnExit`](super::OnExit) and [`OnTransition`](super::OnTransition) schedules like any other
+/// state, and can themselves be the source of other computed states.
+///
+/// The [`Default`] implementation is never used to pick the state.
+///
+/// # Example
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+///
+/// #[derive(States, Clone, PartialEq, Eq, Hash, Debug, Default)]
+/// enum AppState {
+///     #[default]
+///     Menu,
+///     InGame { paused: bool },
+/// }
+///
+/// /// Exists while the game runs, whether it's paused or not.
+/// #[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+/// struct InGame;
+///
+/// impl ComputedStates for InGame {
+///     type SourceStates = AppState;
+///
+///     fn compute(sources: AppState) -> Option<Self> {
+///         match sources {
+///             AppState::InGame { .. } => Some(InGame),
+///             _ => None,
+///         }
+///     }
+/// }
+/// ```
+pub trait ComputedStates:
+    'static + Send + Sync + Clone + PartialEq + Eq + Hash + Debug + Default
+{
+    /// The states this state is computed from: a single [`States`] type, an `Option` of one for
+    /// sources that may not exist, or a tuple of those.
+    type SourceStates: StateSet;
+
+    /// Computes the state from the current value of its sources, returning `None` if it shouldn't
+    /// exist.
+    ///
+    /// This is only called while all non-`Option` sources exist.
+    fn compute(sources: Self::SourceStates) -> Option<Self>;
+}
+
+impl<S: ComputedStates> States for S {}
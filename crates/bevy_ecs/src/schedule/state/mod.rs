@@ -0,0 +1,432 @@
+mod computed_states;
+mod state_set;
+mod sub_states;
+
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Deref;
+
+use crate as bevy_ecs;
+use crate::change_detection::DetectChangesMut;
+use crate::event::{Event, Events, ManualEventReader};
+#[cfg(feature = "bevy_reflect")]
+use crate::reflect::ReflectResource;
+use crate::schedule::{
+    IntoSystemConfigs, IntoSystemSetConfigs, Schedule, ScheduleLabel, SystemSet,
+};
+use crate::system::{Local, Resource};
+use crate::world::World;
+#[cfg(feature = "bevy_reflect")]
+use bevy_reflect::std_traits::ReflectDefault;
+
+pub use bevy_ecs_macros::{States, SubStates};
+pub use computed_states::*;
+pub use state_set::*;
+pub use sub_states::*;
+
+/// Types that can define world-wide states in a finite-state machine.
+///
+/// The [`Default`] trait defines the starting state.
+/// Multiple states can be defined for the same world,
+/// allowing you to classify the state of the world across orthogonal dimensions.
+/// You can access the current state of type `T` with the [`State<T>`] resource,
+/// and the queued state with the [`NextState<T>`] resource.
+///
+/// State transitions typically occur in the [`OnEnter<T::Variant>`] and [`OnExit<T:Variant>`] schedules,
+/// which can be run via the [`apply_state_transition::<T>`] system.
+///
+/// States can also depend on other states: see [`SubStates`] for states that only exist while
+/// their source state has a given value, and [`ComputedStates`] for states derived from one or
+/// more source states.
+///
+/// # Example
+///
+/// ```rust
+/// use bevy_ecs::prelude::States;
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, States)]
+/// enum GameState {
+///  #[default]
+///   MainMenu,
+///   SettingsMenu,
+///   InGame,
+/// }
+///
+/// ```
+pub trait States: 'static + Send + Sync + Clone + PartialEq + Eq + Hash + Debug + Default {}
+
+/// [`States`] that can be changed by queueing a transition in [`NextState<S>`].
+///
+/// This is implemented by `#[derive(States)]` and `#[derive(SubStates)]`, but not by
+/// [`ComputedStates`], whose value is always derived from their sources.
+pub trait FreelyMutableState: States {}
+
+/// The label of a [`Schedule`](super::Schedule) that runs whenever [`State<S>`]
+/// enters this state.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OnEnter<S: States>(pub S);
+
+/// The label of a [`Schedule`](super::Schedule) that runs whenever [`State<S>`]
+/// exits this state.
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OnExit<S: States>(pub S);
+
+/// The label of a [`Schedule`](super::Schedule) that **only** runs whenever [`State<S>`]
+/// exits the `from` state, AND enters the `to` state.
+///
+/// Systems added to this schedule are always ran *after* [`OnExit`], and *before* [`OnEnter`].
+#[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OnTransition<S: States> {
+    /// The state being exited.
+    pub from: S,
+    /// The state being entered.
+    pub to: S,
+}
+
+/// A finite-state machine whose transitions have associated schedules
+/// ([`OnEnter(state)`] and [`OnExit(state)`]).
+///
+/// The current state value can be accessed through this resource. To *change* the state,
+/// queue a transition in the [`NextState<S>`] resource, and it will be applied by the next
+/// [`apply_state_transition::<S>`] system.
+///
+/// The starting state is defined via the [`Default`] implementation for `S`.
+#[derive(Resource, Default, Debug)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Resource, Default)
+)]
+pub struct State<S: States>(S);
+
+impl<S: States> State<S> {
+    /// Creates a new state with a specific value.
+    ///
+    /// To change the state use [`NextState<S>`] rather than using this to modify the `State<S>`.
+    pub fn new(state: S) -> Self {
+        Self(state)
+    }
+
+    /// Get the current state.
+    pub fn get(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S: States> PartialEq<S> for State<S> {
+    fn eq(&self, other: &S) -> bool {
+        self.get() == other
+    }
+}
+
+impl<S: States> Deref for State<S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+/// The next state of [`State<S>`].
+///
+/// To queue a transition, just set the contained value to `Some(next_state)`.
+/// Note that these transitions can be overridden by other systems:
+/// only the actual value of this resource at the time of [`apply_state_transition`] matters.
+#[derive(Resource, Default, Debug)]
+#[cfg_attr(
+    feature = "bevy_reflect",
+    derive(bevy_reflect::Reflect),
+    reflect(Resource, Default)
+)]
+pub struct NextState<S: FreelyMutableState>(pub Option<S>);
+
+impl<S: FreelyMutableState> NextState<S> {
+    /// Tentatively set a planned state transition to `Some(state)`.
+    pub fn set(&mut self, state: S) {
+        self.0 = Some(state);
+    }
+}
+
+/// Run the enter schedule (if it exists) for the current state.
+pub fn run_enter_schedule<S: States>(world: &mut World) {
+    world
+        .try_run_schedule(OnEnter(world.resource::<State<S>>().0.clone()))
+        .ok();
+}
+
+/// If a new state is queued in [`NextState<S>`], this system:
+/// - Takes the new state value from [`NextState<S>`] and updates [`State<S>`].
+/// - Runs the [`OnExit(exited_state)`] schedule, if it exists.
+/// - Runs the [`OnTransition { from: exited_state, to: entered_state }`](OnTransition), if it exists.
+/// - Runs the [`OnEnter(entered_state)`] schedule, if it exists.
+pub fn apply_state_transition<S: FreelyMutableState>(world: &mut World) {
+    // We want to take the `NextState` resource,
+    // but only mark it as changed if it wasn't empty.
+    let mut next_state_resource = world.resource_mut::<NextState<S>>();
+    if let Some(entered) = next_state_resource.bypass_change_detection().0.take() {
+        next_state_resource.set_changed();
+
+        let mut state_resource = world.resource_mut::<State<S>>();
+        if *state_resource != entered {
+            let exited = mem::replace(&mut state_resource.0, entered.clone());
+            // Try to run the schedules if they exist.
+            world.try_run_schedule(OnExit(exited.clone())).ok();
+            world
+                .try_run_schedule(OnTransition {
+                    from: exited,
+                    to: entered.clone(),
+                })
+                .ok();
+            world.try_run_schedule(OnEnter(entered)).ok();
+        }
+    }
+}
+
+/// An event sent whenever [`State<S>`] changes, including when it's added or removed.
+///
+/// Only the state transitions applied in the systems added by [`add_state_transition_systems`],
+/// [`add_computed_state_transition_systems`] and [`add_sub_state_transition_systems`] send this
+/// event.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct StateTransitionEvent<S: States> {
+    /// The state being exited, or `None` if the state didn't exist.
+    pub exited: Option<S>,
+    /// The state being entered, or `None` if the state was removed.
+    pub entered: Option<S>,
+}
+
+/// The steps of a state transition, run in order.
+///
+/// Within each step, the systems of the different states run in dependency order: source states
+/// are updated and entered before their dependent states, which are exited first.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StateTransitionSteps {
+    /// Updates the [`State<S>`] resources, sending a [`StateTransitionEvent<S>`] when they change.
+    DependentTransitions,
+    /// Runs the [`OnExit`] schedules.
+    ExitSchedules,
+    /// Runs the [`OnTransition`] schedules.
+    TransitionSchedules,
+    /// Runs the [`OnEnter`] schedules.
+    EnterSchedules,
+}
+
+/// The [`SystemSet`] updating [`State<S>`] during [`StateTransitionSteps::DependentTransitions`].
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ApplyStateTransition<S: States>(PhantomData<S>);
+
+impl<S: States> Default for ApplyStateTransition<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+struct ExitSchedules<S: States>(PhantomData<S>);
+
+impl<S: States> Default for ExitSchedules<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+struct EnterSchedules<S: States>(PhantomData<S>);
+
+impl<S: States> Default for EnterSchedules<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Adds the systems applying the transitions of `S` to `schedule`.
+///
+/// On the first run, the [`OnEnter`] schedule of the current state is run. After that, a new
+/// state queued in [`NextState<S>`] is applied, running the [`OnExit`], [`OnTransition`] and
+/// [`OnEnter`] schedules.
+///
+/// The [`State<S>`], [`NextState<S>`] and [`Events<StateTransitionEvent<S>>`] resources must be
+/// added to the world separately.
+pub fn add_state_transition_systems<S: FreelyMutableState>(schedule: &mut Schedule) {
+    schedule
+        .add_systems(apply_root_state_transition::<S>.in_set(ApplyStateTransition::<S>::default()));
+    add_transition_schedule_systems::<S>(schedule);
+}
+
+/// Adds the systems computing `S` from its [`ComputedStates::SourceStates`] to `schedule`.
+///
+/// [`State<S>`] is inserted, updated or removed after its sources have been updated, and the
+/// [`OnExit`], [`OnTransition`] and [`OnEnter`] schedules of `S` run when it changes.
+///
+/// The [`Events<StateTransitionEvent<S>>`] resource must be added to the world separately, and the
+/// transition systems of the sources must be added to the same schedule.
+pub fn add_computed_state_transition_systems<S: ComputedStates>(schedule: &mut Schedule) {
+    schedule.add_systems(
+        apply_computed_state_transition::<S>.in_set(ApplyStateTransition::<S>::default()),
+    );
+    S::SourceStates::configure_dependent_state::<S>(schedule);
+    add_transition_schedule_systems::<S>(schedule);
+}
+
+/// Adds the systems applying the transitions of the sub-state `S` to `schedule`.
+///
+/// [`State<S>`] is inserted or removed after its [`SubStates::SourceStates`] have been updated,
+/// depending on [`SubStates::should_exist`]. While it exists, a new state queued in
+/// [`NextState<S>`] is applied as for any other state.
+///
+/// The [`NextState<S>`] and [`Events<StateTransitionEvent<S>>`] resources must be added to the
+/// world separately, and the transition systems of the sources must be added to the same
+/// schedule.
+pub fn add_sub_state_transition_systems<S: SubStates>(schedule: &mut Schedule) {
+    schedule
+        .add_systems(apply_sub_state_transition::<S>.in_set(ApplyStateTransition::<S>::default()));
+    S::SourceStates::configure_dependent_state::<S>(schedule);
+    add_transition_schedule_systems::<S>(schedule);
+}
+
+fn add_transition_schedule_systems<S: States>(schedule: &mut Schedule) {
+    schedule
+        .configure_sets(
+            (
+                StateTransitionSteps::DependentTransitions,
+                StateTransitionSteps::ExitSchedules,
+                StateTransitionSteps::TransitionSchedules,
+                StateTransitionSteps::EnterSchedules,
+            )
+                .chain(),
+        )
+        .configure_sets((
+            ApplyStateTransition::<S>::default().in_set(StateTransitionSteps::DependentTransitions),
+            ExitSchedules::<S>::default().in_set(StateTransitionSteps::ExitSchedules),
+            EnterSchedules::<S>::default().in_set(StateTransitionSteps::EnterSchedules),
+        ))
+        .add_systems((
+            run_exit_schedule::<S>.in_set(ExitSchedules::<S>::default()),
+            run_transition_schedule::<S>.in_set(StateTransitionSteps::TransitionSchedules),
+            run_enter_transition_schedule::<S>.in_set(EnterSchedules::<S>::default()),
+        ));
+}
+
+/// Orders the transition systems of the dependent state `T` relative to those of its source `S`.
+fn configure_dependent_state_sets<S: States, T: States>(schedule: &mut Schedule) {
+    schedule.configure_sets((
+        ApplyStateTransition::<T>::default().after(ApplyStateTransition::<S>::default()),
+        ExitSchedules::<T>::default().before(ExitSchedules::<S>::default()),
+        EnterSchedules::<T>::default().after(EnterSchedules::<S>::default()),
+    ));
+}
+
+/// Sets [`State<S>`] to `state`, removing it if `state` is `None`, and sends a
+/// [`StateTransitionEvent<S>`] if it changed.
+fn set_state<S: States>(world: &mut World, state: Option<S>) {
+    let exited = world
+        .get_resource::<State<S>>()
+        .map(|state| state.get().clone());
+    if exited == state {
+        return;
+    }
+    match &state {
+        Some(state) => world.insert_resource(State(state.clone())),
+        None => {
+            world.remove_resource::<State<S>>();
+        }
+    }
+    world.send_event(StateTransitionEvent {
+        exited,
+        entered: state,
+    });
+}
+
+/// Takes the value of [`NextState<S>`], only marking it as changed if it wasn't empty.
+fn take_next_state<S: FreelyMutableState>(world: &mut World) -> Option<S> {
+    let mut next_state_resource = world.get_resource_mut::<NextState<S>>()?;
+    let next_state = next_state_resource.bypass_change_detection().0.take();
+    if next_state.is_some() {
+        next_state_resource.set_changed();
+    }
+    next_state
+}
+
+fn apply_root_state_transition<S: FreelyMutableState>(
+    world: &mut World,
+    mut entered_initial_state: Local<bool>,
+) {
+    let next_state = take_next_state::<S>(world);
+    if !*entered_initial_state {
+        *entered_initial_state = true;
+        // a state queued before the first transition replaces the initial state
+        if let Some(next_state) = next_state {
+            world.insert_resource(State(next_state));
+        }
+        let entered = world.resource::<State<S>>().get().clone();
+        world.send_event(StateTransitionEvent {
+            exited: None,
+            entered: Some(entered),
+        });
+    } else if let Some(next_state) = next_state {
+        set_state(world, Some(next_state));
+    }
+}
+
+fn apply_computed_state_transition<S: ComputedStates>(world: &mut World) {
+    let state = S::SourceStates::get_current(world).and_then(S::compute);
+    set_state(world, state);
+}
+
+fn apply_sub_state_transition<S: SubStates>(world: &mut World) {
+    let next_state = take_next_state::<S>(world);
+    let state = S::SourceStates::get_current(world)
+        .and_then(S::should_exist)
+        .map(|initial_state| {
+            next_state
+                .or_else(|| {
+                    world
+                        .get_resource::<State<S>>()
+                        .map(|state| state.get().clone())
+                })
+                .unwrap_or(initial_state)
+        });
+    set_state(world, state);
+}
+
+/// Returns the last [`StateTransitionEvent<S>`] sent since `reader` last read them.
+fn last_transition<S: States>(
+    world: &World,
+    reader: &mut ManualEventReader<StateTransitionEvent<S>>,
+) -> Option<StateTransitionEvent<S>> {
+    let events = world.get_resource::<Events<StateTransitionEvent<S>>>()?;
+    reader.read(events).last().cloned()
+}
+
+fn run_exit_schedule<S: States>(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<StateTransitionEvent<S>>>,
+) {
+    if let Some(exited) = last_transition(world, &mut reader).and_then(|event| event.exited) {
+        world.try_run_schedule(OnExit(exited)).ok();
+    }
+}
+
+fn run_transition_schedule<S: States>(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<StateTransitionEvent<S>>>,
+) {
+    if let Some(StateTransitionEvent {
+        exited: Some(from),
+        entered: Some(to),
+    }) = last_transition(world, &mut reader)
+    {
+        world.try_run_schedule(OnTransition { from, to }).ok();
+    }
+}
+
+fn run_enter_transition_schedule<S: States>(
+    world: &mut World,
+    mut reader: Local<ManualEventReader<StateTransitionEvent<S>>>,
+) {
+    if let Some(entered) = last_transition(world, &mut reader).and_then(|event| event.entered) {
+        world.try_run_schedule(OnEnter(entered)).ok();
+    }
+}
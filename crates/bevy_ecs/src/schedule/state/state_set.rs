@@ -0,0 +1,76 @@
+use bevy_utils::all_tuples;
+
+use crate::schedule::Schedule;
+use crate::world::World;
+
+use super::{configure_dependent_state_sets, State, States};
+
+/// A single source of a [`ComputedStates`](super::ComputedStates) or
+/// [`SubStates`](super::SubStates).
+///
+/// This is implemented for any [`States`] `S`, which is required to exist for the dependent state
+/// to be computed, and for `Option<S>`, which is `None` while `S` doesn't exist.
+pub trait InnerStateSet: Sized + Send + Sync + 'static {
+    /// The [`States`] read from the world.
+    type RawState: States;
+
+    /// Converts the current [`State`] into the value given to the dependent state, returning
+    /// `None` if the dependent state can't exist.
+    fn convert_to_usable_state(state: Option<&State<Self::RawState>>) -> Option<Self>;
+}
+
+impl<S: States> InnerStateSet for S {
+    type RawState = Self;
+
+    fn convert_to_usable_state(state: Option<&State<Self>>) -> Option<Self> {
+        state.map(|state| state.get().clone())
+    }
+}
+
+impl<S: States> InnerStateSet for Option<S> {
+    type RawState = S;
+
+    fn convert_to_usable_state(state: Option<&State<S>>) -> Option<Self> {
+        Some(state.map(|state| state.get().clone()))
+    }
+}
+
+/// The sources of a [`ComputedStates`](super::ComputedStates) or
+/// [`SubStates`](super::SubStates): a single [`InnerStateSet`], or a tuple of them.
+pub trait StateSet: Sized + Send + Sync + 'static {
+    /// Reads the current value of every source from `world`, returning `None` if a required
+    /// source doesn't exist.
+    fn get_current(world: &World) -> Option<Self>;
+
+    /// Orders the transition systems of the dependent state `T` relative to those of every
+    /// source in `schedule`: sources are updated and entered first, and exited last.
+    fn configure_dependent_state<T: States>(schedule: &mut Schedule);
+}
+
+impl<S: InnerStateSet> StateSet for S {
+    fn get_current(world: &World) -> Option<Self> {
+        S::convert_to_usable_state(world.get_resource::<State<S::RawState>>())
+    }
+
+    fn configure_dependent_state<T: States>(schedule: &mut Schedule) {
+        configure_dependent_state_sets::<S::RawState, T>(schedule);
+    }
+}
+
+macro_rules! impl_state_set_tuple {
+    ($($param: ident),*) => {
+        impl<$($param: InnerStateSet),*> StateSet for ($($param,)*) {
+            fn get_current(world: &World) -> Option<Self> {
+                Some(($($param::convert_to_usable_state(
+                    world.get_resource::<State<$param::RawState>>(),
+                )?,)*))
+            }
+
+            fn configure_dependent_state<T: States>(schedule: &mut Schedule) {
+                $(configure_dependent_state_sets::<$param::RawState, T>(schedule);)*
+            }
+        }
+    };
+}
+
+all_tuples!(impl_state_set_tuple, 1, 15, S);
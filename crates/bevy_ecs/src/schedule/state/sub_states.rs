@@ -0,0 +1,51 @@
+use super::{FreelyMutableState, StateSet};
+
+/// States that only exist while their source [`States`](super::States) allow it.
+///
+/// The [`State<Self>`](super::State) resource is added when [`SubStates::should_exist`] starts
+/// returning `Some` during [`StateTransitionSteps::DependentTransitions`](super::StateTransitionSteps),
+/// using the value queued in [`NextState<Self>`](super::NextState) if any, and removed when it
+/// returns `None`. While it exists, the state can be changed through `NextState` like any other
+/// state.
+///
+/// Since the state doesn't always exist, use
+/// [`state_exists_and_equals`](crate::schedule::common_conditions::state_exists_and_equals)
+/// rather than [`in_state`](crate::schedule::common_conditions::in_state) to run systems in a
+/// sub-state.
+///
+/// # Example
+///
+/// `#[derive(SubStates)]` implements this trait, as well as [`States`](super::States), for a
+/// single source state, taking the [`Default`] value while the source matches a pattern:
+///
+/// ```rust
+/// use bevy_ecs::prelude::*;
+///
+/// #[derive(States, Clone, PartialEq, Eq, Hash, Debug, Default)]
+/// enum AppState {
+///     #[default]
+///     Menu,
+///     InGame,
+/// }
+///
+/// /// Only exists while in `AppState::InGame`.
+/// #[derive(SubStates, Clone, PartialEq, Eq, Hash, Debug, Default)]
+/// #[source(AppState = AppState::InGame)]
+/// enum GamePhase {
+///     #[default]
+///     Running,
+///     Paused,
+/// }
+/// ```
+pub trait SubStates: FreelyMutableState {
+    /// The states controlling whether this state exists: a single [`States`](super::States)
+    /// type, an `Option` of one for sources that may not exist, or a tuple of those.
+    type SourceStates: StateSet;
+
+    /// Returns the value this state should start with if it should exist given the current value
+    /// of its sources, or `None` if it shouldn't exist.
+    ///
+    /// This is only called while all non-`Option` sources exist. The returned value is ignored
+    /// while the state already exists.
+    fn should_exist(sources: Self::SourceStates) -> Option<Self>;
+}
@@ -90,6 +90,12 @@ impl CommandQueue {
         }
     }
 
+    /// Returns `true` if there are no commands in the queue.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
     /// Execute the queued [`Command`]s in the world.
     /// This clears the queue.
     #[inline]
@@ -128,6 +134,8 @@ impl CommandQueue {
             // since they were stored next to each other by `.push()`.
             // For ZSTs, the type doesn't matter as long as the pointer is non-null.
             let size = unsafe { (meta.apply_command_and_get_size)(cmd, world) };
+            // Apply any commands queued by component hooks triggered by this command.
+            world.flush_commands();
             // Advance the cursor past the command. For ZSTs, the cursor will not move.
             // At this point, it will either point to the next `CommandMeta`,
             // or the cursor will be out of bounds and the loop will end.
@@ -0,0 +1,21 @@
+//! A trait for traversing relationships between entities.
+
+use crate::{entity::Entity, world::EntityRef};
+
+/// A trait for types that describe how to get from one [`Entity`] to the next in some relationship.
+///
+/// This is used by [`EntityEvent::Traversal`](crate::event::EntityEvent::Traversal) to decide
+/// where an [`EntityEvent`](crate::event::EntityEvent) triggered on an entity propagates to next,
+/// for example from a child entity to its parent.
+///
+/// `()` is used for events that don't propagate: it never returns a next entity.
+pub trait Traversal: 'static {
+    /// Returns the next entity to visit after `entity`, if any.
+    fn traverse(entity: EntityRef) -> Option<Entity>;
+}
+
+impl Traversal for () {
+    fn traverse(_: EntityRef) -> Option<Entity> {
+        None
+    }
+}
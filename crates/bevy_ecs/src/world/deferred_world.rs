@@ -0,0 +1,229 @@
+use std::ops::Deref;
+
+use crate::{
+    change_detection::Mut,
+    component::{Component, ComponentId},
+    entity::Entity,
+    event::{Event, EventId, Events, SendBatchIds},
+    observer::Observers,
+    system::{Commands, Resource},
+    world::{EntityMut, World},
+};
+
+/// A [`World`] reference that disallows structural ECS changes.
+///
+/// This includes initializing resources, registering components or spawning entities,
+/// as well as inserting or removing components.
+/// Structural changes can still be requested through [`DeferredWorld::commands`]: they are
+/// queued on the world and applied the next time its commands are flushed.
+///
+/// This is the world access given to [`ComponentHook`](crate::component::ComponentHook)s,
+/// since they run in the middle of a structural change.
+pub struct DeferredWorld<'w> {
+    world: &'w mut World,
+}
+
+impl<'w> Deref for DeferredWorld<'w> {
+    type Target = World;
+
+    fn deref(&self) -> &Self::Target {
+        self.world
+    }
+}
+
+impl<'w> From<&'w mut World> for DeferredWorld<'w> {
+    fn from(world: &'w mut World) -> DeferredWorld<'w> {
+        DeferredWorld { world }
+    }
+}
+
+impl<'w> DeferredWorld<'w> {
+    /// Reborrow self as a new instance of [`DeferredWorld`]
+    #[inline]
+    pub fn reborrow(&mut self) -> DeferredWorld {
+        DeferredWorld { world: self.world }
+    }
+
+    /// Creates a [`Commands`] instance that pushes to the world's command queue.
+    ///
+    /// The queued commands are applied by [`World::flush_commands`], which happens automatically
+    /// once the structural change currently being made is complete.
+    #[inline]
+    pub fn commands(&mut self) -> Commands {
+        let world = &mut *self.world;
+        Commands::new_from_entities(&mut world.command_queue, &world.entities)
+    }
+
+    /// Retrieves a mutable reference to the given `entity`'s [`Component`] of the given type.
+    /// Returns `None` if the `entity` does not have a [`Component`] of the given type.
+    #[inline]
+    pub fn get_mut<T: Component>(&mut self, entity: Entity) -> Option<Mut<T>> {
+        self.world.get_mut(entity)
+    }
+
+    /// Retrieves an [`EntityMut`] that exposes read and write operations for the given `entity`.
+    /// Returns [`None`] if the `entity` does not exist.
+    /// Instead of unwrapping the value returned from this function, prefer [`Self::entity_mut`].
+    #[inline]
+    pub fn get_entity_mut(&mut self, entity: Entity) -> Option<EntityMut> {
+        self.world.get_entity_mut(entity).map(EntityMut::from)
+    }
+
+    /// Retrieves an [`EntityMut`] that exposes read and write operations for the given `entity`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `entity` does not exist.
+    /// Use [`Self::get_entity_mut`] if you want to check for entity existence instead of implicitly panic-ing.
+    #[inline]
+    pub fn entity_mut(&mut self, entity: Entity) -> EntityMut {
+        #[inline(never)]
+        #[cold]
+        fn panic_no_entity(entity: Entity) -> ! {
+            panic!("Entity {entity:?} does not exist");
+        }
+
+        match self.get_entity_mut(entity) {
+            Some(entity) => entity,
+            None => panic_no_entity(entity),
+        }
+    }
+
+    /// Gets a mutable reference to the resource of the given type
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist.
+    /// Use [`get_resource_mut`](DeferredWorld::get_resource_mut) instead if you want to handle this case.
+    #[inline]
+    #[track_caller]
+    pub fn resource_mut<R: Resource>(&mut self) -> Mut<'_, R> {
+        self.world.resource_mut()
+    }
+
+    /// Gets a mutable reference to the resource of the given type if it exists
+    #[inline]
+    pub fn get_resource_mut<R: Resource>(&mut self) -> Option<Mut<'_, R>> {
+        self.world.get_resource_mut()
+    }
+
+    /// Gets a mutable reference to the non-send resource of the given type, if it exists.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resource does not exist.
+    /// Use [`get_non_send_resource_mut`](DeferredWorld::get_non_send_resource_mut) instead if you want to handle this case.
+    ///
+    /// This function will panic if it isn't called from the same thread that the resource was inserted from.
+    #[inline]
+    #[track_caller]
+    pub fn non_send_resource_mut<R: 'static>(&mut self) -> Mut<'_, R> {
+        self.world.non_send_resource_mut()
+    }
+
+    /// Gets a mutable reference to the non-send resource of the given type, if it exists.
+    /// Otherwise returns `None`.
+    ///
+    /// # Panics
+    /// This function will panic if it isn't called from the same thread that the resource was inserted from.
+    #[inline]
+    pub fn get_non_send_resource_mut<R: 'static>(&mut self) -> Option<Mut<'_, R>> {
+        self.world.get_non_send_resource_mut()
+    }
+
+    /// Sends an [`Event`].
+    /// This method returns the [ID](`EventId`) of the sent `event`,
+    /// or [`None`] if the `event` could not be sent.
+    #[inline]
+    pub fn send_event<E: Event>(&mut self, event: E) -> Option<EventId<E>> {
+        self.send_event_batch(std::iter::once(event))?.next()
+    }
+
+    /// Sends a batch of [`Event`]s from an iterator.
+    /// This method returns the [IDs](`EventId`) of the sent `events`,
+    /// or [`None`] if the `event` could not be sent.
+    #[inline]
+    pub fn send_event_batch<E: Event>(
+        &mut self,
+        events: impl IntoIterator<Item = E>,
+    ) -> Option<SendBatchIds<E>> {
+        let Some(mut events_resource) = self.get_resource_mut::<Events<E>>() else {
+            bevy_utils::tracing::error!(
+                "Unable to send event `{}`\n\tEvent must be added to the app with `add_event()`\n\thttps://docs.rs/bevy/*/bevy/app/struct.App.html#method.add_event ",
+                std::any::type_name::<E>()
+            );
+            return None;
+        };
+        Some(events_resource.send_batch(events))
+    }
+
+    /// Returns the index of observers, for [`Observer`](crate::observer::Observer) hooks to keep up to date.
+    #[inline]
+    pub(crate) fn observers_mut(&mut self) -> &mut Observers {
+        &mut self.world.observers
+    }
+
+    /// Triggers all `on_add` hooks for [`ComponentId`] in target.
+    #[inline]
+    pub(crate) fn trigger_on_add(
+        &mut self,
+        entity: Entity,
+        targets: impl Iterator<Item = ComponentId>,
+    ) {
+        for component_id in targets {
+            // SAFETY: Caller ensures that these components exist
+            let hooks = unsafe { self.components().get_info_unchecked(component_id) }.hooks();
+            if let Some(hook) = hooks.on_add {
+                hook(self.reborrow(), entity, component_id);
+            }
+        }
+    }
+
+    /// Triggers all `on_insert` hooks for [`ComponentId`] in target.
+    #[inline]
+    pub(crate) fn trigger_on_insert(
+        &mut self,
+        entity: Entity,
+        targets: impl Iterator<Item = ComponentId>,
+    ) {
+        for component_id in targets {
+            // SAFETY: Caller ensures that these components exist
+            let hooks = unsafe { self.components().get_info_unchecked(component_id) }.hooks();
+            if let Some(hook) = hooks.on_insert {
+                hook(self.reborrow(), entity, component_id);
+            }
+        }
+    }
+
+    /// Triggers all `on_replace` hooks for [`ComponentId`] in target.
+    #[inline]
+    pub(crate) fn trigger_on_replace(
+        &mut self,
+        entity: Entity,
+        targets: impl Iterator<Item = ComponentId>,
+    ) {
+        for component_id in targets {
+            // SAFETY: Caller ensures that these components exist
+            let hooks = unsafe { self.components().get_info_unchecked(component_id) }.hooks();
+            if let Some(hook) = hooks.on_replace {
+                hook(self.reborrow(), entity, component_id);
+            }
+        }
+    }
+
+    /// Triggers all `on_remove` hooks for [`ComponentId`] in target.
+    #[inline]
+    pub(crate) fn trigger_on_remove(
+        &mut self,
+        entity: Entity,
+        targets: impl Iterator<Item = ComponentId>,
+    ) {
+        for component_id in targets {
+            // SAFETY: Caller ensures that these components exist
+            let hooks = unsafe { self.components().get_info_unchecked(component_id) }.hooks();
+            if let Some(hook) = hooks.on_remove {
+                hook(self.reborrow(), entity, component_id);
+            }
+        }
+    }
+}
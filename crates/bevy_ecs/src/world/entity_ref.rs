@@ -1,12 +1,13 @@
 use crate::{
     archetype::{Archetype, ArchetypeId, Archetypes},
-    bundle::{Bundle, BundleInfo, BundleInserter, DynamicBundle},
+    bundle::{Bundle, BundleId, BundleInfo, BundleInserter, DynamicBundle},
     change_detection::MutUntyped,
     component::{Component, ComponentId, ComponentTicks, Components, StorageType},
     entity::{Entities, Entity, EntityLocation},
+    query::DebugCheckedUnwrap,
     removal_detection::RemovedComponentEvents,
     storage::Storages,
-    world::{Mut, World},
+    world::{DeferredWorld, Mut, World},
 };
 use bevy_ptr::{OwningPtr, Ptr};
 use bevy_utils::tracing::debug;
@@ -392,6 +393,7 @@ pub struct EntityWorldMut<'w> {
 
 impl<'w> EntityWorldMut<'w> {
     fn as_unsafe_entity_cell_readonly(&self) -> UnsafeEntityCell<'_> {
+        self.assert_not_despawned();
         UnsafeEntityCell::new(
             self.world.as_unsafe_world_cell_readonly(),
             self.entity,
@@ -399,6 +401,7 @@ impl<'w> EntityWorldMut<'w> {
         )
     }
     fn as_unsafe_entity_cell(&mut self) -> UnsafeEntityCell<'_> {
+        self.assert_not_despawned();
         UnsafeEntityCell::new(
             self.world.as_unsafe_world_cell(),
             self.entity,
@@ -406,6 +409,7 @@ impl<'w> EntityWorldMut<'w> {
         )
     }
     fn into_unsafe_entity_cell(self) -> UnsafeEntityCell<'w> {
+        self.assert_not_despawned();
         UnsafeEntityCell::new(
             self.world.as_unsafe_world_cell(),
             self.entity,
@@ -444,13 +448,39 @@ impl<'w> EntityWorldMut<'w> {
 
     /// Gets metadata indicating the location where the current entity is stored.
     #[inline]
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entity [was despawned](Self::is_despawned).
     pub fn location(&self) -> EntityLocation {
+        self.assert_not_despawned();
         self.location
     }
 
+    /// Returns `true` if the entity was despawned by a command queued from a component hook
+    /// during a structural change made through this [`EntityWorldMut`].
+    ///
+    /// A despawned entity can't be accessed: every method but [`Self::id`] and
+    /// [`Self::despawn`] panics.
+    #[inline]
+    pub fn is_despawned(&self) -> bool {
+        self.location.archetype_id == ArchetypeId::INVALID
+    }
+
+    #[inline]
+    #[track_caller]
+    fn assert_not_despawned(&self) {
+        assert!(
+            !self.is_despawned(),
+            "Entity {:?} was despawned by a command queued from a component hook",
+            self.entity
+        );
+    }
+
     /// Returns the archetype that the current entity belongs to.
     #[inline]
     pub fn archetype(&self) -> &Archetype {
+        self.assert_not_despawned();
         &self.world.archetypes[self.location.archetype_id]
     }
 
@@ -568,23 +598,37 @@ impl<'w> EntityWorldMut<'w> {
     ///
     /// This will overwrite any previous value(s) of the same component type.
     pub fn insert<T: Bundle>(&mut self, bundle: T) -> &mut Self {
+        self.assert_not_despawned();
         let change_tick = self.world.change_tick();
         let bundle_info = self
             .world
             .bundles
             .init_info::<T>(&mut self.world.components, &mut self.world.storages);
+        let bundle_id = bundle_info.id();
+        let old_archetype_id = self.location.archetype_id;
+        self.world
+            .trigger_replace_hooks(self.entity, old_archetype_id, bundle_id);
+        // SAFETY: `bundle_id` was just initialized, and hooks can't register bundles
+        let bundle_info = unsafe { self.world.bundles.get(bundle_id).debug_checked_unwrap() };
         let mut bundle_inserter = bundle_info.get_bundle_inserter(
             &mut self.world.entities,
             &mut self.world.archetypes,
             &self.world.components,
             &mut self.world.storages,
-            self.location.archetype_id,
+            old_archetype_id,
             change_tick,
         );
         // SAFETY: location matches current entity. `T` matches `bundle_info`
         unsafe {
             self.location = bundle_inserter.insert(self.entity, self.location, bundle);
         }
+        self.world.trigger_insert_hooks(
+            self.entity,
+            old_archetype_id,
+            self.location.archetype_id,
+            bundle_id,
+        );
+        self.flush_commands();
 
         self
     }
@@ -604,18 +648,25 @@ impl<'w> EntityWorldMut<'w> {
         component_id: ComponentId,
         component: OwningPtr<'_>,
     ) -> &mut Self {
+        self.assert_not_despawned();
         let change_tick = self.world.change_tick();
 
         let bundles = &mut self.world.bundles;
         let components = &mut self.world.components;
 
         let (bundle_info, storage_type) = bundles.init_component_info(components, component_id);
+        let bundle_id = bundle_info.id();
+        let old_archetype_id = self.location.archetype_id;
+        self.world
+            .trigger_replace_hooks(self.entity, old_archetype_id, bundle_id);
+        // SAFETY: `bundle_id` was just initialized, and hooks can't register bundles
+        let bundle_info = unsafe { self.world.bundles.get(bundle_id).debug_checked_unwrap() };
         let bundle_inserter = bundle_info.get_bundle_inserter(
             &mut self.world.entities,
             &mut self.world.archetypes,
             &self.world.components,
             &mut self.world.storages,
-            self.location.archetype_id,
+            old_archetype_id,
             change_tick,
         );
 
@@ -626,6 +677,13 @@ impl<'w> EntityWorldMut<'w> {
             Some(component).into_iter(),
             Some(storage_type).into_iter(),
         );
+        self.world.trigger_insert_hooks(
+            self.entity,
+            old_archetype_id,
+            self.location.archetype_id,
+            bundle_id,
+        );
+        self.flush_commands();
 
         self
     }
@@ -647,18 +705,26 @@ impl<'w> EntityWorldMut<'w> {
         component_ids: &[ComponentId],
         iter_components: I,
     ) -> &mut Self {
+        self.assert_not_despawned();
         let change_tick = self.world.change_tick();
 
         let bundles = &mut self.world.bundles;
         let components = &mut self.world.components;
 
         let (bundle_info, storage_types) = bundles.init_dynamic_info(components, component_ids);
+        let bundle_id = bundle_info.id();
+        let storage_types = storage_types.clone();
+        let old_archetype_id = self.location.archetype_id;
+        self.world
+            .trigger_replace_hooks(self.entity, old_archetype_id, bundle_id);
+        // SAFETY: `bundle_id` was just initialized, and hooks can't register bundles
+        let bundle_info = unsafe { self.world.bundles.get(bundle_id).debug_checked_unwrap() };
         let bundle_inserter = bundle_info.get_bundle_inserter(
             &mut self.world.entities,
             &mut self.world.archetypes,
             &self.world.components,
             &mut self.world.storages,
-            self.location.archetype_id,
+            old_archetype_id,
             change_tick,
         );
 
@@ -667,12 +733,56 @@ impl<'w> EntityWorldMut<'w> {
             self.entity,
             self.location,
             iter_components,
-            storage_types.iter().cloned(),
+            storage_types.into_iter(),
+        );
+        self.world.trigger_insert_hooks(
+            self.entity,
+            old_archetype_id,
+            self.location.archetype_id,
+            bundle_id,
         );
+        self.flush_commands();
 
         self
     }
 
+    /// Applies the commands queued by component hooks during a structural change to this entity,
+    /// then updates its cached location, which is invalid if one of these commands despawned the
+    /// entity.
+    pub(crate) fn flush_commands(&mut self) {
+        // Flushing reserved entities doesn't move this entity
+        self.world.flush();
+        if !self.world.command_queue.is_empty() {
+            self.world.flush_commands();
+            self.update_location();
+        }
+    }
+
+    /// Runs the `on_replace` and `on_remove` hooks of the components of the bundle `bundle_id` that
+    /// are about to be removed from this entity. If `all_or_nothing` is set, hooks only run if the
+    /// entity has every component of the bundle, matching the behavior of [`EntityWorldMut::take`].
+    fn trigger_remove_hooks(&mut self, bundle_id: BundleId, all_or_nothing: bool) {
+        let archetype = &self.world.archetypes[self.location.archetype_id];
+        if !archetype.has_replace_hook() && !archetype.has_remove_hook() {
+            return;
+        }
+        // SAFETY: `bundle_id` was initialized by the caller
+        let bundle_info = unsafe { self.world.bundles.get(bundle_id).debug_checked_unwrap() };
+        let removed: Vec<ComponentId> = bundle_info
+            .components()
+            .iter()
+            .copied()
+            .filter(|&id| archetype.contains(id))
+            .collect();
+        if all_or_nothing && removed.len() != bundle_info.components().len() {
+            return;
+        }
+        // Hooks can't make structural changes, so `self.location` stays valid.
+        let mut world = DeferredWorld::from(&mut *self.world);
+        world.trigger_on_replace(self.entity, removed.iter().copied());
+        world.trigger_on_remove(self.entity, removed.into_iter());
+    }
+
     /// Removes all components in the [`Bundle`] from the entity and returns their previous values.
     ///
     /// **Note:** If the entity does not have every component in the bundle, this method will not
@@ -680,6 +790,14 @@ impl<'w> EntityWorldMut<'w> {
     // TODO: BundleRemover?
     #[must_use]
     pub fn take<T: Bundle>(&mut self) -> Option<T> {
+        self.assert_not_despawned();
+        let bundle_id = self
+            .world
+            .bundles
+            .init_info::<T>(&mut self.world.components, &mut self.world.storages)
+            .id();
+        self.trigger_remove_hooks(bundle_id, true);
+
         let archetypes = &mut self.world.archetypes;
         let storages = &mut self.world.storages;
         let components = &mut self.world.components;
@@ -740,6 +858,7 @@ impl<'w> EntityWorldMut<'w> {
                 new_archetype_id,
             );
         }
+        self.flush_commands();
 
         Some(result)
     }
@@ -828,6 +947,14 @@ impl<'w> EntityWorldMut<'w> {
     /// Removes any components in the [`Bundle`] from the entity.
     // TODO: BundleRemover?
     pub fn remove<T: Bundle>(&mut self) -> &mut Self {
+        self.assert_not_despawned();
+        let bundle_id = self
+            .world
+            .bundles
+            .init_info::<T>(&mut self.world.components, &mut self.world.storages)
+            .id();
+        self.trigger_remove_hooks(bundle_id, false);
+
         let archetypes = &mut self.world.archetypes;
         let storages = &mut self.world.storages;
         let components = &mut self.world.components;
@@ -886,15 +1013,30 @@ impl<'w> EntityWorldMut<'w> {
                 new_archetype_id,
             );
         }
+        self.flush_commands();
 
         self
     }
 
     /// Despawns the current entity.
+    ///
+    /// Does nothing if the entity [was already despawned](Self::is_despawned).
     pub fn despawn(self) {
+        if self.is_despawned() {
+            return;
+        }
         debug!("Despawning entity {:?}", self.entity);
         let world = self.world;
         world.flush();
+        let archetype = &world.archetypes[self.location.archetype_id];
+        if archetype.has_replace_hook() || archetype.has_remove_hook() {
+            let components: Vec<ComponentId> = archetype.components().collect();
+            let mut world = DeferredWorld::from(&mut *world);
+            world.trigger_on_replace(self.entity, components.iter().copied());
+            world.trigger_on_remove(self.entity, components.into_iter());
+        }
+        // Hooks can reserve entities, which must be flushed before freeing this one
+        world.flush();
         let location = world
             .entities
             .free(self.entity)
@@ -954,6 +1096,7 @@ impl<'w> EntityWorldMut<'w> {
             world.archetypes[moved_location.archetype_id]
                 .set_entity_table_row(moved_location.archetype_row, table_row);
         }
+        world.flush_commands();
     }
 
     /// Gets read-only access to the world that the current entity belongs to.
@@ -1029,8 +1172,14 @@ impl<'w> EntityWorldMut<'w> {
     ///
     /// This is *only* required when using the unsafe function [`EntityWorldMut::world_mut`],
     /// which enables the location to change.
+    ///
+    /// If the entity was despawned, [`Self::is_despawned`] returns `true` afterwards.
     pub fn update_location(&mut self) {
-        self.location = self.world.entities().get(self.entity).unwrap();
+        self.location = self
+            .world
+            .entities()
+            .get(self.entity)
+            .unwrap_or(EntityLocation::INVALID);
     }
 
     /// Gets an Entry into the world for this entity and component for in-place manipulation.
@@ -1537,6 +1686,7 @@ unsafe fn remove_bundle_from_archetype(
         }
 
         let new_archetype_id = archetypes.get_id_or_insert(
+            components,
             next_table_id,
             next_table_components,
             next_sparse_set_components,
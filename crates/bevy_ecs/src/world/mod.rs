@@ -1,5 +1,6 @@
 //! Defines the [`World`] and APIs for accessing it directly.
 
+mod deferred_world;
 mod entity_ref;
 pub mod error;
 mod spawn_batch;
@@ -7,22 +8,27 @@ pub mod unsafe_world_cell;
 mod world_cell;
 
 pub use crate::change_detection::{Mut, Ref, CHECK_TICK_THRESHOLD};
+pub use deferred_world::DeferredWorld;
 pub use entity_ref::{EntityMut, EntityRef, EntityWorldMut, Entry, OccupiedEntry, VacantEntry};
 pub use spawn_batch::*;
 pub use world_cell::*;
 
 use crate::{
     archetype::{ArchetypeComponentId, ArchetypeId, ArchetypeRow, Archetypes},
-    bundle::{Bundle, BundleInserter, BundleSpawner, Bundles},
+    bundle::{Bundle, BundleId, BundleInserter, BundleSpawner, Bundles},
     change_detection::{MutUntyped, TicksMut},
-    component::{Component, ComponentDescriptor, ComponentId, ComponentInfo, Components, Tick},
+    component::{
+        Component, ComponentDescriptor, ComponentHooks, ComponentId, ComponentInfo, Components,
+        Tick,
+    },
     entity::{AllocAtWithoutReplacement, Entities, Entity, EntityLocation},
     event::{Event, EventId, Events, SendBatchIds},
+    observer::Observers,
     query::{DebugCheckedUnwrap, QueryEntityError, QueryState, ReadOnlyWorldQuery, WorldQuery},
     removal_detection::RemovedComponentEvents,
     schedule::{Schedule, ScheduleLabel, Schedules},
     storage::{ResourceData, Storages},
-    system::Resource,
+    system::{CommandQueue, Resource},
     world::error::TryRunScheduleError,
 };
 use bevy_ptr::{OwningPtr, Ptr};
@@ -71,6 +77,9 @@ pub struct World {
     pub(crate) change_tick: AtomicU32,
     pub(crate) last_change_tick: Tick,
     pub(crate) last_check_tick: Tick,
+    /// Commands queued from a [`DeferredWorld`], applied by [`World::flush_commands`].
+    pub(crate) command_queue: CommandQueue,
+    pub(crate) observers: Observers,
 }
 
 impl Default for World {
@@ -89,6 +98,8 @@ impl Default for World {
             change_tick: AtomicU32::new(1),
             last_change_tick: Tick::new(0),
             last_check_tick: Tick::new(0),
+            command_queue: CommandQueue::default(),
+            observers: Observers::default(),
         }
     }
 }
@@ -182,6 +193,35 @@ impl World {
         self.components.init_component::<T>(&mut self.storages)
     }
 
+    /// Returns a mutable reference to the [`ComponentHooks`] for a [`Component`] type.
+    ///
+    /// Will panic if `T` exists in any archetypes.
+    pub fn register_component_hooks<T: Component>(&mut self) -> &mut ComponentHooks {
+        let index = self.init_component::<T>();
+        assert!(
+            !self.archetypes.iter().any(|a| a.contains(index)),
+            "Components hooks cannot be modified if the component already exists in an archetype, use init_component if {} may already be in use",
+            std::any::type_name::<T>()
+        );
+        // SAFETY: We just created this component
+        unsafe { self.components.get_hooks_mut(index).debug_checked_unwrap() }
+    }
+
+    /// Returns a mutable reference to the [`ComponentHooks`] for a [`Component`] with the given id if it exists.
+    ///
+    /// Will panic if `id` exists in any archetypes.
+    pub fn register_component_hooks_by_id(
+        &mut self,
+        id: ComponentId,
+    ) -> Option<&mut ComponentHooks> {
+        assert!(
+            !self.archetypes.iter().any(|a| a.contains(id)),
+            "Components hooks cannot be modified if the component already exists in an archetype, use init_component if the component with id {:?} may already be in use",
+            id
+        );
+        self.components.get_hooks_mut(id)
+    }
+
     /// Initializes a new [`Component`] type and returns the [`ComponentId`] created for it.
     ///
     /// This method differs from [`World::init_component`] in that it uses a [`ComponentDescriptor`]
@@ -680,6 +720,9 @@ impl World {
     /// a corresponding [`EntityWorldMut`], which can be used to add components to the entity or
     /// retrieve its id.
     ///
+    /// If a command queued by a component hook despawns the entity, the returned [`EntityWorldMut`]
+    /// [is despawned](EntityWorldMut::is_despawned): only its id can be used.
+    ///
     /// ```
     /// use bevy_ecs::{bundle::Bundle, component::Component, world::World};
     ///
@@ -739,10 +782,12 @@ impl World {
         self.flush();
         let change_tick = self.change_tick();
         let entity = self.entities.alloc();
+        let bundle_id;
         let entity_location = {
             let bundle_info = self
                 .bundles
                 .init_info::<B>(&mut self.components, &mut self.storages);
+            bundle_id = bundle_info.id();
             let mut spawner = bundle_info.get_bundle_spawner(
                 &mut self.entities,
                 &mut self.archetypes,
@@ -754,9 +799,18 @@ impl World {
             // SAFETY: bundle's type matches `bundle_info`, entity is allocated but non-existent
             unsafe { spawner.spawn_non_existent(entity, bundle) }
         };
+        self.trigger_insert_hooks(
+            entity,
+            ArchetypeId::EMPTY,
+            entity_location.archetype_id,
+            bundle_id,
+        );
 
-        // SAFETY: entity and location are valid, as they were just created above
-        unsafe { EntityWorldMut::new(self, entity, entity_location) }
+        // SAFETY: entity and location are valid, as hooks can't make structural changes
+        let mut entity = unsafe { EntityWorldMut::new(self, entity, entity_location) };
+        // The commands queued by the hooks may despawn the entity
+        entity.flush_commands();
+        entity
     }
 
     /// # Safety
@@ -1438,6 +1492,38 @@ impl World {
         let bundle_info = self
             .bundles
             .init_info::<B>(&mut self.components, &mut self.storages);
+        let bundle_id = bundle_info.id();
+        // entities whose hooks need to run once the batch is written, with their previous archetype
+        let mut hooked_entities = Vec::new();
+        let has_hooks = bundle_info.components().iter().any(|&id| {
+            // SAFETY: components in a bundle are always initialized
+            let hooks = unsafe { self.components.get_info_unchecked(id) }.hooks();
+            hooks.on_add.is_some() || hooks.on_insert.is_some()
+        });
+        let has_replace_hooks = bundle_info.components().iter().any(|&id| {
+            // SAFETY: components in a bundle are always initialized
+            unsafe { self.components.get_info_unchecked(id) }
+                .hooks()
+                .on_replace
+                .is_some()
+        });
+        if has_replace_hooks {
+            // `on_replace` hooks must run before each insertion, so entities can't be batched
+            let mut invalid_entities = Vec::new();
+            for (entity, bundle) in iter {
+                match self.get_or_spawn(entity) {
+                    Some(mut entity_mut) => {
+                        entity_mut.insert(bundle);
+                    }
+                    None => invalid_entities.push(entity),
+                }
+            }
+            return if invalid_entities.is_empty() {
+                Ok(())
+            } else {
+                Err(invalid_entities)
+            };
+        }
         enum SpawnOrInsert<'a, 'b> {
             Spawn(BundleSpawner<'a, 'b>),
             Insert(BundleInserter<'a, 'b>, ArchetypeId),
@@ -1466,6 +1552,9 @@ impl World {
                 .alloc_at_without_replacement(entity)
             {
                 AllocAtWithoutReplacement::Exists(location) => {
+                    if has_hooks {
+                        hooked_entities.push((entity, location.archetype_id));
+                    }
                     match spawn_or_insert {
                         SpawnOrInsert::Insert(ref mut inserter, archetype)
                             if location.archetype_id == archetype =>
@@ -1490,6 +1579,9 @@ impl World {
                     };
                 }
                 AllocAtWithoutReplacement::DidNotExist => {
+                    if has_hooks {
+                        hooked_entities.push((entity, ArchetypeId::EMPTY));
+                    }
                     if let SpawnOrInsert::Spawn(ref mut spawner) = spawn_or_insert {
                         // SAFETY: `entity` is allocated (but non existent), bundle matches inserter
                         unsafe { spawner.spawn_non_existent(entity, bundle) };
@@ -1512,6 +1604,14 @@ impl World {
             }
         }
 
+        for (entity, old_archetype_id) in hooked_entities {
+            // SAFETY: the entity was just spawned or inserted into, and hooks can't despawn entities
+            let new_archetype_id =
+                unsafe { self.entities.get(entity).debug_checked_unwrap() }.archetype_id;
+            self.trigger_insert_hooks(entity, old_archetype_id, new_archetype_id, bundle_id);
+        }
+        self.flush_commands();
+
         if invalid_entities.is_empty() {
             Ok(())
         } else {
@@ -1735,6 +1835,87 @@ impl World {
         }
     }
 
+    /// Applies any commands in the world's internal [`CommandQueue`].
+    ///
+    /// Commands are pushed to this queue by [`DeferredWorld::commands`], typically from within
+    /// a [`ComponentHook`](crate::component::ComponentHook). The queue is flushed automatically
+    /// at the end of each structural change made through the [`World`] (spawning or despawning
+    /// entities, inserting or removing components), and after each command of any other
+    /// [`CommandQueue`] is applied.
+    ///
+    /// Entities reserved by the commands, such as with [`Commands::spawn_empty`], are flushed even
+    /// if the queue is empty.
+    ///
+    /// [`Commands::spawn_empty`]: crate::system::Commands::spawn_empty
+    pub fn flush_commands(&mut self) {
+        self.flush();
+        if !self.command_queue.is_empty() {
+            let mut commands = std::mem::take(&mut self.command_queue);
+            commands.apply(self);
+        }
+    }
+
+    /// Runs the `on_replace` hooks of the components in the bundle `bundle_id` that `entity`,
+    /// currently in `archetype_id`, already has, before they are overwritten by an insertion.
+    pub(crate) fn trigger_replace_hooks(
+        &mut self,
+        entity: Entity,
+        archetype_id: ArchetypeId,
+        bundle_id: BundleId,
+    ) {
+        let archetype = &self.archetypes[archetype_id];
+        if !archetype.has_replace_hook() {
+            return;
+        }
+        // SAFETY: the bundle is about to be used to insert components on `entity`
+        let bundle_info = unsafe { self.bundles.get(bundle_id).debug_checked_unwrap() };
+        let replaced: Vec<ComponentId> = bundle_info
+            .components()
+            .iter()
+            .copied()
+            .filter(|&id| archetype.contains(id))
+            .collect();
+        DeferredWorld::from(self).trigger_on_replace(entity, replaced.into_iter());
+    }
+
+    /// Runs the `on_add` and `on_insert` hooks of the components in the bundle `bundle_id`
+    /// that was just inserted on `entity`, moving it from `old_archetype_id` to `new_archetype_id`.
+    pub(crate) fn trigger_insert_hooks(
+        &mut self,
+        entity: Entity,
+        old_archetype_id: ArchetypeId,
+        new_archetype_id: ArchetypeId,
+        bundle_id: BundleId,
+    ) {
+        let new_archetype = &self.archetypes[new_archetype_id];
+        let has_add_hook = new_archetype.has_add_hook();
+        let has_insert_hook = new_archetype.has_insert_hook();
+        if !has_add_hook && !has_insert_hook {
+            return;
+        }
+        let old_archetype = &self.archetypes[old_archetype_id];
+        // SAFETY: the bundle was just used to insert components on `entity`
+        let bundle_info = unsafe { self.bundles.get(bundle_id).debug_checked_unwrap() };
+        let added: Vec<ComponentId> = if has_add_hook {
+            bundle_info
+                .components()
+                .iter()
+                .copied()
+                .filter(|&id| !old_archetype.contains(id))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let inserted: Vec<ComponentId> = if has_insert_hook {
+            bundle_info.components().to_vec()
+        } else {
+            Vec::new()
+        };
+        let mut world = DeferredWorld::from(self);
+        world.trigger_on_add(entity, added.into_iter());
+        world.trigger_on_insert(entity, inserted.into_iter());
+    }
+
     /// Increments the world's current change tick and returns the old value.
     #[inline]
     pub fn increment_change_tick(&self) -> Tick {
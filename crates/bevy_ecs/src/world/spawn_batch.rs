@@ -1,6 +1,9 @@
 use crate::{
-    bundle::{Bundle, BundleSpawner},
+    archetype::ArchetypeId,
+    bundle::{Bundle, BundleId, BundleSpawner},
+    component::Tick,
     entity::Entity,
+    query::DebugCheckedUnwrap,
     world::World,
 };
 use std::iter::FusedIterator;
@@ -15,7 +18,20 @@ where
     I::Item: Bundle,
 {
     inner: I,
-    spawner: BundleSpawner<'w, 'w>,
+    spawner: Spawner<'w>,
+}
+
+/// Spawns the entities of a [`SpawnBatchIter`].
+///
+/// When none of the bundle's components have hooks, a single [`BundleSpawner`] is kept for the
+/// whole batch. Otherwise the world is borrowed between each entity so that hooks can run.
+enum Spawner<'w> {
+    Direct(BundleSpawner<'w, 'w>),
+    WithHooks {
+        world: &'w mut World,
+        bundle_id: BundleId,
+        change_tick: Tick,
+    },
 }
 
 impl<'w, I> SpawnBatchIter<'w, I>
@@ -34,18 +50,51 @@ where
         let (lower, upper) = iter.size_hint();
         let length = upper.unwrap_or(lower);
 
-        let bundle_info = world
+        let bundle_id = world
             .bundles
-            .init_info::<I::Item>(&mut world.components, &mut world.storages);
+            .init_info::<I::Item>(&mut world.components, &mut world.storages)
+            .id();
         world.entities.reserve(length as u32);
-        let mut spawner = bundle_info.get_bundle_spawner(
-            &mut world.entities,
-            &mut world.archetypes,
-            &world.components,
-            &mut world.storages,
-            change_tick,
-        );
-        spawner.reserve_storage(length);
+        // SAFETY: `bundle_id` was just initialized
+        let has_hooks = unsafe { world.bundles.get(bundle_id).debug_checked_unwrap() }
+            .components()
+            .iter()
+            .any(|&id| {
+                // SAFETY: components in a bundle are always initialized
+                let hooks = unsafe { world.components.get_info_unchecked(id) }.hooks();
+                hooks.on_add.is_some() || hooks.on_insert.is_some()
+            });
+
+        let spawner = if has_hooks {
+            // SAFETY: `bundle_id` was just initialized
+            let bundle_info = unsafe { world.bundles.get(bundle_id).debug_checked_unwrap() };
+            bundle_info
+                .get_bundle_spawner(
+                    &mut world.entities,
+                    &mut world.archetypes,
+                    &world.components,
+                    &mut world.storages,
+                    change_tick,
+                )
+                .reserve_storage(length);
+            Spawner::WithHooks {
+                world,
+                bundle_id,
+                change_tick,
+            }
+        } else {
+            // SAFETY: `bundle_id` was just initialized
+            let bundle_info = unsafe { world.bundles.get(bundle_id).debug_checked_unwrap() };
+            let mut spawner = bundle_info.get_bundle_spawner(
+                &mut world.entities,
+                &mut world.archetypes,
+                &world.components,
+                &mut world.storages,
+                change_tick,
+            );
+            spawner.reserve_storage(length);
+            Spawner::Direct(spawner)
+        };
 
         Self {
             inner: iter,
@@ -73,8 +122,31 @@ where
 
     fn next(&mut self) -> Option<Entity> {
         let bundle = self.inner.next()?;
-        // SAFETY: bundle matches spawner type
-        unsafe { Some(self.spawner.spawn(bundle)) }
+        match &mut self.spawner {
+            // SAFETY: bundle matches spawner type
+            Spawner::Direct(spawner) => unsafe { Some(spawner.spawn(bundle)) },
+            Spawner::WithHooks {
+                world,
+                bundle_id,
+                change_tick,
+            } => {
+                // SAFETY: `bundle_id` was initialized in `SpawnBatchIter::new`
+                let bundle_info = unsafe { world.bundles.get(*bundle_id).debug_checked_unwrap() };
+                let mut spawner = bundle_info.get_bundle_spawner(
+                    &mut world.entities,
+                    &mut world.archetypes,
+                    &world.components,
+                    &mut world.storages,
+                    *change_tick,
+                );
+                // SAFETY: bundle matches spawner type
+                let entity = unsafe { spawner.spawn(bundle) };
+                let archetype_id = spawner.archetype.id();
+                world.trigger_insert_hooks(entity, ArchetypeId::EMPTY, archetype_id, *bundle_id);
+                world.flush_commands();
+                Some(entity)
+            }
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
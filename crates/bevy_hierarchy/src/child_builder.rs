@@ -3,6 +3,7 @@ use bevy_ecs::{
     bundle::Bundle,
     entity::Entity,
     prelude::Events,
+    relationship::RelationshipTarget,
     system::{Command, Commands, EntityCommands},
     world::{EntityWorldMut, World},
 };
@@ -16,51 +17,19 @@ fn push_events(world: &mut World, events: impl IntoIterator<Item = HierarchyEven
     }
 }
 
-/// Adds `child` to `parent`'s [`Children`], without checking if it is already present there.
-///
-/// This might cause unexpected results when removing duplicate children.
-fn push_child_unchecked(world: &mut World, parent: Entity, child: Entity) {
-    let mut parent = world.entity_mut(parent);
-    if let Some(mut children) = parent.get_mut::<Children>() {
-        children.0.push(child);
-    } else {
-        parent.insert(Children(smallvec::smallvec![child]));
-    }
-}
-
 /// Sets [`Parent`] of the `child` to `new_parent`. Inserts [`Parent`] if `child` doesn't have one.
-fn update_parent(world: &mut World, child: Entity, new_parent: Entity) -> Option<Entity> {
-    let mut child = world.entity_mut(child);
-    if let Some(mut parent) = child.get_mut::<Parent>() {
-        let previous = parent.0;
-        *parent = Parent(new_parent);
-        Some(previous)
-    } else {
-        child.insert(Parent(new_parent));
-        None
-    }
-}
-
-/// Remove child from the parent's [`Children`] component.
 ///
-/// Removes the [`Children`] component from the parent if it's empty.
-fn remove_from_children(world: &mut World, parent: Entity, child: Entity) {
-    let Some(mut parent) = world.get_entity_mut(parent) else {
-        return;
-    };
-    let Some(mut children) = parent.get_mut::<Children>() else {
-        return;
-    };
-    children.0.retain(|x| *x != child);
-    if children.is_empty() {
-        parent.remove::<Children>();
+/// The [`Children`] of the previous and new parents are updated by the [`Parent`] hooks.
+fn update_parent(world: &mut World, child: Entity, new_parent: Entity) -> Option<Entity> {
+    let previous = world.get::<Parent>(child).map(Parent::get);
+    if previous != Some(new_parent) {
+        world.entity_mut(child).insert(Parent(new_parent));
     }
+    previous
 }
 
-/// Update the [`Parent`] component of the `child`.
-/// Removes the `child` from the previous parent's [`Children`].
-///
-/// Does not update the new parents [`Children`] component.
+/// Update the [`Parent`] component of the `child`, which also moves it to the end of `parent`'s
+/// [`Children`].
 ///
 /// Does nothing if `child` was already a child of `parent`.
 ///
@@ -72,7 +41,6 @@ fn update_old_parent(world: &mut World, child: Entity, parent: Entity) {
         if previous_parent == parent {
             return;
         }
-        remove_from_children(world, previous_parent, child);
 
         push_events(
             world,
@@ -87,10 +55,8 @@ fn update_old_parent(world: &mut World, child: Entity, parent: Entity) {
     }
 }
 
-/// Update the [`Parent`] components of the `children`.
-/// Removes the `children` from their previous parent's [`Children`].
-///
-/// Does not update the new parents [`Children`] component.
+/// Update the [`Parent`] components of the `children`, which also moves them to the end of
+/// `parent`'s [`Children`].
 ///
 /// Does nothing for a child if it was already a child of `parent`.
 ///
@@ -104,7 +70,6 @@ fn update_old_parents(world: &mut World, parent: Entity, children: &[Entity]) {
                 continue;
             }
 
-            remove_from_children(world, previous, child);
             events.push(HierarchyEvent::ChildMoved {
                 child,
                 previous_parent: previous,
@@ -117,6 +82,18 @@ fn update_old_parents(world: &mut World, parent: Entity, children: &[Entity]) {
     push_events(world, events);
 }
 
+/// Moves `children`, which must already be children of `parent`, to the given `index` of its
+/// [`Children`], or to the end if `index` is `None`.
+fn reorder_children(world: &mut World, parent: Entity, children: &[Entity], index: Option<usize>) {
+    let Some(mut parent_children) = world.get_mut::<Children>(parent) else {
+        return;
+    };
+    let collection = parent_children.collection_mut_risky();
+    collection.retain(|value| !children.contains(value));
+    let index = index.map_or(collection.len(), |index| index.min(collection.len()));
+    collection.insert_from_slice(index, children);
+}
+
 /// Removes entities in `children` from `parent`'s [`Children`], removing the component if it ends up empty.
 /// Also removes [`Parent`] component from `children`.
 fn remove_children(parent: Entity, children: &[Entity], world: &mut World) {
@@ -136,26 +113,19 @@ fn remove_children(parent: Entity, children: &[Entity], world: &mut World) {
         }
     }
     push_events(world, events);
-
-    let mut parent = world.entity_mut(parent);
-    if let Some(mut parent_children) = parent.get_mut::<Children>() {
-        parent_children
-            .0
-            .retain(|parent_child| !children.contains(parent_child));
-
-        if parent_children.is_empty() {
-            parent.remove::<Children>();
-        }
-    }
 }
 
-/// Removes all children from `parent` by removing its [`Children`] component, as well as removing
-/// [`Parent`] component from its children.
+/// Removes all children from `parent` by removing the [`Parent`] component from its children,
+/// which also removes its [`Children`] component.
 fn clear_children(parent: Entity, world: &mut World) {
-    if let Some(children) = world.entity_mut(parent).take::<Children>() {
-        for &child in &children.0 {
-            world.entity_mut(child).remove::<Parent>();
-        }
+    let Some(children) = world
+        .get::<Children>(parent)
+        .map(|children| children.to_vec())
+    else {
+        return;
+    };
+    for child in children {
+        world.entity_mut(child).remove::<Parent>();
     }
 }
 
@@ -483,7 +453,6 @@ impl<'w> WorldChildBuilder<'w> {
     /// Also adds [`Parent`] component to the created entity.
     pub fn spawn(&mut self, bundle: impl Bundle + Send + Sync + 'static) -> EntityWorldMut<'_> {
         let entity = self.world.spawn((bundle, Parent(self.parent))).id();
-        push_child_unchecked(self.world, self.parent, entity);
         push_events(
             self.world,
             [HierarchyEvent::ChildAdded {
@@ -498,7 +467,6 @@ impl<'w> WorldChildBuilder<'w> {
     /// Also adds [`Parent`] component to the created entity.
     pub fn spawn_empty(&mut self) -> EntityWorldMut<'_> {
         let entity = self.world.spawn(Parent(self.parent)).id();
-        push_child_unchecked(self.world, self.parent, entity);
         push_events(
             self.world,
             [HierarchyEvent::ChildAdded {
@@ -601,13 +569,8 @@ impl<'w> BuildWorldChildren for EntityWorldMut<'w> {
         }
         self.world_scope(|world| {
             update_old_parent(world, child, parent);
+            reorder_children(world, parent, &[child], None);
         });
-        if let Some(mut children_component) = self.get_mut::<Children>() {
-            children_component.0.retain(|value| child != *value);
-            children_component.0.push(child);
-        } else {
-            self.insert(Children::from_entities(&[child]));
-        }
         self
     }
 
@@ -618,15 +581,8 @@ impl<'w> BuildWorldChildren for EntityWorldMut<'w> {
         }
         self.world_scope(|world| {
             update_old_parents(world, parent, children);
+            reorder_children(world, parent, children, None);
         });
-        if let Some(mut children_component) = self.get_mut::<Children>() {
-            children_component
-                .0
-                .retain(|value| !children.contains(value));
-            children_component.0.extend(children.iter().cloned());
-        } else {
-            self.insert(Children::from_entities(children));
-        }
         self
     }
 
@@ -637,15 +593,8 @@ impl<'w> BuildWorldChildren for EntityWorldMut<'w> {
         }
         self.world_scope(|world| {
             update_old_parents(world, parent, children);
+            reorder_children(world, parent, children, Some(index));
         });
-        if let Some(mut children_component) = self.get_mut::<Children>() {
-            children_component
-                .0
-                .retain(|value| !children.contains(value));
-            children_component.0.insert_from_slice(index, children);
-        } else {
-            self.insert(Children::from_entities(children));
-        }
         self
     }
 
@@ -669,7 +618,6 @@ impl<'w> BuildWorldChildren for EntityWorldMut<'w> {
         let child = self.id();
         if let Some(parent) = self.take::<Parent>().map(|p| p.get()) {
             self.world_scope(|world| {
-                remove_from_children(world, parent, child);
                 push_events(world, [HierarchyEvent::ChildRemoved { child, parent }]);
             });
         }
@@ -1,3 +1,4 @@
+use crate::components::parent::Parent;
 use bevy_ecs::{
     component::Component,
     entity::{Entity, EntityMapper, MapEntities},
@@ -12,9 +13,9 @@ use std::ops::Deref;
 
 /// Contains references to the child entities of this entity.
 ///
-/// Each child must contain a [`Parent`] component that points back to this entity.
-/// This component rarely needs to be created manually,
-/// consider using higher level utilities like [`BuildChildren::with_children`]
+/// This is the [`RelationshipTarget`](bevy_ecs::relationship::RelationshipTarget) of [`Parent`]:
+/// it is kept in sync with the [`Parent`] components pointing to this entity, and should not be
+/// inserted manually. Consider using higher level utilities like [`BuildChildren::with_children`]
 /// which are safer and easier to use.
 ///
 /// See [`HierarchyQueryExt`] for hierarchy related methods on [`Query`].
@@ -24,6 +25,7 @@ use std::ops::Deref;
 /// [`Parent`]: crate::components::parent::Parent
 /// [`BuildChildren::with_children`]: crate::child_builder::BuildChildren::with_children
 #[derive(Component, Debug, Reflect)]
+#[relationship_target(relationship = Parent)]
 #[reflect(Component, MapEntities)]
 pub struct Children(pub(crate) SmallVec<[Entity; 8]>);
 
@@ -46,11 +48,6 @@ impl FromWorld for Children {
 }
 
 impl Children {
-    /// Constructs a [`Children`] component with the given entities.
-    pub(crate) fn from_entities(entities: &[Entity]) -> Self {
-        Self(SmallVec::from_slice(entities))
-    }
-
     /// Swaps the child at `a_index` with the child at `b_index`.
     pub fn swap(&mut self, a_index: usize, b_index: usize) {
         self.0.swap(a_index, b_index);
@@ -1,8 +1,10 @@
+use crate::components::children::Children;
 use bevy_ecs::{
     component::Component,
     entity::{Entity, EntityMapper, MapEntities},
     reflect::{ReflectComponent, ReflectMapEntities},
-    world::{FromWorld, World},
+    traversal::Traversal,
+    world::{EntityRef, FromWorld, World},
 };
 use bevy_reflect::Reflect;
 use std::ops::Deref;
@@ -10,9 +12,10 @@ use std::ops::Deref;
 /// Holds a reference to the parent entity of this entity.
 /// This component should only be present on entities that actually have a parent entity.
 ///
-/// Parent entity must have this entity stored in its [`Children`] component.
-/// It is hard to set up parent/child relationships manually,
-/// consider using higher level utilities like [`BuildChildren::with_children`].
+/// This is a [`Relationship`](bevy_ecs::relationship::Relationship): inserting it adds this entity
+/// to the parent's [`Children`] component, and removing it removes this entity from there.
+/// Consider using higher level utilities like [`BuildChildren::with_children`], which also send
+/// [`HierarchyEvent`](crate::HierarchyEvent)s.
 ///
 /// See [`HierarchyQueryExt`] for hierarchy related methods on [`Query`].
 ///
@@ -21,6 +24,7 @@ use std::ops::Deref;
 /// [`Children`]: super::children::Children
 /// [`BuildChildren::with_children`]: crate::child_builder::BuildChildren::with_children
 #[derive(Component, Debug, Eq, PartialEq, Reflect)]
+#[relationship(relationship_target = Children)]
 #[reflect(Component, MapEntities, PartialEq)]
 pub struct Parent(pub(crate) Entity);
 
@@ -57,6 +61,14 @@ impl MapEntities for Parent {
     }
 }
 
+/// Events with `#[event(traversal = Parent)]` propagate from an entity to its parent,
+/// bubbling up the hierarchy.
+impl Traversal for Parent {
+    fn traverse(entity: EntityRef) -> Option<Entity> {
+        entity.get::<Parent>().map(Parent::get)
+    }
+}
+
 impl Deref for Parent {
     type Target = Entity;
 
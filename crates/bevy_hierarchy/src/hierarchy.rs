@@ -1,4 +1,4 @@
-use crate::components::{Children, Parent};
+use crate::components::Children;
 use bevy_ecs::{
     entity::Entity,
     system::{Command, EntityCommands},
@@ -21,15 +21,9 @@ pub struct DespawnChildrenRecursive {
 }
 
 /// Function for despawning an entity and all its children
+///
+/// The entity's own parent forgets about it through the [`Parent`](crate::Parent) hooks.
 pub fn despawn_with_children_recursive(world: &mut World, entity: Entity) {
-    // first, make the entity's own parent forget about it
-    if let Some(parent) = world.get::<Parent>(entity).map(|parent| parent.0) {
-        if let Some(mut children) = world.get_mut::<Children>(parent) {
-            children.0.retain(|c| *c != entity);
-        }
-    }
-
-    // then despawn the entity and all of its children
     despawn_with_children_recursive_inner(world, entity);
 }
 
@@ -208,13 +202,11 @@ mod tests {
             .collect::<Vec<_>>();
         results.sort_unstable_by_key(|(_, index)| *index);
 
-        {
-            let children = world.get::<Children>(grandparent_entity).unwrap();
-            assert!(
-                !children.iter().any(|&i| i == parent_entity),
-                "grandparent should no longer know about its child which has been removed"
-            );
-        }
+        // the grandparent had a single child, so it has no `Children` left
+        assert!(
+            world.get::<Children>(grandparent_entity).is_none(),
+            "grandparent should no longer know about its child which has been removed"
+        );
 
         assert_eq!(
             results,
@@ -0,0 +1,193 @@
+use std::marker::PhantomData;
+
+use bevy_app::{App, Plugin, StateTransition};
+use bevy_ecs::{prelude::*, schedule::StateTransitionSteps};
+
+use crate::DespawnRecursiveExt;
+
+/// Entities marked with this component are despawned recursively, along with their children,
+/// when the app exits the state `S`.
+///
+/// This requires the [`StateScopedPlugin<S>`].
+///
+/// ```
+/// # use bevy_app::prelude::*;
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_hierarchy::{StateScoped, StateScopedPlugin};
+/// #[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+/// enum GameState {
+///     #[default]
+///     MainMenu,
+///     InGame,
+/// }
+///
+/// fn spawn_menu(mut commands: Commands) {
+///     // despawned when leaving `GameState::MainMenu`, no cleanup system needed
+///     commands.spawn(StateScoped(GameState::MainMenu));
+/// }
+///
+/// # let mut app = App::new();
+/// app.add_state::<GameState>()
+///     .add_plugins(StateScopedPlugin::<GameState>::default())
+///     .add_systems(OnEnter(GameState::MainMenu), spawn_menu);
+/// ```
+#[derive(Component, Clone, Debug)]
+pub struct StateScoped<S: States>(pub S);
+
+/// Despawns recursively the entities with a [`StateScoped<S>`] component matching the state
+/// exited since this system last ran, including when [`State<S>`] was removed.
+///
+/// The [`StateScopedPlugin<S>`] runs this system in the [`StateTransition`] schedule, after the
+/// [`OnExit`] schedules and before the [`OnTransition`] and [`OnEnter`] schedules, so the entities
+/// of the exited state are still there on exit and gone on enter. Transitions applied manually
+/// with [`apply_state_transition`] are cleaned up on the next [`StateTransition`].
+pub fn clear_state_scoped_entities<S: States>(world: &mut World, mut current: Local<Option<S>>) {
+    let state = world
+        .get_resource::<State<S>>()
+        .map(|state| state.get().clone());
+    let previous = std::mem::replace(&mut *current, state);
+    let Some(exited) = previous.filter(|previous| current.as_ref() != Some(previous)) else {
+        return;
+    };
+    let entities: Vec<Entity> = world
+        .query::<(Entity, &StateScoped<S>)>()
+        .iter(world)
+        .filter(|(_, scope)| scope.0 == exited)
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in entities {
+        if let Some(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+/// Adds [`clear_state_scoped_entities<S>`] to the [`StateTransition`] schedule.
+///
+/// The state `S` must be added to the app separately.
+pub struct StateScopedPlugin<S: States>(PhantomData<fn() -> S>);
+
+impl<S: States> Default for StateScopedPlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: States> Plugin for StateScopedPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            StateTransition,
+            clear_state_scoped_entities::<S>
+                .after(StateTransitionSteps::ExitSchedules)
+                .before(StateTransitionSteps::TransitionSchedules),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::prelude::*;
+    use bevy_ecs::prelude::*;
+
+    use super::{StateScoped, StateScopedPlugin};
+    use crate::BuildWorldChildren;
+
+    #[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+    enum GameState {
+        #[default]
+        MainMenu,
+        InGame,
+    }
+
+    #[derive(Resource, Default)]
+    struct Log(Vec<&'static str>);
+
+    #[derive(Component)]
+    struct Menu;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_state::<GameState>()
+            .add_plugins(StateScopedPlugin::<GameState>::default())
+            .init_resource::<Log>()
+            .add_systems(
+                OnExit(GameState::MainMenu),
+                |menus: Query<(), With<Menu>>, mut log: ResMut<Log>| {
+                    log.0.push(if menus.is_empty() {
+                        "exit menu without menu"
+                    } else {
+                        "exit menu"
+                    });
+                },
+            )
+            .add_systems(
+                OnEnter(GameState::InGame),
+                |menus: Query<(), With<Menu>>, mut log: ResMut<Log>| {
+                    log.0.push(if menus.is_empty() {
+                        "enter game"
+                    } else {
+                        "enter game with menu"
+                    });
+                },
+            );
+        app.update();
+        app
+    }
+
+    #[test]
+    fn state_scoped_entities_are_despawned_on_exit() {
+        let mut app = app();
+
+        let menu = app
+            .world
+            .spawn((StateScoped(GameState::MainMenu), Menu))
+            .id();
+        let child = app.world.spawn_empty().set_parent(menu).id();
+        let level = app.world.spawn(StateScoped(GameState::InGame)).id();
+
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::InGame);
+        app.update();
+        assert!(app.world.get_entity(menu).is_none());
+        assert!(app.world.get_entity(child).is_none());
+        assert!(app.world.get_entity(level).is_some());
+        // The menu is there on exit, and gone on enter
+        assert_eq!(app.world.resource::<Log>().0, ["exit menu", "enter game"]);
+
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::MainMenu);
+        app.update();
+        assert!(app.world.get_entity(level).is_none());
+    }
+
+    #[test]
+    fn state_scoped_entities_are_despawned_on_manual_transition() {
+        let mut app = app();
+        app.add_systems(Update, apply_state_transition::<GameState>);
+        let menu = app
+            .world
+            .spawn((StateScoped(GameState::MainMenu), Menu))
+            .id();
+
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::InGame);
+        // Applies the transition in `Update` only, as `StateTransition` runs before it
+        app.world.run_schedule(Update);
+        assert_eq!(*app.world.resource::<State<GameState>>(), GameState::InGame);
+        // The manual transition runs the schedules once, and the menu is despawned in the next
+        // `StateTransition`
+        assert_eq!(
+            app.world.resource::<Log>().0,
+            ["exit menu", "enter game with menu"]
+        );
+        app.update();
+        assert!(app.world.get_entity(menu).is_none());
+        assert_eq!(
+            app.world.resource::<Log>().0,
+            ["exit menu", "enter game with menu"]
+        );
+    }
+}
@@ -0,0 +1,658 @@
+//! Maps raw device input to game-specific actions.
+//!
+//! Instead of reading [`Input<KeyCode>`] or [`Axis<GamepadAxis>`] directly, gameplay code defines
+//! an action type, binds each action to one or more [`Binding`]s in an [`InputMap`], and reads the
+//! resulting [`ActionState`]. Bindings can be changed at runtime by mutating the [`InputMap`]
+//! resource, and saved or loaded as binding profiles with the `serialize` feature.
+//!
+//! ```
+//! # use bevy_app::prelude::*;
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_input::{prelude::*, action::*, InputPlugin};
+//! #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+//! enum PlayerAction {
+//!     Jump,
+//!     Move,
+//!     Save,
+//! }
+//!
+//! let mut input_map = InputMap::default();
+//! input_map
+//!     .insert(PlayerAction::Jump, KeyCode::Space)
+//!     .insert(PlayerAction::Jump, GamepadButtonType::South)
+//!     .insert(PlayerAction::Move, Binding::wasd())
+//!     .insert(PlayerAction::Move, Binding::left_stick())
+//!     .insert(
+//!         PlayerAction::Save,
+//!         Binding::chord([Modifier::Control.into(), KeyCode::S.into()]),
+//!     );
+//!
+//! fn jump(actions: Res<ActionState<PlayerAction>>) {
+//!     if actions.just_pressed(PlayerAction::Jump) {
+//!         // jump
+//!     }
+//!     let direction = actions.axis_pair(PlayerAction::Move);
+//!     // move
+//! }
+//!
+//! App::new()
+//!     .add_plugins((InputPlugin, ActionPlugin::<PlayerAction>::default()))
+//!     .insert_resource(input_map)
+//!     .add_systems(Update, jump);
+//! ```
+
+use std::{fmt::Debug, hash::Hash, marker::PhantomData};
+
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::prelude::*;
+use bevy_math::Vec2;
+use bevy_reflect::Reflect;
+use bevy_utils::HashMap;
+
+use crate::{
+    gamepad::{
+        gamepad_axis_event_system, gamepad_button_event_system, Gamepad, GamepadAxis,
+        GamepadAxisType, GamepadButton, GamepadButtonType, Gamepads,
+    },
+    keyboard::{keyboard_input_system, KeyCode},
+    mouse::{mouse_button_input_system, MouseButton},
+    Axis, Input, InputSystem,
+};
+
+#[cfg(feature = "serialize")]
+use bevy_reflect::{ReflectDeserialize, ReflectSerialize};
+
+/// How far a gamepad axis must be pushed for a [`Binding::GamepadAxis`] to be pressed.
+pub const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+/// A type that can be used as an action: usually a fieldless enum.
+///
+/// This is implemented for all types with the required bounds.
+pub trait Actionlike: 'static + Send + Sync + Clone + Eq + Hash + Debug {}
+
+impl<T: 'static + Send + Sync + Clone + Eq + Hash + Debug> Actionlike for T {}
+
+/// A modifier key, matching both its left and right keys.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Debug, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub enum Modifier {
+    /// [`KeyCode::ShiftLeft`] or [`KeyCode::ShiftRight`].
+    Shift,
+    /// [`KeyCode::ControlLeft`] or [`KeyCode::ControlRight`].
+    Control,
+    /// [`KeyCode::AltLeft`] or [`KeyCode::AltRight`].
+    Alt,
+    /// [`KeyCode::SuperLeft`] or [`KeyCode::SuperRight`].
+    Super,
+}
+
+impl Modifier {
+    /// The left and right keys of this modifier.
+    pub fn keys(self) -> [KeyCode; 2] {
+        match self {
+            Modifier::Shift => [KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            Modifier::Control => [KeyCode::ControlLeft, KeyCode::ControlRight],
+            Modifier::Alt => [KeyCode::AltLeft, KeyCode::AltRight],
+            Modifier::Super => [KeyCode::SuperLeft, KeyCode::SuperRight],
+        }
+    }
+}
+
+/// A single button-like input.
+///
+/// Gamepad buttons match the button on any connected gamepad, unless [`InputMap::gamepad`] is
+/// set.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Debug, Hash, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub enum InputButton {
+    /// A keyboard key.
+    Key(KeyCode),
+    /// A modifier key, on either side of the keyboard.
+    Modifier(Modifier),
+    /// A mouse button.
+    Mouse(MouseButton),
+    /// A gamepad button.
+    Gamepad(GamepadButtonType),
+}
+
+impl From<KeyCode> for InputButton {
+    fn from(key_code: KeyCode) -> Self {
+        InputButton::Key(key_code)
+    }
+}
+
+impl From<Modifier> for InputButton {
+    fn from(modifier: Modifier) -> Self {
+        InputButton::Modifier(modifier)
+    }
+}
+
+impl From<MouseButton> for InputButton {
+    fn from(button: MouseButton) -> Self {
+        InputButton::Mouse(button)
+    }
+}
+
+impl From<GamepadButtonType> for InputButton {
+    fn from(button_type: GamepadButtonType) -> Self {
+        InputButton::Gamepad(button_type)
+    }
+}
+
+/// An input an action can be bound to in an [`InputMap`].
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[reflect(Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    reflect(Serialize, Deserialize)
+)]
+pub enum Binding {
+    /// A single button, with a value of `1.0` while pressed.
+    Button(InputButton),
+    /// Several buttons that must all be pressed at the same time, like `Ctrl + S`.
+    Chord(Vec<InputButton>),
+    /// A gamepad axis, pressed while pushed further than [`AXIS_PRESS_THRESHOLD`] in either
+    /// direction. Its value is the position of the axis, and its axis pair is `(value, 0)`.
+    GamepadAxis(GamepadAxisType),
+    /// Two gamepad axes forming a stick, pressed while not centered. Its axis pair is the
+    /// position of the stick, and its value the length of that position.
+    GamepadStick {
+        /// The horizontal axis.
+        x: GamepadAxisType,
+        /// The vertical axis.
+        y: GamepadAxisType,
+    },
+    /// A virtual stick made of four buttons, like `WASD`, pressed while any of them is pressed.
+    /// Its axis pair is the direction of the pressed buttons, normalized, and its value the
+    /// length of that direction.
+    VirtualDPad {
+        /// The button pointing up: `(0, 1)`.
+        up: InputButton,
+        /// The button pointing down: `(0, -1)`.
+        down: InputButton,
+        /// The button pointing left: `(-1, 0)`.
+        left: InputButton,
+        /// The button pointing right: `(1, 0)`.
+        right: InputButton,
+    },
+}
+
+impl Binding {
+    /// A chord of all `buttons`.
+    pub fn chord(buttons: impl IntoIterator<Item = InputButton>) -> Self {
+        Binding::Chord(buttons.into_iter().collect())
+    }
+
+    /// A [`Binding::VirtualDPad`] made of the `W`, `A`, `S` and `D` keys.
+    pub fn wasd() -> Self {
+        Binding::VirtualDPad {
+            up: KeyCode::W.into(),
+            down: KeyCode::S.into(),
+            left: KeyCode::A.into(),
+            right: KeyCode::D.into(),
+        }
+    }
+
+    /// A [`Binding::VirtualDPad`] made of the arrow keys.
+    pub fn arrow_keys() -> Self {
+        Binding::VirtualDPad {
+            up: KeyCode::Up.into(),
+            down: KeyCode::Down.into(),
+            left: KeyCode::Left.into(),
+            right: KeyCode::Right.into(),
+        }
+    }
+
+    /// A [`Binding::VirtualDPad`] made of the gamepad directional pad.
+    pub fn dpad() -> Self {
+        Binding::VirtualDPad {
+            up: GamepadButtonType::DPadUp.into(),
+            down: GamepadButtonType::DPadDown.into(),
+            left: GamepadButtonType::DPadLeft.into(),
+            right: GamepadButtonType::DPadRight.into(),
+        }
+    }
+
+    /// A [`Binding::GamepadStick`] made of the left stick axes.
+    pub fn left_stick() -> Self {
+        Binding::GamepadStick {
+            x: GamepadAxisType::LeftStickX,
+            y: GamepadAxisType::LeftStickY,
+        }
+    }
+
+    /// A [`Binding::GamepadStick`] made of the right stick axes.
+    pub fn right_stick() -> Self {
+        Binding::GamepadStick {
+            x: GamepadAxisType::RightStickX,
+            y: GamepadAxisType::RightStickY,
+        }
+    }
+}
+
+impl<T: Into<InputButton>> From<T> for Binding {
+    fn from(button: T) -> Self {
+        Binding::Button(button.into())
+    }
+}
+
+/// The bindings of each action of type `A`.
+///
+/// This resource can be modified at runtime to rebind actions. With the `serialize` feature, it
+/// can be serialized to save and load binding profiles.
+#[derive(Resource, Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound(
+        serialize = "A: serde::Serialize",
+        deserialize = "A: serde::Deserialize<'de>"
+    ))
+)]
+pub struct InputMap<A: Actionlike> {
+    bindings: HashMap<A, Vec<Binding>>,
+    /// The gamepad whose input is read, or `None` to read all connected gamepads.
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub gamepad: Option<Gamepad>,
+}
+
+impl<A: Actionlike> Default for InputMap<A> {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::default(),
+            gamepad: None,
+        }
+    }
+}
+
+impl<A: Actionlike> InputMap<A> {
+    /// Binds `action` to `binding`, in addition to its existing bindings.
+    pub fn insert(&mut self, action: A, binding: impl Into<Binding>) -> &mut Self {
+        let binding = binding.into();
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self
+    }
+
+    /// Replaces all the bindings of `action`.
+    pub fn set(&mut self, action: A, bindings: impl IntoIterator<Item = Binding>) -> &mut Self {
+        self.bindings.insert(action, bindings.into_iter().collect());
+        self
+    }
+
+    /// Removes `binding` from the bindings of `action`, returning `true` if it was bound.
+    pub fn remove(&mut self, action: &A, binding: &Binding) -> bool {
+        let Some(bindings) = self.bindings.get_mut(action) else {
+            return false;
+        };
+        let len = bindings.len();
+        bindings.retain(|bound| bound != binding);
+        bindings.len() != len
+    }
+
+    /// Removes all the bindings of `action`, returning them.
+    pub fn clear_action(&mut self, action: &A) -> Vec<Binding> {
+        self.bindings.remove(action).unwrap_or_default()
+    }
+
+    /// Returns the bindings of `action`.
+    pub fn bindings(&self, action: &A) -> &[Binding] {
+        self.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Iterates over all actions and their bindings.
+    pub fn iter(&self) -> impl Iterator<Item = (&A, &[Binding])> {
+        self.bindings
+            .iter()
+            .map(|(action, bindings)| (action, bindings.as_slice()))
+    }
+}
+
+/// The state of a single action, combined from all its bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ActionData {
+    /// Whether any binding is pressed.
+    pub pressed: bool,
+    /// Whether any binding was pressed during the previous update.
+    pub pressed_last_update: bool,
+    /// The value of the binding with the largest absolute value.
+    pub value: f32,
+    /// The sum of the axis pairs of all bindings, clamped to a length of `1`.
+    pub axis_pair: Vec2,
+}
+
+/// The state of every action of type `A`, updated from the [`InputMap<A>`] during
+/// [`InputSystem`].
+#[derive(Resource, Debug, Clone)]
+pub struct ActionState<A: Actionlike> {
+    actions: HashMap<A, ActionData>,
+}
+
+impl<A: Actionlike> Default for ActionState<A> {
+    fn default() -> Self {
+        Self {
+            actions: HashMap::default(),
+        }
+    }
+}
+
+impl<A: Actionlike> ActionState<A> {
+    /// Returns the state of `action`.
+    pub fn action_data(&self, action: A) -> ActionData {
+        self.actions.get(&action).copied().unwrap_or_default()
+    }
+
+    /// Returns `true` if `action` is pressed.
+    pub fn pressed(&self, action: A) -> bool {
+        self.action_data(action).pressed
+    }
+
+    /// Returns `true` if `action` was pressed during the last update.
+    pub fn just_pressed(&self, action: A) -> bool {
+        let data = self.action_data(action);
+        data.pressed && !data.pressed_last_update
+    }
+
+    /// Returns `true` if `action` was released during the last update.
+    pub fn just_released(&self, action: A) -> bool {
+        let data = self.action_data(action);
+        !data.pressed && data.pressed_last_update
+    }
+
+    /// Returns the value of `action`, in `[-1, 1]` for axes.
+    pub fn value(&self, action: A) -> f32 {
+        self.action_data(action).value
+    }
+
+    /// Returns the two-dimensional value of `action`, with a length of at most `1`.
+    pub fn axis_pair(&self, action: A) -> Vec2 {
+        self.action_data(action).axis_pair
+    }
+
+    /// Overrides the state of `action` until the next update, for example to simulate input.
+    pub fn set_action_data(&mut self, action: A, data: ActionData) {
+        self.actions.insert(action, data);
+    }
+}
+
+/// The raw input read by [`update_action_state`].
+struct RawInput<'a> {
+    keys: &'a Input<KeyCode>,
+    mouse_buttons: &'a Input<MouseButton>,
+    gamepad_buttons: &'a Input<GamepadButton>,
+    gamepad_axes: &'a Axis<GamepadAxis>,
+    gamepads: Vec<Gamepad>,
+}
+
+impl RawInput<'_> {
+    fn pressed(&self, button: InputButton) -> bool {
+        match button {
+            InputButton::Key(key_code) => self.keys.pressed(key_code),
+            InputButton::Modifier(modifier) => self.keys.any_pressed(modifier.keys()),
+            InputButton::Mouse(button) => self.mouse_buttons.pressed(button),
+            InputButton::Gamepad(button_type) => self.gamepads.iter().any(|&gamepad| {
+                self.gamepad_buttons
+                    .pressed(GamepadButton::new(gamepad, button_type))
+            }),
+        }
+    }
+
+    /// The value of the axis with the largest absolute value among the gamepads.
+    fn axis(&self, axis_type: GamepadAxisType) -> f32 {
+        self.gamepads
+            .iter()
+            .filter_map(|&gamepad| self.gamepad_axes.get(GamepadAxis::new(gamepad, axis_type)))
+            .fold(
+                0.0,
+                |max: f32, value| {
+                    if value.abs() > max.abs() {
+                        value
+                    } else {
+                        max
+                    }
+                },
+            )
+    }
+
+    /// Returns whether `binding` is pressed, its value and its axis pair.
+    fn read(&self, binding: &Binding) -> (bool, f32, Vec2) {
+        let button_value = |button: InputButton| f32::from(u8::from(self.pressed(button)));
+        match binding {
+            Binding::Button(button) => {
+                let pressed = self.pressed(*button);
+                (pressed, f32::from(u8::from(pressed)), Vec2::ZERO)
+            }
+            Binding::Chord(buttons) => {
+                let pressed =
+                    !buttons.is_empty() && buttons.iter().all(|&button| self.pressed(button));
+                (pressed, f32::from(u8::from(pressed)), Vec2::ZERO)
+            }
+            Binding::GamepadAxis(axis_type) => {
+                let value = self.axis(*axis_type);
+                (
+                    value.abs() > AXIS_PRESS_THRESHOLD,
+                    value,
+                    Vec2::new(value, 0.0),
+                )
+            }
+            Binding::GamepadStick { x, y } => {
+                let axis_pair = Vec2::new(self.axis(*x), self.axis(*y)).clamp_length_max(1.0);
+                (axis_pair != Vec2::ZERO, axis_pair.length(), axis_pair)
+            }
+            Binding::VirtualDPad {
+                up,
+                down,
+                left,
+                right,
+            } => {
+                let axis_pair = Vec2::new(
+                    button_value(*right) - button_value(*left),
+                    button_value(*up) - button_value(*down),
+                )
+                .normalize_or_zero();
+                let pressed = [up, down, left, right]
+                    .into_iter()
+                    .any(|&button| self.pressed(button));
+                (pressed, axis_pair.length(), axis_pair)
+            }
+        }
+    }
+}
+
+/// Updates the [`ActionState<A>`] from the raw input and the [`InputMap<A>`].
+#[allow(clippy::too_many_arguments)]
+pub fn update_action_state<A: Actionlike>(
+    input_map: Res<InputMap<A>>,
+    mut action_state: ResMut<ActionState<A>>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepads: Res<Gamepads>,
+) {
+    let raw_input = RawInput {
+        keys: &keys,
+        mouse_buttons: &mouse_buttons,
+        gamepad_buttons: &gamepad_buttons,
+        gamepad_axes: &gamepad_axes,
+        gamepads: match input_map.gamepad {
+            Some(gamepad) => vec![gamepad],
+            None => gamepads.iter().collect(),
+        },
+    };
+
+    let action_state = &mut action_state.actions;
+    // actions that were unbound since the last update are released
+    for data in action_state.values_mut() {
+        data.pressed_last_update = data.pressed;
+        data.pressed = false;
+        data.value = 0.0;
+        data.axis_pair = Vec2::ZERO;
+    }
+    for (action, bindings) in input_map.iter() {
+        let data = action_state.entry(action.clone()).or_default();
+        for binding in bindings {
+            let (pressed, value, axis_pair) = raw_input.read(binding);
+            data.pressed |= pressed;
+            if value.abs() > data.value.abs() {
+                data.value = value;
+            }
+            data.axis_pair += axis_pair;
+        }
+        data.axis_pair = data.axis_pair.clamp_length_max(1.0);
+    }
+}
+
+/// Adds the [`InputMap<A>`] and [`ActionState<A>`] resources, and updates the action state during
+/// [`InputSystem`], after the raw input.
+///
+/// The [`InputPlugin`](crate::InputPlugin) must be added too.
+pub struct ActionPlugin<A: Actionlike>(PhantomData<fn() -> A>);
+
+impl<A: Actionlike> Default for ActionPlugin<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A: Actionlike> Plugin for ActionPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap<A>>()
+            .init_resource::<ActionState<A>>()
+            .add_systems(
+                PreUpdate,
+                update_action_state::<A>
+                    .in_set(InputSystem)
+                    .after(keyboard_input_system)
+                    .after(mouse_button_input_system)
+                    .after(gamepad_button_event_system)
+                    .after(gamepad_axis_event_system),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::App;
+    use bevy_math::Vec2;
+
+    use super::*;
+    use crate::InputPlugin;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    enum TestAction {
+        Jump,
+        Move,
+        Save,
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((InputPlugin, ActionPlugin::<TestAction>::default()));
+        app.world
+            .resource_mut::<InputMap<TestAction>>()
+            .insert(TestAction::Jump, KeyCode::Space)
+            .insert(TestAction::Jump, GamepadButtonType::South)
+            .insert(TestAction::Move, Binding::wasd())
+            .insert(TestAction::Move, Binding::left_stick())
+            .insert(
+                TestAction::Save,
+                Binding::chord([Modifier::Control.into(), KeyCode::S.into()]),
+            );
+        app
+    }
+
+    fn press(app: &mut App, key_code: KeyCode) {
+        app.world.resource_mut::<Input<KeyCode>>().press(key_code);
+    }
+
+    fn actions(app: &App) -> &ActionState<TestAction> {
+        app.world.resource::<ActionState<TestAction>>()
+    }
+
+    #[test]
+    fn buttons() {
+        let mut app = app();
+        let gamepad = Gamepad::new(0);
+        app.world.resource_mut::<InputMap<TestAction>>().gamepad = Some(gamepad);
+        // the raw input is only cleared by the input systems, so it's set after the first update
+        app.update();
+        app.world
+            .resource_mut::<Input<GamepadButton>>()
+            .press(GamepadButton::new(gamepad, GamepadButtonType::South));
+        app.update();
+        assert!(actions(&app).just_pressed(TestAction::Jump));
+        assert_eq!(actions(&app).value(TestAction::Jump), 1.0);
+
+        app.update();
+        assert!(actions(&app).pressed(TestAction::Jump));
+        assert!(!actions(&app).just_pressed(TestAction::Jump));
+
+        app.world
+            .resource_mut::<Input<GamepadButton>>()
+            .release(GamepadButton::new(gamepad, GamepadButtonType::South));
+        app.update();
+        assert!(actions(&app).just_released(TestAction::Jump));
+    }
+
+    #[test]
+    fn chords() {
+        let mut app = app();
+        app.update();
+        press(&mut app, KeyCode::S);
+        app.update();
+        assert!(!actions(&app).pressed(TestAction::Save));
+        press(&mut app, KeyCode::ControlRight);
+        app.update();
+        assert!(actions(&app).just_pressed(TestAction::Save));
+    }
+
+    #[test]
+    fn virtual_dpad() {
+        let mut app = app();
+        app.update();
+        press(&mut app, KeyCode::W);
+        press(&mut app, KeyCode::D);
+        app.update();
+        let axis_pair = actions(&app).axis_pair(TestAction::Move);
+        assert!((axis_pair - Vec2::new(1.0, 1.0).normalize()).length() < 1e-6);
+        assert!(actions(&app).pressed(TestAction::Move));
+    }
+
+    #[test]
+    fn rebinding() {
+        let mut app = app();
+        app.update();
+        let mut input_map = app.world.resource_mut::<InputMap<TestAction>>();
+        assert!(input_map.remove(&TestAction::Jump, &KeyCode::Space.into()));
+        input_map.insert(TestAction::Jump, MouseButton::Left);
+
+        press(&mut app, KeyCode::Space);
+        app.update();
+        assert!(!actions(&app).pressed(TestAction::Jump));
+        app.world
+            .resource_mut::<Input<MouseButton>>()
+            .press(MouseButton::Left);
+        app.update();
+        assert!(actions(&app).pressed(TestAction::Jump));
+
+        app.world
+            .resource_mut::<InputMap<TestAction>>()
+            .clear_action(&TestAction::Jump);
+        app.update();
+        assert!(actions(&app).just_released(TestAction::Jump));
+    }
+}
@@ -6,6 +6,7 @@
 //!
 //! `bevy` currently supports keyboard, mouse, gamepad, and touch inputs.
 
+pub mod action;
 mod axis;
 /// Common run conditions
 pub mod common_conditions;
@@ -23,6 +24,7 @@ pub use input::*;
 pub mod prelude {
     #[doc(hidden)]
     pub use crate::{
+        action::{ActionPlugin, ActionState, InputMap},
         gamepad::{
             Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, Gamepads,
         },
@@ -63,9 +65,14 @@ pub struct InputPlugin;
 #[derive(Debug, PartialEq, Eq, Clone, Hash, SystemSet)]
 pub struct InputSystem;
 
+/// Label for systems that send input events during [`PreUpdate`], before the [`InputSystem`]
+/// reads them, like the gamepad events of `bevy_gilrs`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, SystemSet)]
+pub struct InputEventSystem;
+
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
-        app
+        app.configure_sets(PreUpdate, InputEventSystem.before(InputSystem))
             // keyboard
             .add_event::<KeyboardInput>()
             .init_resource::<Input<KeyCode>>()
@@ -146,6 +153,11 @@ impl Plugin for InputPlugin {
             .register_type::<ButtonSettings>()
             .register_type::<AxisSettings>()
             .register_type::<ButtonAxisSettings>();
+
+        // Register action types
+        app.register_type::<action::Modifier>()
+            .register_type::<action::InputButton>()
+            .register_type::<action::Binding>();
     }
 }
 
@@ -188,6 +188,12 @@ pub mod gizmos {
     pub use bevy_gizmos::*;
 }
 
+#[cfg(feature = "bevy_remote")]
+pub mod remote {
+    //! Inspection and edition of a running app from external tools
+    pub use bevy_remote::*;
+}
+
 #[cfg(feature = "bevy_dynamic_plugin")]
 pub mod dynamic_plugin {
     //! Dynamic linking of plugins
@@ -0,0 +1,542 @@
+//! The methods added by default by the [`RemotePlugin`](crate::RemotePlugin).
+//!
+//! Each method has a name constant, a parameters type, and a function processing it.
+
+use bevy_ecs::{
+    component::ComponentId,
+    entity::Entity,
+    reflect::{AppTypeRegistry, ReflectComponent},
+    world::World,
+};
+use bevy_reflect::{
+    serde::{ReflectSerializer, UntypedReflectDeserializer},
+    Reflect, TypeRegistration, TypeRegistry,
+};
+use serde::{de::DeserializeOwned, de::DeserializeSeed, Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{BrpError, BrpResult};
+
+/// Reads component values of an entity: takes [`BrpGetParams`], returns an object mapping each
+/// component type path to its value.
+pub const BRP_GET_METHOD: &str = "bevy/get";
+
+/// Finds the entities with some components: takes [`BrpQueryParams`], returns a list of
+/// [`BrpQueryRow`].
+pub const BRP_QUERY_METHOD: &str = "bevy/query";
+
+/// Spawns an entity: takes [`BrpSpawnParams`], returns a [`BrpSpawnResponse`].
+pub const BRP_SPAWN_METHOD: &str = "bevy/spawn";
+
+/// Inserts components into an entity, replacing their previous values: takes
+/// [`BrpInsertParams`], returns `null`.
+pub const BRP_INSERT_METHOD: &str = "bevy/insert";
+
+/// Removes components from an entity: takes [`BrpRemoveParams`], returns `null`.
+pub const BRP_REMOVE_METHOD: &str = "bevy/remove";
+
+/// Despawns an entity, without its children: takes [`BrpDestroyParams`], returns `null`.
+pub const BRP_DESTROY_METHOD: &str = "bevy/destroy";
+
+/// Lists the type paths of the registered component types, or of the components of an entity:
+/// takes optional [`BrpListParams`], returns a sorted list of type paths.
+///
+/// Only components are listed: the types of the registry without `#[reflect(Component)]`, and the
+/// components of an entity that aren't registered, are left out.
+pub const BRP_LIST_METHOD: &str = "bevy/list";
+
+/// The parameters of [`BRP_GET_METHOD`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrpGetParams {
+    /// The entity to read.
+    pub entity: Entity,
+    /// The type paths of the components to read.
+    pub components: Vec<String>,
+}
+
+/// The parameters of [`BRP_QUERY_METHOD`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrpQueryParams {
+    /// The type paths of the components to read, which the entities must have.
+    pub components: Vec<String>,
+    /// The type paths of other components the entities must have.
+    #[serde(default)]
+    pub with: Vec<String>,
+    /// The type paths of components the entities must not have.
+    #[serde(default)]
+    pub without: Vec<String>,
+}
+
+/// An entity found by [`BRP_QUERY_METHOD`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrpQueryRow {
+    /// The entity.
+    pub entity: Entity,
+    /// The values of the requested components, by type path.
+    pub components: Map<String, Value>,
+}
+
+/// The parameters of [`BRP_SPAWN_METHOD`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrpSpawnParams {
+    /// The values of the components to spawn the entity with, by type path.
+    pub components: Map<String, Value>,
+}
+
+/// The result of [`BRP_SPAWN_METHOD`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrpSpawnResponse {
+    /// The spawned entity.
+    pub entity: Entity,
+}
+
+/// The parameters of [`BRP_INSERT_METHOD`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrpInsertParams {
+    /// The entity to insert the components into.
+    pub entity: Entity,
+    /// The values of the components to insert, by type path.
+    pub components: Map<String, Value>,
+}
+
+/// The parameters of [`BRP_REMOVE_METHOD`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrpRemoveParams {
+    /// The entity to remove the components from.
+    pub entity: Entity,
+    /// The type paths of the components to remove.
+    pub components: Vec<String>,
+}
+
+/// The parameters of [`BRP_DESTROY_METHOD`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrpDestroyParams {
+    /// The entity to despawn.
+    pub entity: Entity,
+}
+
+/// The parameters of [`BRP_LIST_METHOD`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct BrpListParams {
+    /// The entity whose components are listed, instead of all registered components.
+    #[serde(default)]
+    pub entity: Option<Entity>,
+}
+
+/// Processes a [`BRP_GET_METHOD`] request.
+pub fn process_remote_get_request(params: Option<Value>, world: &mut World) -> BrpResult {
+    let BrpGetParams { entity, components } = parse_params(params)?;
+    let app_type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = app_type_registry.read();
+    let entity_ref = world
+        .get_entity(entity)
+        .ok_or(BrpError::entity_not_found(entity))?;
+
+    let mut values = Map::new();
+    for type_path in &components {
+        let reflect_component = get_reflect_component(&type_registry, type_path)?;
+        let value = reflect_component.reflect(entity_ref).ok_or_else(|| {
+            BrpError::component_error(format!("Entity {entity:?} has no {type_path} component"))
+        })?;
+        values.extend(serialize_component(value, &type_registry)?);
+    }
+    Ok(Value::Object(values))
+}
+
+/// Processes a [`BRP_QUERY_METHOD`] request.
+pub fn process_remote_query_request(params: Option<Value>, world: &mut World) -> BrpResult {
+    let BrpQueryParams {
+        components,
+        with,
+        without,
+    } = parse_params(params)?;
+    let app_type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = app_type_registry.read();
+
+    let components = components
+        .iter()
+        .map(|type_path| {
+            let registration = get_component_registration(&type_registry, type_path)?;
+            Ok((
+                world.components().get_id(registration.type_id()),
+                registration.data::<ReflectComponent>().unwrap(),
+            ))
+        })
+        .collect::<Result<Vec<_>, BrpError>>()?;
+    let with = get_component_ids(world, &type_registry, &with)?;
+    let without = get_component_ids(world, &type_registry, &without)?;
+
+    // a required component that was never added to the world can't match any entity
+    let Some(required) = components
+        .iter()
+        .map(|(id, _)| *id)
+        .chain(with)
+        .collect::<Option<Vec<ComponentId>>>()
+    else {
+        return Ok(Value::Array(Vec::new()));
+    };
+    let excluded = without.into_iter().flatten().collect::<Vec<_>>();
+
+    let mut rows = Vec::new();
+    for archetype in world.archetypes().iter() {
+        if !required.iter().all(|&id| archetype.contains(id))
+            || excluded.iter().any(|&id| archetype.contains(id))
+        {
+            continue;
+        }
+        for archetype_entity in archetype.entities() {
+            let entity = archetype_entity.entity();
+            let entity_ref = world.entity(entity);
+            let mut values = Map::new();
+            for (_, reflect_component) in &components {
+                let value = reflect_component.reflect(entity_ref).unwrap();
+                values.extend(serialize_component(value, &type_registry)?);
+            }
+            rows.push(BrpQueryRow {
+                entity,
+                components: values,
+            });
+        }
+    }
+    serialize_result(rows)
+}
+
+/// Processes a [`BRP_SPAWN_METHOD`] request.
+pub fn process_remote_spawn_request(params: Option<Value>, world: &mut World) -> BrpResult {
+    let BrpSpawnParams { components } = parse_params(params)?;
+    let components = deserialize_components(world, components)?;
+
+    let mut entity_world_mut = world.spawn_empty();
+    for (reflect_component, value) in components {
+        reflect_component.insert(&mut entity_world_mut, &*value);
+    }
+    serialize_result(BrpSpawnResponse {
+        entity: entity_world_mut.id(),
+    })
+}
+
+/// Processes a [`BRP_INSERT_METHOD`] request.
+pub fn process_remote_insert_request(params: Option<Value>, world: &mut World) -> BrpResult {
+    let BrpInsertParams { entity, components } = parse_params(params)?;
+    let components = deserialize_components(world, components)?;
+
+    let mut entity_world_mut = world
+        .get_entity_mut(entity)
+        .ok_or(BrpError::entity_not_found(entity))?;
+    for (reflect_component, value) in components {
+        reflect_component.insert(&mut entity_world_mut, &*value);
+    }
+    Ok(Value::Null)
+}
+
+/// Processes a [`BRP_REMOVE_METHOD`] request.
+pub fn process_remote_remove_request(params: Option<Value>, world: &mut World) -> BrpResult {
+    let BrpRemoveParams { entity, components } = parse_params(params)?;
+    let app_type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = app_type_registry.read();
+    let components = components
+        .iter()
+        .map(|type_path| get_reflect_component(&type_registry, type_path))
+        .collect::<Result<Vec<_>, BrpError>>()?;
+
+    let mut entity_world_mut = world
+        .get_entity_mut(entity)
+        .ok_or(BrpError::entity_not_found(entity))?;
+    for reflect_component in components {
+        reflect_component.remove(&mut entity_world_mut);
+    }
+    Ok(Value::Null)
+}
+
+/// Processes a [`BRP_DESTROY_METHOD`] request.
+pub fn process_remote_destroy_request(params: Option<Value>, world: &mut World) -> BrpResult {
+    let BrpDestroyParams { entity } = parse_params(params)?;
+    if !world.despawn(entity) {
+        return Err(BrpError::entity_not_found(entity));
+    }
+    Ok(Value::Null)
+}
+
+/// Processes a [`BRP_LIST_METHOD`] request, listing components only.
+pub fn process_remote_list_request(params: Option<Value>, world: &mut World) -> BrpResult {
+    let BrpListParams { entity } = match params {
+        Some(params) => parse_params(Some(params))?,
+        None => BrpListParams::default(),
+    };
+    let app_type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = app_type_registry.read();
+
+    let mut type_paths = match entity {
+        Some(entity) => {
+            let entity_ref = world
+                .get_entity(entity)
+                .ok_or(BrpError::entity_not_found(entity))?;
+            entity_ref
+                .archetype()
+                .components()
+                .filter_map(|id| world.components().get_info(id)?.type_id())
+                .filter_map(|type_id| type_registry.get(type_id))
+                .map(|registration| registration.type_info().type_path().to_owned())
+                .collect::<Vec<_>>()
+        }
+        None => type_registry
+            .iter()
+            .filter(|registration| registration.data::<ReflectComponent>().is_some())
+            .map(|registration| registration.type_info().type_path().to_owned())
+            .collect(),
+    };
+    type_paths.sort_unstable();
+    serialize_result(type_paths)
+}
+
+fn parse_params<T: DeserializeOwned>(params: Option<Value>) -> Result<T, BrpError> {
+    let params = params.ok_or_else(|| BrpError::invalid_params("missing params"))?;
+    serde_json::from_value(params).map_err(BrpError::invalid_params)
+}
+
+fn serialize_result(result: impl Serialize) -> BrpResult {
+    serde_json::to_value(result).map_err(|err| BrpError::component_error(err.to_string()))
+}
+
+fn get_component_registration<'r>(
+    type_registry: &'r TypeRegistry,
+    type_path: &str,
+) -> Result<&'r TypeRegistration, BrpError> {
+    let registration = type_registry
+        .get_with_type_path(type_path)
+        .ok_or_else(|| BrpError::component_error(format!("Unknown type path {type_path}")))?;
+    if registration.data::<ReflectComponent>().is_none() {
+        return Err(BrpError::component_error(format!(
+            "{type_path} is not registered as a reflected component"
+        )));
+    }
+    Ok(registration)
+}
+
+fn get_reflect_component<'r>(
+    type_registry: &'r TypeRegistry,
+    type_path: &str,
+) -> Result<&'r ReflectComponent, BrpError> {
+    get_component_registration(type_registry, type_path)
+        .map(|registration| registration.data::<ReflectComponent>().unwrap())
+}
+
+/// Returns the [`ComponentId`] of each component, or `None` for components that were never added
+/// to the world.
+fn get_component_ids(
+    world: &World,
+    type_registry: &TypeRegistry,
+    type_paths: &[String],
+) -> Result<Vec<Option<ComponentId>>, BrpError> {
+    type_paths
+        .iter()
+        .map(|type_path| {
+            let registration = get_component_registration(type_registry, type_path)?;
+            Ok(world.components().get_id(registration.type_id()))
+        })
+        .collect()
+}
+
+/// Serializes a component as an object with a single entry mapping its type path to its value.
+fn serialize_component(
+    value: &dyn Reflect,
+    type_registry: &TypeRegistry,
+) -> Result<Map<String, Value>, BrpError> {
+    match serde_json::to_value(ReflectSerializer::new(value, type_registry)) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => unreachable!("ReflectSerializer always serializes a map"),
+        Err(err) => Err(BrpError::component_error(format!(
+            "Failed to serialize {}: {err}",
+            value.reflect_type_path()
+        ))),
+    }
+}
+
+/// Deserializes the component values of an object mapping type paths to values.
+fn deserialize_components(
+    world: &World,
+    components: Map<String, Value>,
+) -> Result<Vec<(ReflectComponent, Box<dyn Reflect>)>, BrpError> {
+    let app_type_registry = world.resource::<AppTypeRegistry>().clone();
+    let type_registry = app_type_registry.read();
+    components
+        .into_iter()
+        .map(|(type_path, value)| {
+            let reflect_component = get_reflect_component(&type_registry, &type_path)?.clone();
+            let value = UntypedReflectDeserializer::new(&type_registry)
+                .deserialize(Value::Object(Map::from_iter([(type_path.clone(), value)])))
+                .map_err(|err| {
+                    BrpError::component_error(format!("Failed to deserialize {type_path}: {err}"))
+                })?;
+            Ok((reflect_component, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::*;
+    use bevy_reflect::Reflect;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::error_codes;
+
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    #[derive(Component, Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Player;
+
+    const HEALTH: &str = "bevy_remote::builtin_methods::tests::Health";
+    const PLAYER: &str = "bevy_remote::builtin_methods::tests::Player";
+
+    fn world() -> World {
+        let mut world = World::new();
+        let type_registry = AppTypeRegistry::default();
+        {
+            let mut type_registry = type_registry.write();
+            type_registry.register::<Health>();
+            type_registry.register::<Player>();
+        }
+        world.insert_resource(type_registry);
+        world
+    }
+
+    #[test]
+    fn spawn_get_and_insert() {
+        let mut world = world();
+        let result = process_remote_spawn_request(
+            Some(json!({ "components": { HEALTH: { "current": 5, "max": 10 } } })),
+            &mut world,
+        )
+        .unwrap();
+        let entity: BrpSpawnResponse = serde_json::from_value(result).unwrap();
+        assert_eq!(
+            world.get::<Health>(entity.entity),
+            Some(&Health {
+                current: 5,
+                max: 10
+            })
+        );
+
+        process_remote_insert_request(
+            Some(json!({
+                "entity": entity.entity,
+                "components": { HEALTH: { "current": 8, "max": 10 }, PLAYER: {} },
+            })),
+            &mut world,
+        )
+        .unwrap();
+        let result = process_remote_get_request(
+            Some(json!({ "entity": entity.entity, "components": [HEALTH] })),
+            &mut world,
+        )
+        .unwrap();
+        assert_eq!(result, json!({ HEALTH: { "current": 8, "max": 10 } }));
+        assert!(world.entity(entity.entity).contains::<Player>());
+    }
+
+    #[test]
+    fn query_with_filters() {
+        let mut world = world();
+        let player = world.spawn((Health { current: 1, max: 2 }, Player)).id();
+        let enemy = world.spawn(Health { current: 3, max: 4 }).id();
+
+        let query = |world: &mut World, params: Value| {
+            let result = process_remote_query_request(Some(params), world).unwrap();
+            serde_json::from_value::<Vec<BrpQueryRow>>(result)
+                .unwrap()
+                .into_iter()
+                .map(|row| row.entity)
+                .collect::<Vec<_>>()
+        };
+        let mut all = query(&mut world, json!({ "components": [HEALTH] }));
+        all.sort();
+        assert_eq!(all, [player, enemy]);
+        assert_eq!(
+            query(
+                &mut world,
+                json!({ "components": [HEALTH], "with": [PLAYER] })
+            ),
+            [player]
+        );
+        assert_eq!(
+            query(
+                &mut world,
+                json!({ "components": [HEALTH], "without": [PLAYER] })
+            ),
+            [enemy]
+        );
+
+        let result =
+            process_remote_query_request(Some(json!({ "components": [HEALTH] })), &mut world)
+                .unwrap();
+        let row = result
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|row| row["entity"] == json!(enemy))
+            .unwrap()
+            .clone();
+        assert_eq!(
+            row["components"],
+            json!({ HEALTH: { "current": 3, "max": 4 } })
+        );
+    }
+
+    #[test]
+    fn remove_destroy_and_list() {
+        let mut world = world();
+        let entity = world.spawn((Health::default(), Player)).id();
+
+        let list = process_remote_list_request(None, &mut world).unwrap();
+        assert_eq!(list, json!([HEALTH, PLAYER]));
+
+        process_remote_remove_request(
+            Some(json!({ "entity": entity, "components": [PLAYER] })),
+            &mut world,
+        )
+        .unwrap();
+        let list =
+            process_remote_list_request(Some(json!({ "entity": entity })), &mut world).unwrap();
+        assert_eq!(list, json!([HEALTH]));
+
+        process_remote_destroy_request(Some(json!({ "entity": entity })), &mut world).unwrap();
+        let error = process_remote_destroy_request(Some(json!({ "entity": entity })), &mut world)
+            .unwrap_err();
+        assert_eq!(error.code, error_codes::ENTITY_NOT_FOUND);
+    }
+
+    #[test]
+    fn invalid_requests() {
+        let mut world = world();
+        let entity = world.spawn(Player).id();
+
+        let error = process_remote_get_request(None, &mut world).unwrap_err();
+        assert_eq!(error.code, error_codes::INVALID_PARAMS);
+        let error = process_remote_get_request(
+            Some(json!({ "entity": entity, "components": ["unknown::Type"] })),
+            &mut world,
+        )
+        .unwrap_err();
+        assert_eq!(error.code, error_codes::COMPONENT_ERROR);
+        let error = process_remote_get_request(
+            Some(json!({ "entity": entity, "components": [HEALTH] })),
+            &mut world,
+        )
+        .unwrap_err();
+        assert_eq!(error.code, error_codes::COMPONENT_ERROR);
+        let error = process_remote_insert_request(
+            Some(json!({ "entity": entity, "components": { HEALTH: "not a struct" } })),
+            &mut world,
+        )
+        .unwrap_err();
+        assert_eq!(error.code, error_codes::COMPONENT_ERROR);
+    }
+}
@@ -0,0 +1,412 @@
+//! The Bevy Remote Protocol (BRP): inspects and edits the [`World`] of a running [`App`] from
+//! external tools, like inspectors or scripted test drivers.
+//!
+//! Adding the [`RemotePlugin`] starts a server listening on a local TCP port (or a Unix socket),
+//! which stops when the app exits.
+//! Clients send [JSON-RPC 2.0](https://www.jsonrpc.org/specification) requests, one per line, and
+//! receive one response per line for each request with an `id`. Requests are processed in
+//! [`Last`], with exclusive access to the [`World`].
+//!
+//! ```json
+//! {"jsonrpc": "2.0", "id": 0, "method": "bevy/get", "params": {"entity": 4294967298, "components": ["bevy_transform::components::transform::Transform"]}}
+//! {"jsonrpc": "2.0", "id": 0, "result": {"bevy_transform::components::transform::Transform": {"translation": [0.0, 0.0, 0.0], ...}}}
+//! ```
+//!
+//! Entities are identified by their [`Entity::to_bits`] value, and components by the type path of
+//! a type registered in the [`AppTypeRegistry`] with `#[reflect(Component)]`. Component values
+//! use the format of [`bevy_reflect::serde`].
+//!
+//! The built-in methods are listed in [`builtin_methods`]. More methods can be added with
+//! [`RemotePlugin::with_method`].
+//!
+//! [`Entity::to_bits`]: bevy_ecs::entity::Entity::to_bits
+//! [`AppTypeRegistry`]: bevy_ecs::reflect::AppTypeRegistry
+
+pub mod builtin_methods;
+mod server;
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+#[cfg(unix)]
+use std::path::PathBuf;
+
+use bevy_app::{App, AppExit, Last, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_log::error;
+use bevy_utils::HashMap;
+use crossbeam_channel::{Receiver, Sender};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The address the [`RemotePlugin`] listens on by default: `127.0.0.1:15702`.
+pub const DEFAULT_ADDRESS: SocketAddr =
+    SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), DEFAULT_PORT);
+
+/// The port the [`RemotePlugin`] listens on by default.
+pub const DEFAULT_PORT: u16 = 15702;
+
+/// Where the [`RemotePlugin`] server listens for connections.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RemoteTransport {
+    /// A TCP socket bound to this address.
+    Tcp(SocketAddr),
+    /// A Unix domain socket at this path, which is created by the server and removed when the app
+    /// exits. A stale socket left at this path is replaced.
+    #[cfg(unix)]
+    Unix(PathBuf),
+    /// No server: requests are only sent through [`RemoteRequests::sender`].
+    None,
+}
+
+/// A method handler: takes the `params` of a request and returns its result.
+pub type RemoteMethod = fn(Option<Value>, &mut World) -> BrpResult;
+
+/// The result of a [`RemoteMethod`].
+pub type BrpResult = Result<Value, BrpError>;
+
+/// Adds a server exposing the [`World`] through the Bevy Remote Protocol.
+///
+/// See the [crate docs](crate) for more information.
+pub struct RemotePlugin {
+    transport: RemoteTransport,
+    methods: Vec<(String, RemoteMethod)>,
+}
+
+impl Default for RemotePlugin {
+    fn default() -> Self {
+        Self {
+            transport: RemoteTransport::Tcp(DEFAULT_ADDRESS),
+            methods: Vec::new(),
+        }
+        .with_method(
+            builtin_methods::BRP_GET_METHOD,
+            builtin_methods::process_remote_get_request,
+        )
+        .with_method(
+            builtin_methods::BRP_QUERY_METHOD,
+            builtin_methods::process_remote_query_request,
+        )
+        .with_method(
+            builtin_methods::BRP_SPAWN_METHOD,
+            builtin_methods::process_remote_spawn_request,
+        )
+        .with_method(
+            builtin_methods::BRP_INSERT_METHOD,
+            builtin_methods::process_remote_insert_request,
+        )
+        .with_method(
+            builtin_methods::BRP_REMOVE_METHOD,
+            builtin_methods::process_remote_remove_request,
+        )
+        .with_method(
+            builtin_methods::BRP_DESTROY_METHOD,
+            builtin_methods::process_remote_destroy_request,
+        )
+        .with_method(
+            builtin_methods::BRP_LIST_METHOD,
+            builtin_methods::process_remote_list_request,
+        )
+    }
+}
+
+impl RemotePlugin {
+    /// Sets where the server listens for connections.
+    pub fn with_transport(mut self, transport: RemoteTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Adds a method, replacing any built-in method with the same name.
+    pub fn with_method(mut self, name: impl Into<String>, method: RemoteMethod) -> Self {
+        self.methods.push((name.into(), method));
+        self
+    }
+}
+
+impl Plugin for RemotePlugin {
+    fn build(&self, app: &mut App) {
+        let mut methods = RemoteMethods::default();
+        for (name, method) in &self.methods {
+            methods.insert(name.clone(), *method);
+        }
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        match server::start_server(&self.transport, sender.clone()) {
+            Ok(Some(server)) => {
+                app.insert_resource(server);
+            }
+            Ok(None) => {}
+            Err(err) => error!(
+                "Failed to start the remote server on {:?}: {err}",
+                self.transport
+            ),
+        }
+
+        app.insert_resource(methods)
+            .insert_resource(RemoteRequests { sender, receiver })
+            .add_systems(Last, (process_remote_requests, stop_remote_server).chain());
+    }
+}
+
+/// The methods available to remote clients, by name.
+#[derive(Resource, Default)]
+pub struct RemoteMethods(HashMap<String, RemoteMethod>);
+
+impl RemoteMethods {
+    /// Adds a method, returning the method it replaced if any.
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        method: RemoteMethod,
+    ) -> Option<RemoteMethod> {
+        self.0.insert(name.into(), method)
+    }
+
+    /// Returns the method with this name.
+    pub fn get(&self, name: &str) -> Option<RemoteMethod> {
+        self.0.get(name).copied()
+    }
+}
+
+/// A request waiting to be processed, along with the channel its response is sent to.
+pub struct BrpMessage {
+    /// The request.
+    pub request: BrpRequest,
+    /// The channel receiving the response.
+    pub sender: Sender<BrpResponse>,
+}
+
+/// The queue of requests processed by [`process_remote_requests`].
+#[derive(Resource)]
+pub struct RemoteRequests {
+    sender: Sender<BrpMessage>,
+    receiver: Receiver<BrpMessage>,
+}
+
+impl RemoteRequests {
+    /// Returns a channel to send requests through, for custom transports.
+    pub fn sender(&self) -> Sender<BrpMessage> {
+        self.sender.clone()
+    }
+}
+
+/// A JSON-RPC request.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrpRequest {
+    /// The protocol version: always `"2.0"`.
+    pub jsonrpc: String,
+    /// The name of the method to call.
+    pub method: String,
+    /// The identifier of this request, echoed in its response. Requests without one are
+    /// notifications, which don't get a response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    /// The parameters of the method.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+/// A JSON-RPC response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrpResponse {
+    /// The protocol version: always `"2.0"`.
+    pub jsonrpc: String,
+    /// The identifier of the request, or `null` if it couldn't be read.
+    pub id: Option<Value>,
+    /// The result or error of the request.
+    #[serde(flatten)]
+    pub payload: BrpPayload,
+}
+
+impl BrpResponse {
+    /// Creates a response to the request with this `id`.
+    pub fn new(id: Option<Value>, result: BrpResult) -> Self {
+        Self {
+            jsonrpc: "2.0".to_owned(),
+            id,
+            payload: match result {
+                Ok(result) => BrpPayload::Result(result),
+                Err(error) => BrpPayload::Error(error),
+            },
+        }
+    }
+}
+
+/// The outcome of a request.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrpPayload {
+    /// The request succeeded.
+    Result(Value),
+    /// The request failed.
+    Error(BrpError),
+}
+
+/// A JSON-RPC error.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BrpError {
+    /// The kind of error: one of the [`error_codes`].
+    pub code: i16,
+    /// A description of the error.
+    pub message: String,
+    /// Additional information about the error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl BrpError {
+    /// Creates an error without additional data.
+    pub fn new(code: i16, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// The parameters of the request are missing or malformed.
+    pub fn invalid_params(message: impl std::fmt::Display) -> Self {
+        Self::new(
+            error_codes::INVALID_PARAMS,
+            format!("Invalid params: {message}"),
+        )
+    }
+
+    /// The entity doesn't exist.
+    pub fn entity_not_found(entity: Entity) -> Self {
+        Self::new(
+            error_codes::ENTITY_NOT_FOUND,
+            format!("Entity {entity:?} does not exist"),
+        )
+    }
+
+    /// The component can't be accessed.
+    pub fn component_error(message: impl Into<String>) -> Self {
+        Self::new(error_codes::COMPONENT_ERROR, message)
+    }
+}
+
+/// The `code` of a [`BrpError`].
+pub mod error_codes {
+    /// The request isn't valid JSON.
+    pub const PARSE_ERROR: i16 = -32700;
+    /// The request isn't a valid JSON-RPC request.
+    pub const INVALID_REQUEST: i16 = -32600;
+    /// The method doesn't exist.
+    pub const METHOD_NOT_FOUND: i16 = -32601;
+    /// The parameters of the method are invalid.
+    pub const INVALID_PARAMS: i16 = -32602;
+    /// The entity doesn't exist.
+    pub const ENTITY_NOT_FOUND: i16 = -23401;
+    /// The component type is unknown, isn't a reflected component, is missing from the entity,
+    /// or its value couldn't be deserialized.
+    pub const COMPONENT_ERROR: i16 = -23402;
+}
+
+/// Stops the server from accepting connections when the app exits.
+fn stop_remote_server(
+    mut exit: EventReader<AppExit>,
+    server: Option<ResMut<server::RemoteServer>>,
+) {
+    if exit.read().next().is_some() {
+        if let Some(mut server) = server {
+            server.stop();
+        }
+    }
+}
+
+/// Processes all the requests received since the last run, sending their responses.
+pub fn process_remote_requests(world: &mut World) {
+    let Some(receiver) = world
+        .get_resource::<RemoteRequests>()
+        .map(|requests| requests.receiver.clone())
+    else {
+        return;
+    };
+    while let Ok(BrpMessage { request, sender }) = receiver.try_recv() {
+        let result = process_request(world, &request);
+        // the client may have disconnected since it sent the request
+        let _ = sender.send(BrpResponse::new(request.id, result));
+    }
+}
+
+fn process_request(world: &mut World, request: &BrpRequest) -> BrpResult {
+    if request.jsonrpc != "2.0" {
+        return Err(BrpError::new(
+            error_codes::INVALID_REQUEST,
+            "Only JSON-RPC 2.0 is supported",
+        ));
+    }
+    let method = world
+        .get_resource::<RemoteMethods>()
+        .and_then(|methods| methods.get(&request.method))
+        .ok_or_else(|| {
+            BrpError::new(
+                error_codes::METHOD_NOT_FOUND,
+                format!("Method {} not found", request.method),
+            )
+        })?;
+    method(request.params.clone(), world)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::App;
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn send(app: &mut App, method: &str, params: Option<Value>) -> BrpResponse {
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let request = BrpRequest {
+            jsonrpc: "2.0".to_owned(),
+            method: method.to_owned(),
+            id: Some(json!(1)),
+            params,
+        };
+        app.world
+            .resource::<RemoteRequests>()
+            .sender()
+            .send(BrpMessage { request, sender })
+            .unwrap();
+        app.update();
+        receiver.try_recv().unwrap()
+    }
+
+    #[test]
+    fn process_requests() {
+        let mut app = App::new();
+        app.add_plugins(
+            RemotePlugin::default()
+                .with_transport(RemoteTransport::None)
+                .with_method("test/ping", |_, _| Ok(json!("pong"))),
+        );
+
+        let response = send(&mut app, "test/ping", None);
+        assert_eq!(response.id, Some(json!(1)));
+        assert_eq!(response.payload, BrpPayload::Result(json!("pong")));
+
+        let response = send(&mut app, "bevy/spawn", Some(json!({ "components": {} })));
+        let BrpPayload::Result(result) = response.payload else {
+            panic!("spawn failed: {:?}", response.payload);
+        };
+        let entity = serde_json::from_value::<Entity>(result["entity"].clone()).unwrap();
+        assert!(app.world.get_entity(entity).is_some());
+
+        let response = send(&mut app, "test/unknown", None);
+        let BrpPayload::Error(error) = response.payload else {
+            panic!("unknown method succeeded");
+        };
+        assert_eq!(error.code, error_codes::METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn response_format() {
+        let response = BrpResponse::new(
+            Some(json!(3)),
+            Err(BrpError::new(error_codes::INVALID_REQUEST, "bad")),
+        );
+        assert_eq!(
+            serde_json::to_value(response).unwrap(),
+            json!({ "jsonrpc": "2.0", "id": 3, "error": { "code": -32600, "message": "bad" } })
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use bevy_ecs::system::Resource;
+use bevy_log::{debug, warn};
+use crossbeam_channel::Sender;
+use serde_json::Value;
+
+use crate::{error_codes, BrpError, BrpMessage, BrpRequest, BrpResponse, RemoteTransport};
+
+/// The server accepting connections in the background, stopped when the app exits or when this
+/// is dropped.
+#[derive(Resource)]
+pub(crate) struct RemoteServer {
+    stopped: Arc<AtomicBool>,
+    address: ServerAddress,
+}
+
+/// Where a [`RemoteServer`] listens.
+enum ServerAddress {
+    Tcp(SocketAddr),
+    /// The socket file, which is removed when the server stops.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl RemoteServer {
+    /// Stops accepting connections. The connections already accepted stay open until their client
+    /// disconnects or the app stops processing requests.
+    pub(crate) fn stop(&mut self) {
+        if self.stopped.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        // Wakes up the listener blocked on `accept`, which then sees that it's stopped
+        match &self.address {
+            ServerAddress::Tcp(address) => {
+                TcpStream::connect(address).ok();
+            }
+            #[cfg(unix)]
+            ServerAddress::Unix(path) => {
+                std::os::unix::net::UnixStream::connect(path).ok();
+                std::fs::remove_file(path).ok();
+            }
+        }
+    }
+}
+
+impl Drop for RemoteServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Starts accepting connections on `transport` in a background thread, forwarding the requests
+/// to `sender`.
+///
+/// Returns the running server, if `transport` isn't [`RemoteTransport::None`].
+pub(crate) fn start_server(
+    transport: &RemoteTransport,
+    sender: Sender<BrpMessage>,
+) -> io::Result<Option<RemoteServer>> {
+    let stopped = Arc::new(AtomicBool::new(false));
+    let address = match transport {
+        RemoteTransport::Tcp(address) => {
+            let listener = TcpListener::bind(address)?;
+            let mut address = listener.local_addr()?;
+            // The listener is woken up through a connection, which needs a specific address
+            if address.ip().is_unspecified() {
+                address.set_ip(match address.ip() {
+                    IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                    IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+                });
+            }
+            spawn_listener(
+                std::iter::from_fn(move || Some(listener.accept().map(|(stream, _)| stream))),
+                stopped.clone(),
+                sender,
+                |stream| Ok((stream.try_clone()?, stream)),
+            );
+            ServerAddress::Tcp(address)
+        }
+        #[cfg(unix)]
+        RemoteTransport::Unix(path) => {
+            use std::os::unix::fs::FileTypeExt;
+
+            // A socket left behind by an app that didn't shut down cleanly
+            if std::fs::symlink_metadata(path)
+                .is_ok_and(|metadata| metadata.file_type().is_socket())
+            {
+                std::fs::remove_file(path)?;
+            }
+            let listener = std::os::unix::net::UnixListener::bind(path)?;
+            spawn_listener(
+                std::iter::from_fn(move || Some(listener.accept().map(|(stream, _)| stream))),
+                stopped.clone(),
+                sender,
+                |stream| Ok((stream.try_clone()?, stream)),
+            );
+            ServerAddress::Unix(path.clone())
+        }
+        RemoteTransport::None => return Ok(None),
+    };
+    Ok(Some(RemoteServer { stopped, address }))
+}
+
+/// Accepts connections from `incoming` in a background thread until `stopped` is set, handling
+/// each connection in its own thread once `split` into its read and write halves.
+fn spawn_listener<S, R, W>(
+    incoming: impl Iterator<Item = io::Result<S>> + Send + 'static,
+    stopped: Arc<AtomicBool>,
+    sender: Sender<BrpMessage>,
+    split: fn(S) -> io::Result<(R, W)>,
+) where
+    S: Send + 'static,
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    thread::spawn(move || {
+        for stream in incoming {
+            if stopped.load(Ordering::Relaxed) {
+                break;
+            }
+            let connection = match stream.and_then(split) {
+                Ok(connection) => connection,
+                Err(err) => {
+                    warn!("Failed to accept a remote connection: {err}");
+                    continue;
+                }
+            };
+            let sender = sender.clone();
+            thread::spawn(move || {
+                let (reader, writer) = connection;
+                if let Err(err) = handle_connection(reader, writer, &sender) {
+                    debug!("Remote connection closed: {err}");
+                }
+            });
+        }
+    });
+}
+
+/// Reads one request per line, writing one response per line, until the client disconnects or the
+/// app stops processing requests.
+fn handle_connection(
+    reader: impl Read,
+    mut writer: impl Write,
+    sender: &Sender<BrpMessage>,
+) -> io::Result<()> {
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match parse_request(&line) {
+            Ok(request) => {
+                let id = request.id.clone();
+                let (response_sender, response_receiver) = crossbeam_channel::bounded(1);
+                let message = BrpMessage {
+                    request,
+                    sender: response_sender,
+                };
+                if sender.send(message).is_err() {
+                    return Ok(());
+                }
+                let Ok(response) = response_receiver.recv() else {
+                    return Ok(());
+                };
+                // notifications don't get a response
+                if id.is_none() {
+                    continue;
+                }
+                response
+            }
+            Err(error) => BrpResponse::new(None, Err(error)),
+        };
+        serde_json::to_writer(&mut writer, &response)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+fn parse_request(line: &str) -> Result<BrpRequest, BrpError> {
+    let value: Value = serde_json::from_str(line)
+        .map_err(|err| BrpError::new(error_codes::PARSE_ERROR, err.to_string()))?;
+    serde_json::from_value(value)
+        .map_err(|err| BrpError::new(error_codes::INVALID_REQUEST, err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn tcp_listener_stops() {
+        let address = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let mut server = start_server(&RemoteTransport::Tcp(address), sender)
+            .unwrap()
+            .unwrap();
+        let ServerAddress::Tcp(address) = server.address else {
+            unreachable!();
+        };
+        TcpStream::connect(address).unwrap();
+
+        server.stop();
+        // The listener is closed once its thread sees that it's stopped
+        let closed = (0..100).any(|_| {
+            thread::sleep(Duration::from_millis(10));
+            TcpStream::connect(address).is_err()
+        });
+        assert!(closed);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_replaces_stale_socket() {
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        let path = std::env::temp_dir().join(format!("bevy_remote_{}.sock", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        // Dropping a listener leaves its socket file behind
+        drop(UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let server = start_server(&RemoteTransport::Unix(path.clone()), sender)
+            .unwrap()
+            .unwrap();
+        UnixStream::connect(&path).unwrap();
+
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_keeps_other_files() {
+        let path = std::env::temp_dir().join(format!("bevy_remote_{}.txt", std::process::id()));
+        std::fs::write(&path, "not a socket").unwrap();
+
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        assert!(start_server(&RemoteTransport::Unix(path.clone()), sender).is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not a socket");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
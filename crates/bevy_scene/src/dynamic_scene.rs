@@ -4,7 +4,7 @@ use bevy_ecs::{
     reflect::{AppTypeRegistry, ReflectComponent, ReflectMapEntities},
     world::World,
 };
-use bevy_reflect::{Reflect, TypePath, TypeRegistryArc};
+use bevy_reflect::{Reflect, ReflectFromReflect, TypePath, TypeRegistryArc};
 use bevy_utils::{EntityHashMap, HashMap};
 use std::any::TypeId;
 
@@ -92,20 +92,24 @@ impl DynamicScene {
             reflect_resource.apply_or_insert(world, &**resource);
         }
 
-        // For each component types that reference other entities, we keep track
-        // of which entities in the scene use that component.
+        // For each component types that reference other entities and couldn't be mapped before
+        // being inserted, we keep track of which entities in the scene use that component.
         // This is so we can update the scene-internal references to references
         // of the actual entities in the world.
         let mut scene_mappings: HashMap<TypeId, Vec<Entity>> = HashMap::default();
 
+        // Fetch the entity with the given entity id from the `entity_map`
+        // or spawn a new entity with a transiently unique id if there is
+        // no corresponding entry. This is done for all entities first, so that components
+        // can be mapped to any of them before being inserted.
         for scene_entity in &self.entities {
-            // Fetch the entity with the given entity id from the `entity_map`
-            // or spawn a new entity with a transiently unique id if there is
-            // no corresponding entry.
-            let entity = *entity_map
+            entity_map
                 .entry(scene_entity.entity)
                 .or_insert_with(|| world.spawn_empty().id());
-            let entity_mut = &mut world.entity_mut(entity);
+        }
+
+        for scene_entity in &self.entities {
+            let entity = entity_map[&scene_entity.entity];
 
             // Apply/ add each component to the given entity.
             for component in &scene_entity.components {
@@ -126,19 +130,33 @@ impl DynamicScene {
                         }
                     })?;
 
-                // If this component references entities in the scene, track it
-                // so we can update it to the entity in the world.
-                if registration.data::<ReflectMapEntities>().is_some() {
-                    scene_mappings
-                        .entry(registration.type_id())
-                        .or_insert(Vec::new())
-                        .push(entity);
+                // If this component references entities in the scene, map them to the entities
+                // in the world before inserting it, so that its hooks see the right entities.
+                // Otherwise, track it to update it once all components are inserted.
+                let mut mapped = None;
+                if let Some(map_entities_reflect) = registration.data::<ReflectMapEntities>() {
+                    mapped = registration
+                        .data::<ReflectFromReflect>()
+                        .and_then(|from_reflect| from_reflect.from_reflect(&**component))
+                        .and_then(|mut value| {
+                            map_entities_reflect
+                                .map_reflected(&mut *value, world, entity_map)
+                                .then_some(value)
+                        });
+                    if mapped.is_none() {
+                        scene_mappings
+                            .entry(registration.type_id())
+                            .or_insert(Vec::new())
+                            .push(entity);
+                    }
                 }
 
                 // If the entity already has the given component attached,
                 // just apply the (possibly) new value, otherwise add the
                 // component to the entity.
-                reflect_component.apply_or_insert(entity_mut, &**component);
+                let entity_mut = &mut world.entity_mut(entity);
+                reflect_component
+                    .apply_or_insert(entity_mut, mapped.as_deref().unwrap_or(&**component));
             }
         }
 
@@ -192,7 +210,7 @@ where
 #[cfg(test)]
 mod tests {
     use bevy_ecs::{reflect::AppTypeRegistry, system::Command, world::World};
-    use bevy_hierarchy::{AddChild, Parent};
+    use bevy_hierarchy::{AddChild, Children, Parent};
     use bevy_utils::EntityHashMap;
 
     use crate::dynamic_scene_builder::DynamicSceneBuilder;
@@ -272,5 +290,15 @@ mod tests {
                 .get(),
             "something is wrong with the this test or the code reloading scenes since the relationship between scene entities is broken"
         );
+        assert_eq!(
+            &[original_child_entity][..],
+            &**world.get::<Children>(original_parent_entity).unwrap(),
+            "scene entities should be added to the children of their mapped parent"
+        );
+        assert_eq!(
+            &[from_scene_child_entity][..],
+            &**world.get::<Children>(from_scene_parent_entity).unwrap(),
+            "scene entities should be added to the children of their mapped parent"
+        );
     }
 }
@@ -0,0 +1,385 @@
+//! A headless [`App`] harness for integration tests.
+//!
+//! [`TestApp`] builds an app with the minimal plugins, input handling and a stand-in primary
+//! window, without any runner: tests step frames explicitly with a fixed frame time, inject
+//! input, and then assert on the [`World`](bevy_ecs::world::World).
+//!
+//! ```
+//! # use bevy_ecs::prelude::*;
+//! # use bevy_input::prelude::*;
+//! # use bevy_test_harness::{InputScript, ScriptedInput, TestApp};
+//! #[derive(Resource, Default)]
+//! struct Jumps(u32);
+//!
+//! fn jump(keys: Res<Input<KeyCode>>, mut jumps: ResMut<Jumps>) {
+//!     if keys.just_pressed(KeyCode::Space) {
+//!         jumps.0 += 1;
+//!     }
+//! }
+//!
+//! let mut app = TestApp::new();
+//! app.init_resource::<Jumps>().add_systems(bevy_app::Update, jump);
+//!
+//! app.run_script(
+//!     &InputScript::new()
+//!         .at_frame(0, ScriptedInput::KeyPress(KeyCode::Space))
+//!         .at_frame(1, ScriptedInput::KeyRelease(KeyCode::Space))
+//!         .at_frame(2, ScriptedInput::KeyPress(KeyCode::Space)),
+//! );
+//! assert_eq!(app.world.resource::<Jumps>().0, 2);
+//! ```
+//!
+//! Input can also be recorded from a running app with the [`InputRecorderPlugin`], and replayed
+//! with [`TestApp::play_recording`] to reproduce a bug deterministically.
+
+mod recording;
+mod script;
+
+pub use recording::*;
+pub use script::*;
+
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+use bevy_app::App;
+use bevy_core::{FrameCount, FrameCountPlugin, TaskPoolPlugin, TypeRegistrationPlugin};
+use bevy_ecs::{entity::Entity, query::With};
+use bevy_input::InputPlugin;
+use bevy_time::{TimePlugin, TimeUpdateStrategy};
+use bevy_window::{ExitCondition, PrimaryWindow, Window, WindowPlugin};
+
+/// The time each frame of a [`TestApp`] lasts by default: 1/60th of a second.
+pub const DEFAULT_FRAME_TIME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// A headless [`App`] for integration tests, stepped manually.
+///
+/// It contains the [`TaskPoolPlugin`], [`TypeRegistrationPlugin`], [`FrameCountPlugin`],
+/// [`TimePlugin`], [`InputPlugin`] and a [`WindowPlugin`] spawning a primary [`Window`] entity,
+/// which isn't backed by an actual window. [`Time`](bevy_time::Time) advances by a fixed frame
+/// time on each update, except for the first one which has a zero delta, like in any other app.
+///
+/// It dereferences to the [`App`], to add plugins and systems or access the world.
+pub struct TestApp {
+    app: App,
+    primary_window: Entity,
+}
+
+impl Default for TestApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestApp {
+    /// Creates an app, with a frame time of [`DEFAULT_FRAME_TIME`].
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            TaskPoolPlugin::default(),
+            TypeRegistrationPlugin,
+            FrameCountPlugin,
+            TimePlugin,
+            InputPlugin,
+            WindowPlugin {
+                primary_window: Some(Window::default()),
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            },
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(DEFAULT_FRAME_TIME));
+
+        let primary_window = app
+            .world
+            .query_filtered::<Entity, With<PrimaryWindow>>()
+            .single(&app.world);
+        Self {
+            app,
+            primary_window,
+        }
+    }
+
+    /// Sets the time each frame lasts.
+    pub fn with_frame_time(mut self, frame_time: Duration) -> Self {
+        self.app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(frame_time));
+        self
+    }
+
+    /// Returns the stand-in primary window entity, which receives the injected input.
+    pub fn primary_window(&self) -> Entity {
+        self.primary_window
+    }
+
+    /// Returns the number of frames run so far.
+    pub fn frame_count(&self) -> u32 {
+        self.app.world.resource::<FrameCount>().0
+    }
+
+    /// Runs `frames` frames.
+    pub fn step(&mut self, frames: u32) -> &mut Self {
+        for _ in 0..frames {
+            self.app.update();
+        }
+        self
+    }
+
+    /// Sends the event corresponding to `input`, which is processed during the next frame.
+    pub fn send_input(&mut self, input: &ScriptedInput) -> &mut Self {
+        input.send(&mut self.app.world, self.primary_window);
+        self
+    }
+
+    /// Runs the frames of `script`, sending its inputs at the start of their frame.
+    pub fn run_script(&mut self, script: &InputScript) -> &mut Self {
+        for frame in 0..script.len_frames() {
+            for input in script.inputs_at(frame) {
+                input.send(&mut self.app.world, self.primary_window);
+            }
+            self.app.update();
+        }
+        self
+    }
+
+    /// Replays the frames of `recording`, sending their inputs at the start of their frame and
+    /// advancing time by their recorded delta.
+    ///
+    /// The frame time of the app is restored afterwards.
+    pub fn play_recording(&mut self, recording: &InputRecording) -> &mut Self {
+        let update_strategy = self.app.world.remove_resource::<TimeUpdateStrategy>();
+        for frame in &recording.frames {
+            self.app
+                .insert_resource(TimeUpdateStrategy::ManualDuration(frame.delta));
+            for input in &frame.inputs {
+                input.send(&mut self.app.world, self.primary_window);
+            }
+            self.app.update();
+        }
+        if let Some(update_strategy) = update_strategy {
+            self.app.insert_resource(update_strategy);
+        }
+        self
+    }
+
+    /// Returns the wrapped [`App`].
+    pub fn into_app(self) -> App {
+        self.app
+    }
+}
+
+impl Deref for TestApp {
+    type Target = App;
+
+    fn deref(&self) -> &Self::Target {
+        &self.app
+    }
+}
+
+impl DerefMut for TestApp {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.app
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy_app::PreUpdate;
+    use bevy_app::Update;
+    use bevy_core::FrameCount;
+    use bevy_ecs::system::{Res, ResMut, Resource};
+    use bevy_ecs::{event::EventWriter, schedule::IntoSystemConfigs, system::Local};
+    use bevy_input::{
+        gamepad::{
+            Gamepad, GamepadButton, GamepadButtonChangedEvent, GamepadButtonType,
+            GamepadConnection, GamepadConnectionEvent, GamepadEvent, GamepadInfo, Gamepads,
+        },
+        prelude::*,
+        InputEventSystem,
+    };
+    use bevy_time::Time;
+
+    use super::*;
+
+    #[test]
+    fn step_with_fixed_frame_time() {
+        let mut app = TestApp::new().with_frame_time(Duration::from_millis(100));
+        app.step(5);
+        assert_eq!(app.frame_count(), 5);
+        let time = app.world.resource::<Time>();
+        assert_eq!(time.delta(), Duration::from_millis(100));
+        // the first frame has a zero delta
+        assert_eq!(time.elapsed(), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn scripted_keyboard_and_mouse_input() {
+        let mut app = TestApp::new();
+        let script = InputScript::new()
+            .at_frame(0, ScriptedInput::KeyPress(KeyCode::A))
+            .at_frame(0, ScriptedInput::MousePress(MouseButton::Left))
+            .at_frame(2, ScriptedInput::KeyRelease(KeyCode::A));
+
+        app.run_script(&script);
+        assert_eq!(app.frame_count(), 3);
+        let keys = app.world.resource::<Input<KeyCode>>();
+        assert!(!keys.pressed(KeyCode::A));
+        assert!(keys.just_released(KeyCode::A));
+        assert!(app
+            .world
+            .resource::<Input<MouseButton>>()
+            .pressed(MouseButton::Left));
+
+        app.send_input(&ScriptedInput::MouseRelease(MouseButton::Left))
+            .step(1);
+        assert!(!app
+            .world
+            .resource::<Input<MouseButton>>()
+            .pressed(MouseButton::Left));
+    }
+
+    #[test]
+    fn scripted_gamepad_input() {
+        let mut app = TestApp::new();
+        let script = InputScript::from_ron(
+            "(events: [
+                (frame: 0, input: Gamepad(Connection((
+                    gamepad: (id: 0),
+                    connection: Connected((name: \"Test\")),
+                )))),
+                (frame: 1, input: Gamepad(Button((
+                    gamepad: (id: 0),
+                    button_type: South,
+                    value: 1.0,
+                )))),
+            ])",
+        )
+        .unwrap();
+
+        app.run_script(&script);
+        let gamepad = Gamepad::new(0);
+        assert!(app.world.resource::<Gamepads>().contains(gamepad));
+        assert!(app
+            .world
+            .resource::<Input<GamepadButton>>()
+            .pressed(GamepadButton::new(gamepad, GamepadButtonType::South)));
+    }
+
+    #[derive(Resource, Default, Debug, PartialEq)]
+    struct Jumps(Vec<Duration>);
+
+    fn jump(keys: Res<Input<KeyCode>>, time: Res<Time>, mut jumps: ResMut<Jumps>) {
+        if keys.just_pressed(KeyCode::Space) {
+            jumps.0.push(time.elapsed());
+        }
+    }
+
+    #[test]
+    fn record_and_play() {
+        let mut app = TestApp::new().with_frame_time(Duration::from_millis(10));
+        app.add_plugins(InputRecorderPlugin::default())
+            .init_resource::<Jumps>()
+            .add_systems(Update, jump);
+        let script = InputScript::new()
+            .at_frame(1, ScriptedInput::KeyPress(KeyCode::Space))
+            .at_frame(2, ScriptedInput::KeyRelease(KeyCode::Space))
+            .at_frame(2, ScriptedInput::MousePress(MouseButton::Left));
+        app.run_script(&script);
+        let mut app = app.with_frame_time(Duration::from_millis(25));
+        app.send_input(&ScriptedInput::KeyPress(KeyCode::Space))
+            .step(2);
+
+        let recording = &app.world.resource::<InputRecorder>().recording;
+        assert_eq!(recording.frames.len(), 5);
+        assert_eq!(recording.frames[3].delta, Duration::from_millis(25));
+        let recording = InputRecording::from_bytes(&recording.to_bytes().unwrap()).unwrap();
+
+        let mut replay = TestApp::new();
+        replay.init_resource::<Jumps>().add_systems(Update, jump);
+        replay.play_recording(&recording);
+        assert_eq!(
+            replay.world.resource::<Jumps>(),
+            app.world.resource::<Jumps>()
+        );
+        assert_eq!(
+            replay.world.resource::<Jumps>().0,
+            [Duration::from_millis(10), Duration::from_millis(45)]
+        );
+        assert!(replay
+            .world
+            .resource::<Input<MouseButton>>()
+            .pressed(MouseButton::Left));
+    }
+
+    /// Sends gamepad events during `PreUpdate` like `bevy_gilrs`: a connection on the first
+    /// frame, then a press of the south button on the third frame.
+    fn fake_gilrs(mut events: EventWriter<GamepadEvent>, mut frame: Local<u32>) {
+        let gamepad = Gamepad::new(0);
+        match *frame {
+            0 => {
+                events.send(GamepadEvent::Connection(GamepadConnectionEvent::new(
+                    gamepad,
+                    GamepadConnection::Connected(GamepadInfo {
+                        name: "Test".to_string(),
+                    }),
+                )));
+            }
+            2 => {
+                events.send(GamepadEvent::Button(GamepadButtonChangedEvent::new(
+                    gamepad,
+                    GamepadButtonType::South,
+                    1.0,
+                )));
+            }
+            _ => {}
+        }
+        *frame += 1;
+    }
+
+    #[derive(Resource, Default, Debug, PartialEq)]
+    struct Presses(Vec<u32>);
+
+    fn south_presses(
+        buttons: Res<Input<GamepadButton>>,
+        frame: Res<FrameCount>,
+        mut presses: ResMut<Presses>,
+    ) {
+        if buttons.just_pressed(GamepadButton::new(
+            Gamepad::new(0),
+            GamepadButtonType::South,
+        )) {
+            presses.0.push(frame.0);
+        }
+    }
+
+    #[test]
+    fn record_and_play_gamepad() {
+        let mut app = TestApp::new();
+        app.add_plugins(InputRecorderPlugin::default())
+            .init_resource::<Presses>()
+            .add_systems(PreUpdate, fake_gilrs.in_set(InputEventSystem))
+            .add_systems(Update, south_presses);
+        app.step(4);
+        assert_eq!(app.world.resource::<Presses>().0, [2]);
+
+        let recording = &app.world.resource::<InputRecorder>().recording;
+        assert!(matches!(
+            recording.frames[0].inputs[..],
+            [RecordedInput::Gamepad(GamepadEvent::Connection(_))]
+        ));
+        assert!(matches!(
+            recording.frames[2].inputs[..],
+            [RecordedInput::Gamepad(GamepadEvent::Button(_))]
+        ));
+
+        let mut replay = TestApp::new();
+        replay
+            .init_resource::<Presses>()
+            .add_systems(Update, south_presses);
+        replay.play_recording(recording);
+        assert_eq!(
+            replay.world.resource::<Presses>(),
+            app.world.resource::<Presses>()
+        );
+    }
+}
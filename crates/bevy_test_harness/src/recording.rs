@@ -0,0 +1,277 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy_app::{App, AppExit, Last, Plugin, PreUpdate};
+use bevy_ecs::{prelude::*, system::SystemParam};
+use bevy_input::{
+    gamepad::{GamepadConnectionEvent, GamepadEvent},
+    keyboard::KeyboardInput,
+    mouse::{MouseButtonInput, MouseMotion, MouseWheel},
+    touch::TouchInput,
+    touchpad::{TouchpadMagnify, TouchpadRotate},
+    InputEventSystem, InputSystem,
+};
+use bevy_time::{Real, Time};
+use bevy_utils::tracing::{error, info};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// An input event recorded by the [`InputRecorderPlugin`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum RecordedInput {
+    /// A [`KeyboardInput`] event.
+    Keyboard(KeyboardInput),
+    /// A [`MouseButtonInput`] event.
+    MouseButton(MouseButtonInput),
+    /// A [`MouseMotion`] event.
+    MouseMotion(MouseMotion),
+    /// A [`MouseWheel`] event.
+    MouseWheel(MouseWheel),
+    /// A [`GamepadEvent`].
+    Gamepad(GamepadEvent),
+    /// A [`TouchInput`] event.
+    Touch(TouchInput),
+    /// A [`TouchpadMagnify`] event.
+    TouchpadMagnify(TouchpadMagnify),
+    /// A [`TouchpadRotate`] event.
+    TouchpadRotate(TouchpadRotate),
+}
+
+impl RecordedInput {
+    /// Sends this event, as received by `window` for window events.
+    pub fn send(&self, world: &mut World, window: Entity) {
+        match self.clone() {
+            RecordedInput::Keyboard(event) => {
+                world.send_event(KeyboardInput { window, ..event });
+            }
+            RecordedInput::MouseButton(event) => {
+                world.send_event(MouseButtonInput { window, ..event });
+            }
+            RecordedInput::MouseMotion(event) => {
+                world.send_event(event);
+            }
+            RecordedInput::MouseWheel(event) => {
+                world.send_event(MouseWheel { window, ..event });
+            }
+            RecordedInput::Gamepad(event) => {
+                world.send_event(event);
+            }
+            RecordedInput::Touch(event) => {
+                world.send_event(event);
+            }
+            RecordedInput::TouchpadMagnify(event) => {
+                world.send_event(event);
+            }
+            RecordedInput::TouchpadRotate(event) => {
+                world.send_event(event);
+            }
+        }
+    }
+}
+
+/// The inputs of a single recorded frame.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RecordedFrame {
+    /// The real time elapsed since the previous frame.
+    pub delta: Duration,
+    /// The input events processed during the frame, in order for each event type.
+    pub inputs: Vec<RecordedInput>,
+}
+
+/// An error that occurs when saving or loading an [`InputRecording`].
+#[derive(Error, Debug)]
+pub enum InputRecordingError {
+    /// The file couldn't be read or written.
+    #[error("could not access the input recording file: {0}")]
+    Io(#[from] std::io::Error),
+    /// The recording couldn't be encoded or decoded.
+    #[error("invalid input recording: {0}")]
+    Encoding(#[from] bincode::Error),
+}
+
+/// The input events and frame times of an app, recorded by the [`InputRecorderPlugin`] and
+/// replayed by [`TestApp::play_recording`](crate::TestApp::play_recording).
+///
+/// Recordings are stored in a compact binary format.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct InputRecording {
+    /// The recorded frames, starting with the first frame of the app.
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    /// Encodes the recording.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, InputRecordingError> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// Decodes a recording encoded with [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, InputRecordingError> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+
+    /// Writes the recording to the file at `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), InputRecordingError> {
+        fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Reads a recording from the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InputRecordingError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+
+/// The recording made by the [`InputRecorderPlugin`].
+#[derive(Resource, Debug, Default)]
+pub struct InputRecorder {
+    /// The frames recorded so far.
+    pub recording: InputRecording,
+    /// Whether recording is paused. Frames aren't recorded at all while paused, so a recording
+    /// with pauses doesn't replay like the original app.
+    pub paused: bool,
+}
+
+/// Records the input events processed by `bevy_input` and the real frame time of each frame
+/// into the [`InputRecorder`] resource.
+///
+/// The recording can be saved with [`InputRecording::save`], or automatically when the app exits
+/// with [`save_on_exit`](Self::save_on_exit), then replayed headlessly by a
+/// [`TestApp`](crate::TestApp) to reproduce a bug or as a regression test.
+///
+/// Gamepads connected when the app starts are recorded as connection events of the first frame.
+/// Input events sent during [`PreUpdate`] must be sent in the [`InputEventSystem`] set to be
+/// recorded on the frame they are sent, as the gamepad events of `bevy_gilrs` are.
+#[derive(Default)]
+pub struct InputRecorderPlugin {
+    /// The file the recording is written to when the app exits, if any.
+    pub save_on_exit: Option<PathBuf>,
+}
+
+impl InputRecorderPlugin {
+    /// Writes the recording to the file at `path` when the app exits.
+    pub fn save_on_exit(path: impl Into<PathBuf>) -> Self {
+        Self {
+            save_on_exit: Some(path.into()),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct SaveOnExit(PathBuf);
+
+impl Plugin for InputRecorderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputRecorder>().add_systems(
+            PreUpdate,
+            record_inputs.after(InputEventSystem).before(InputSystem),
+        );
+
+        if let Some(path) = &self.save_on_exit {
+            app.insert_resource(SaveOnExit(path.clone()))
+                .add_systems(Last, save_recording_on_exit);
+        }
+    }
+}
+
+#[derive(SystemParam)]
+struct InputEvents<'w, 's> {
+    keyboard: EventReader<'w, 's, KeyboardInput>,
+    mouse_button: EventReader<'w, 's, MouseButtonInput>,
+    mouse_motion: EventReader<'w, 's, MouseMotion>,
+    mouse_wheel: EventReader<'w, 's, MouseWheel>,
+    gamepad: EventReader<'w, 's, GamepadEvent>,
+    gamepad_connection: EventReader<'w, 's, GamepadConnectionEvent>,
+    touch: EventReader<'w, 's, TouchInput>,
+    touchpad_magnify: EventReader<'w, 's, TouchpadMagnify>,
+    touchpad_rotate: EventReader<'w, 's, TouchpadRotate>,
+}
+
+fn record_inputs(
+    mut recorder: ResMut<InputRecorder>,
+    time: Res<Time<Real>>,
+    mut events: InputEvents,
+    mut started: Local<bool>,
+) {
+    let mut inputs = Vec::new();
+    // Connection events are sent by `gamepad_event_system` from the recorded `GamepadEvent`s,
+    // except for the gamepads connected at startup.
+    if *started {
+        events.gamepad_connection.clear();
+    } else {
+        *started = true;
+        inputs.extend(
+            events
+                .gamepad_connection
+                .read()
+                .cloned()
+                .map(|event| RecordedInput::Gamepad(GamepadEvent::Connection(event))),
+        );
+    }
+    inputs.extend(events.keyboard.read().cloned().map(RecordedInput::Keyboard));
+    inputs.extend(
+        events
+            .mouse_button
+            .read()
+            .cloned()
+            .map(RecordedInput::MouseButton),
+    );
+    inputs.extend(
+        events
+            .mouse_motion
+            .read()
+            .cloned()
+            .map(RecordedInput::MouseMotion),
+    );
+    inputs.extend(
+        events
+            .mouse_wheel
+            .read()
+            .cloned()
+            .map(RecordedInput::MouseWheel),
+    );
+    inputs.extend(events.gamepad.read().cloned().map(RecordedInput::Gamepad));
+    inputs.extend(events.touch.read().cloned().map(RecordedInput::Touch));
+    inputs.extend(
+        events
+            .touchpad_magnify
+            .read()
+            .cloned()
+            .map(RecordedInput::TouchpadMagnify),
+    );
+    inputs.extend(
+        events
+            .touchpad_rotate
+            .read()
+            .cloned()
+            .map(RecordedInput::TouchpadRotate),
+    );
+
+    if !recorder.paused {
+        recorder.recording.frames.push(RecordedFrame {
+            delta: time.delta(),
+            inputs,
+        });
+    }
+}
+
+fn save_recording_on_exit(
+    mut exit_events: EventReader<AppExit>,
+    recorder: Res<InputRecorder>,
+    path: Res<SaveOnExit>,
+) {
+    if exit_events.read().last().is_none() {
+        return;
+    }
+    match recorder.recording.save(&path.0) {
+        Ok(()) => info!("Saved the input recording to {}", path.0.display()),
+        Err(err) => error!(
+            "Failed to save the input recording to {}: {}",
+            path.0.display(),
+            err
+        ),
+    }
+}
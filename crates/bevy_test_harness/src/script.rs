@@ -0,0 +1,122 @@
+use bevy_ecs::{entity::Entity, world::World};
+use bevy_input::{
+    gamepad::GamepadEvent,
+    keyboard::{KeyCode, KeyboardInput},
+    mouse::{MouseButton, MouseButtonInput},
+    ButtonState,
+};
+use serde::{Deserialize, Serialize};
+
+/// A single input injected by a [`TestApp`](crate::TestApp).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ScriptedInput {
+    /// Sends a [`KeyboardInput`] pressing this key.
+    KeyPress(KeyCode),
+    /// Sends a [`KeyboardInput`] releasing this key.
+    KeyRelease(KeyCode),
+    /// Sends a [`MouseButtonInput`] pressing this button.
+    MousePress(MouseButton),
+    /// Sends a [`MouseButtonInput`] releasing this button.
+    MouseRelease(MouseButton),
+    /// Sends this [`GamepadEvent`].
+    Gamepad(GamepadEvent),
+}
+
+impl ScriptedInput {
+    /// Sends the event corresponding to this input, as received by `window`.
+    pub fn send(&self, world: &mut World, window: Entity) {
+        match self {
+            ScriptedInput::KeyPress(key_code) | ScriptedInput::KeyRelease(key_code) => {
+                world.send_event(KeyboardInput {
+                    scan_code: 0,
+                    key_code: Some(*key_code),
+                    state: self.button_state(),
+                    window,
+                });
+            }
+            ScriptedInput::MousePress(button) | ScriptedInput::MouseRelease(button) => {
+                world.send_event(MouseButtonInput {
+                    button: *button,
+                    state: self.button_state(),
+                    window,
+                });
+            }
+            ScriptedInput::Gamepad(event) => {
+                world.send_event(event.clone());
+            }
+        }
+    }
+
+    fn button_state(&self) -> ButtonState {
+        match self {
+            ScriptedInput::KeyPress(_) | ScriptedInput::MousePress(_) => ButtonState::Pressed,
+            _ => ButtonState::Released,
+        }
+    }
+}
+
+/// An input sent at a given frame of an [`InputScript`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScriptedEvent {
+    /// The frame, counted from the start of the script, at which the input is sent.
+    pub frame: u32,
+    /// The input.
+    pub input: ScriptedInput,
+}
+
+/// A sequence of inputs, run by [`TestApp::run_script`](crate::TestApp::run_script).
+///
+/// Scripts can be built in code or loaded from RON:
+///
+/// ```
+/// # use bevy_test_harness::InputScript;
+/// let script = InputScript::from_ron(
+///     "(events: [
+///         (frame: 0, input: KeyPress(Space)),
+///         (frame: 3, input: KeyRelease(Space)),
+///         (frame: 3, input: MousePress(Left)),
+///     ])",
+/// )
+/// .unwrap();
+/// assert_eq!(script.len_frames(), 4);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct InputScript {
+    /// The inputs of the script. Inputs at the same frame are sent in order.
+    pub events: Vec<ScriptedEvent>,
+}
+
+impl InputScript {
+    /// Creates an empty script.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deserializes a script from RON.
+    pub fn from_ron(ron: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(ron)
+    }
+
+    /// Adds an input sent at `frame`.
+    pub fn at_frame(mut self, frame: u32, input: ScriptedInput) -> Self {
+        self.events.push(ScriptedEvent { frame, input });
+        self
+    }
+
+    /// The number of frames needed to send every input of the script.
+    pub fn len_frames(&self) -> u32 {
+        self.events
+            .iter()
+            .map(|event| event.frame + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the inputs sent at `frame`, in order.
+    pub fn inputs_at(&self, frame: u32) -> impl Iterator<Item = &ScriptedInput> {
+        self.events
+            .iter()
+            .filter(move |event| event.frame == frame)
+            .map(|event| &event.input)
+    }
+}